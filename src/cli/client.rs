@@ -11,7 +11,7 @@ use crate::cli::options::{
 use crate::cli::report::{ApiResponse, ReportError};
 use crate::commons::api::{
     AllCertAuthIssues, CaRepoDetails, CertAuthIssues, ChildCaInfo, CurrentRepoState,
-    ParentCaContact, PublisherDetails, PublisherList, Token,
+    ParentCaContact, PublisherDetails, PublisherList, Token, UpdateChildRequest,
 };
 use crate::commons::remote::rfc8183;
 use crate::commons::util::httpclient;
@@ -100,6 +100,24 @@ impl KrillClient {
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::Delete(handle) => {
+                let uri = format!("api/v1/cas/{}", handle);
+                self.delete(&uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::Export(handle, export) => {
+                let uri = format!("api/v1/cas/{}/export", handle);
+                let archive = self.post_json_with_response(&uri, export).await?;
+                Ok(ApiResponse::CaExportArchive(archive))
+            }
+
+            CaCommand::Import(handle, import) => {
+                let uri = format!("api/v1/cas/{}/import", handle);
+                self.post_json(&uri, import).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::UpdateId(handle) => {
                 let uri = format!("api/v1/cas/{}/id", handle);
                 self.post_empty(&uri).await?;
@@ -182,6 +200,15 @@ impl KrillClient {
                 self.post_json(&uri, req).await?;
                 Ok(ApiResponse::Empty)
             }
+            CaCommand::ChildLeaseAdd(handle, child, lease) => {
+                let uri = format!("api/v1/cas/{}/children/{}", handle, child);
+                let info: ChildCaInfo = self.get_json(&uri).await?;
+                let mut leases = info.leases().clone();
+                leases.push(lease);
+                self.post_json(&uri, UpdateChildRequest::leases(leases))
+                    .await?;
+                Ok(ApiResponse::Empty)
+            }
             CaCommand::ChildDelete(handle, child) => {
                 let uri = format!("api/v1/cas/{}/children/{}", handle, child);
                 self.delete(&uri).await?;
@@ -198,6 +225,16 @@ impl KrillClient {
                 self.post_empty(&uri).await?;
                 Ok(ApiResponse::Empty)
             }
+            CaCommand::KeyRollPolicyShow(handle) => {
+                let uri = format!("api/v1/cas/{}/keys/policy", handle);
+                let policy = self.get_json(&uri).await?;
+                Ok(ApiResponse::KeyRollPolicy(policy))
+            }
+            CaCommand::KeyRollPolicyUpdate(handle, policy) => {
+                let uri = format!("api/v1/cas/{}/keys/policy", handle);
+                self.post_json(&uri, policy).await?;
+                Ok(ApiResponse::Empty)
+            }
 
             CaCommand::RouteAuthorizationsList(handle) => {
                 let uri = format!("api/v1/cas/{}/routes", handle);
@@ -211,6 +248,105 @@ impl KrillClient {
                 Ok(ApiResponse::Empty)
             }
 
+            CaCommand::RouteAuthorizationsImport(handle, import) => {
+                let uri = format!("api/v1/cas/{}/routes/import", handle);
+                let updates = self.post_json_with_response(&uri, import).await?;
+                Ok(ApiResponse::RouteAuthorizationUpdates(updates))
+            }
+
+            CaCommand::RouteAuthorizationsExport(handle, format) => {
+                let uri = format!("api/v1/cas/{}/routes/export/{}", handle, format);
+                let uri = self.resolve_uri(&uri);
+                let exported = httpclient::get_text(&uri, Some(&self.token))
+                    .await
+                    .map_err(Error::HttpClientError)?;
+                Ok(ApiResponse::GenericBody(exported))
+            }
+
+            CaCommand::BgpAnalysisFull(handle) => {
+                let uri = format!("api/v1/cas/{}/routes/analysis/full", handle);
+                let report = self.get_json(&uri).await?;
+                Ok(ApiResponse::BgpAnalysisFull(report))
+            }
+
+            CaCommand::BgpAnalysisDryRun(handle, updates) => {
+                let uri = format!("api/v1/cas/{}/routes/analysis/dryrun", handle);
+                let dry_run = self.post_json_with_response(&uri, updates).await?;
+                Ok(ApiResponse::BgpAnalysisDryRun(dry_run))
+            }
+
+            CaCommand::BgpAnalysisSuggest(handle) => {
+                let uri = format!("api/v1/cas/{}/routes/analysis/suggest", handle);
+                let suggestion = self.get_json(&uri).await?;
+                Ok(ApiResponse::RoaSuggestions(suggestion))
+            }
+
+            CaCommand::RoaPolicyShow(handle) => {
+                let uri = format!("api/v1/cas/{}/routes/policy", handle);
+                let policy = self.get_json(&uri).await?;
+                Ok(ApiResponse::RoaPackingPolicy(policy))
+            }
+
+            CaCommand::RoaPolicyUpdate(handle, policy) => {
+                let uri = format!("api/v1/cas/{}/routes/policy", handle);
+                self.post_json(&uri, policy).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::AspasList(handle) => {
+                let uri = format!("api/v1/cas/{}/aspas", handle);
+                let aspas = self.get_json(&uri).await?;
+                Ok(ApiResponse::AspaDefinitions(aspas))
+            }
+
+            CaCommand::AspasUpdate(handle, updates) => {
+                let uri = format!("api/v1/cas/{}/aspas", handle);
+                self.post_json(&uri, updates).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::GhostbusterShow(handle) => {
+                let uri = format!("api/v1/cas/{}/ghostbusters", handle);
+                let vcard = self.get_json(&uri).await?;
+                Ok(ApiResponse::Ghostbuster(vcard))
+            }
+
+            CaCommand::GhostbusterUpdate(handle, vcard) => {
+                let uri = format!("api/v1/cas/{}/ghostbusters", handle);
+                self.post_json(&uri, vcard).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::GhostbusterRemove(handle) => {
+                let uri = format!("api/v1/cas/{}/ghostbusters", handle);
+                self.delete(&uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::PublicationTimingShow(handle) => {
+                let uri = format!("api/v1/cas/{}/timing", handle);
+                let timing = self.get_json(&uri).await?;
+                Ok(ApiResponse::PublicationTiming(timing))
+            }
+
+            CaCommand::PublicationTimingUpdate(handle, timing) => {
+                let uri = format!("api/v1/cas/{}/timing", handle);
+                self.post_json(&uri, timing).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::PublicationTimingReset(handle) => {
+                let uri = format!("api/v1/cas/{}/timing", handle);
+                self.delete(&uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+
+            CaCommand::AspasUpdateProviders(handle, customer, update) => {
+                let uri = format!("api/v1/cas/{}/aspas/as/{}", handle, customer);
+                self.post_json(&uri, update).await?;
+                Ok(ApiResponse::Empty)
+            }
+
             CaCommand::Show(handle) => {
                 let uri = format!("api/v1/cas/{}", handle);
                 let ca_info = self.get_json(&uri).await?;
@@ -262,6 +398,10 @@ impl KrillClient {
                 let stales = self.get_json(&uri).await?;
                 Ok(ApiResponse::PublisherList(stales))
             }
+            PublishersCommand::Issues => {
+                let issues = self.get_json("api/v1/pubd/issues").await?;
+                Ok(ApiResponse::RepositoryIssues(issues))
+            }
             PublishersCommand::Stats => {
                 let stats = self.get_json("stats/repo").await?;
                 Ok(ApiResponse::RepoStats(stats))
@@ -272,6 +412,11 @@ impl KrillClient {
                     .await?;
                 Ok(ApiResponse::Rfc8183RepositoryResponse(res))
             }
+            PublishersCommand::UpdatePublisherIdCert(handle, req) => {
+                let uri = format!("api/v1/publishers/{}/id", handle);
+                self.post_json(&uri, req).await?;
+                Ok(ApiResponse::Empty)
+            }
             PublishersCommand::RemovePublisher(handle) => {
                 let uri = format!("api/v1/publishers/{}", handle);
                 self.delete(&uri).await?;
@@ -287,6 +432,51 @@ impl KrillClient {
                 let res = self.get_json(&uri).await?;
                 Ok(ApiResponse::Rfc8183RepositoryResponse(res))
             }
+            PublishersCommand::UpdateQuota(handle, quota) => {
+                let uri = format!("api/v1/publishers/{}/quota", handle);
+                self.post_json(&uri, quota).await?;
+                Ok(ApiResponse::Empty)
+            }
+            PublishersCommand::ShowObjects(handle) => {
+                let uri = format!("api/v1/publishers/{}/objects", handle);
+                let objects = self.get_json(&uri).await?;
+                Ok(ApiResponse::PublisherObjects(objects))
+            }
+            PublishersCommand::History(handle, options) => {
+                let uri = format!("api/v1/publishers/{}/history{}", handle, options.range());
+                let history = self.get_json(&uri).await?;
+                Ok(ApiResponse::PublisherHistory(history))
+            }
+            PublishersCommand::SuspendPublisher(handle) => {
+                let uri = format!("api/v1/publishers/{}/suspend", handle);
+                self.post_empty(&uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+            PublishersCommand::ReactivatePublisher(handle) => {
+                let uri = format!("api/v1/publishers/{}/reactivate", handle);
+                self.post_empty(&uri).await?;
+                Ok(ApiResponse::Empty)
+            }
+            PublishersCommand::VerifyRepository(repair) => {
+                let uri = "api/v1/pubd/verify";
+                let verification = if repair {
+                    self.post_empty_with_response(uri).await?
+                } else {
+                    self.get_json(uri).await?
+                };
+                Ok(ApiResponse::RepositoryVerification(verification))
+            }
+            PublishersCommand::ResetRrdpSession => {
+                self.post_empty("api/v1/pubd/session-reset").await?;
+                Ok(ApiResponse::Empty)
+            }
+            PublishersCommand::RsyncdConf => {
+                let uri = self.resolve_uri("api/v1/pubd/rsyncd-conf");
+                let stanza = httpclient::get_text(&uri, Some(&self.token))
+                    .await
+                    .map_err(Error::HttpClientError)?;
+                Ok(ApiResponse::GenericBody(stanza))
+            }
         }
     }
 
@@ -358,6 +548,13 @@ impl KrillClient {
             .map_err(Error::HttpClientError)
     }
 
+    async fn post_empty_with_response<T: DeserializeOwned>(&self, uri: &str) -> Result<T, Error> {
+        let uri = self.resolve_uri(uri);
+        httpclient::post_empty_with_response(&uri, Some(&self.token))
+            .await
+            .map_err(Error::HttpClientError)
+    }
+
     async fn post_json(&self, uri: &str, data: impl Serialize) -> Result<(), Error> {
         let uri = self.resolve_uri(uri);
         httpclient::post_json(&uri, data, Some(&self.token))
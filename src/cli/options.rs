@@ -13,9 +13,12 @@ use rpki::x509::Time;
 use crate::cli::report::{ReportError, ReportFormat};
 use crate::commons::api::RepositoryUpdate;
 use crate::commons::api::{
-    AddChildRequest, AuthorizationFmtError, CertAuthInit, ChildAuthRequest, ChildHandle, Handle,
-    ParentCaContact, ParentCaReq, ParentHandle, PublisherHandle, ResourceSet, ResourceSetError,
-    RoaDefinitionUpdates, Token, UpdateChildRequest,
+    AddChildRequest, AsNumber, AspaDefinition, AspaDefinitionUpdates, AspaProvidersUpdate,
+    AuthorizationFmtError, CaExportArchive, CaExportRequest, CaImportRequest, CertAuthInit,
+    ChildAuthRequest, ChildHandle, GhostbusterVcard, Handle, KeyRollPolicy, MaintenanceWindow,
+    ParentCaContact, ParentCaReq, ParentHandle, ProviderAs, PublicationTiming, PublisherHandle,
+    PublisherQuota, ResourceLease, ResourceSet, ResourceSetError, RoaDefinitionFormat,
+    RoaDefinitionUpdates, RoaImport, RoaPackingPolicy, Token, UpdateChildRequest,
 };
 use crate::commons::remote::id::IdCert;
 use crate::commons::remote::rfc8183;
@@ -162,6 +165,16 @@ impl Options {
         )
     }
 
+    fn add_passphrase_arg<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.arg(
+            Arg::with_name(KRILL_CLI_PASSPHRASE_ARG)
+                .value_name("passphrase")
+                .long(KRILL_CLI_PASSPHRASE_ARG)
+                .help("The passphrase for the CA export. Or set env: KRILL_CLI_PASSPHRASE")
+                .required(false),
+        )
+    }
+
     fn add_child_arg<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         app.arg(
             Arg::with_name("child")
@@ -295,18 +308,8 @@ impl Options {
         app.subcommand(sub)
     }
 
-    fn make_cas_show_history_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("history").about("Show full history of a CA.");
-
-        sub = Self::add_general_args(sub);
-        sub = Self::add_my_ca_arg(sub);
-
-        sub = sub.arg(
-            Arg::with_name("full")
-                .long("full")
-                .help("Show history including publication.")
-                .required(false),
-        );
+    fn add_history_range_args<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = app;
 
         sub = sub.arg(
             Arg::with_name("rows")
@@ -340,6 +343,24 @@ impl Options {
                 .required(false),
         );
 
+        sub
+    }
+
+    fn make_cas_show_history_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("history").about("Show full history of a CA.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("full")
+                .long("full")
+                .help("Show history including publication.")
+                .required(false),
+        );
+
+        sub = Self::add_history_range_args(sub);
+
         app.subcommand(sub)
     }
 
@@ -370,6 +391,47 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_delete_ca_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("delete").about(
+            "Delete a CA. Revokes its keys, withdraws its objects, and archives its history.",
+        );
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_export_ca_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("export").about(
+            "Export a CA, including its keys, encrypted with a passphrase. Prints the export.",
+        );
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_passphrase_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_import_ca_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("import")
+            .about("Import a CA that was exported from another Krill server.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_passphrase_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("file")
+                .long("file")
+                .help("The file containing the CA export.")
+                .value_name("<file>")
+                .required(true),
+        );
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_children_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let sub = SubCommand::with_name("add").about("Add a child to a CA.");
 
@@ -415,6 +477,33 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_children_lease_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("lease")
+            .about("Delegate additional resources to a child for a limited time.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_child_arg(sub);
+        sub = Self::add_child_resource_args(sub);
+        sub = sub
+            .arg(
+                Arg::with_name("until")
+                    .long("until")
+                    .help("End of the lease in RFC 3339 format, e.g. 2020-04-09T19:37:02Z")
+                    .value_name("<RFC 3339 DateTime>")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("clear")
+                    .long("clear")
+                    .help("Remove all leases for this child")
+                    .conflicts_with_all(&["asn", "ipv4", "ipv6", "until"])
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_children_response_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub =
             SubCommand::with_name("response").about("Get the RFC8183 response for a child.");
@@ -452,6 +541,7 @@ impl Options {
 
         sub = Self::make_cas_children_add_sc(sub);
         sub = Self::make_cas_children_update_sc(sub);
+        sub = Self::make_cas_children_lease_sc(sub);
         sub = Self::make_cas_children_info_sc(sub);
         sub = Self::make_cas_children_remove_sc(sub);
         sub = Self::make_cas_children_response_sc(sub);
@@ -564,11 +654,65 @@ impl Options {
         app.subcommand(sub)
     }
 
+    fn make_cas_keyroll_policy_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("policy").about("Show the automatic key roll policy.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_keyroll_set_policy_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("set-policy")
+            .about("Set the policy for automatic key rolls by Krill.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("enable")
+                    .long("enable")
+                    .help("Let Krill roll keys automatically. Disabled if not present.")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("max_age_days")
+                    .long("max-age-days")
+                    .help("Start a roll for keys older than this. Default: 365")
+                    .value_name("days")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("staging_hours")
+                    .long("staging-hours")
+                    .help("Activate new keys after this staging period. Default: 24")
+                    .value_name("hours")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("window")
+                    .long("window")
+                    .help(
+                        "Only roll during this window (UTC), e.g. \"sat 02-06\". May be repeated.",
+                    )
+                    .value_name("window")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
     fn make_cas_keyroll_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("keyroll").about("Perform a manual key-roll in Krill.");
+        let mut sub = SubCommand::with_name("keyroll").about("Manage key rolls in Krill.");
 
         sub = Self::make_cas_keyroll_init_sc(sub);
         sub = Self::make_cas_keyroll_activate_sc(sub);
+        sub = Self::make_cas_keyroll_policy_sc(sub);
+        sub = Self::make_cas_keyroll_set_policy_sc(sub);
 
         app.subcommand(sub)
     }
@@ -607,35 +751,55 @@ impl Options {
         app.subcommand(sub)
     }
 
-    fn make_cas_routes_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("roas").about("Manage ROAs for your CA.");
-
-        sub = Self::make_cas_routes_list_sc(sub);
-        sub = Self::make_cas_routes_update_sc(sub);
-
-        app.subcommand(sub)
+    fn add_roa_format_arg<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.arg(
+            Arg::with_name("format")
+                .long("format")
+                .help("The format to use: csv (prefix,max-length,asn,comment), json or slurm.")
+                .value_name("csv|json|slurm")
+                .required(true),
+        )
     }
 
-    fn make_cas_repo_request_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("request").about("Show RFC8183 Publisher Request.");
+    fn make_cas_routes_import_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("import")
+            .about("Import authorizations in bulk from a CSV, JSON or SLURM file.");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_format_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("file")
+                    .long("file")
+                    .help("The file to import.")
+                    .value_name("<file>")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("replace")
+                    .long("replace")
+                    .help("Replace all current authorizations, rather than adding to them.")
+                    .required(false),
+            );
 
         app.subcommand(sub)
     }
 
-    fn make_cas_repo_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("show").about("Show current repo config.");
+    fn make_cas_routes_export_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("export")
+            .about("Export authorizations in CSV, JSON or SLURM format.");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
+        sub = Self::add_roa_format_arg(sub);
 
         app.subcommand(sub)
     }
 
-    fn make_cas_repo_state_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("state").about("Show current repo state.");
+    fn make_cas_routes_policy_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("policy").about("Show the ROA packing policy.");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
@@ -643,655 +807,1572 @@ impl Options {
         app.subcommand(sub)
     }
 
-    fn make_cas_repo_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub =
-            SubCommand::with_name("update").about("Change which repository this CA uses.");
-
-        let mut embedded =
-            SubCommand::with_name("embedded").about("Use the embedded server in krill");
-        embedded = Self::add_general_args(embedded);
-        embedded = Self::add_my_ca_arg(embedded);
+    fn make_cas_routes_set_policy_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("set-policy")
+            .about("Set the ROA packing policy, and re-issue ROAs accordingly.");
 
-        let mut remote = SubCommand::with_name("remote").about("Use a remote server (RECOMMENDED)");
-        remote = Self::add_general_args(remote);
-        remote = Self::add_my_ca_arg(remote);
-        remote = remote.arg(
-            Arg::with_name("rfc8183")
-                .value_name("file")
-                .long("rfc8183")
-                .help("File containing the RFC8183 XML. Defaults to reading from STDIN")
-                .required(false),
-        );
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
 
-        sub = sub.subcommand(embedded);
-        sub = sub.subcommand(remote);
+        sub = sub
+            .arg(
+                Arg::with_name("aggregate")
+                    .long("aggregate")
+                    .help("Combine authorizations for the same ASN into a single ROA.")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("max_prefixes")
+                    .long("max-prefixes")
+                    .help("The maximum number of prefixes in an aggregated ROA.")
+                    .value_name("number")
+                    .requires("aggregate")
+                    .required(false),
+            );
 
         app.subcommand(sub)
     }
 
-    fn make_cas_repo_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("repo").about("Manage the repository for your CA.");
+    fn make_cas_routes_analyze_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("analyze")
+            .about("Show how current ROAs affect the BGP announcements for your prefixes.");
 
-        sub = Self::make_cas_repo_request_sc(sub);
-        sub = Self::make_cas_repo_show_sc(sub);
-        sub = Self::make_cas_repo_state_sc(sub);
-        sub = Self::make_cas_repo_update_sc(sub);
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
 
         app.subcommand(sub)
     }
 
-    fn make_cas_issues_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("issues").about("Show issues for CAs.");
+    fn make_cas_routes_dryrun_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("dryrun")
+            .about("Show how an update would affect the BGP announcements for your prefixes.");
 
         sub = Self::add_general_args(sub);
         sub = Self::add_my_ca_arg(sub);
 
+        sub = sub.arg(
+            Arg::with_name("delta")
+                .long("delta")
+                .help("Provide a delta file using the same format as for 'roas update'.")
+                .value_name("<file>")
+                .required(true),
+        );
+
         app.subcommand(sub)
     }
 
-    fn make_publishers_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("list").about("List all publishers.");
+    fn make_cas_routes_suggest_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("suggest")
+            .about("Suggest ROA updates based on the BGP announcements for your prefixes.");
+
         sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
         app.subcommand(sub)
     }
 
-    fn make_publishers_stale_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("stale")
-            .about("List all publishers which have not published in a while.");
-        sub = Self::add_general_args(sub);
-        sub = sub.arg(
-            Arg::with_name("seconds")
-                .value_name("seconds")
-                .long("seconds")
-                .help("The number of seconds since last publication.")
-                .required(true),
-        );
+    fn make_cas_routes_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("roas").about("Manage ROAs for your CA.");
+
+        sub = Self::make_cas_routes_list_sc(sub);
+        sub = Self::make_cas_routes_update_sc(sub);
+        sub = Self::make_cas_routes_import_sc(sub);
+        sub = Self::make_cas_routes_export_sc(sub);
+        sub = Self::make_cas_routes_policy_sc(sub);
+        sub = Self::make_cas_routes_set_policy_sc(sub);
+        sub = Self::make_cas_routes_analyze_sc(sub);
+        sub = Self::make_cas_routes_dryrun_sc(sub);
+        sub = Self::make_cas_routes_suggest_sc(sub);
+
         app.subcommand(sub)
     }
 
-    fn make_publishers_stats_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("stats").about("Show publication server stats.");
+    fn make_cas_aspas_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("list").about("Show current ASPA definitions.");
+
         sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
         app.subcommand(sub)
     }
 
-    fn add_publisher_arg<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        app.arg(
-            Arg::with_name("publisher")
-                .value_name("handle")
-                .short("p")
-                .long("publisher")
-                .help("The handle (name) of the publisher.")
-                .required(true),
-        )
-    }
+    fn make_cas_aspas_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("add")
+            .about("Add an ASPA definition, or replace the definition for the same customer.");
 
-    fn make_publishers_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("add").about("Add a publisher.");
         sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
 
         sub = sub.arg(
-            Arg::with_name("rfc8183")
-                .value_name("file")
-                .long("rfc8183")
-                .help("RFC8183 Publisher Request XML file containing a certificate (tag is ignored)")
-                .required(true)
-        ).arg(
-            Arg::with_name("publisher")
-                .value_name("handle")
-                .short("p")
-                .long("publisher")
-                .help("Override the publisher handle in the XML.")
-                .required(false),
+            Arg::with_name("aspa")
+                .long("aspa")
+                .help("The ASPA definition, e.g.: 65000 => 65001, 65002(v4), 65003(v6)")
+                .value_name("definition")
+                .required(true),
         );
 
         app.subcommand(sub)
     }
 
-    fn make_publishers_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("remove").about("Remove a publisher.");
+    fn make_cas_aspas_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("remove").about("Remove the ASPA definition for a customer.");
+
         sub = Self::add_general_args(sub);
-        sub = Self::add_publisher_arg(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub.arg(
+            Arg::with_name("customer")
+                .long("customer")
+                .help("The customer ASN, e.g.: 65000")
+                .value_name("ASN")
+                .required(true),
+        );
+
         app.subcommand(sub)
     }
 
-    fn make_publishers_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("show").about("Show details for a publisher.");
+    fn make_cas_aspas_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("update")
+            .about("Update the providers in the ASPA definition for a customer.");
+
         sub = Self::add_general_args(sub);
-        sub = Self::add_publisher_arg(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("customer")
+                    .long("customer")
+                    .help("The customer ASN, e.g.: 65000")
+                    .value_name("ASN")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("add")
+                    .long("add")
+                    .help("Provider to add or replace, e.g.: 65001 or 65001(v4). May be repeated.")
+                    .value_name("provider")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("remove")
+                    .long("remove")
+                    .help("Provider ASN to remove, e.g.: 65002. May be repeated.")
+                    .value_name("ASN")
+                    .multiple(true)
+                    .number_of_values(1)
+                    .required(false),
+            );
+
         app.subcommand(sub)
     }
 
-    fn make_publishers_response_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("response")
-            .about("Show RFC8183 Repository Response for a publisher.");
-        sub = Self::add_general_args(sub);
-        sub = Self::add_publisher_arg(sub);
+    fn make_cas_aspas_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("aspas").about("Manage ASPAs for your CA.");
+
+        sub = Self::make_cas_aspas_list_sc(sub);
+        sub = Self::make_cas_aspas_add_sc(sub);
+        sub = Self::make_cas_aspas_remove_sc(sub);
+        sub = Self::make_cas_aspas_update_sc(sub);
+
         app.subcommand(sub)
     }
 
-    fn make_publishers_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let mut sub = SubCommand::with_name("publishers").about("Manage publishers in Krill.");
+    fn make_cas_ghostbusters_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show")
+            .about("Show the Ghostbusters contact details published by your CA.");
 
-        sub = Self::make_publishers_list_sc(sub);
-        sub = Self::make_publishers_stale_sc(sub);
-        sub = Self::make_publishers_stats_sc(sub);
-        sub = Self::make_publishers_add_sc(sub);
-        sub = Self::make_publishers_remove_sc(sub);
-        sub = Self::make_publishers_show_sc(sub);
-        sub = Self::make_publishers_response_sc(sub);
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
 
         app.subcommand(sub)
     }
 
-    fn make_bulk_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+    fn make_cas_ghostbusters_set_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("set")
+            .about("Set the Ghostbusters contact details published by your CA.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("fn")
+                    .long("fn")
+                    .help("The full name of the contact, e.g.: \"Network Operations\"")
+                    .value_name("name")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("org")
+                    .long("org")
+                    .help("Optional. The organization name.")
+                    .value_name("organization")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("email")
+                    .long("email")
+                    .help("Optional. The contact email address.")
+                    .value_name("email")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("tel")
+                    .long("tel")
+                    .help("Optional. The contact telephone number.")
+                    .value_name("telephone")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("adr")
+                    .long("adr")
+                    .help("Optional. The postal address, as a single line.")
+                    .value_name("address")
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_ghostbusters_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove")
+            .about("Remove the Ghostbusters record published by your CA.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_ghostbusters_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("ghostbusters")
+            .about("Manage the Ghostbusters contact record for your CA.");
+
+        sub = Self::make_cas_ghostbusters_show_sc(sub);
+        sub = Self::make_cas_ghostbusters_set_sc(sub);
+        sub = Self::make_cas_ghostbusters_remove_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_timing_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show")
+            .about("Show the manifest and CRL timing used by your CA.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_timing_set_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("set")
+            .about("Set the manifest and CRL timing for your CA, overriding the server default.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("next_update_hours")
+                    .long("next-update-hours")
+                    .help("The next update time for manifests and CRLs, in hours.")
+                    .value_name("hours")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("valid_days")
+                    .long("valid-days")
+                    .help("The validity of manifest EE certificates, in days.")
+                    .value_name("days")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("republish_hours")
+                    .long("republish-hours")
+                    .help("Republish when the next update time is less than this many hours away.")
+                    .value_name("hours")
+                    .required(true),
+            );
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_timing_reset_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("reset")
+            .about("Use the server default manifest and CRL timing for your CA.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_timing_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("timing")
+            .about("Manage the manifest and CRL timing for your CA.");
+
+        sub = Self::make_cas_timing_show_sc(sub);
+        sub = Self::make_cas_timing_set_sc(sub);
+        sub = Self::make_cas_timing_reset_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_request_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("request").about("Show RFC8183 Publisher Request.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show").about("Show current repo config.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_state_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("state").about("Show current repo state.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("update").about("Change which repository this CA uses.");
+
+        let mut embedded =
+            SubCommand::with_name("embedded").about("Use the embedded server in krill");
+        embedded = Self::add_general_args(embedded);
+        embedded = Self::add_my_ca_arg(embedded);
+
+        let mut remote = SubCommand::with_name("remote").about("Use a remote server (RECOMMENDED)");
+        remote = Self::add_general_args(remote);
+        remote = Self::add_my_ca_arg(remote);
+        remote = remote.arg(
+            Arg::with_name("rfc8183")
+                .value_name("file")
+                .long("rfc8183")
+                .help("File containing the RFC8183 XML. Defaults to reading from STDIN")
+                .required(false),
+        );
+
+        sub = sub.subcommand(embedded);
+        sub = sub.subcommand(remote);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_repo_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("repo").about("Manage the repository for your CA.");
+
+        sub = Self::make_cas_repo_request_sc(sub);
+        sub = Self::make_cas_repo_show_sc(sub);
+        sub = Self::make_cas_repo_state_sc(sub);
+        sub = Self::make_cas_repo_update_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_cas_issues_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("issues").about("Show issues for CAs.");
+
+        sub = Self::add_general_args(sub);
+        sub = Self::add_my_ca_arg(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_list_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("list").about("List all publishers.");
+        sub = Self::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_stale_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("stale")
+            .about("List all publishers which have not published in a while.");
+        sub = Self::add_general_args(sub);
+        sub = sub.arg(
+            Arg::with_name("seconds")
+                .value_name("seconds")
+                .long("seconds")
+                .help("The number of seconds since last publication.")
+                .required(true),
+        );
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_issues_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("issues")
+            .about("Show publishers which are stale according to the configured thresholds.");
+        sub = Self::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_stats_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("stats").about("Show publication server stats.");
+        sub = Self::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn add_publisher_arg<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        app.arg(
+            Arg::with_name("publisher")
+                .value_name("handle")
+                .short("p")
+                .long("publisher")
+                .help("The handle (name) of the publisher.")
+                .required(true),
+        )
+    }
+
+    fn make_publishers_add_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("add").about("Add a publisher.");
+        sub = Self::add_general_args(sub);
+
+        sub = sub.arg(
+            Arg::with_name("rfc8183")
+                .value_name("file")
+                .long("rfc8183")
+                .help("RFC8183 Publisher Request XML file containing a certificate (tag is ignored)")
+                .required(true)
+        ).arg(
+            Arg::with_name("publisher")
+                .value_name("handle")
+                .short("p")
+                .long("publisher")
+                .help("Override the publisher handle in the XML.")
+                .required(false),
+        );
+
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_update_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("update")
+            .about("Update the identity certificate of a publisher, keeping its content.");
+        sub = Self::add_general_args(sub);
+
+        sub = sub
+            .arg(
+                Arg::with_name("request")
+                    .value_name("file")
+                    .long("request")
+                    .help("RFC8183 Publisher Request XML file containing the new certificate.")
+                    .required(true),
+            )
+            .arg(
+                Arg::with_name("publisher")
+                    .value_name("handle")
+                    .short("p")
+                    .long("publisher")
+                    .help("Override the publisher handle in the XML.")
+                    .required(false),
+            );
+
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_remove_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("remove").about("Remove a publisher.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_show_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("show").about("Show details for a publisher.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_response_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("response")
+            .about("Show RFC8183 Repository Response for a publisher.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_quota_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("quota")
+            .about("Set the publication quota for a publisher. Omitted limits are unlimited.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        sub = sub
+            .arg(
+                Arg::with_name("max_objects")
+                    .value_name("number")
+                    .long("max-objects")
+                    .help("The maximum number of objects.")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("max_size")
+                    .value_name("bytes")
+                    .long("max-size")
+                    .help("The maximum total size of all objects (base64 encoded).")
+                    .required(false),
+            )
+            .arg(
+                Arg::with_name("max_object_size")
+                    .value_name("bytes")
+                    .long("max-object-size")
+                    .help("The maximum size of a single object (base64 encoded).")
+                    .required(false),
+            );
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_suspend_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("suspend")
+            .about("Suspend a publisher. Its current objects remain published.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_reactivate_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub =
+            SubCommand::with_name("reactivate").about("Reactivate a suspended publisher.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_objects_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("objects")
+            .about("Show the objects published by a publisher, with decoded details.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_history_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("history")
+            .about("Show the history of publish requests for a publisher.");
+        sub = Self::add_general_args(sub);
+        sub = Self::add_publisher_arg(sub);
+        sub = Self::add_history_range_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_verify_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("verify")
+            .about("Compare the rsync, RRDP and IPFS content with the objects of all publishers.");
+        sub = Self::add_general_args(sub);
+        sub = sub.arg(
+            Arg::with_name("repair")
+                .long("repair")
+                .help("Rebuild the rsync, RRDP and IPFS content if differences are found.")
+                .required(false),
+        );
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_session_reset_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("session-reset")
+            .about("Start a new RRDP session, so that validators fetch a full snapshot.");
+        sub = Self::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_rsyncd_conf_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("rsyncd-conf")
+            .about("Show an rsyncd.conf module for serving the rsync repository.");
+        sub = Self::add_general_args(sub);
+        app.subcommand(sub)
+    }
+
+    fn make_publishers_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let mut sub = SubCommand::with_name("publishers").about("Manage publishers in Krill.");
+
+        sub = Self::make_publishers_list_sc(sub);
+        sub = Self::make_publishers_stale_sc(sub);
+        sub = Self::make_publishers_issues_sc(sub);
+        sub = Self::make_publishers_stats_sc(sub);
+        sub = Self::make_publishers_add_sc(sub);
+        sub = Self::make_publishers_update_sc(sub);
+        sub = Self::make_publishers_remove_sc(sub);
+        sub = Self::make_publishers_show_sc(sub);
+        sub = Self::make_publishers_response_sc(sub);
+        sub = Self::make_publishers_quota_sc(sub);
+        sub = Self::make_publishers_suspend_sc(sub);
+        sub = Self::make_publishers_reactivate_sc(sub);
+        sub = Self::make_publishers_history_sc(sub);
+        sub = Self::make_publishers_objects_sc(sub);
+        sub = Self::make_publishers_verify_sc(sub);
+        sub = Self::make_publishers_session_reset_sc(sub);
+        sub = Self::make_publishers_rsyncd_conf_sc(sub);
+
+        app.subcommand(sub)
+    }
+
+    fn make_bulk_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
         let mut sub = SubCommand::with_name("bulk")
             .about("Manually trigger refresh/republish/resync for all cas");
 
-        let mut refresh = SubCommand::with_name("refresh")
-            .about("Force that all CAs ask their parents for updated certificates");
-        refresh = Self::add_general_args(refresh);
+        let mut refresh = SubCommand::with_name("refresh")
+            .about("Force that all CAs ask their parents for updated certificates");
+        refresh = Self::add_general_args(refresh);
+
+        let mut republish = SubCommand::with_name("publish").about(
+            "Force that all CAs create new objects if needed (in which case they will also sync)",
+        );
+        republish = Self::add_general_args(republish);
+
+        let mut resync =
+            SubCommand::with_name("sync").about("Force that all CAs sync with their repo server");
+        resync = Self::add_general_args(resync);
+
+        sub = sub
+            .subcommand(refresh)
+            .subcommand(republish)
+            .subcommand(resync);
+
+        app.subcommand(sub)
+    }
+
+    fn make_health_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let health = SubCommand::with_name("health").about("Perform an authenticated health check");
+        let health = Self::add_general_args(health);
+        app.subcommand(health)
+    }
+
+    fn make_info_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
+        let info = SubCommand::with_name("info").about("Show server info");
+        let info = Self::add_general_args(info);
+        app.subcommand(info)
+    }
+
+    fn make_matches<'a>() -> ArgMatches<'a> {
+        let mut app = App::new(KRILL_CLIENT_APP).version(KRILL_VERSION);
+
+        app = Self::make_config_sc(app);
+        app = Self::make_cas_list_sc(app);
+        app = Self::make_cas_show_ca_sc(app);
+        app = Self::make_cas_show_history_sc(app);
+        app = Self::make_cas_show_action_sc(app);
+        app = Self::make_cas_add_ca_sc(app);
+        app = Self::make_cas_delete_ca_sc(app);
+        app = Self::make_cas_export_ca_sc(app);
+        app = Self::make_cas_import_ca_sc(app);
+        app = Self::make_cas_children_sc(app);
+        app = Self::make_cas_parents_sc(app);
+        app = Self::make_cas_keyroll_sc(app);
+        app = Self::make_cas_routes_sc(app);
+        app = Self::make_cas_aspas_sc(app);
+        app = Self::make_cas_ghostbusters_sc(app);
+        app = Self::make_cas_timing_sc(app);
+        app = Self::make_cas_repo_sc(app);
+        app = Self::make_cas_issues_sc(app);
+
+        app = Self::make_publishers_sc(app);
+
+        app = Self::make_health_sc(app);
+
+        app = Self::make_info_sc(app);
+
+        app = Self::make_bulk_sc(app);
+
+        app.get_matches()
+    }
+
+    //---------------------- Parsing
+
+    fn read_file_arg(path: &str) -> Result<Bytes, Error> {
+        let path = PathBuf::from(path);
+        file::read(&path).map_err(Error::IoError)
+    }
+
+    fn parse_my_ca(matches: &ArgMatches) -> Result<Handle, Error> {
+        let my_ca = {
+            let mut my_ca = None;
+
+            if let Ok(my_ca_env) = env::var(KRILL_CLI_MY_CA_ENV) {
+                my_ca = Some(Handle::from_str(&my_ca_env).map_err(|_| Error::InvalidHandle)?);
+            }
+
+            if let Some(my_ca_str) = matches.value_of(KRILL_CLI_MY_CA_ARG) {
+                my_ca = Some(Handle::from_str(my_ca_str).map_err(|_| Error::InvalidHandle)?);
+            }
+
+            my_ca.ok_or_else(|| {
+                Error::missing_arg_with_env(KRILL_CLI_MY_CA_ARG, KRILL_CLI_MY_CA_ENV)
+            })?
+        };
+
+        Ok(my_ca)
+    }
+
+    fn parse_passphrase(matches: &ArgMatches) -> Result<String, Error> {
+        match matches.value_of(KRILL_CLI_PASSPHRASE_ARG) {
+            Some(passphrase) => Ok(passphrase.to_string()),
+            None => env::var(KRILL_CLI_PASSPHRASE_ENV).map_err(|_| {
+                Error::missing_arg_with_env(KRILL_CLI_PASSPHRASE_ARG, KRILL_CLI_PASSPHRASE_ENV)
+            }),
+        }
+    }
+
+    fn parse_resource_args(matches: &ArgMatches) -> Result<Option<ResourceSet>, Error> {
+        let asn = matches.value_of("asn");
+        let v4 = matches.value_of("ipv4");
+        let v6 = matches.value_of("ipv6");
+
+        if asn.is_some() || v4.is_some() || v6.is_some() {
+            let asn = asn.unwrap_or_else(|| "");
+            let v4 = v4.unwrap_or_else(|| "");
+            let v6 = v6.unwrap_or_else(|| "");
+
+            Ok(Some(ResourceSet::from_strs(asn, v4, v6)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_matches_repo_config(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let rrdp_base: uri::Https = matches
+            .value_of("rrdp")
+            .map(uri::Https::from_str)
+            .unwrap()?;
+
+        if !rrdp_base.as_str().ends_with('/') {
+            return Err(Error::general("URI for --rrdp MUST end with a '/'"));
+        }
+
+        let rsync_base = matches
+            .value_of("rsync")
+            .map(uri::Rsync::from_str)
+            .unwrap()?;
+
+        if !rsync_base.to_string().ends_with('/') {
+            return Err(Error::general("URI for --rsync MUST end with a '/'"));
+        }
+
+        let mut details = KrillInitDetails::default();
+        details.with_rsync_base(rsync_base);
+        details.with_rrdp_service_uri(rrdp_base);
+
+        if let Some(data) = matches.value_of("data") {
+            if !data.ends_with('/') {
+                return Err(Error::general("Path for --data MUST end with a '/'"));
+            }
+            details.with_data_dir(data);
+        }
+
+        if let Some(log_file) = matches.value_of("logfile") {
+            details.with_log_file(log_file);
+        }
+
+        let command = Command::Init(details);
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_simple_config(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let mut details = KrillInitDetails::default();
+        if let Some(data) = matches.value_of("data") {
+            if !data.ends_with('/') {
+                return Err(Error::general("Path for --data MUST end with a '/'"));
+            }
+            details.with_data_dir(data);
+        }
+        if let Some(log_file) = matches.value_of("logfile") {
+            details.with_log_file(log_file);
+        }
+
+        let command = Command::Init(details);
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_config(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("repo") {
+            Self::parse_matches_repo_config(m)
+        } else if let Some(m) = matches.subcommand_matches("simple") {
+            Self::parse_matches_simple_config(m)
+        } else {
+            Err(Error::UnrecognisedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_list(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let command = Command::CertAuth(CaCommand::List);
+        Ok(Options::make(general_args, command))
+    }
 
-        let mut republish = SubCommand::with_name("publish").about(
-            "Force that all CAs create new objects if needed (in which case they will also sync)",
-        );
-        republish = Self::add_general_args(republish);
+    fn parse_matches_cas_add(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
 
-        let mut resync =
-            SubCommand::with_name("sync").about("Force that all CAs sync with their repo server");
-        resync = Self::add_general_args(resync);
+        let init = CertAuthInit::new(my_ca);
 
-        sub = sub
-            .subcommand(refresh)
-            .subcommand(republish)
-            .subcommand(resync);
+        let command = Command::CertAuth(CaCommand::Init(init));
 
-        app.subcommand(sub)
+        Ok(Options::make(general_args, command))
     }
 
-    fn make_health_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let health = SubCommand::with_name("health").about("Perform an authenticated health check");
-        let health = Self::add_general_args(health);
-        app.subcommand(health)
-    }
+    fn parse_matches_cas_delete(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
 
-    fn make_info_sc<'a, 'b>(app: App<'a, 'b>) -> App<'a, 'b> {
-        let info = SubCommand::with_name("info").about("Show server info");
-        let info = Self::add_general_args(info);
-        app.subcommand(info)
+        let command = Command::CertAuth(CaCommand::Delete(my_ca));
+
+        Ok(Options::make(general_args, command))
     }
 
-    fn make_matches<'a>() -> ArgMatches<'a> {
-        let mut app = App::new(KRILL_CLIENT_APP).version(KRILL_VERSION);
+    fn parse_matches_cas_export(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let passphrase = Self::parse_passphrase(matches)?;
 
-        app = Self::make_config_sc(app);
-        app = Self::make_cas_list_sc(app);
-        app = Self::make_cas_show_ca_sc(app);
-        app = Self::make_cas_show_history_sc(app);
-        app = Self::make_cas_show_action_sc(app);
-        app = Self::make_cas_add_ca_sc(app);
-        app = Self::make_cas_children_sc(app);
-        app = Self::make_cas_parents_sc(app);
-        app = Self::make_cas_keyroll_sc(app);
-        app = Self::make_cas_routes_sc(app);
-        app = Self::make_cas_repo_sc(app);
-        app = Self::make_cas_issues_sc(app);
+        let export = CaExportRequest::new(passphrase);
+        let command = Command::CertAuth(CaCommand::Export(my_ca, export));
 
-        app = Self::make_publishers_sc(app);
+        Ok(Options::make(general_args, command))
+    }
 
-        app = Self::make_health_sc(app);
+    fn parse_matches_cas_import(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let passphrase = Self::parse_passphrase(matches)?;
 
-        app = Self::make_info_sc(app);
+        let path = matches.value_of("file").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let archive: CaExportArchive = serde_json::from_slice(bytes.as_ref())
+            .map_err(|e| Error::general(&format!("Invalid CA export file: {}", e)))?;
 
-        app = Self::make_bulk_sc(app);
+        let handle = archive.handle().clone();
+        let import = CaImportRequest::new(passphrase, archive);
+        let command = Command::CertAuth(CaCommand::Import(handle, import));
 
-        app.get_matches()
+        Ok(Options::make(general_args, command))
     }
 
-    //---------------------- Parsing
+    fn parse_matches_cas_show(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
 
-    fn read_file_arg(path: &str) -> Result<Bytes, Error> {
-        let path = PathBuf::from(path);
-        file::read(&path).map_err(Error::IoError)
+        let command = Command::CertAuth(CaCommand::Show(my_ca));
+        Ok(Options::make(general_args, command))
     }
 
-    fn parse_my_ca(matches: &ArgMatches) -> Result<Handle, Error> {
-        let my_ca = {
-            let mut my_ca = None;
+    fn parse_history_range(
+        matches: &ArgMatches,
+        options: &mut HistoryOptions,
+    ) -> Result<(), Error> {
+        if let Some(offset) = matches.value_of("offset") {
+            let offset = u64::from_str(offset)
+                .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string())))?;
+            options.offset = offset
+        }
 
-            if let Ok(my_ca_env) = env::var(KRILL_CLI_MY_CA_ENV) {
-                my_ca = Some(Handle::from_str(&my_ca_env).map_err(|_| Error::InvalidHandle)?);
+        if let Some(rows) = matches.value_of("rows") {
+            let rows = u64::from_str(rows)
+                .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string())))?;
+            if rows > 250 {
+                return Err(Error::general("No more than 250 rows allowed in history"));
             }
+            options.rows = rows
+        }
 
-            if let Some(my_ca_str) = matches.value_of(KRILL_CLI_MY_CA_ARG) {
-                my_ca = Some(Handle::from_str(my_ca_str).map_err(|_| Error::InvalidHandle)?);
+        if let Some(after) = matches.value_of("after") {
+            let time = Time::from_str(after)
+                .map_err(|e| Error::general(&format!("invalid date format: {}", e.to_string())))?;
+            options.after = Some(time);
+        }
+
+        if let Some(after) = matches.value_of("before") {
+            let time = Time::from_str(after)
+                .map_err(|e| Error::general(&format!("invalid date format: {}", e.to_string())))?;
+            options.before = Some(time);
+        }
+
+        Ok(())
+    }
+
+    fn parse_matches_cas_history(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let mut options = HistoryOptions::default();
+        if matches.is_present("full") {
+            options.short = false;
+        }
+
+        Self::parse_history_range(matches, &mut options)?;
+
+        let command = Command::CertAuth(CaCommand::ShowHistory(my_ca, options));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_action(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let key = matches.value_of("key").unwrap();
+
+        let command = Command::CertAuth(CaCommand::ShowAction(my_ca, key.to_string()));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_add_general(
+        matches: &ArgMatches,
+        rfc8183_opt: Option<rfc8183::ChildRequest>,
+    ) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let resources =
+            Self::parse_resource_args(matches)?.ok_or_else(|| Error::MissingResources)?;
+
+        let auth_request = match rfc8183_opt {
+            Some(rfc8183) => ChildAuthRequest::Rfc8183(rfc8183),
+            None => ChildAuthRequest::Embedded,
+        };
+
+        let child_request = AddChildRequest::new(child, resources, auth_request);
+        let command = Command::CertAuth(CaCommand::ChildAdd(my_ca, child_request));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_add(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("embedded") {
+            Self::parse_matches_cas_children_add_general(m, None)
+        } else if let Some(m) = matches.subcommand_matches("remote") {
+            let path = m.value_of("rfc8183").unwrap();
+            let bytes = Self::read_file_arg(path)?;
+            let request = rfc8183::ChildRequest::validate(bytes.as_ref())?;
+            Self::parse_matches_cas_children_add_general(m, Some(request))
+        } else {
+            Err(Error::UnrecognisedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_children_update(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let id_cert = {
+            if let Some(path) = matches.value_of("idcert") {
+                let bytes = Self::read_file_arg(path)?;
+                let id_cert = IdCert::decode(bytes).map_err(|_| Error::InvalidChildIdCert)?;
+                Some(id_cert)
+            } else {
+                None
             }
+        };
+        let resources = Self::parse_resource_args(matches)?;
 
-            my_ca.ok_or_else(|| {
-                Error::missing_arg_with_env(KRILL_CLI_MY_CA_ARG, KRILL_CLI_MY_CA_ENV)
-            })?
+        let update = UpdateChildRequest::new(id_cert, resources);
+
+        let command = Command::CertAuth(CaCommand::ChildUpdate(my_ca, child, update));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_lease(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let command = if matches.is_present("clear") {
+            let update = UpdateChildRequest::leases(vec![]);
+            CaCommand::ChildUpdate(my_ca, child, update)
+        } else {
+            let resources =
+                Self::parse_resource_args(matches)?.ok_or_else(|| Error::MissingResources)?;
+
+            let until = matches
+                .value_of("until")
+                .ok_or_else(|| Error::general("--until is required, unless --clear is used"))?;
+            let until = Time::from_str(until)
+                .map_err(|e| Error::general(&format!("invalid date format: {}", e.to_string())))?;
+
+            CaCommand::ChildLeaseAdd(my_ca, child, ResourceLease::new(resources, until))
         };
 
-        Ok(my_ca)
+        Ok(Options::make(general_args, Command::CertAuth(command)))
+    }
+
+    fn parse_matches_cas_children_info(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let command = Command::CertAuth(CaCommand::ChildInfo(my_ca, child));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_response(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let command = Command::CertAuth(CaCommand::ParentResponse(my_ca, child));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let child = matches.value_of("child").unwrap();
+        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+
+        let command = Command::CertAuth(CaCommand::ChildDelete(my_ca, child));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_children(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("add") {
+            Self::parse_matches_cas_children_add(m)
+        } else if let Some(m) = matches.subcommand_matches("response") {
+            Self::parse_matches_cas_children_response(m)
+        } else if let Some(m) = matches.subcommand_matches("info") {
+            Self::parse_matches_cas_children_info(m)
+        } else if let Some(m) = matches.subcommand_matches("update") {
+            Self::parse_matches_cas_children_update(m)
+        } else if let Some(m) = matches.subcommand_matches("lease") {
+            Self::parse_matches_cas_children_lease(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_children_remove(m)
+        } else {
+            Err(Error::UnrecognisedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_parents_request(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::ChildRequest(my_ca));
+
+        Ok(Options::make(general_args, command))
     }
 
-    fn parse_resource_args(matches: &ArgMatches) -> Result<Option<ResourceSet>, Error> {
-        let asn = matches.value_of("asn");
-        let v4 = matches.value_of("ipv4");
-        let v6 = matches.value_of("ipv6");
+    fn parse_matches_cas_parents_add_general(
+        matches: &ArgMatches,
+        response_opt: Option<rfc8183::ParentResponse>,
+    ) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
 
-        if asn.is_some() || v4.is_some() || v6.is_some() {
-            let asn = asn.unwrap_or_else(|| "");
-            let v4 = v4.unwrap_or_else(|| "");
-            let v6 = v6.unwrap_or_else(|| "");
+        let parent = matches.value_of("parent").unwrap();
+        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
+        let contact = match response_opt {
+            Some(response) => ParentCaContact::for_rfc6492(response),
+            None => ParentCaContact::Embedded,
+        };
+        let parent_req = ParentCaReq::new(parent, contact);
 
-            Ok(Some(ResourceSet::from_strs(asn, v4, v6)?))
+        let command = Command::CertAuth(CaCommand::AddParent(my_ca, parent_req));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_parents_add(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("embedded") {
+            Self::parse_matches_cas_parents_add_general(m, None)
+        } else if let Some(m) = matches.subcommand_matches("remote") {
+            let path = m.value_of("rfc8183").unwrap();
+            let bytes = Self::read_file_arg(path)?;
+            let response = rfc8183::ParentResponse::validate(bytes.as_ref())?;
+            Self::parse_matches_cas_parents_add_general(m, Some(response))
         } else {
-            Ok(None)
+            Err(Error::UnrecognisedSubCommand)
         }
     }
 
-    fn parse_matches_repo_config(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_parents_update(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
-        let rrdp_base: uri::Https = matches
-            .value_of("rrdp")
-            .map(uri::Https::from_str)
-            .unwrap()?;
-
-        if !rrdp_base.as_str().ends_with('/') {
-            return Err(Error::general("URI for --rrdp MUST end with a '/'"));
-        }
+        let my_ca = Self::parse_my_ca(matches)?;
 
-        let rsync_base = matches
-            .value_of("rsync")
-            .map(uri::Rsync::from_str)
-            .unwrap()?;
+        let parent = matches.value_of("parent").unwrap();
+        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
 
-        if !rsync_base.to_string().ends_with('/') {
-            return Err(Error::general("URI for --rsync MUST end with a '/'"));
-        }
+        let path = matches.value_of("rfc8183").unwrap();
+        let bytes = Self::read_file_arg(path)?;
+        let response = rfc8183::ParentResponse::validate(bytes.as_ref())?;
 
-        let mut details = KrillInitDetails::default();
-        details.with_rsync_base(rsync_base);
-        details.with_rrdp_service_uri(rrdp_base);
+        let contact = ParentCaContact::for_rfc6492(response);
 
-        if let Some(data) = matches.value_of("data") {
-            if !data.ends_with('/') {
-                return Err(Error::general("Path for --data MUST end with a '/'"));
-            }
-            details.with_data_dir(data);
-        }
+        let command = Command::CertAuth(CaCommand::UpdateParentContact(my_ca, parent, contact));
+        Ok(Options::make(general_args, command))
+    }
 
-        if let Some(log_file) = matches.value_of("logfile") {
-            details.with_log_file(log_file);
-        }
+    fn parse_matches_cas_parents_info(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let parent = matches.value_of("parent").unwrap();
+        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
 
-        let command = Command::Init(details);
+        let command = Command::CertAuth(CaCommand::MyParentCaContact(my_ca, parent));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_simple_config(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_parents_remove(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
-        let mut details = KrillInitDetails::default();
-        if let Some(data) = matches.value_of("data") {
-            if !data.ends_with('/') {
-                return Err(Error::general("Path for --data MUST end with a '/'"));
-            }
-            details.with_data_dir(data);
-        }
-        if let Some(log_file) = matches.value_of("logfile") {
-            details.with_log_file(log_file);
-        }
+        let my_ca = Self::parse_my_ca(matches)?;
+        let parent = matches.value_of("parent").unwrap();
+        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
 
-        let command = Command::Init(details);
+        let command = Command::CertAuth(CaCommand::RemoveParent(my_ca, parent));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_config(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("repo") {
-            Self::parse_matches_repo_config(m)
-        } else if let Some(m) = matches.subcommand_matches("simple") {
-            Self::parse_matches_simple_config(m)
+    fn parse_matches_cas_parents(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("request") {
+            Self::parse_matches_cas_parents_request(m)
+        } else if let Some(m) = matches.subcommand_matches("add") {
+            Self::parse_matches_cas_parents_add(m)
+        } else if let Some(m) = matches.subcommand_matches("update") {
+            Self::parse_matches_cas_parents_update(m)
+        } else if let Some(m) = matches.subcommand_matches("contact") {
+            Self::parse_matches_cas_parents_info(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_parents_remove(m)
         } else {
             Err(Error::UnrecognisedSubCommand)
         }
     }
 
-    fn parse_matches_cas_list(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_keyroll_init(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
-        let command = Command::CertAuth(CaCommand::List);
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::KeyRollInit(my_ca));
+
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_add(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_keyroll_activate(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let init = CertAuthInit::new(my_ca);
-
-        let command = Command::CertAuth(CaCommand::Init(init));
+        let command = Command::CertAuth(CaCommand::KeyRollActivate(my_ca));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_show(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_keyroll_policy(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let command = Command::CertAuth(CaCommand::Show(my_ca));
+        let command = Command::CertAuth(CaCommand::KeyRollPolicyShow(my_ca));
+
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_history(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_keyroll_set_policy(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let mut options = HistoryOptions::default();
-        if matches.is_present("full") {
-            options.short = false;
-        }
-
-        if let Some(offset) = matches.value_of("offset") {
-            let offset = u64::from_str(offset)
-                .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string())))?;
-            options.offset = offset
+        fn parse_number(value: Option<&str>, default: u32) -> Result<u32, Error> {
+            match value {
+                None => Ok(default),
+                Some(number) => u32::from_str(number)
+                    .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string()))),
+            }
         }
 
-        if let Some(rows) = matches.value_of("rows") {
-            let rows = u64::from_str(rows)
-                .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string())))?;
-            if rows > 250 {
-                return Err(Error::general("No more than 250 rows allowed in history"));
+        let default = KeyRollPolicy::default();
+        let enabled = matches.is_present("enable");
+        let max_age_days =
+            parse_number(matches.value_of("max_age_days"), default.max_key_age_days())?;
+        let staging_hours =
+            parse_number(matches.value_of("staging_hours"), default.staging_hours())?;
+
+        let mut windows = vec![];
+        if let Some(values) = matches.values_of("window") {
+            for value in values {
+                let window = MaintenanceWindow::from_str(value)
+                    .map_err(|e| Error::general(&e.to_string()))?;
+                windows.push(window);
             }
-            options.rows = rows
         }
 
-        if let Some(after) = matches.value_of("after") {
-            let time = Time::from_str(after)
-                .map_err(|e| Error::general(&format!("invalid date format: {}", e.to_string())))?;
-            options.after = Some(time);
+        let policy = KeyRollPolicy::new(enabled, max_age_days, staging_hours, windows);
+        if !policy.is_valid() {
+            return Err(Error::general(
+                "The staging period must be at least one hour, and shorter than the maximum key age",
+            ));
         }
 
-        if let Some(after) = matches.value_of("before") {
-            let time = Time::from_str(after)
-                .map_err(|e| Error::general(&format!("invalid date format: {}", e.to_string())))?;
-            options.before = Some(time);
-        }
+        let command = Command::CertAuth(CaCommand::KeyRollPolicyUpdate(my_ca, policy));
 
-        let command = Command::CertAuth(CaCommand::ShowHistory(my_ca, options));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_action(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_keyroll(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("init") {
+            Self::parse_matches_cas_keyroll_init(m)
+        } else if let Some(m) = matches.subcommand_matches("activate") {
+            Self::parse_matches_cas_keyroll_activate(m)
+        } else if let Some(m) = matches.subcommand_matches("policy") {
+            Self::parse_matches_cas_keyroll_policy(m)
+        } else if let Some(m) = matches.subcommand_matches("set-policy") {
+            Self::parse_matches_cas_keyroll_set_policy(m)
+        } else {
+            Err(Error::UnrecognisedSubCommand)
+        }
+    }
+
+    fn parse_matches_cas_routes_list(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
-        let key = matches.value_of("key").unwrap();
 
-        let command = Command::CertAuth(CaCommand::ShowAction(my_ca, key.to_string()));
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsList(my_ca));
+
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children_add_general(
-        matches: &ArgMatches,
-        rfc8183_opt: Option<rfc8183::ChildRequest>,
-    ) -> Result<Options, Error> {
+    fn parse_matches_cas_routes_update(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let child = matches.value_of("child").unwrap();
-        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+        let updates = {
+            let path = matches.value_of("delta").unwrap();
+            let bytes = Self::read_file_arg(path)?;
+            let updates_str = unsafe { from_utf8_unchecked(&bytes) };
+            RoaDefinitionUpdates::from_str(updates_str)?
+        };
 
-        let resources =
-            Self::parse_resource_args(matches)?.ok_or_else(|| Error::MissingResources)?;
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsUpdate(my_ca, updates));
 
-        let auth_request = match rfc8183_opt {
-            Some(rfc8183) => ChildAuthRequest::Rfc8183(rfc8183),
-            None => ChildAuthRequest::Embedded,
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_roa_format(matches: &ArgMatches) -> Result<RoaDefinitionFormat, Error> {
+        let format = matches.value_of("format").unwrap();
+        Ok(RoaDefinitionFormat::from_str(format)?)
+    }
+
+    fn parse_matches_cas_routes_import(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let format = Self::parse_roa_format(matches)?;
+        let replace = matches.is_present("replace");
+
+        let content = {
+            let path = matches.value_of("file").unwrap();
+            let bytes = Self::read_file_arg(path)?;
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| Error::general("import file must be UTF-8 encoded"))?
         };
 
-        let child_request = AddChildRequest::new(child, resources, auth_request);
-        let command = Command::CertAuth(CaCommand::ChildAdd(my_ca, child_request));
+        let import = RoaImport::new(format, replace, content);
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsImport(my_ca, import));
+
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children_add(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("embedded") {
-            Self::parse_matches_cas_children_add_general(m, None)
-        } else if let Some(m) = matches.subcommand_matches("remote") {
-            let path = m.value_of("rfc8183").unwrap();
-            let bytes = Self::read_file_arg(path)?;
-            let request = rfc8183::ChildRequest::validate(bytes.as_ref())?;
-            Self::parse_matches_cas_children_add_general(m, Some(request))
-        } else {
-            Err(Error::UnrecognisedSubCommand)
-        }
+    fn parse_matches_cas_routes_export(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+        let format = Self::parse_roa_format(matches)?;
+
+        let command = Command::CertAuth(CaCommand::RouteAuthorizationsExport(my_ca, format));
+
+        Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children_update(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_routes_analyze(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let child = matches.value_of("child").unwrap();
-        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+        let command = Command::CertAuth(CaCommand::BgpAnalysisFull(my_ca));
 
-        let id_cert = {
-            if let Some(path) = matches.value_of("idcert") {
-                let bytes = Self::read_file_arg(path)?;
-                let id_cert = IdCert::decode(bytes).map_err(|_| Error::InvalidChildIdCert)?;
-                Some(id_cert)
-            } else {
-                None
-            }
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_routes_dryrun(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let updates = {
+            let path = matches.value_of("delta").unwrap();
+            let bytes = Self::read_file_arg(path)?;
+            let updates_str = unsafe { from_utf8_unchecked(&bytes) };
+            RoaDefinitionUpdates::from_str(updates_str)?
         };
-        let resources = Self::parse_resource_args(matches)?;
 
-        let update = UpdateChildRequest::new(id_cert, resources);
+        let command = Command::CertAuth(CaCommand::BgpAnalysisDryRun(my_ca, updates));
 
-        let command = Command::CertAuth(CaCommand::ChildUpdate(my_ca, child, update));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children_info(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_routes_suggest(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let child = matches.value_of("child").unwrap();
-        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+        let command = Command::CertAuth(CaCommand::BgpAnalysisSuggest(my_ca));
 
-        let command = Command::CertAuth(CaCommand::ChildInfo(my_ca, child));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children_response(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_routes_policy(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let child = matches.value_of("child").unwrap();
-        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+        let command = Command::CertAuth(CaCommand::RoaPolicyShow(my_ca));
 
-        let command = Command::CertAuth(CaCommand::ParentResponse(my_ca, child));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children_remove(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_routes_set_policy(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let child = matches.value_of("child").unwrap();
-        let child = Handle::from_str(child).map_err(|_| Error::InvalidHandle)?;
+        let aggregate = matches.is_present("aggregate");
+        let max_prefixes = match matches.value_of("max_prefixes") {
+            None => None,
+            Some(max) => {
+                let max = usize::from_str(max)
+                    .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string())))?;
+                if max == 0 {
+                    return Err(Error::general("--max-prefixes must be at least 1"));
+                }
+                Some(max)
+            }
+        };
+
+        let policy = RoaPackingPolicy::new(aggregate, max_prefixes);
+
+        let command = Command::CertAuth(CaCommand::RoaPolicyUpdate(my_ca, policy));
 
-        let command = Command::CertAuth(CaCommand::ChildDelete(my_ca, child));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_children(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("add") {
-            Self::parse_matches_cas_children_add(m)
-        } else if let Some(m) = matches.subcommand_matches("response") {
-            Self::parse_matches_cas_children_response(m)
-        } else if let Some(m) = matches.subcommand_matches("info") {
-            Self::parse_matches_cas_children_info(m)
+    fn parse_matches_cas_routes(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("list") {
+            Self::parse_matches_cas_routes_list(m)
         } else if let Some(m) = matches.subcommand_matches("update") {
-            Self::parse_matches_cas_children_update(m)
-        } else if let Some(m) = matches.subcommand_matches("remove") {
-            Self::parse_matches_cas_children_remove(m)
+            Self::parse_matches_cas_routes_update(m)
+        } else if let Some(m) = matches.subcommand_matches("import") {
+            Self::parse_matches_cas_routes_import(m)
+        } else if let Some(m) = matches.subcommand_matches("export") {
+            Self::parse_matches_cas_routes_export(m)
+        } else if let Some(m) = matches.subcommand_matches("policy") {
+            Self::parse_matches_cas_routes_policy(m)
+        } else if let Some(m) = matches.subcommand_matches("set-policy") {
+            Self::parse_matches_cas_routes_set_policy(m)
+        } else if let Some(m) = matches.subcommand_matches("analyze") {
+            Self::parse_matches_cas_routes_analyze(m)
+        } else if let Some(m) = matches.subcommand_matches("dryrun") {
+            Self::parse_matches_cas_routes_dryrun(m)
+        } else if let Some(m) = matches.subcommand_matches("suggest") {
+            Self::parse_matches_cas_routes_suggest(m)
         } else {
             Err(Error::UnrecognisedSubCommand)
         }
     }
 
-    fn parse_matches_cas_parents_request(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_aspas_list(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let command = Command::CertAuth(CaCommand::ChildRequest(my_ca));
+        let command = Command::CertAuth(CaCommand::AspasList(my_ca));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_parents_add_general(
-        matches: &ArgMatches,
-        response_opt: Option<rfc8183::ParentResponse>,
-    ) -> Result<Options, Error> {
+    fn parse_matches_cas_aspas_add(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let parent = matches.value_of("parent").unwrap();
-        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
-        let contact = match response_opt {
-            Some(response) => ParentCaContact::for_rfc6492(response),
-            None => ParentCaContact::Embedded,
-        };
-        let parent_req = ParentCaReq::new(parent, contact);
+        let definition = AspaDefinition::from_str(matches.value_of("aspa").unwrap())?;
+        let updates = AspaDefinitionUpdates::new(vec![definition], vec![]);
 
-        let command = Command::CertAuth(CaCommand::AddParent(my_ca, parent_req));
-        Ok(Options::make(general_args, command))
-    }
+        let command = Command::CertAuth(CaCommand::AspasUpdate(my_ca, updates));
 
-    fn parse_matches_cas_parents_add(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("embedded") {
-            Self::parse_matches_cas_parents_add_general(m, None)
-        } else if let Some(m) = matches.subcommand_matches("remote") {
-            let path = m.value_of("rfc8183").unwrap();
-            let bytes = Self::read_file_arg(path)?;
-            let response = rfc8183::ParentResponse::validate(bytes.as_ref())?;
-            Self::parse_matches_cas_parents_add_general(m, Some(response))
-        } else {
-            Err(Error::UnrecognisedSubCommand)
-        }
+        Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_parents_update(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_aspas_remove(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let parent = matches.value_of("parent").unwrap();
-        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
-
-        let path = matches.value_of("rfc8183").unwrap();
-        let bytes = Self::read_file_arg(path)?;
-        let response = rfc8183::ParentResponse::validate(bytes.as_ref())?;
+        let customer = AsNumber::from_str(matches.value_of("customer").unwrap())?;
+        let updates = AspaDefinitionUpdates::new(vec![], vec![customer]);
 
-        let contact = ParentCaContact::for_rfc6492(response);
+        let command = Command::CertAuth(CaCommand::AspasUpdate(my_ca, updates));
 
-        let command = Command::CertAuth(CaCommand::UpdateParentContact(my_ca, parent, contact));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_parents_info(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_aspas_update(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
-        let parent = matches.value_of("parent").unwrap();
-        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
 
-        let command = Command::CertAuth(CaCommand::MyParentCaContact(my_ca, parent));
-        Ok(Options::make(general_args, command))
-    }
+        let customer = AsNumber::from_str(matches.value_of("customer").unwrap())?;
 
-    fn parse_matches_cas_parents_remove(matches: &ArgMatches) -> Result<Options, Error> {
-        let general_args = GeneralArgs::from_matches(matches)?;
-        let my_ca = Self::parse_my_ca(matches)?;
-        let parent = matches.value_of("parent").unwrap();
-        let parent = Handle::from_str(parent).map_err(|_| Error::InvalidHandle)?;
+        let mut added = vec![];
+        if let Some(values) = matches.values_of("add") {
+            for value in values {
+                added.push(ProviderAs::from_str(value)?);
+            }
+        }
+
+        let mut removed = vec![];
+        if let Some(values) = matches.values_of("remove") {
+            for value in values {
+                removed.push(AsNumber::from_str(value)?);
+            }
+        }
+
+        let update = AspaProvidersUpdate::new(added, removed);
+        if update.is_empty() {
+            return Err(Error::general(
+                "Use --add and/or --remove to update providers",
+            ));
+        }
+
+        let command = Command::CertAuth(CaCommand::AspasUpdateProviders(my_ca, customer, update));
 
-        let command = Command::CertAuth(CaCommand::RemoveParent(my_ca, parent));
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_parents(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("request") {
-            Self::parse_matches_cas_parents_request(m)
+    fn parse_matches_cas_aspas(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("list") {
+            Self::parse_matches_cas_aspas_list(m)
         } else if let Some(m) = matches.subcommand_matches("add") {
-            Self::parse_matches_cas_parents_add(m)
-        } else if let Some(m) = matches.subcommand_matches("update") {
-            Self::parse_matches_cas_parents_update(m)
-        } else if let Some(m) = matches.subcommand_matches("contact") {
-            Self::parse_matches_cas_parents_info(m)
+            Self::parse_matches_cas_aspas_add(m)
         } else if let Some(m) = matches.subcommand_matches("remove") {
-            Self::parse_matches_cas_parents_remove(m)
+            Self::parse_matches_cas_aspas_remove(m)
+        } else if let Some(m) = matches.subcommand_matches("update") {
+            Self::parse_matches_cas_aspas_update(m)
         } else {
             Err(Error::UnrecognisedSubCommand)
         }
     }
 
-    fn parse_matches_cas_keyroll_init(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_ghostbusters_show(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let command = Command::CertAuth(CaCommand::KeyRollInit(my_ca));
+        let command = Command::CertAuth(CaCommand::GhostbusterShow(my_ca));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_keyroll_activate(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_ghostbusters_set(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let command = Command::CertAuth(CaCommand::KeyRollActivate(my_ca));
+        let full_name = matches.value_of("fn").unwrap().to_string();
+        let org = matches.value_of("org").map(|s| s.to_string());
+        let email = matches.value_of("email").map(|s| s.to_string());
+        let tel = matches.value_of("tel").map(|s| s.to_string());
+        let adr = matches.value_of("adr").map(|s| s.to_string());
+
+        let vcard = GhostbusterVcard::new(full_name, org, email, tel, adr);
+        if !vcard.is_valid() {
+            return Err(Error::general(
+                "A Ghostbusters record needs a full name, and at least one of --email, --tel or --adr",
+            ));
+        }
+
+        let command = Command::CertAuth(CaCommand::GhostbusterUpdate(my_ca, vcard));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_keyroll(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("init") {
-            Self::parse_matches_cas_keyroll_init(m)
-        } else if let Some(m) = matches.subcommand_matches("activate") {
-            Self::parse_matches_cas_keyroll_activate(m)
+    fn parse_matches_cas_ghostbusters_remove(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::GhostbusterRemove(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_ghostbusters(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_ghostbusters_show(m)
+        } else if let Some(m) = matches.subcommand_matches("set") {
+            Self::parse_matches_cas_ghostbusters_set(m)
+        } else if let Some(m) = matches.subcommand_matches("remove") {
+            Self::parse_matches_cas_ghostbusters_remove(m)
         } else {
             Err(Error::UnrecognisedSubCommand)
         }
     }
 
-    fn parse_matches_cas_routes_list(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_timing_show(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let command = Command::CertAuth(CaCommand::RouteAuthorizationsList(my_ca));
+        let command = Command::CertAuth(CaCommand::PublicationTimingShow(my_ca));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_routes_update(matches: &ArgMatches) -> Result<Options, Error> {
+    fn parse_matches_cas_timing_set(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let my_ca = Self::parse_my_ca(matches)?;
 
-        let updates = {
-            let path = matches.value_of("delta").unwrap();
-            let bytes = Self::read_file_arg(path)?;
-            let updates_str = unsafe { from_utf8_unchecked(&bytes) };
-            RoaDefinitionUpdates::from_str(updates_str)?
-        };
+        fn parse_number(value: &str) -> Result<u32, Error> {
+            u32::from_str(value)
+                .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string())))
+        }
 
-        let command = Command::CertAuth(CaCommand::RouteAuthorizationsUpdate(my_ca, updates));
+        let next_update_hours = parse_number(matches.value_of("next_update_hours").unwrap())?;
+        let valid_days = parse_number(matches.value_of("valid_days").unwrap())?;
+        let republish_hours = parse_number(matches.value_of("republish_hours").unwrap())?;
+
+        let timing = PublicationTiming::new(next_update_hours, valid_days, republish_hours);
+        if !timing.is_valid() {
+            return Err(Error::general(
+                "The republish hours must be at least 1 and less than the next update hours, and the valid days must cover the next update hours",
+            ));
+        }
+
+        let command = Command::CertAuth(CaCommand::PublicationTimingUpdate(my_ca, timing));
 
         Ok(Options::make(general_args, command))
     }
 
-    fn parse_matches_cas_routes(matches: &ArgMatches) -> Result<Options, Error> {
-        if let Some(m) = matches.subcommand_matches("list") {
-            Self::parse_matches_cas_routes_list(m)
-        } else if let Some(m) = matches.subcommand_matches("update") {
-            Self::parse_matches_cas_routes_update(m)
+    fn parse_matches_cas_timing_reset(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let my_ca = Self::parse_my_ca(matches)?;
+
+        let command = Command::CertAuth(CaCommand::PublicationTimingReset(my_ca));
+
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_cas_timing(matches: &ArgMatches) -> Result<Options, Error> {
+        if let Some(m) = matches.subcommand_matches("show") {
+            Self::parse_matches_cas_timing_show(m)
+        } else if let Some(m) = matches.subcommand_matches("set") {
+            Self::parse_matches_cas_timing_set(m)
+        } else if let Some(m) = matches.subcommand_matches("reset") {
+            Self::parse_matches_cas_timing_reset(m)
         } else {
             Err(Error::UnrecognisedSubCommand)
         }
@@ -1390,6 +2471,12 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_publishers_issues(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let command = Command::Publishers(PublishersCommand::Issues);
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_publishers_stats(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let command = Command::Publishers(PublishersCommand::Stats);
@@ -1415,6 +2502,25 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_publishers_update(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+
+        let path = matches.value_of("request").unwrap();
+        let path = PathBuf::from(path);
+        let bytes = file::read(&path)?;
+        let req = rfc8183::PublisherRequest::validate(bytes.as_ref())?;
+
+        let publisher = match matches.value_of("publisher") {
+            Some(publisher_str) => {
+                PublisherHandle::from_str(publisher_str).map_err(|_| Error::InvalidHandle)?
+            }
+            None => req.publisher_handle().clone(),
+        };
+
+        let command = Command::Publishers(PublishersCommand::UpdatePublisherIdCert(publisher, req));
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_publishers_remove(matches: &ArgMatches) -> Result<Options, Error> {
         let general_args = GeneralArgs::from_matches(matches)?;
         let publisher = Self::parse_publisher_arg(matches)?;
@@ -1436,21 +2542,115 @@ impl Options {
         Ok(Options::make(general_args, command))
     }
 
+    fn parse_matches_publishers_quota(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let publisher = Self::parse_publisher_arg(matches)?;
+
+        let limit = |name: &str| -> Result<Option<usize>, Error> {
+            match matches.value_of(name) {
+                None => Ok(None),
+                Some(value) => usize::from_str(value)
+                    .map(Some)
+                    .map_err(|e| Error::general(&format!("invalid number: {}", e.to_string()))),
+            }
+        };
+
+        let quota = PublisherQuota::new(
+            limit("max_objects")?,
+            limit("max_size")?,
+            limit("max_object_size")?,
+        );
+
+        let command = Command::Publishers(PublishersCommand::UpdateQuota(publisher, quota));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_suspend(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let publisher = Self::parse_publisher_arg(matches)?;
+        let command = Command::Publishers(PublishersCommand::SuspendPublisher(publisher));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_reactivate(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let publisher = Self::parse_publisher_arg(matches)?;
+        let command = Command::Publishers(PublishersCommand::ReactivatePublisher(publisher));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_objects(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let publisher = Self::parse_publisher_arg(matches)?;
+        let command = Command::Publishers(PublishersCommand::ShowObjects(publisher));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_history(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let publisher = Self::parse_publisher_arg(matches)?;
+
+        let mut options = HistoryOptions::default();
+        Self::parse_history_range(matches, &mut options)?;
+
+        let command = Command::Publishers(PublishersCommand::History(publisher, options));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_verify(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let repair = matches.is_present("repair");
+        let command = Command::Publishers(PublishersCommand::VerifyRepository(repair));
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_session_reset(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let command = Command::Publishers(PublishersCommand::ResetRrdpSession);
+        Ok(Options::make(general_args, command))
+    }
+
+    fn parse_matches_publishers_rsyncd_conf(matches: &ArgMatches) -> Result<Options, Error> {
+        let general_args = GeneralArgs::from_matches(matches)?;
+        let command = Command::Publishers(PublishersCommand::RsyncdConf);
+        Ok(Options::make(general_args, command))
+    }
+
     fn parse_matches_publishers(matches: &ArgMatches) -> Result<Options, Error> {
         if let Some(m) = matches.subcommand_matches("list") {
             Self::parse_matches_publishers_list(m)
         } else if let Some(m) = matches.subcommand_matches("stale") {
             Self::parse_matches_publishers_stale(m)
+        } else if let Some(m) = matches.subcommand_matches("issues") {
+            Self::parse_matches_publishers_issues(m)
         } else if let Some(m) = matches.subcommand_matches("stats") {
             Self::parse_matches_publishers_stats(m)
         } else if let Some(m) = matches.subcommand_matches("add") {
             Self::parse_matches_publishers_add(m)
+        } else if let Some(m) = matches.subcommand_matches("update") {
+            Self::parse_matches_publishers_update(m)
         } else if let Some(m) = matches.subcommand_matches("remove") {
             Self::parse_matches_publishers_remove(m)
         } else if let Some(m) = matches.subcommand_matches("show") {
             Self::parse_matches_publishers_show(m)
         } else if let Some(m) = matches.subcommand_matches("response") {
             Self::parse_matches_publishers_repo_response(m)
+        } else if let Some(m) = matches.subcommand_matches("quota") {
+            Self::parse_matches_publishers_quota(m)
+        } else if let Some(m) = matches.subcommand_matches("suspend") {
+            Self::parse_matches_publishers_suspend(m)
+        } else if let Some(m) = matches.subcommand_matches("reactivate") {
+            Self::parse_matches_publishers_reactivate(m)
+        } else if let Some(m) = matches.subcommand_matches("history") {
+            Self::parse_matches_publishers_history(m)
+        } else if let Some(m) = matches.subcommand_matches("objects") {
+            Self::parse_matches_publishers_objects(m)
+        } else if let Some(m) = matches.subcommand_matches("verify") {
+            Self::parse_matches_publishers_verify(m)
+        } else if let Some(m) = matches.subcommand_matches("session-reset") {
+            Self::parse_matches_publishers_session_reset(m)
+        } else if let Some(m) = matches.subcommand_matches("rsyncd-conf") {
+            Self::parse_matches_publishers_rsyncd_conf(m)
         } else {
             Err(Error::UnrecognisedSubCommand)
         }
@@ -1493,6 +2693,12 @@ impl Options {
             Self::parse_matches_cas_list(m)
         } else if let Some(m) = matches.subcommand_matches("add") {
             Self::parse_matches_cas_add(m)
+        } else if let Some(m) = matches.subcommand_matches("delete") {
+            Self::parse_matches_cas_delete(m)
+        } else if let Some(m) = matches.subcommand_matches("export") {
+            Self::parse_matches_cas_export(m)
+        } else if let Some(m) = matches.subcommand_matches("import") {
+            Self::parse_matches_cas_import(m)
         } else if let Some(m) = matches.subcommand_matches("show") {
             Self::parse_matches_cas_show(m)
         } else if let Some(m) = matches.subcommand_matches("history") {
@@ -1507,6 +2713,12 @@ impl Options {
             Self::parse_matches_cas_keyroll(m)
         } else if let Some(m) = matches.subcommand_matches("roas") {
             Self::parse_matches_cas_routes(m)
+        } else if let Some(m) = matches.subcommand_matches("aspas") {
+            Self::parse_matches_cas_aspas(m)
+        } else if let Some(m) = matches.subcommand_matches("ghostbusters") {
+            Self::parse_matches_cas_ghostbusters(m)
+        } else if let Some(m) = matches.subcommand_matches("timing") {
+            Self::parse_matches_cas_timing(m)
         } else if let Some(m) = matches.subcommand_matches("repo") {
             Self::parse_matches_cas_repo(m)
         } else if let Some(m) = matches.subcommand_matches("issues") {
@@ -1562,6 +2774,18 @@ pub enum CaCommand {
     #[display(fmt = "init ca: '{}'", _0)]
     Init(CertAuthInit),
 
+    // Delete a CA
+    #[display(fmt = "delete ca: '{}'", _0)]
+    Delete(Handle),
+
+    // Export a CA, including its keys
+    #[display(fmt = "export ca: '{}'", _0)]
+    Export(Handle, CaExportRequest),
+
+    // Import a CA exported from another server
+    #[display(fmt = "import ca: '{}'", _0)]
+    Import(Handle, CaImportRequest),
+
     // Update CA id
     #[display(fmt = "update id for ca: '{}'", _0)]
     UpdateId(Handle),
@@ -1609,6 +2833,9 @@ pub enum CaCommand {
     #[display(fmt = "update child {} of ca: '{}'", _1, _0)]
     ChildUpdate(Handle, ChildHandle, UpdateChildRequest),
 
+    #[display(fmt = "add lease for child {} of ca: '{}' -> {}", _1, _0, _2)]
+    ChildLeaseAdd(Handle, ChildHandle, ResourceLease),
+
     #[display(fmt = "delete child {} of ca: '{}'", _1, _0)]
     ChildDelete(Handle, ChildHandle),
 
@@ -1618,12 +2845,71 @@ pub enum CaCommand {
     #[display(fmt = "activate key roll for ca: '{}'", _0)]
     KeyRollActivate(Handle),
 
+    #[display(fmt = "show key roll policy for ca: '{}'", _0)]
+    KeyRollPolicyShow(Handle),
+
+    #[display(fmt = "Update key roll policy for ca: '{}' -> {}", _0, _1)]
+    KeyRollPolicyUpdate(Handle, KeyRollPolicy),
+
     #[display(fmt = "list ROAS for ca: '{}'", _0)]
     RouteAuthorizationsList(Handle),
 
     #[display(fmt = "Update ROAS for ca: '{}' -> {}", _0, _1)]
     RouteAuthorizationsUpdate(Handle, RoaDefinitionUpdates),
 
+    #[display(fmt = "Import ROAS for ca: '{}' -> {}", _0, _1)]
+    RouteAuthorizationsImport(Handle, RoaImport),
+
+    #[display(fmt = "Export ROAS for ca: '{}' as {}", _0, _1)]
+    RouteAuthorizationsExport(Handle, RoaDefinitionFormat),
+
+    #[display(fmt = "analyze ROAs against BGP for ca: '{}'", _0)]
+    BgpAnalysisFull(Handle),
+
+    #[display(fmt = "dry run ROA update against BGP for ca: '{}' -> {}", _0, _1)]
+    BgpAnalysisDryRun(Handle, RoaDefinitionUpdates),
+
+    #[display(fmt = "suggest ROA updates from BGP for ca: '{}'", _0)]
+    BgpAnalysisSuggest(Handle),
+
+    #[display(fmt = "show ROA packing policy for ca: '{}'", _0)]
+    RoaPolicyShow(Handle),
+
+    #[display(fmt = "Update ROA packing policy for ca: '{}' -> {}", _0, _1)]
+    RoaPolicyUpdate(Handle, RoaPackingPolicy),
+
+    #[display(fmt = "list ASPAs for ca: '{}'", _0)]
+    AspasList(Handle),
+
+    #[display(fmt = "Update ASPAs for ca: '{}' -> {}", _0, _1)]
+    AspasUpdate(Handle, AspaDefinitionUpdates),
+
+    #[display(
+        fmt = "Update ASPA providers for customer {} in ca: '{}' -> {}",
+        _1,
+        _0,
+        _2
+    )]
+    AspasUpdateProviders(Handle, AsNumber, AspaProvidersUpdate),
+
+    #[display(fmt = "Show Ghostbusters record for ca: '{}'", _0)]
+    GhostbusterShow(Handle),
+
+    #[display(fmt = "Update Ghostbusters record for ca: '{}' -> {}", _0, _1)]
+    GhostbusterUpdate(Handle, GhostbusterVcard),
+
+    #[display(fmt = "Remove Ghostbusters record for ca: '{}'", _0)]
+    GhostbusterRemove(Handle),
+
+    #[display(fmt = "Show publication timing for ca: '{}'", _0)]
+    PublicationTimingShow(Handle),
+
+    #[display(fmt = "Update publication timing for ca: '{}' -> {}", _0, _1)]
+    PublicationTimingUpdate(Handle, PublicationTiming),
+
+    #[display(fmt = "Reset publication timing to default for ca: '{}'", _0)]
+    PublicationTimingReset(Handle),
+
     // Show details for this CA
     #[display(fmt = "Show details for ca: '{}'", _0)]
     Show(Handle),
@@ -1663,33 +2949,35 @@ impl Default for HistoryOptions {
     }
 }
 
-impl fmt::Display for HistoryOptions {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut s = if self.short { "short" } else { "full" }.to_string();
-
+impl HistoryOptions {
+    /// Returns the rows, offset, after and before path elements, as far as
+    /// they are needed.
+    pub fn range(&self) -> String {
         if let Some(before) = self.before {
             let after = self.after.map(|t| t.timestamp()).unwrap_or_else(|| 0);
-            s.push_str(&format!(
+            format!(
                 "/{}/{}/{}/{}",
                 self.rows,
                 self.offset,
                 after,
                 before.timestamp()
-            ));
+            )
         } else if let Some(after) = self.after {
-            s.push_str(&format!(
-                "/{}/{}/{}",
-                self.rows,
-                self.offset,
-                after.timestamp()
-            ));
+            format!("/{}/{}/{}", self.rows, self.offset, after.timestamp())
         } else if self.offset != 0 {
-            s.push_str(&format!("/{}/{}", self.rows, self.offset));
+            format!("/{}/{}", self.rows, self.offset)
         } else if self.rows != 100 {
-            s.push_str(&format!("/{}", self.rows));
+            format!("/{}", self.rows)
+        } else {
+            String::new()
         }
+    }
+}
 
-        write!(f, "{}", s)
+impl fmt::Display for HistoryOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mode = if self.short { "short" } else { "full" };
+        write!(f, "{}{}", mode, self.range())
     }
 }
 
@@ -1711,6 +2999,9 @@ pub enum PublishersCommand {
     #[display(fmt = "Add publisher")]
     AddPublisher(rfc8183::PublisherRequest),
 
+    #[display(fmt = "Update identity certificate for publisher '{}'", _0)]
+    UpdatePublisherIdCert(PublisherHandle, rfc8183::PublisherRequest),
+
     #[display(fmt = "Show publisher '{}", _0)]
     ShowPublisher(PublisherHandle),
 
@@ -1720,12 +3011,39 @@ pub enum PublishersCommand {
     #[display(fmt = "reposisitory response for publisher '{}'", _0)]
     RepositoryResponse(PublisherHandle),
 
+    #[display(fmt = "Update quota for publisher '{}' to: {}", _0, _1)]
+    UpdateQuota(PublisherHandle, PublisherQuota),
+
+    #[display(fmt = "Suspend publisher '{}'", _0)]
+    SuspendPublisher(PublisherHandle),
+
+    #[display(fmt = "Reactivate publisher '{}'", _0)]
+    ReactivatePublisher(PublisherHandle),
+
+    #[display(fmt = "Show objects for publisher '{}'", _0)]
+    ShowObjects(PublisherHandle),
+
+    #[display(fmt = "Show history for publisher '{}'", _0)]
+    History(PublisherHandle, HistoryOptions),
+
+    #[display(fmt = "Verify repository content, repair: {}", _0)]
+    VerifyRepository(bool),
+
+    #[display(fmt = "Reset RRDP session")]
+    ResetRrdpSession,
+
+    #[display(fmt = "Show rsyncd.conf module")]
+    RsyncdConf,
+
     #[display(
         fmt = "Show publishers which last published longer than '{}' seconds ago",
         _0
     )]
     StalePublishers(i64),
 
+    #[display(fmt = "Show stale publisher issues")]
+    Issues,
+
     #[display(fmt = "Show server stats")]
     Stats,
 
@@ -6,11 +6,14 @@ use serde::Serialize;
 use rpki::x509::Time;
 
 use crate::commons::api::{
-    AllCertAuthIssues, CaCommandDetails, CaCommandResult, CaRepoDetails, CertAuthInfo,
-    CertAuthIssues, CertAuthList, ChildCaInfo, CommandHistory, CurrentObjects, CurrentRepoState,
-    ParentCaContact, PublisherDetails, PublisherList, RepositoryContact, RoaDefinition, ServerInfo,
-    StoredEffect,
+    AllCertAuthIssues, AspaDefinition, CaCommandDetails, CaCommandResult, CaExportArchive,
+    CaRepoDetails, CertAuthInfo, CertAuthIssues, CertAuthList, ChildCaInfo, CommandHistory,
+    CurrentObjects, CurrentRepoState, GhostbusterVcard, KeyRollPolicy, ParentCaContact,
+    PublicationTiming, PublishedObjectDetails, PublisherDetails, PublisherHistory, PublisherList,
+    PublisherObjects, RepositoryContact, RepositoryIssues, RepositoryVerification, RoaDefinition,
+    RoaDefinitionUpdates, RoaPackingPolicy, ServerInfo, StoredEffect,
 };
+use crate::commons::bgp::{BgpAnalysisDryRun, BgpAnalysisReport};
 use crate::commons::eventsourcing::WithStorableDetails;
 use crate::commons::remote::api::ClientInfo;
 use crate::commons::remote::rfc8183;
@@ -30,6 +33,15 @@ pub enum ApiResponse {
     CertAuthAction(CaCommandDetails),
     CertAuths(CertAuthList),
     RouteAuthorizations(Vec<RoaDefinition>),
+    RoaPackingPolicy(RoaPackingPolicy),
+    KeyRollPolicy(KeyRollPolicy),
+    BgpAnalysisFull(BgpAnalysisReport),
+    BgpAnalysisDryRun(BgpAnalysisDryRun),
+    RoaSuggestions(RoaDefinitionUpdates),
+    RouteAuthorizationUpdates(RoaDefinitionUpdates),
+    AspaDefinitions(Vec<AspaDefinition>),
+    Ghostbuster(Option<GhostbusterVcard>),
+    PublicationTiming(PublicationTiming),
 
     ParentCaContact(ParentCaContact),
 
@@ -37,7 +49,11 @@ pub enum ApiResponse {
 
     PublisherDetails(PublisherDetails),
     PublisherList(PublisherList),
+    PublisherHistory(PublisherHistory),
+    PublisherObjects(PublisherObjects),
     RepoStats(RepoStats),
+    RepositoryVerification(RepositoryVerification),
+    RepositoryIssues(RepositoryIssues),
 
     Rfc8181ClientList(Vec<ClientInfo>),
     Rfc8183RepositoryResponse(rfc8183::RepositoryResponse),
@@ -50,6 +66,8 @@ pub enum ApiResponse {
     CertAuthIssues(CertAuthIssues),
     AllCertAuthIssues(AllCertAuthIssues),
 
+    CaExportArchive(CaExportArchive),
+
     Empty,               // Typically a successful post just gets an empty 200 response
     GenericBody(String), // For when the server echos Json to a successful post
 }
@@ -69,17 +87,33 @@ impl ApiResponse {
                 ApiResponse::CertAuthIssues(issues) => Ok(Some(issues.report(fmt)?)),
                 ApiResponse::AllCertAuthIssues(issues) => Ok(Some(issues.report(fmt)?)),
                 ApiResponse::RouteAuthorizations(auths) => Ok(Some(auths.report(fmt)?)),
+                ApiResponse::RoaPackingPolicy(policy) => Ok(Some(policy.report(fmt)?)),
+                ApiResponse::KeyRollPolicy(policy) => Ok(Some(policy.report(fmt)?)),
+                ApiResponse::BgpAnalysisFull(report) => Ok(Some(report.report(fmt)?)),
+                ApiResponse::BgpAnalysisDryRun(dry_run) => Ok(Some(dry_run.report(fmt)?)),
+                ApiResponse::RoaSuggestions(suggestion) => Ok(Some(suggestion.report(fmt)?)),
+                ApiResponse::RouteAuthorizationUpdates(updates) => Ok(Some(updates.report(fmt)?)),
+                ApiResponse::AspaDefinitions(aspas) => Ok(Some(aspas.report(fmt)?)),
+                ApiResponse::Ghostbuster(vcard) => Ok(Some(vcard.report(fmt)?)),
+                ApiResponse::PublicationTiming(timing) => Ok(Some(timing.report(fmt)?)),
                 ApiResponse::ParentCaContact(contact) => Ok(Some(contact.report(fmt)?)),
                 ApiResponse::ChildInfo(info) => Ok(Some(info.report(fmt)?)),
                 ApiResponse::PublisherList(list) => Ok(Some(list.report(fmt)?)),
                 ApiResponse::PublisherDetails(details) => Ok(Some(details.report(fmt)?)),
+                ApiResponse::PublisherHistory(history) => Ok(Some(history.report(fmt)?)),
+                ApiResponse::PublisherObjects(objects) => Ok(Some(objects.report(fmt)?)),
                 ApiResponse::RepoStats(stats) => Ok(Some(stats.report(fmt)?)),
+                ApiResponse::RepositoryVerification(verification) => {
+                    Ok(Some(verification.report(fmt)?))
+                }
+                ApiResponse::RepositoryIssues(issues) => Ok(Some(issues.report(fmt)?)),
                 ApiResponse::Rfc8181ClientList(list) => Ok(Some(list.report(fmt)?)),
                 ApiResponse::Rfc8183ChildRequest(req) => Ok(Some(req.report(fmt)?)),
                 ApiResponse::Rfc8183PublisherRequest(req) => Ok(Some(req.report(fmt)?)),
                 ApiResponse::Rfc8183RepositoryResponse(res) => Ok(Some(res.report(fmt)?)),
                 ApiResponse::RepoDetails(details) => Ok(Some(details.report(fmt)?)),
                 ApiResponse::RepoState(state) => Ok(Some(state.report(fmt)?)),
+                ApiResponse::CaExportArchive(archive) => Ok(Some(archive.report(fmt)?)),
                 ApiResponse::GenericBody(body) => Ok(Some(body.clone())),
                 ApiResponse::Empty => Ok(None),
             }
@@ -205,6 +239,7 @@ impl Report for CertAuthInfo {
             res.push_str(&format!("Resource Class: {}\n", name,));
             res.push_str(&format!("Parent: {}\n", rc.parent_handle()));
             res.push_str(&format!("{}", rc.keys()));
+            res.push_str(&format!("Key roll: {}\n", rc.key_roll()));
 
             res.push_str("Current objects:\n");
             print_objects(&mut res, rc.current_objects());
@@ -219,6 +254,13 @@ impl Report for CertAuthInfo {
         } else {
             res.push_str("<none>\n");
         }
+        res.push_str("\n");
+
+        res.push_str(&format!("Key roll policy: {}\n", self.keyroll_policy()));
+        res.push_str(&format!(
+            "Publication timing: {}\n",
+            self.publication_timing()
+        ));
 
         Ok(res)
     }
@@ -248,6 +290,203 @@ impl Report for CommandHistory {
     }
 }
 
+impl Report for PublisherObjects {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+
+        for object in self.objects() {
+            res.push_str(&format!("uri: {}\n", object.uri()));
+            res.push_str(&format!("  hash: {}\n", object.hash()));
+            res.push_str(&format!("  size: {}\n", object.size()));
+
+            match object.details() {
+                PublishedObjectDetails::Certificate {
+                    ski,
+                    aki,
+                    resources,
+                    not_before,
+                    not_after,
+                } => {
+                    res.push_str("  type: certificate\n");
+                    res.push_str(&format!("  ski: {}\n", ski));
+                    if let Some(aki) = aki {
+                        res.push_str(&format!("  aki: {}\n", aki));
+                    }
+                    match resources {
+                        Some(resources) => res.push_str(&format!("  resources: {}\n", resources)),
+                        None => res.push_str("  resources: inherit\n"),
+                    }
+                    res.push_str(&format!(
+                        "  validity: {} - {}\n",
+                        not_before.to_rfc3339(),
+                        not_after.to_rfc3339()
+                    ));
+                }
+                PublishedObjectDetails::Manifest {
+                    number,
+                    this_update,
+                    next_update,
+                    entries,
+                } => {
+                    res.push_str("  type: manifest\n");
+                    res.push_str(&format!("  number: {}\n", number));
+                    res.push_str(&format!("  this update: {}\n", this_update.to_rfc3339()));
+                    res.push_str(&format!("  next update: {}\n", next_update.to_rfc3339()));
+                    res.push_str("  entries:\n");
+                    for entry in entries {
+                        res.push_str(&format!("    {} {}\n", entry.hash(), entry.file()));
+                    }
+                }
+                PublishedObjectDetails::Crl {
+                    number,
+                    this_update,
+                    next_update,
+                    revoked,
+                } => {
+                    res.push_str("  type: crl\n");
+                    res.push_str(&format!("  number: {}\n", number));
+                    res.push_str(&format!("  this update: {}\n", this_update.to_rfc3339()));
+                    res.push_str(&format!("  next update: {}\n", next_update.to_rfc3339()));
+                    res.push_str("  revoked serials:");
+                    for serial in revoked {
+                        res.push_str(&format!(" {}", serial));
+                    }
+                    res.push_str("\n");
+                }
+                PublishedObjectDetails::Roa {
+                    definitions,
+                    not_after,
+                } => {
+                    res.push_str("  type: roa\n");
+                    res.push_str(&format!("  not after: {}\n", not_after.to_rfc3339()));
+                    for definition in definitions {
+                        res.push_str(&format!("  {}\n", definition));
+                    }
+                }
+                PublishedObjectDetails::Ghostbusters { vcard, not_after } => {
+                    res.push_str("  type: ghostbusters\n");
+                    res.push_str(&format!("  not after: {}\n", not_after.to_rfc3339()));
+                    for line in vcard.lines() {
+                        res.push_str(&format!("  {}\n", line));
+                    }
+                }
+                PublishedObjectDetails::Unknown => res.push_str("  type: unknown\n"),
+                PublishedObjectDetails::Invalid { error } => {
+                    res.push_str(&format!("  invalid: {}\n", error))
+                }
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl Report for PublisherHistory {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+
+        res.push_str("time::serial::published::updated::withdrawn::success\n");
+
+        for record in self.records() {
+            let serial = match record.serial() {
+                Some(serial) => serial.to_string(),
+                None => "-".to_string(),
+            };
+            let success_string = match record.error() {
+                Some(msg) => format!("ERROR -> {}", msg),
+                None => "OK".to_string(),
+            };
+            res.push_str(&format!(
+                "{}::{}::{}::{}::{}::{}\n",
+                record.time().to_rfc3339_opts(SecondsFormat::Secs, true),
+                serial,
+                record.published(),
+                record.updated(),
+                record.withdrawn(),
+                success_string
+            ))
+        }
+
+        Ok(res)
+    }
+}
+
+impl Report for RepositoryVerification {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+
+        res.push_str(&format!(
+            "RRDP session: {}, serial: {}\n",
+            self.session(),
+            self.serial()
+        ));
+
+        if self.is_consistent() {
+            res.push_str("All content is consistent.\n");
+            return Ok(res);
+        }
+
+        for publisher in self.publishers() {
+            res.push_str(&format!("publisher: {}\n", publisher.publisher()));
+            if !publisher.rsync().is_empty() {
+                res.push_str("  rsync:\n");
+                for diff in publisher.rsync() {
+                    res.push_str(&format!("    {}\n", diff));
+                }
+            }
+            if !publisher.rrdp().is_empty() {
+                res.push_str("  rrdp snapshot:\n");
+                for diff in publisher.rrdp() {
+                    res.push_str(&format!("    {}\n", diff));
+                }
+            }
+        }
+
+        if !self.unowned_rsync().is_empty() {
+            res.push_str("rsync objects not owned by any publisher:\n");
+            for uri in self.unowned_rsync() {
+                res.push_str(&format!("  {}\n", uri));
+            }
+        }
+
+        if !self.unowned_rrdp().is_empty() {
+            res.push_str("rrdp snapshot objects not owned by any publisher:\n");
+            for uri in self.unowned_rrdp() {
+                res.push_str(&format!("  {}\n", uri));
+            }
+        }
+
+        if !self.rrdp_files().is_empty() {
+            res.push_str("rrdp files:\n");
+            for diff in self.rrdp_files() {
+                res.push_str(&format!("  {}\n", diff));
+            }
+        }
+
+        let ipfs = self.ipfs();
+        if !ipfs.is_consistent() {
+            let unknown = "unknown".to_string();
+            res.push_str(&format!(
+                "ipfs root: expected {}, published {}\n",
+                ipfs.expected().unwrap_or(&unknown),
+                ipfs.published().unwrap_or(&unknown)
+            ));
+        }
+
+        if self.needs_new_session() {
+            res.push_str("The RRDP snapshot is wrong, a new RRDP session is needed.\n");
+        }
+
+        if self.repaired() {
+            res.push_str("Repaired: all content was rebuilt.\n");
+        } else {
+            res.push_str("Use --repair to rebuild all content.\n");
+        }
+
+        Ok(res)
+    }
+}
+
 impl Report for CaCommandDetails {
     fn text(&self) -> Result<String, ReportError> {
         let mut res = String::new();
@@ -312,6 +551,9 @@ impl Report for PublisherList {
                 first = false;
             }
             res.push_str(p.handle().as_str());
+            if p.suspended() {
+                res.push_str(" (suspended)");
+            }
         }
         Ok(res)
     }
@@ -327,6 +569,19 @@ impl Report for RepoStats {
         res.push_str(&format!("RRDP session: {}\n", self.session()));
         res.push_str(&format!("RRDP serial:  {}\n", self.serial()));
         res.push_str("\n");
+
+        let retention = self.retention();
+        res.push_str(&format!("RRDP retention: {}\n", retention.policy()));
+        res.push_str(&format!(
+            "RRDP deltas: {}, size: {}, snapshot size: {}",
+            retention.deltas(),
+            retention.deltas_size(),
+            retention.snapshot_size()
+        ));
+        if let Some(oldest) = retention.oldest_delta() {
+            res.push_str(&format!(", oldest serial: {}", oldest));
+        }
+        res.push_str("\n\n");
         res.push_str("Publisher, Objects, Size, Last Updated\n");
         for (publisher, stats) in self.get_publishers() {
             res.push_str(&format!(
@@ -352,6 +607,18 @@ impl Report for PublisherDetails {
         res.push_str(&format!("handle: {}\n", self.handle()));
         res.push_str(&format!("id: {}", self.id_cert().ski_hex()));
         res.push_str(&format!("base uri: {}\n", self.base_uri().to_string()));
+        if self.suspended() {
+            res.push_str("status: suspended\n");
+        }
+
+        let usage = self.usage();
+        res.push_str(&format!("quota: {}\n", self.quota()));
+        res.push_str(&format!(
+            "usage: objects: {}, size: {}, largest object: {}\n",
+            usage.objects(),
+            usage.size(),
+            usage.largest_object()
+        ));
 
         Ok(res)
     }
@@ -407,6 +674,150 @@ impl Report for Vec<RoaDefinition> {
     }
 }
 
+impl Report for Vec<AspaDefinition> {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+        for a in self.iter() {
+            res.push_str(&format!("{}\n", a));
+        }
+        Ok(res)
+    }
+}
+
+impl Report for KeyRollPolicy {
+    fn text(&self) -> Result<String, ReportError> {
+        Ok(format!("{}\n", self))
+    }
+}
+
+impl Report for CaExportArchive {
+    fn text(&self) -> Result<String, ReportError> {
+        // The export is only useful as json, so that it can be imported.
+        Ok(self.json())
+    }
+}
+
+impl Report for PublicationTiming {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+        res.push_str(&format!(
+            "Next update:     {} hours\n",
+            self.next_update_hours()
+        ));
+        res.push_str(&format!("Validity:        {} days\n", self.valid_days()));
+        res.push_str(&format!(
+            "Republish:       {} hours before next update\n",
+            self.republish_hours()
+        ));
+        Ok(res)
+    }
+}
+
+impl Report for RoaPackingPolicy {
+    fn text(&self) -> Result<String, ReportError> {
+        Ok(format!("{}\n", self))
+    }
+}
+
+impl Report for BgpAnalysisReport {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+
+        match self.loaded() {
+            Some(loaded) => res.push_str(&format!(
+                "BGP announcements loaded at: {}\n",
+                loaded.to_rfc3339_opts(SecondsFormat::Secs, true)
+            )),
+            None => res.push_str("BGP announcements not loaded\n"),
+        }
+
+        res.push_str("\nAnnouncements:\n");
+        for entry in self.entries() {
+            res.push_str(&format!("  {} ({})", entry.announcement(), entry.state()));
+            if !entry.roas().is_empty() {
+                let roas: Vec<String> = entry.roas().iter().map(|r| r.to_string()).collect();
+                res.push_str(&format!(" ROAs: {}", roas.join(", ")));
+            }
+            res.push('\n');
+        }
+
+        if !self.roas_unseen().is_empty() {
+            res.push_str("\nROAs that do not make any announcement valid:\n");
+            for roa in self.roas_unseen() {
+                res.push_str(&format!("  {}\n", roa));
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl Report for RoaDefinitionUpdates {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut added: Vec<String> = self.added().iter().map(|a| format!("A: {}", a)).collect();
+        let mut removed: Vec<String> = self.removed().iter().map(|r| format!("R: {}", r)).collect();
+        added.sort();
+        removed.sort();
+
+        let mut res = String::new();
+        for line in added.into_iter().chain(removed.into_iter()) {
+            res.push_str(&line);
+            res.push('\n');
+        }
+        Ok(res)
+    }
+}
+
+impl Report for BgpAnalysisDryRun {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+
+        if self.changes().is_empty() {
+            res.push_str("No announcements would change validity.\n");
+        } else {
+            res.push_str("Announcements that would change validity:\n");
+            for change in self.changes() {
+                res.push_str(&format!(
+                    "  {}: {} -> {}\n",
+                    change.announcement(),
+                    change.before(),
+                    change.after()
+                ));
+            }
+        }
+
+        res.push_str("\nAfter the update:\n");
+        res.push_str(&self.report().text()?);
+
+        Ok(res)
+    }
+}
+
+impl Report for Option<GhostbusterVcard> {
+    fn text(&self) -> Result<String, ReportError> {
+        match self {
+            None => Ok("No Ghostbusters record configured\n".to_string()),
+            Some(vcard) => {
+                let mut res = String::new();
+                res.push_str(&format!("Full name:    {}\n", vcard.full_name()));
+                if let Some(org) = vcard.org() {
+                    res.push_str(&format!("Organization: {}\n", org));
+                }
+                if let Some(email) = vcard.email() {
+                    res.push_str(&format!("Email:        {}\n", email));
+                }
+                if let Some(tel) = vcard.tel() {
+                    res.push_str(&format!("Telephone:    {}\n", tel));
+                }
+                if let Some(adr) = vcard.adr() {
+                    res.push_str(&format!("Address:      {}\n", adr));
+                }
+                Ok(res)
+            }
+        }
+    }
+}
+
 impl Report for CaRepoDetails {
     fn text(&self) -> Result<String, ReportError> {
         let mut res = String::new();
@@ -469,6 +880,12 @@ impl Report for CertAuthIssues {
                     res.push_str(&format!("Parent '{}' has issue: {}\n", parent, issue));
                 }
             }
+            for (rcn, issue) in self.key_roll_issues().iter() {
+                res.push_str(&format!("Key roll in RC '{}' has issue: {}\n", rcn, issue));
+            }
+            for (child, issue) in self.child_issues().iter() {
+                res.push_str(&format!("Child '{}' has issue: {}\n", child, issue));
+            }
         }
         Ok(res)
     }
@@ -493,6 +910,35 @@ impl Report for AllCertAuthIssues {
                         res.push_str(&format!("   Parent '{}' has issue: {}\n", parent, issue));
                     }
                 }
+                for (rcn, issue) in issues.key_roll_issues().iter() {
+                    res.push_str(&format!(
+                        "   Key roll in RC '{}' has issue: {}\n",
+                        rcn, issue
+                    ));
+                }
+                for (child, issue) in issues.child_issues().iter() {
+                    res.push_str(&format!("   Child '{}' has issue: {}\n", child, issue));
+                }
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl Report for RepositoryIssues {
+    fn text(&self) -> Result<String, ReportError> {
+        let mut res = String::new();
+        if self.is_empty() {
+            res.push_str("no issues found\n");
+        } else {
+            let mut publishers: Vec<_> = self.publishers().iter().collect();
+            publishers.sort_by_key(|(publisher, _)| publisher.as_str());
+
+            for (publisher, issues) in publishers {
+                res.push_str(&format!("Publisher '{}' is stale:\n", publisher));
+                for issue in issues {
+                    res.push_str(&format!("   {}\n", issue));
+                }
             }
         }
         Ok(res)
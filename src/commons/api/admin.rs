@@ -1,5 +1,7 @@
 //! Support for admin tasks, such as managing publishers and RFC8181 clients
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::path::PathBuf;
 use std::str::{from_utf8_unchecked, FromStr};
@@ -9,13 +11,17 @@ use serde::de;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use rpki::cert::Cert;
-use rpki::crypto::Signer;
+use rpki::crl::Crl;
+use rpki::crypto::{KeyIdentifier, Signer};
+use rpki::manifest::Manifest;
+use rpki::roa::Roa;
+use rpki::sigobj::SignedObject;
 use rpki::uri;
-use rpki::x509::Time;
+use rpki::x509::{Serial, Time};
 
-use crate::commons::api::ca::{ResourceSet, TrustAnchorLocator};
-use crate::commons::api::rrdp::PublishElement;
-use crate::commons::api::{Link, RepoInfo};
+use crate::commons::api::ca::{ResourceLease, ResourceSet, TrustAnchorLocator};
+use crate::commons::api::rrdp::{PublishElement, RrdpSession};
+use crate::commons::api::{HexEncodedHash, Link, RepoInfo, RoaDefinition};
 use crate::commons::remote::id::IdCert;
 use crate::commons::remote::rfc8183;
 
@@ -167,11 +173,13 @@ impl fmt::Display for Token {
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct PublisherSummary {
     handle: PublisherHandle,
+    #[serde(default)]
+    suspended: bool,
     links: Vec<Link>,
 }
 
 impl PublisherSummary {
-    pub fn from(handle: &Handle, path_publishers: &str) -> PublisherSummary {
+    pub fn from(handle: &Handle, suspended: bool, path_publishers: &str) -> PublisherSummary {
         let mut links = Vec::new();
         let self_link = Link {
             rel: "self".to_string(),
@@ -181,6 +189,7 @@ impl PublisherSummary {
 
         PublisherSummary {
             handle: handle.clone(),
+            suspended,
             links,
         }
     }
@@ -188,6 +197,10 @@ impl PublisherSummary {
     pub fn handle(&self) -> &PublisherHandle {
         &self.handle
     }
+
+    pub fn suspended(&self) -> bool {
+        self.suspended
+    }
 }
 
 //------------ PublisherList -------------------------------------------------
@@ -199,10 +212,14 @@ pub struct PublisherList {
 }
 
 impl PublisherList {
-    pub fn build(publishers: &[Handle], path_publishers: &str) -> PublisherList {
+    pub fn build(
+        publishers: &[Handle],
+        suspended: &[Handle],
+        path_publishers: &str,
+    ) -> PublisherList {
         let publishers: Vec<PublisherSummary> = publishers
             .iter()
-            .map(|p| PublisherSummary::from(&p, path_publishers))
+            .map(|p| PublisherSummary::from(&p, suspended.contains(p), path_publishers))
             .collect();
 
         PublisherList { publishers }
@@ -213,6 +230,188 @@ impl PublisherList {
     }
 }
 
+//------------ PublisherQuota ------------------------------------------------
+
+/// Limits on what a publisher may have published in the repository. Sizes
+/// are measured in the same way as in the repository stats, i.e. as the
+/// length of the base64 encoded objects. A limit of None means unlimited.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherQuota {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_objects: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_object_size: Option<usize>,
+}
+
+impl PublisherQuota {
+    pub fn new(
+        max_objects: Option<usize>,
+        max_size: Option<usize>,
+        max_object_size: Option<usize>,
+    ) -> Self {
+        PublisherQuota {
+            max_objects,
+            max_size,
+            max_object_size,
+        }
+    }
+
+    pub fn max_objects(&self) -> Option<usize> {
+        self.max_objects
+    }
+
+    pub fn max_size(&self) -> Option<usize> {
+        self.max_size
+    }
+
+    pub fn max_object_size(&self) -> Option<usize> {
+        self.max_object_size
+    }
+}
+
+impl fmt::Display for PublisherQuota {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn limit(l: Option<usize>) -> String {
+            l.map(|l| l.to_string())
+                .unwrap_or_else(|| "unlimited".to_string())
+        }
+        write!(
+            f,
+            "max objects: {}, max size: {}, max object size: {}",
+            limit(self.max_objects),
+            limit(self.max_size),
+            limit(self.max_object_size)
+        )
+    }
+}
+
+//------------ StalePublisherThreshold ---------------------------------------
+
+/// Determines when a publisher is considered stale: when it did not publish
+/// for 'update_mins' minutes, or when any of its manifests will expire
+/// within 'manifest_mins' minutes.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StalePublisherThreshold {
+    update_mins: u64,
+    manifest_mins: u64,
+}
+
+impl StalePublisherThreshold {
+    pub fn new(update_mins: u64, manifest_mins: u64) -> Self {
+        StalePublisherThreshold {
+            update_mins,
+            manifest_mins,
+        }
+    }
+
+    pub fn update_mins(&self) -> u64 {
+        self.update_mins
+    }
+
+    pub fn manifest_mins(&self) -> u64 {
+        self.manifest_mins
+    }
+}
+
+impl fmt::Display for StalePublisherThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "not updated for {} minutes, or manifest expiring within {} minutes",
+            self.update_mins, self.manifest_mins
+        )
+    }
+}
+
+//------------ RepositoryIssues ----------------------------------------------
+
+/// Issues found for the publishers in the repository.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RepositoryIssues {
+    publishers: HashMap<PublisherHandle, Vec<PublisherIssue>>,
+}
+
+impl Default for RepositoryIssues {
+    fn default() -> Self {
+        RepositoryIssues {
+            publishers: HashMap::new(),
+        }
+    }
+}
+
+impl RepositoryIssues {
+    pub fn add(&mut self, publisher: PublisherHandle, issues: Vec<PublisherIssue>) {
+        self.publishers.insert(publisher, issues);
+    }
+
+    pub fn publishers(&self) -> &HashMap<PublisherHandle, Vec<PublisherIssue>> {
+        &self.publishers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.publishers.is_empty()
+    }
+}
+
+//------------ PublisherIssue ------------------------------------------------
+
+/// The reasons why a publisher can be considered stale.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublisherIssue {
+    NeverPublished,
+    NotUpdatedSince(Time),
+    ManifestExpires(Time),
+}
+
+impl fmt::Display for PublisherIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PublisherIssue::NeverPublished => write!(f, "never published"),
+            PublisherIssue::NotUpdatedSince(time) => {
+                write!(f, "not updated since {}", time.to_rfc3339())
+            }
+            PublisherIssue::ManifestExpires(time) => {
+                write!(f, "manifest expires at {}", time.to_rfc3339())
+            }
+        }
+    }
+}
+
+//------------ PublisherUsage ------------------------------------------------
+
+/// Current usage of a publisher, to compare against its quota.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherUsage {
+    objects: usize,
+    size: usize,
+    largest_object: usize,
+}
+
+impl PublisherUsage {
+    pub fn new(objects: usize, size: usize, largest_object: usize) -> Self {
+        PublisherUsage {
+            objects,
+            size,
+            largest_object,
+        }
+    }
+
+    pub fn objects(&self) -> usize {
+        self.objects
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn largest_object(&self) -> usize {
+        self.largest_object
+    }
+}
+
 //------------ PublisherDetails ----------------------------------------------
 
 /// This type defines the publisher details for:
@@ -223,6 +422,12 @@ pub struct PublisherDetails {
     id_cert: IdCert,
     base_uri: uri::Rsync,
     current_files: Vec<PublishElement>,
+    #[serde(default)]
+    suspended: bool,
+    #[serde(default)]
+    quota: PublisherQuota,
+    #[serde(default)]
+    usage: PublisherUsage,
 }
 
 impl PublisherDetails {
@@ -231,12 +436,18 @@ impl PublisherDetails {
         id_cert: IdCert,
         base_uri: &uri::Rsync,
         current_files: Vec<PublishElement>,
+        suspended: bool,
+        quota: PublisherQuota,
+        usage: PublisherUsage,
     ) -> Self {
         PublisherDetails {
             handle: handle.clone(),
             id_cert,
             base_uri: base_uri.clone(),
             current_files,
+            suspended,
+            quota,
+            usage,
         }
     }
 
@@ -252,6 +463,466 @@ impl PublisherDetails {
     pub fn current_files(&self) -> &Vec<PublishElement> {
         &self.current_files
     }
+    pub fn suspended(&self) -> bool {
+        self.suspended
+    }
+    pub fn quota(&self) -> &PublisherQuota {
+        &self.quota
+    }
+    pub fn usage(&self) -> &PublisherUsage {
+        &self.usage
+    }
+}
+
+//------------ PublisherObjects ----------------------------------------------
+
+/// All objects currently published by a publisher, with decoded details.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherObjects {
+    publisher: PublisherHandle,
+    objects: Vec<PublishedObject>,
+}
+
+impl PublisherObjects {
+    pub fn new(publisher: PublisherHandle, objects: Vec<PublishedObject>) -> Self {
+        PublisherObjects { publisher, objects }
+    }
+
+    pub fn publisher(&self) -> &PublisherHandle {
+        &self.publisher
+    }
+
+    pub fn objects(&self) -> &Vec<PublishedObject> {
+        &self.objects
+    }
+}
+
+//------------ PublishedObject -----------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublishedObject {
+    uri: uri::Rsync,
+    hash: HexEncodedHash,
+    size: usize,
+    details: PublishedObjectDetails,
+}
+
+impl PublishedObject {
+    pub fn uri(&self) -> &uri::Rsync {
+        &self.uri
+    }
+
+    pub fn hash(&self) -> &HexEncodedHash {
+        &self.hash
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn details(&self) -> &PublishedObjectDetails {
+        &self.details
+    }
+}
+
+impl From<&PublishElement> for PublishedObject {
+    fn from(element: &PublishElement) -> Self {
+        let bytes = element.base64().to_bytes();
+        let hash = HexEncodedHash::from_content(bytes.as_ref());
+        let details = PublishedObjectDetails::decode(element.uri(), bytes);
+
+        PublishedObject {
+            uri: element.uri().clone(),
+            hash,
+            size: element.size(),
+            details,
+        }
+    }
+}
+
+//------------ PublishedObjectDetails ----------------------------------------
+
+/// The decoded details of a published object, based on its file extension.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+#[allow(clippy::large_enum_variant)]
+pub enum PublishedObjectDetails {
+    Certificate {
+        ski: KeyIdentifier,
+        aki: Option<KeyIdentifier>,
+        /// Not set if the certificate inherits resources
+        resources: Option<ResourceSet>,
+        not_before: Time,
+        not_after: Time,
+    },
+    Manifest {
+        number: Serial,
+        this_update: Time,
+        next_update: Time,
+        entries: Vec<ManifestEntry>,
+    },
+    Crl {
+        number: Serial,
+        this_update: Time,
+        next_update: Time,
+        revoked: Vec<Serial>,
+    },
+    Roa {
+        definitions: Vec<RoaDefinition>,
+        not_after: Time,
+    },
+    Ghostbusters {
+        vcard: String,
+        not_after: Time,
+    },
+    Unknown,
+    Invalid {
+        error: String,
+    },
+}
+
+impl PublishedObjectDetails {
+    pub fn decode(uri: &uri::Rsync, bytes: Bytes) -> Self {
+        let uri = uri.to_string();
+
+        if uri.ends_with(".cer") {
+            match Cert::decode(bytes) {
+                Ok(cert) => Self::from(&cert),
+                Err(_) => Self::invalid("cannot parse certificate"),
+            }
+        } else if uri.ends_with(".mft") {
+            match Manifest::decode(bytes, true) {
+                Ok(mft) => Self::from(&mft),
+                Err(_) => Self::invalid("cannot parse manifest"),
+            }
+        } else if uri.ends_with(".crl") {
+            match Crl::decode(bytes) {
+                Ok(crl) => Self::from(&crl),
+                Err(_) => Self::invalid("cannot parse CRL"),
+            }
+        } else if uri.ends_with(".roa") {
+            match Roa::decode(bytes, true) {
+                Ok(roa) => Self::from(&roa),
+                Err(_) => Self::invalid("cannot parse ROA"),
+            }
+        } else if uri.ends_with(".gbr") {
+            match SignedObject::decode(bytes, true) {
+                Ok(gbr) => Self::ghostbusters(&gbr),
+                Err(_) => Self::invalid("cannot parse Ghostbusters record"),
+            }
+        } else {
+            PublishedObjectDetails::Unknown
+        }
+    }
+
+    /// The rpki library has no type for Ghostbusters records, so the vCard
+    /// is taken from the content of the signed object as is.
+    fn ghostbusters(gbr: &SignedObject) -> Self {
+        let content = gbr.content().to_bytes();
+        PublishedObjectDetails::Ghostbusters {
+            vcard: String::from_utf8_lossy(content.as_ref()).to_string(),
+            not_after: gbr.cert().validity().not_after(),
+        }
+    }
+
+    fn invalid(error: &str) -> Self {
+        PublishedObjectDetails::Invalid {
+            error: error.to_string(),
+        }
+    }
+}
+
+impl From<&Cert> for PublishedObjectDetails {
+    fn from(cert: &Cert) -> Self {
+        PublishedObjectDetails::Certificate {
+            ski: cert.subject_key_identifier(),
+            aki: cert.authority_key_identifier(),
+            resources: ResourceSet::try_from(cert).ok(),
+            not_before: cert.validity().not_before(),
+            not_after: cert.validity().not_after(),
+        }
+    }
+}
+
+impl From<&Manifest> for PublishedObjectDetails {
+    fn from(mft: &Manifest) -> Self {
+        let content = mft.content();
+        let entries = content
+            .iter()
+            .map(|entry| {
+                ManifestEntry::new(
+                    String::from_utf8_lossy(entry.file().as_ref()).to_string(),
+                    hex::encode(entry.hash()),
+                )
+            })
+            .collect();
+
+        PublishedObjectDetails::Manifest {
+            number: content.manifest_number(),
+            this_update: content.this_update(),
+            next_update: content.next_update(),
+            entries,
+        }
+    }
+}
+
+impl From<&Crl> for PublishedObjectDetails {
+    fn from(crl: &Crl) -> Self {
+        let revoked = crl
+            .revoked_certs()
+            .iter()
+            .map(|entry| entry.user_certificate())
+            .collect();
+
+        PublishedObjectDetails::Crl {
+            number: crl.crl_number(),
+            this_update: crl.this_update(),
+            next_update: crl.next_update(),
+            revoked,
+        }
+    }
+}
+
+impl From<&Roa> for PublishedObjectDetails {
+    fn from(roa: &Roa) -> Self {
+        PublishedObjectDetails::Roa {
+            definitions: RoaDefinition::from_roa(roa),
+            not_after: roa.cert().validity().not_after(),
+        }
+    }
+}
+
+//------------ ManifestEntry -------------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ManifestEntry {
+    file: String,
+    hash: String,
+}
+
+impl ManifestEntry {
+    pub fn new(file: String, hash: String) -> Self {
+        ManifestEntry { file, hash }
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// The hex encoded SHA-256 hash of the file.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+}
+
+//------------ RepositoryVerification ----------------------------------------
+
+/// The result of comparing the rsync tree, the RRDP files and the IPFS root
+/// with the objects that publishers have in the repository.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RepositoryVerification {
+    session: RrdpSession,
+    serial: u64,
+    /// Only publishers for which differences were found are included.
+    publishers: Vec<PublisherVerification>,
+    /// Objects in the rsync tree that do not belong to any publisher.
+    unowned_rsync: Vec<uri::Rsync>,
+    /// Objects in the RRDP snapshot that do not belong to any publisher.
+    unowned_rrdp: Vec<uri::Rsync>,
+    rrdp_files: Vec<RrdpFileDifference>,
+    ipfs: IpfsRootVerification,
+    #[serde(default)]
+    repaired: bool,
+}
+
+impl RepositoryVerification {
+    pub fn new(
+        session: RrdpSession,
+        serial: u64,
+        publishers: Vec<PublisherVerification>,
+        unowned_rsync: Vec<uri::Rsync>,
+        unowned_rrdp: Vec<uri::Rsync>,
+        rrdp_files: Vec<RrdpFileDifference>,
+        ipfs: IpfsRootVerification,
+    ) -> Self {
+        RepositoryVerification {
+            session,
+            serial,
+            publishers,
+            unowned_rsync,
+            unowned_rrdp,
+            rrdp_files,
+            ipfs,
+            repaired: false,
+        }
+    }
+
+    pub fn session(&self) -> RrdpSession {
+        self.session
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
+    pub fn publishers(&self) -> &Vec<PublisherVerification> {
+        &self.publishers
+    }
+
+    pub fn unowned_rsync(&self) -> &Vec<uri::Rsync> {
+        &self.unowned_rsync
+    }
+
+    pub fn unowned_rrdp(&self) -> &Vec<uri::Rsync> {
+        &self.unowned_rrdp
+    }
+
+    pub fn rrdp_files(&self) -> &Vec<RrdpFileDifference> {
+        &self.rrdp_files
+    }
+
+    pub fn ipfs(&self) -> &IpfsRootVerification {
+        &self.ipfs
+    }
+
+    pub fn repaired(&self) -> bool {
+        self.repaired
+    }
+
+    pub fn set_repaired(&mut self) {
+        self.repaired = true;
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.publishers.is_empty()
+            && self.unowned_rsync.is_empty()
+            && self.unowned_rrdp.is_empty()
+            && self.rrdp_files.is_empty()
+            && self.ipfs.is_consistent()
+    }
+
+    /// The RRDP snapshot kept by the repository no longer matches the
+    /// objects of its publishers. This cannot be fixed with a delta, because
+    /// there is no delta that validators can trust, so a new RRDP session
+    /// is needed.
+    pub fn needs_new_session(&self) -> bool {
+        !self.unowned_rrdp.is_empty() || self.publishers.iter().any(|p| !p.rrdp.is_empty())
+    }
+}
+
+//------------ PublisherVerification -----------------------------------------
+
+/// The differences found for the objects of a single publisher.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherVerification {
+    publisher: PublisherHandle,
+    rsync: Vec<ObjectDifference>,
+    rrdp: Vec<ObjectDifference>,
+}
+
+impl PublisherVerification {
+    pub fn new(
+        publisher: PublisherHandle,
+        rsync: Vec<ObjectDifference>,
+        rrdp: Vec<ObjectDifference>,
+    ) -> Self {
+        PublisherVerification {
+            publisher,
+            rsync,
+            rrdp,
+        }
+    }
+
+    pub fn publisher(&self) -> &PublisherHandle {
+        &self.publisher
+    }
+
+    pub fn rsync(&self) -> &Vec<ObjectDifference> {
+        &self.rsync
+    }
+
+    pub fn rrdp(&self) -> &Vec<ObjectDifference> {
+        &self.rrdp
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rsync.is_empty() && self.rrdp.is_empty()
+    }
+}
+
+//------------ ObjectDifference ----------------------------------------------
+
+/// A difference between the objects of a publisher, and what is actually
+/// published for it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectDifference {
+    Missing(uri::Rsync),
+    Unexpected(uri::Rsync),
+    Modified(uri::Rsync),
+}
+
+impl fmt::Display for ObjectDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjectDifference::Missing(uri) => write!(f, "missing: {}", uri),
+            ObjectDifference::Unexpected(uri) => write!(f, "unexpected: {}", uri),
+            ObjectDifference::Modified(uri) => write!(f, "modified: {}", uri),
+        }
+    }
+}
+
+//------------ RrdpFileDifference --------------------------------------------
+
+/// An RRDP file that is missing on disk, or does not have the content that
+/// is referenced in the notification file. The file is relative to the RRDP
+/// base directory.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RrdpFileDifference {
+    Missing(String),
+    Modified(String),
+}
+
+impl fmt::Display for RrdpFileDifference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RrdpFileDifference::Missing(file) => write!(f, "missing: {}", file),
+            RrdpFileDifference::Modified(file) => write!(f, "modified: {}", file),
+        }
+    }
+}
+
+//------------ IpfsRootVerification ------------------------------------------
+
+/// The root CID that the rsync tree should have in IPFS, and the root CID
+/// that is currently published under the repository's IPNS name. Either
+/// is not set if it could not be determined.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct IpfsRootVerification {
+    expected: Option<String>,
+    published: Option<String>,
+}
+
+impl IpfsRootVerification {
+    pub fn new(expected: Option<String>, published: Option<String>) -> Self {
+        IpfsRootVerification {
+            expected,
+            published,
+        }
+    }
+
+    pub fn expected(&self) -> Option<&String> {
+        self.expected.as_ref()
+    }
+
+    pub fn published(&self) -> Option<&String> {
+        self.published.as_ref()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.expected.is_some() && self.expected == self.published
+    }
 }
 
 //------------ PublisherClientRequest ----------------------------------------
@@ -573,16 +1244,23 @@ pub enum ChildAuthRequest {
 pub struct UpdateChildRequest {
     id_cert: Option<IdCert>,
     resources: Option<ResourceSet>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    leases: Option<Vec<ResourceLease>>,
 }
 
 impl UpdateChildRequest {
     pub fn new(id_cert: Option<IdCert>, resources: Option<ResourceSet>) -> Self {
-        UpdateChildRequest { id_cert, resources }
+        UpdateChildRequest {
+            id_cert,
+            resources,
+            leases: None,
+        }
     }
     pub fn id_cert(id_cert: IdCert) -> Self {
         UpdateChildRequest {
             id_cert: Some(id_cert),
             resources: None,
+            leases: None,
         }
     }
 
@@ -590,11 +1268,28 @@ impl UpdateChildRequest {
         UpdateChildRequest {
             id_cert: None,
             resources: Some(resources),
+            leases: None,
+        }
+    }
+
+    /// Replaces all time-limited resource leases for the child. Use an
+    /// empty list to remove all leases.
+    pub fn leases(leases: Vec<ResourceLease>) -> Self {
+        UpdateChildRequest {
+            id_cert: None,
+            resources: None,
+            leases: Some(leases),
         }
     }
 
-    pub fn unpack(self) -> (Option<IdCert>, Option<ResourceSet>) {
-        (self.id_cert, self.resources)
+    pub fn unpack(
+        self,
+    ) -> (
+        Option<IdCert>,
+        Option<ResourceSet>,
+        Option<Vec<ResourceLease>>,
+    ) {
+        (self.id_cert, self.resources, self.leases)
     }
 }
 
@@ -606,6 +1301,12 @@ impl fmt::Display for UpdateChildRequest {
         if let Some(resources) = &self.resources {
             write!(f, "new resources: {} ", resources)?;
         }
+        if let Some(leases) = &self.leases {
+            write!(f, "new leases: ")?;
+            for lease in leases {
+                write!(f, "{} ", lease)?;
+            }
+        }
         Ok(())
     }
 }
@@ -663,6 +1364,43 @@ mod tests {
         assert_eq!(handle.to_path_buf(), expected_path_buf);
     }
 
+    #[test]
+    fn should_decode_published_objects() {
+        let der = include_bytes!("../../../test-resources/ta.cer");
+        let uri = uri::Rsync::from_str("rsync://localhost/repo/ta/ta.cer").unwrap();
+        match PublishedObjectDetails::decode(&uri, Bytes::from_static(der)) {
+            PublishedObjectDetails::Certificate { aki, .. } => assert!(aki.is_none()),
+            _ => panic!("Expected certificate"),
+        }
+
+        let der = include_bytes!("../../../test-resources/validation/child.mft");
+        let uri = uri::Rsync::from_str("rsync://localhost/repo/child/0/child.mft").unwrap();
+        match PublishedObjectDetails::decode(&uri, Bytes::from_static(der)) {
+            PublishedObjectDetails::Manifest { entries, .. } => {
+                assert!(!entries.is_empty());
+                for entry in entries {
+                    assert!(!entry.file().is_empty());
+                    assert_eq!(64, entry.hash().len());
+                }
+            }
+            _ => panic!("Expected manifest"),
+        }
+
+        for name in &["0.roa", "0.gbr"] {
+            let uri = uri::Rsync::from_str(&format!("rsync://localhost/repo/ta/{}", name)).unwrap();
+            match PublishedObjectDetails::decode(&uri, Bytes::from_static(b"garbage")) {
+                PublishedObjectDetails::Invalid { .. } => {}
+                _ => panic!("Expected invalid object"),
+            }
+        }
+
+        let uri = uri::Rsync::from_str("rsync://localhost/repo/ta/file.txt").unwrap();
+        assert_eq!(
+            PublishedObjectDetails::decode(&uri, Bytes::from_static(b"text")),
+            PublishedObjectDetails::Unknown
+        );
+    }
+
     #[test]
     fn should_make_handle_from_dir() {
         let path = PathBuf::from("a/b/abcDEF012+=-_");
@@ -0,0 +1,359 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::commons::api::{AsNumber, AuthorizationFmtError};
+
+//------------ AspaDefinition ----------------------------------------------
+
+/// This type defines an Autonomous System Provider Authorization (ASPA), i.e.
+/// a customer ASN, and the provider ASNs that it authorizes to propagate its
+/// routes, optionally limited to one address family.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaDefinition {
+    customer: AsNumber,
+    providers: Vec<ProviderAs>,
+}
+
+impl AspaDefinition {
+    /// Creates a new definition. Providers are kept in order of their ASN,
+    /// as this is how they need to appear in the ASPA object.
+    pub fn new(customer: AsNumber, mut providers: Vec<ProviderAs>) -> Self {
+        providers.sort_by_key(|p| p.provider());
+        AspaDefinition {
+            customer,
+            providers,
+        }
+    }
+
+    pub fn customer(&self) -> AsNumber {
+        self.customer
+    }
+
+    pub fn providers(&self) -> &Vec<ProviderAs> {
+        &self.providers
+    }
+
+    /// Returns true if there is at least one provider, if no provider ASN is
+    /// listed more than once, and if the customer is not one of the providers.
+    pub fn providers_valid(&self) -> bool {
+        let mut seen = HashSet::new();
+        !self.providers.is_empty()
+            && self
+                .providers
+                .iter()
+                .all(|p| p.provider() != self.customer && seen.insert(p.provider()))
+    }
+
+    /// Returns a copy of this definition with its providers in order. Use this
+    /// for definitions that were deserialized from user input.
+    pub fn normalized(&self) -> Self {
+        AspaDefinition::new(self.customer, self.providers.clone())
+    }
+
+    /// Applies an update to the providers of this definition. Added providers
+    /// replace existing entries for the same ASN, e.g. to change the AFI limit.
+    pub fn apply_update(&mut self, update: &AspaProvidersUpdate) {
+        for removed in update.removed() {
+            self.providers.retain(|p| p.provider() != *removed);
+        }
+        for added in update.added() {
+            self.providers.retain(|p| p.provider() != added.provider());
+            self.providers.push(*added);
+        }
+        self.providers.sort_by_key(|p| p.provider());
+    }
+}
+
+impl FromStr for AspaDefinition {
+    type Err = AuthorizationFmtError;
+
+    // "65000 => 65001, 65002(v4), 65003(v6)"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split("=>");
+
+        let customer_str = parts.next().ok_or_else(|| AuthorizationFmtError::aspa(s))?;
+        let customer = AsNumber::from_str(customer_str)?;
+
+        let providers_str = parts.next().ok_or_else(|| AuthorizationFmtError::aspa(s))?;
+        if parts.next().is_some() {
+            return Err(AuthorizationFmtError::aspa(s));
+        }
+
+        let mut providers = vec![];
+        for provider_str in providers_str.split(',') {
+            providers.push(ProviderAs::from_str(provider_str)?);
+        }
+
+        Ok(AspaDefinition::new(customer, providers))
+    }
+}
+
+impl fmt::Display for AspaDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} =>", self.customer)?;
+        for (idx, provider) in self.providers.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, " {}", provider)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ ProviderAs --------------------------------------------------
+
+/// A provider ASN in an ASPA, with an optional limit to the address family
+/// for which it is authorized.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ProviderAs {
+    provider: AsNumber,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    afi_limit: Option<Afi>,
+}
+
+impl ProviderAs {
+    pub fn new(provider: AsNumber, afi_limit: Option<Afi>) -> Self {
+        ProviderAs {
+            provider,
+            afi_limit,
+        }
+    }
+
+    pub fn provider(&self) -> AsNumber {
+        self.provider
+    }
+
+    pub fn afi_limit(&self) -> Option<Afi> {
+        self.afi_limit
+    }
+}
+
+impl FromStr for ProviderAs {
+    type Err = AuthorizationFmtError;
+
+    // "65001" or "65001(v4)"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        match s.find('(') {
+            None => Ok(ProviderAs::new(AsNumber::from_str(s)?, None)),
+            Some(idx) => {
+                if !s.ends_with(')') {
+                    return Err(AuthorizationFmtError::aspa(s));
+                }
+                let provider = AsNumber::from_str(&s[..idx])?;
+                let afi = Afi::from_str(&s[idx + 1..s.len() - 1])?;
+                Ok(ProviderAs::new(provider, Some(afi)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for ProviderAs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.afi_limit {
+            None => write!(f, "{}", self.provider),
+            Some(afi) => write!(f, "{}({})", self.provider, afi),
+        }
+    }
+}
+
+//------------ Afi ---------------------------------------------------------
+
+/// The address family to which a provider authorization may be limited.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Afi {
+    V4,
+    V6,
+}
+
+impl Afi {
+    /// Returns the two octet Address Family Identifier, as used in the
+    /// afiLimit field of ASPA objects.
+    pub fn to_bytes(self) -> [u8; 2] {
+        match self {
+            Afi::V4 => [0, 1],
+            Afi::V6 => [0, 2],
+        }
+    }
+}
+
+impl FromStr for Afi {
+    type Err = AuthorizationFmtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "v4" => Ok(Afi::V4),
+            "v6" => Ok(Afi::V6),
+            _ => Err(AuthorizationFmtError::aspa(s)),
+        }
+    }
+}
+
+impl fmt::Display for Afi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Afi::V4 => write!(f, "v4"),
+            Afi::V6 => write!(f, "v6"),
+        }
+    }
+}
+
+//------------ AspaDefinitionUpdates ---------------------------------------
+
+/// This type defines an update to the ASPA definitions of a CA. Definitions
+/// in add_or_replace are added, or replace an existing definition for the
+/// same customer ASN. Definitions for customer ASNs in remove are removed.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaDefinitionUpdates {
+    add_or_replace: Vec<AspaDefinition>,
+    remove: Vec<AsNumber>,
+}
+
+impl AspaDefinitionUpdates {
+    pub fn new(add_or_replace: Vec<AspaDefinition>, remove: Vec<AsNumber>) -> Self {
+        AspaDefinitionUpdates {
+            add_or_replace,
+            remove,
+        }
+    }
+
+    pub fn add_or_replace(&self) -> &Vec<AspaDefinition> {
+        &self.add_or_replace
+    }
+
+    pub fn remove(&self) -> &Vec<AsNumber> {
+        &self.remove
+    }
+
+    pub fn unpack(self) -> (Vec<AspaDefinition>, Vec<AsNumber>) {
+        (self.add_or_replace, self.remove)
+    }
+}
+
+impl fmt::Display for AspaDefinitionUpdates {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.add_or_replace.is_empty() {
+            write!(f, "add or replace:")?;
+            for definition in &self.add_or_replace {
+                write!(f, " '{}'", definition)?;
+            }
+        }
+        if !self.remove.is_empty() {
+            if !self.add_or_replace.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "remove:")?;
+            for customer in &self.remove {
+                write!(f, " {}", customer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//------------ AspaProvidersUpdate -----------------------------------------
+
+/// This type defines an update to the providers of an existing ASPA
+/// definition.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaProvidersUpdate {
+    added: Vec<ProviderAs>,
+    removed: Vec<AsNumber>,
+}
+
+impl AspaProvidersUpdate {
+    pub fn new(added: Vec<ProviderAs>, removed: Vec<AsNumber>) -> Self {
+        AspaProvidersUpdate { added, removed }
+    }
+
+    pub fn added(&self) -> &Vec<ProviderAs> {
+        &self.added
+    }
+
+    pub fn removed(&self) -> &Vec<AsNumber> {
+        &self.removed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl fmt::Display for AspaProvidersUpdate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.added.is_empty() {
+            write!(f, "add:")?;
+            for provider in &self.added {
+                write!(f, " {}", provider)?;
+            }
+        }
+        if !self.removed.is_empty() {
+            if !self.added.is_empty() {
+                write!(f, " ")?;
+            }
+            write!(f, "remove:")?;
+            for provider in &self.removed {
+                write!(f, " {}", provider)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_aspa_definition() {
+        let def = AspaDefinition::from_str("AS65000 => 65003(v6), AS65001, 65002(v4)").unwrap();
+
+        assert_eq!(AsNumber::new(65000), def.customer());
+        assert_eq!(
+            &vec![
+                ProviderAs::new(AsNumber::new(65001), None),
+                ProviderAs::new(AsNumber::new(65002), Some(Afi::V4)),
+                ProviderAs::new(AsNumber::new(65003), Some(Afi::V6)),
+            ],
+            def.providers()
+        );
+        assert!(def.providers_valid());
+
+        assert_eq!("65000 => 65001, 65002(v4), 65003(v6)", def.to_string());
+
+        let json = serde_json::to_string(&def).unwrap();
+        let des: AspaDefinition = serde_json::from_str(&json).unwrap();
+        assert_eq!(def, des);
+
+        assert!(AspaDefinition::from_str("65000").is_err());
+        assert!(AspaDefinition::from_str("65000 => 65001(v5)").is_err());
+        assert!(AspaDefinition::from_str("65000 => 65001 => 65002").is_err());
+
+        let customer_as_provider = AspaDefinition::from_str("65000 => 65000").unwrap();
+        assert!(!customer_as_provider.providers_valid());
+
+        let duplicate_provider = AspaDefinition::from_str("65000 => 65001, 65001(v4)").unwrap();
+        assert!(!duplicate_provider.providers_valid());
+    }
+
+    #[test]
+    fn update_aspa_providers() {
+        let mut def = AspaDefinition::from_str("65000 => 65001, 65002").unwrap();
+
+        let update = AspaProvidersUpdate::new(
+            vec![
+                ProviderAs::from_str("65002(v6)").unwrap(),
+                ProviderAs::from_str("65003").unwrap(),
+            ],
+            vec![AsNumber::new(65001)],
+        );
+        def.apply_update(&update);
+
+        assert_eq!("65000 => 65002(v6), 65003", def.to_string());
+    }
+}
@@ -22,12 +22,13 @@ use rpki::x509::{Serial, Time};
 use crate::commons::api::publication;
 use crate::commons::api::publication::Publish;
 use crate::commons::api::{
-    Base64, ChildHandle, ErrorResponse, Handle, HexEncodedHash, IssuanceRequest, ListReply,
-    ParentCaContact, ParentHandle, RepositoryContact, RequestResourceLimit, RoaDefinition,
+    AsNumber, AspaDefinition, Base64, ChildHandle, ErrorResponse, Handle, HexEncodedHash,
+    IssuanceRequest, KeyRollPolicy, KeyRollStatus, ListReply, ParentCaContact, ParentHandle,
+    PublicationTiming, RepositoryContact, RequestResourceLimit, RoaDefinition,
 };
 use crate::commons::remote::id::IdCert;
 use crate::commons::util::ext_serde;
-use crate::daemon::ca::RouteAuthorization;
+use crate::daemon::ca::{Aspa, Ghostbuster, RoaAggregateKey, RouteAuthorization};
 use crate::ipfs::ipfs::{RepoPubKey, TalPubKey, PubKey};
 use rpki::uri::Scheme::Ipns;
 
@@ -148,13 +149,20 @@ impl From<&IdCert> for IdCertPem {
 pub struct ChildCaInfo {
     id_cert: Option<IdCertPem>,
     entitled_resources: ResourceSet,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    leases: Vec<ResourceLease>,
 }
 
 impl ChildCaInfo {
-    pub fn new(id_cert: Option<&IdCert>, entitled_resources: ResourceSet) -> Self {
+    pub fn new(
+        id_cert: Option<&IdCert>,
+        entitled_resources: ResourceSet,
+        leases: Vec<ResourceLease>,
+    ) -> Self {
         ChildCaInfo {
             id_cert: id_cert.map(IdCertPem::from),
             entitled_resources,
+            leases,
         }
     }
 
@@ -165,6 +173,10 @@ impl ChildCaInfo {
     pub fn entitled_resources(&self) -> &ResourceSet {
         &self.entitled_resources
     }
+
+    pub fn leases(&self) -> &Vec<ResourceLease> {
+        &self.leases
+    }
 }
 
 impl fmt::Display for ChildCaInfo {
@@ -173,7 +185,56 @@ impl fmt::Display for ChildCaInfo {
             writeln!(f, "{}", id.pem())?;
             writeln!(f, "SHA256 hash of PEM encoded certificate: {}", id.hash())?;
         }
-        writeln!(f, "resources: {}", self.entitled_resources)
+        writeln!(f, "resources: {}", self.entitled_resources)?;
+        for lease in &self.leases {
+            writeln!(f, "lease: {}", lease)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ ResourceLease -------------------------------------------------
+
+/// A block of resources delegated to a child for a limited time only.
+///
+/// The resources are included in the child's entitlements until the lease
+/// expires, and certificates issued to the child for these resources will
+/// not be valid beyond the end of the lease.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResourceLease {
+    resources: ResourceSet,
+    not_after: Time,
+}
+
+impl ResourceLease {
+    pub fn new(resources: ResourceSet, not_after: Time) -> Self {
+        ResourceLease {
+            resources,
+            not_after,
+        }
+    }
+
+    pub fn resources(&self) -> &ResourceSet {
+        &self.resources
+    }
+
+    pub fn not_after(&self) -> Time {
+        self.not_after
+    }
+
+    pub fn is_expired(&self, now: Time) -> bool {
+        self.not_after <= now
+    }
+}
+
+impl fmt::Display for ResourceLease {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} until {}",
+            self.resources,
+            self.not_after.to_rfc3339()
+        )
     }
 }
 
@@ -688,6 +749,34 @@ impl From<&Roa> for CurrentObject {
     }
 }
 
+impl From<&Aspa> for CurrentObject {
+    fn from(aspa: &Aspa) -> Self {
+        let content = Base64::from(aspa);
+        let serial = aspa.cert().serial_number();
+        let expires = aspa.cert().validity().not_after();
+
+        CurrentObject {
+            content,
+            serial,
+            expires,
+        }
+    }
+}
+
+impl From<&Ghostbuster> for CurrentObject {
+    fn from(gbr: &Ghostbuster) -> Self {
+        let content = Base64::from(gbr);
+        let serial = gbr.cert().serial_number();
+        let expires = gbr.cert().validity().not_after();
+
+        CurrentObject {
+            content,
+            serial,
+            expires,
+        }
+    }
+}
+
 //------------ ObjectName ----------------------------------------------------
 
 /// This type is used to represent the (deterministic) file names for
@@ -699,6 +788,12 @@ impl ObjectName {
     pub fn new(ki: &KeyIdentifier, extension: &str) -> Self {
         ObjectName(format!("{}.{}", ki, extension))
     }
+
+    /// There is at most one Ghostbusters record per resource class, so its
+    /// name does not depend on its content.
+    pub fn ghostbusters() -> Self {
+        ObjectName("ghostbusters.gbr".to_string())
+    }
 }
 
 impl From<&Cert> for ObjectName {
@@ -725,12 +820,30 @@ impl From<&RouteAuthorization> for ObjectName {
     }
 }
 
+impl From<&RoaAggregateKey> for ObjectName {
+    fn from(key: &RoaAggregateKey) -> Self {
+        ObjectName(format!("{}.roa", key))
+    }
+}
+
 impl From<&RoaDefinition> for ObjectName {
     fn from(def: &RoaDefinition) -> Self {
         ObjectName(format!("{}.roa", hex::encode(def.to_string())))
     }
 }
 
+impl From<&AsNumber> for ObjectName {
+    fn from(customer: &AsNumber) -> Self {
+        ObjectName(format!("AS{}.asa", customer))
+    }
+}
+
+impl From<&AspaDefinition> for ObjectName {
+    fn from(def: &AspaDefinition) -> Self {
+        Self::from(&def.customer())
+    }
+}
+
 impl Into<Bytes> for ObjectName {
     fn into(self) -> Bytes {
         Bytes::from(self.0)
@@ -1087,6 +1200,19 @@ impl ObjectsDelta {
         &self.withdrawn
     }
 
+    /// Adds the added, updated and withdrawn objects of one kind, e.g. all
+    /// ROAs, to this delta.
+    pub fn extend(
+        &mut self,
+        mut added: Vec<AddedObject>,
+        mut updated: Vec<UpdatedObject>,
+        mut withdrawn: Vec<WithdrawnObject>,
+    ) {
+        self.added.append(&mut added);
+        self.updated.append(&mut updated);
+        self.withdrawn.append(&mut withdrawn);
+    }
+
     pub fn len(&self) -> usize {
         self.added.len() + self.updated.len() + self.withdrawn.len()
     }
@@ -1599,6 +1725,8 @@ pub struct CertAuthInfo {
     resources: ResourceSet,
     resource_classes: HashMap<ResourceClassName, ResourceClassInfo>,
     children: Vec<ChildHandle>,
+    keyroll_policy: KeyRollPolicy,
+    publication_timing: PublicationTiming,
 }
 
 impl CertAuthInfo {
@@ -1609,6 +1737,8 @@ impl CertAuthInfo {
         parents: HashMap<ParentHandle, ParentCaContact>,
         resource_classes: HashMap<ResourceClassName, ResourceClassInfo>,
         children: Vec<ChildHandle>,
+        keyroll_policy: KeyRollPolicy,
+        publication_timing: PublicationTiming,
     ) -> Self {
         let parents = parents
             .into_iter()
@@ -1631,6 +1761,8 @@ impl CertAuthInfo {
             resources,
             resource_classes,
             children,
+            keyroll_policy,
+            publication_timing,
         }
     }
 
@@ -1662,6 +1794,15 @@ impl CertAuthInfo {
         &self.children
     }
 
+    pub fn keyroll_policy(&self) -> &KeyRollPolicy {
+        &self.keyroll_policy
+    }
+
+    /// The effective timing for manifests and CRLs for this CA.
+    pub fn publication_timing(&self) -> &PublicationTiming {
+        &self.publication_timing
+    }
+
     pub fn published_objects(&self) -> Vec<Publish> {
         let mut res = vec![];
 
@@ -1683,6 +1824,7 @@ pub struct ResourceClassInfo {
     name_space: String,
     parent_handle: ParentHandle,
     keys: ResourceClassKeysInfo,
+    key_roll: KeyRollStatus,
     current_objects: CurrentObjects,
 }
 
@@ -1691,12 +1833,14 @@ impl ResourceClassInfo {
         name_space: String,
         parent_handle: ParentHandle,
         keys: ResourceClassKeysInfo,
+        key_roll: KeyRollStatus,
         current_objects: CurrentObjects,
     ) -> Self {
         ResourceClassInfo {
             name_space,
             parent_handle,
             keys,
+            key_roll,
             current_objects,
         }
     }
@@ -1710,6 +1854,9 @@ impl ResourceClassInfo {
     pub fn keys(&self) -> &ResourceClassKeysInfo {
         &self.keys
     }
+    pub fn key_roll(&self) -> &KeyRollStatus {
+        &self.key_roll
+    }
 
     pub fn current_key(&self) -> Option<&CertifiedKeyInfo> {
         self.keys.current_key()
@@ -1901,6 +2048,10 @@ impl AllCertAuthIssues {
 pub struct CertAuthIssues {
     repo: Option<ErrorResponse>,
     parents: HashMap<ParentHandle, ErrorResponse>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    key_rolls: HashMap<ResourceClassName, ErrorResponse>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    children: HashMap<ChildHandle, ErrorResponse>,
 }
 
 impl Default for CertAuthIssues {
@@ -1908,6 +2059,8 @@ impl Default for CertAuthIssues {
         CertAuthIssues {
             repo: None,
             parents: HashMap::new(),
+            key_rolls: HashMap::new(),
+            children: HashMap::new(),
         }
     }
 }
@@ -1929,8 +2082,27 @@ impl CertAuthIssues {
         &self.parents
     }
 
+    pub fn add_key_roll_issue(&mut self, rcn: ResourceClassName, issue: ErrorResponse) {
+        self.key_rolls.insert(rcn, issue);
+    }
+
+    pub fn key_roll_issues(&self) -> &HashMap<ResourceClassName, ErrorResponse> {
+        &self.key_rolls
+    }
+
+    pub fn add_child_issue(&mut self, child: ChildHandle, issue: ErrorResponse) {
+        self.children.insert(child, issue);
+    }
+
+    pub fn child_issues(&self) -> &HashMap<ChildHandle, ErrorResponse> {
+        &self.children
+    }
+
     pub fn is_empty(&self) -> bool {
-        self.repo.is_none() && self.parents.is_empty()
+        self.repo.is_none()
+            && self.parents.is_empty()
+            && self.key_rolls.is_empty()
+            && self.children.is_empty()
     }
 }
 
@@ -0,0 +1,79 @@
+use crate::commons::api::Handle;
+use crate::commons::util::encrypt::EncryptedData;
+
+//------------ CaExportArchive -----------------------------------------------
+
+/// An export of a single CA, including its private keys and its contacts
+/// with parents, children and its repository. The content is encrypted with
+/// a passphrase, only the handle of the CA and the version of Krill that
+/// made the export are readable without it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CaExportArchive {
+    handle: Handle,
+    krill_version: String,
+    content: EncryptedData,
+}
+
+impl CaExportArchive {
+    pub fn new(handle: Handle, krill_version: String, content: EncryptedData) -> Self {
+        CaExportArchive {
+            handle,
+            krill_version,
+            content,
+        }
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    pub fn krill_version(&self) -> &str {
+        &self.krill_version
+    }
+
+    pub fn content(&self) -> &EncryptedData {
+        &self.content
+    }
+}
+
+//------------ CaExportRequest -----------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CaExportRequest {
+    passphrase: String,
+}
+
+impl CaExportRequest {
+    pub fn new(passphrase: String) -> Self {
+        CaExportRequest { passphrase }
+    }
+
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+}
+
+//------------ CaImportRequest -----------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CaImportRequest {
+    passphrase: String,
+    archive: CaExportArchive,
+}
+
+impl CaImportRequest {
+    pub fn new(passphrase: String, archive: CaExportArchive) -> Self {
+        CaImportRequest {
+            passphrase,
+            archive,
+        }
+    }
+
+    pub fn passphrase(&self) -> &str {
+        &self.passphrase
+    }
+
+    pub fn archive(&self) -> &CaExportArchive {
+        &self.archive
+    }
+}
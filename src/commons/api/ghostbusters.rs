@@ -0,0 +1,164 @@
+use std::fmt;
+
+//------------ GhostbusterVcard --------------------------------------------
+
+/// The contact details that a CA publishes in a Ghostbusters record, as
+/// described in RFC 6493. This is a deliberately limited subset of vCard:
+/// a full name (FN), and optionally an organization (ORG), email address
+/// (EMAIL), telephone number (TEL), and postal address (ADR). The RFC
+/// requires that at least one of ADR, TEL or EMAIL is present.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GhostbusterVcard {
+    full_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    org: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    adr: Option<String>,
+}
+
+impl GhostbusterVcard {
+    pub fn new(
+        full_name: String,
+        org: Option<String>,
+        email: Option<String>,
+        tel: Option<String>,
+        adr: Option<String>,
+    ) -> Self {
+        GhostbusterVcard {
+            full_name,
+            org,
+            email,
+            tel,
+            adr,
+        }
+    }
+
+    pub fn full_name(&self) -> &str {
+        &self.full_name
+    }
+
+    pub fn org(&self) -> Option<&String> {
+        self.org.as_ref()
+    }
+
+    pub fn email(&self) -> Option<&String> {
+        self.email.as_ref()
+    }
+
+    pub fn tel(&self) -> Option<&String> {
+        self.tel.as_ref()
+    }
+
+    pub fn adr(&self) -> Option<&String> {
+        self.adr.as_ref()
+    }
+
+    /// A record needs a full name, and at least one way to get in touch.
+    pub fn is_valid(&self) -> bool {
+        !self.full_name.trim().is_empty()
+            && (self.email.is_some() || self.tel.is_some() || self.adr.is_some())
+    }
+
+    /// Returns the vCard (version 4.0) text for this record. The address
+    /// is taken as a single line, and used as the street address.
+    pub fn to_vcard(&self) -> String {
+        let mut res = String::new();
+
+        res.push_str("BEGIN:VCARD\r\n");
+        res.push_str("VERSION:4.0\r\n");
+        res.push_str(&format!("FN:{}\r\n", Self::escape(&self.full_name)));
+        if let Some(org) = &self.org {
+            res.push_str(&format!("ORG:{}\r\n", Self::escape(org)));
+        }
+        if let Some(adr) = &self.adr {
+            res.push_str(&format!("ADR:;;{};;;;\r\n", Self::escape(adr)));
+        }
+        if let Some(tel) = &self.tel {
+            res.push_str(&format!("TEL:{}\r\n", Self::escape(tel)));
+        }
+        if let Some(email) = &self.email {
+            res.push_str(&format!("EMAIL:{}\r\n", Self::escape(email)));
+        }
+        res.push_str("END:VCARD\r\n");
+
+        res
+    }
+
+    // Escapes text values as described in section 3.4 of RFC 6350.
+    fn escape(value: &str) -> String {
+        let mut res = String::with_capacity(value.len());
+        for c in value.trim().chars() {
+            match c {
+                '\\' => res.push_str("\\\\"),
+                ',' => res.push_str("\\,"),
+                ';' => res.push_str("\\;"),
+                '\n' => res.push_str("\\n"),
+                '\r' => {}
+                _ => res.push(c),
+            }
+        }
+        res
+    }
+}
+
+impl fmt::Display for GhostbusterVcard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.full_name)?;
+        if let Some(org) = &self.org {
+            write!(f, " ({})", org)?;
+        }
+        if let Some(email) = &self.email {
+            write!(f, " email: {}", email)?;
+        }
+        if let Some(tel) = &self.tel {
+            write!(f, " tel: {}", tel)?;
+        }
+        if let Some(adr) = &self.adr {
+            write!(f, " adr: {}", adr)?;
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vcard_text() {
+        let vcard = GhostbusterVcard::new(
+            "Network Operations".to_string(),
+            Some("Example, Inc.".to_string()),
+            Some("noc@example.com".to_string()),
+            None,
+            Some("Street 1; City".to_string()),
+        );
+        assert!(vcard.is_valid());
+
+        let expected = concat!(
+            "BEGIN:VCARD\r\n",
+            "VERSION:4.0\r\n",
+            "FN:Network Operations\r\n",
+            "ORG:Example\\, Inc.\r\n",
+            "ADR:;;Street 1\\; City;;;;\r\n",
+            "EMAIL:noc@example.com\r\n",
+            "END:VCARD\r\n",
+        );
+        assert_eq!(expected, vcard.to_vcard());
+    }
+
+    #[test]
+    fn vcard_needs_contact() {
+        let vcard = GhostbusterVcard::new("NOC".to_string(), None, None, None, None);
+        assert!(!vcard.is_valid());
+
+        let vcard = GhostbusterVcard::new(" ".to_string(), None, None, Some("+1".into()), None);
+        assert!(!vcard.is_valid());
+    }
+}
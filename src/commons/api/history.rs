@@ -8,10 +8,12 @@ use rpki::x509::Time;
 use chrono::Utc;
 use chrono::{DateTime, NaiveDateTime};
 
+use crate::commons::api::rrdp::RrdpRetention;
 use crate::commons::api::{
-    ArgKey, ArgVal, ChildHandle, Handle, Label, Message, ParentHandle, PublisherHandle,
-    RequestResourceLimit, ResourceClassName, ResourceSet, RevocationRequest, RoaDefinitionUpdates,
-    StorableParentContact,
+    ArgKey, ArgVal, AsNumber, AspaDefinitionUpdates, AspaProvidersUpdate, ChildHandle,
+    GhostbusterVcard, Handle, KeyRollPolicy, Label, Message, ParentHandle, PublicationTiming,
+    PublisherHandle, PublisherQuota, RequestResourceLimit, ResourceClassName, ResourceLease,
+    ResourceSet, RevocationRequest, RoaDefinitionUpdates, RoaPackingPolicy, StorableParentContact,
 };
 use crate::commons::eventsourcing::{
     CommandKey, CommandKeyError, StoredCommand, WithStorableDetails,
@@ -235,6 +237,10 @@ pub struct CommandHistoryCriteria {
 }
 
 impl CommandHistoryCriteria {
+    pub fn set_include(&mut self, labels: &[&str]) {
+        self.label_includes = Some(labels.iter().map(|s| (*s).to_string()).collect());
+    }
+
     pub fn set_exclude(&mut self, labels: &[&str]) {
         self.label_excludes = Some(labels.iter().map(|s| (*s).to_string()).collect());
     }
@@ -307,6 +313,123 @@ impl Default for CommandHistoryCriteria {
     }
 }
 
+//------------ PublisherHistory ----------------------------------------------
+
+/// The history of RFC8181 publish requests received from a single publisher.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherHistory {
+    publisher: PublisherHandle,
+    offset: usize,
+    total: usize,
+    records: Vec<PublisherHistoryRecord>,
+}
+
+impl PublisherHistory {
+    pub fn new(
+        publisher: PublisherHandle,
+        offset: usize,
+        total: usize,
+        records: Vec<PublisherHistoryRecord>,
+    ) -> Self {
+        PublisherHistory {
+            publisher,
+            offset,
+            total,
+            records,
+        }
+    }
+
+    pub fn publisher(&self) -> &PublisherHandle {
+        &self.publisher
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn records(&self) -> &Vec<PublisherHistoryRecord> {
+        &self.records
+    }
+}
+
+//------------ PublisherHistoryRecord ----------------------------------------
+
+/// Describes a single publish request: when it was received, how many
+/// objects were published, updated and withdrawn, and the resulting RRDP
+/// serial. Rejected requests have no serial, but include the error instead.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherHistoryRecord {
+    key: String,
+    timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    serial: Option<u64>,
+    published: usize,
+    updated: usize,
+    withdrawn: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl PublisherHistoryRecord {
+    pub fn new(
+        key: String,
+        timestamp: i64,
+        serial: Option<u64>,
+        published: usize,
+        updated: usize,
+        withdrawn: usize,
+        error: Option<String>,
+    ) -> Self {
+        PublisherHistoryRecord {
+            key,
+            timestamp,
+            serial,
+            published,
+            updated,
+            withdrawn,
+            error,
+        }
+    }
+
+    pub fn key(&self) -> &String {
+        &self.key
+    }
+
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    pub fn serial(&self) -> Option<u64> {
+        self.serial
+    }
+
+    pub fn published(&self) -> usize {
+        self.published
+    }
+
+    pub fn updated(&self) -> usize {
+        self.updated
+    }
+
+    pub fn withdrawn(&self) -> usize {
+        self.withdrawn
+    }
+
+    pub fn error(&self) -> Option<&String> {
+        self.error.as_ref()
+    }
+
+    pub fn time(&self) -> Time {
+        let seconds = self.timestamp / 1000;
+        let time = NaiveDateTime::from_timestamp(seconds, 0);
+        Time::from(DateTime::from_utc(time, Utc))
+    }
+}
+
 //------------ StorableCaCommand -------------------------------------------
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -317,6 +440,8 @@ pub enum StorableCaCommand {
     ChildAdd(ChildHandle, Option<String>, ResourceSet),
     ChildUpdateResources(ChildHandle, ResourceSet),
     ChildUpdateId(ChildHandle, String),
+    ChildUpdateLeases(ChildHandle, Vec<ResourceLease>),
+    ChildExpireLeases(ChildHandle),
     ChildCertify(
         ChildHandle,
         ResourceClassName,
@@ -334,10 +459,16 @@ pub enum StorableCaCommand {
     KeyRollInitiate(i64),
     KeyRollActivate(i64),
     KeyRollFinish(ResourceClassName),
+    KeyRollPolicyUpdate(KeyRollPolicy),
     RoaDefinitionUpdates(RoaDefinitionUpdates),
+    RoaPolicyUpdate(RoaPackingPolicy),
+    AspasUpdate(AspaDefinitionUpdates),
+    AspasUpdateProviders(AsNumber, AspaProvidersUpdate),
+    GhostbusterUpdate(Option<GhostbusterVcard>),
     Republish,
     RepoUpdate(Option<ServiceUri>),
     RepoRemoveOld,
+    PublicationTimingUpdate(Option<PublicationTiming>),
 }
 
 impl WithStorableDetails for StorableCaCommand {
@@ -360,6 +491,12 @@ impl WithStorableDetails for StorableCaCommand {
                     .with_child(child)
                     .with_id_ski(Some(id))
             }
+            StorableCaCommand::ChildUpdateLeases(child, _) => {
+                CommandSummary::new("cmd-ca-child-update-leases", &self).with_child(child)
+            }
+            StorableCaCommand::ChildExpireLeases(child) => {
+                CommandSummary::new("cmd-ca-child-expire-leases", &self).with_child(child)
+            }
             StorableCaCommand::ChildCertify(child, rcn, _limit, ki) => {
                 CommandSummary::new("cmd-ca-child-certify", &self)
                     .with_child(child)
@@ -408,17 +545,46 @@ impl WithStorableDetails for StorableCaCommand {
             StorableCaCommand::KeyRollFinish(rcn) => {
                 CommandSummary::new("cmd-ca-keyroll-finish", &self).with_rcn(rcn)
             }
+            StorableCaCommand::KeyRollPolicyUpdate(policy) => {
+                CommandSummary::new("cmd-ca-keyroll-policy-update", &self)
+                    .with_arg("policy", policy)
+            }
             StorableCaCommand::RoaDefinitionUpdates(updates) => {
                 CommandSummary::new("cmd-ca-roas-updated", &self)
                     .with_added(updates.added().len())
                     .with_removed(updates.removed().len())
             }
+            StorableCaCommand::RoaPolicyUpdate(policy) => {
+                CommandSummary::new("cmd-ca-roas-policy-update", &self)
+                    .with_arg("policy", policy)
+            }
+            StorableCaCommand::AspasUpdate(updates) => {
+                CommandSummary::new("cmd-ca-aspas-update", &self)
+                    .with_added(updates.add_or_replace().len())
+                    .with_removed(updates.remove().len())
+            }
+            StorableCaCommand::AspasUpdateProviders(customer, update) => {
+                CommandSummary::new("cmd-ca-aspa-providers-update", &self)
+                    .with_arg("customer", customer)
+                    .with_added(update.added().len())
+                    .with_removed(update.removed().len())
+            }
+            StorableCaCommand::GhostbusterUpdate(_) => {
+                CommandSummary::new("cmd-ca-ghostbusters-update", &self)
+            }
             StorableCaCommand::Republish => CommandSummary::new("cmd-ca-publish", &self),
             StorableCaCommand::RepoUpdate(service_uri_opt) => {
                 CommandSummary::new("cmd-ca-repo-update", &self)
                     .with_service_uri_opt(service_uri_opt.as_ref())
             }
             StorableCaCommand::RepoRemoveOld => CommandSummary::new("cmd-ca-repo-clean", &self),
+            StorableCaCommand::PublicationTimingUpdate(timing_opt) => {
+                let summary = CommandSummary::new("cmd-ca-publication-timing-update", &self);
+                match timing_opt {
+                    None => summary,
+                    Some(timing) => summary.with_arg("timing", timing),
+                }
+            }
         }
     }
 }
@@ -453,6 +619,19 @@ impl fmt::Display for StorableCaCommand {
             StorableCaCommand::ChildUpdateId(child, id_ski) => {
                 write!(f, "Update child '{}' RFC 8183 key '{}'", child, id_ski)
             }
+            StorableCaCommand::ChildUpdateLeases(child, leases) => {
+                write!(f, "Update leases for child '{}' to:", child)?;
+                if leases.is_empty() {
+                    write!(f, " <none>")?;
+                }
+                for lease in leases {
+                    write!(f, " {}", lease)?;
+                }
+                Ok(())
+            }
+            StorableCaCommand::ChildExpireLeases(child) => {
+                write!(f, "Remove expired leases for child '{}'", child)
+            }
             StorableCaCommand::ChildCertify(child, _rcn, _limit, key) => {
                 write!(f, "Issue certificate to child '{}' for key '{}", child, key)
             }
@@ -513,6 +692,9 @@ impl fmt::Display for StorableCaCommand {
             StorableCaCommand::KeyRollFinish(rcn) => {
                 write!(f, "Retire old revoked key in RC '{}'", rcn)
             }
+            StorableCaCommand::KeyRollPolicyUpdate(policy) => {
+                write!(f, "Update key roll policy to: {}", policy)
+            }
 
             // ------------------------------------------------------------
             // ROA Support
@@ -523,6 +705,29 @@ impl fmt::Display for StorableCaCommand {
                 updates.added().len(),
                 updates.removed().len()
             ),
+            StorableCaCommand::RoaPolicyUpdate(policy) => {
+                write!(f, "Update ROA packing policy to: {}", policy)
+            }
+
+            // ------------------------------------------------------------
+            // ASPA Support
+            // ------------------------------------------------------------
+            StorableCaCommand::AspasUpdate(updates) => {
+                write!(f, "Update ASPA definitions {}", updates)
+            }
+            StorableCaCommand::AspasUpdateProviders(customer, update) => write!(
+                f,
+                "Update ASPA providers for customer '{}' {}",
+                customer, update
+            ),
+
+            // ------------------------------------------------------------
+            // Ghostbusters Support
+            // ------------------------------------------------------------
+            StorableCaCommand::GhostbusterUpdate(vcard) => match vcard {
+                Some(vcard) => write!(f, "Update Ghostbusters record to: {}", vcard),
+                None => write!(f, "Remove Ghostbusters record"),
+            },
 
             // ------------------------------------------------------------
             // Publishing
@@ -533,6 +738,10 @@ impl fmt::Display for StorableCaCommand {
                 Some(uri) => write!(f, "Update repo to server at: {}", uri),
             },
             StorableCaCommand::RepoRemoveOld => write!(f, "Clean up old repository"),
+            StorableCaCommand::PublicationTimingUpdate(timing_opt) => match timing_opt {
+                None => write!(f, "Revert to default publication timing"),
+                Some(timing) => write!(f, "Update publication timing to: {}", timing),
+            },
         }
     }
 }
@@ -543,22 +752,45 @@ impl fmt::Display for StorableCaCommand {
 #[allow(clippy::large_enum_variant)]
 #[serde(rename_all = "snake_case")]
 pub enum StorableRepositoryCommand {
-    AddPublisher(PublisherHandle, String),
+    AddPublisher(PublisherHandle, String, #[serde(default)] PublisherQuota),
     RemovePublisher(PublisherHandle),
+    UpdatePublisherQuota(PublisherHandle, PublisherQuota),
+    UpdatePublisherIdCert(PublisherHandle, String),
+    SuspendPublisher(PublisherHandle),
+    ReactivatePublisher(PublisherHandle),
     Publish(PublisherHandle, usize, usize, usize),
+    UpdateRrdpRetention(RrdpRetention),
+    ResetRrdpSession,
 }
 
 impl WithStorableDetails for StorableRepositoryCommand {
     fn summary(&self) -> CommandSummary {
         match self {
-            StorableRepositoryCommand::AddPublisher(publisher, ski) => {
+            StorableRepositoryCommand::AddPublisher(publisher, ski, quota) => {
                 CommandSummary::new("pubd-publisher-add", &self)
                     .with_publisher(publisher)
                     .with_id_ski(Some(ski))
+                    .with_arg("quota", quota)
             }
             StorableRepositoryCommand::RemovePublisher(publisher) => {
                 CommandSummary::new("pubd-publisher-remove", &self).with_publisher(publisher)
             }
+            StorableRepositoryCommand::UpdatePublisherQuota(publisher, quota) => {
+                CommandSummary::new("pubd-publisher-quota", &self)
+                    .with_publisher(publisher)
+                    .with_arg("quota", quota)
+            }
+            StorableRepositoryCommand::UpdatePublisherIdCert(publisher, ski) => {
+                CommandSummary::new("pubd-publisher-id-update", &self)
+                    .with_publisher(publisher)
+                    .with_id_ski(Some(ski))
+            }
+            StorableRepositoryCommand::SuspendPublisher(publisher) => {
+                CommandSummary::new("pubd-publisher-suspend", &self).with_publisher(publisher)
+            }
+            StorableRepositoryCommand::ReactivatePublisher(publisher) => {
+                CommandSummary::new("pubd-publisher-reactivate", &self).with_publisher(publisher)
+            }
             StorableRepositoryCommand::Publish(publisher, published, updated, withdrawn) => {
                 CommandSummary::new("pubd-publish", &self)
                     .with_publisher(publisher)
@@ -566,6 +798,32 @@ impl WithStorableDetails for StorableRepositoryCommand {
                     .with_arg("updated", updated)
                     .with_arg("withdrawn", withdrawn)
             }
+            StorableRepositoryCommand::UpdateRrdpRetention(retention) => {
+                CommandSummary::new("pubd-rrdp-retention", &self)
+                    .with_arg("delta_files_min_nr", retention.delta_files_min_nr())
+                    .with_arg(
+                        "delta_files_max_size_pct",
+                        retention.delta_files_max_size_pct(),
+                    )
+                    .with_arg("files_retain_mins", retention.files_retain_mins())
+            }
+            StorableRepositoryCommand::ResetRrdpSession => {
+                CommandSummary::new("pubd-rrdp-session-reset", &self)
+            }
+        }
+    }
+
+    fn subject(&self) -> Option<&Handle> {
+        match self {
+            StorableRepositoryCommand::AddPublisher(publisher, _, _)
+            | StorableRepositoryCommand::RemovePublisher(publisher)
+            | StorableRepositoryCommand::UpdatePublisherQuota(publisher, _)
+            | StorableRepositoryCommand::UpdatePublisherIdCert(publisher, _)
+            | StorableRepositoryCommand::SuspendPublisher(publisher)
+            | StorableRepositoryCommand::ReactivatePublisher(publisher)
+            | StorableRepositoryCommand::Publish(publisher, _, _, _) => Some(publisher),
+            StorableRepositoryCommand::UpdateRrdpRetention(_)
+            | StorableRepositoryCommand::ResetRrdpSession => None,
         }
     }
 }
@@ -573,17 +831,37 @@ impl WithStorableDetails for StorableRepositoryCommand {
 impl fmt::Display for StorableRepositoryCommand {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            StorableRepositoryCommand::AddPublisher(pbl, ski) => {
-                write!(f, "Added publisher '{}' with RFC8183 key '{}'", pbl, ski)
-            }
+            StorableRepositoryCommand::AddPublisher(pbl, ski, quota) => write!(
+                f,
+                "Added publisher '{}' with RFC8183 key '{}' and quota: {}",
+                pbl, ski, quota
+            ),
             StorableRepositoryCommand::RemovePublisher(pbl) => {
                 write!(f, "Removed publisher '{}'", pbl)
             }
+            StorableRepositoryCommand::UpdatePublisherQuota(pbl, quota) => {
+                write!(f, "Updated quota for publisher '{}' to: {}", pbl, quota)
+            }
+            StorableRepositoryCommand::UpdatePublisherIdCert(pbl, ski) => write!(
+                f,
+                "Updated RFC8183 key for publisher '{}' to '{}'",
+                pbl, ski
+            ),
+            StorableRepositoryCommand::SuspendPublisher(pbl) => {
+                write!(f, "Suspended publisher '{}'", pbl)
+            }
+            StorableRepositoryCommand::ReactivatePublisher(pbl) => {
+                write!(f, "Reactivated publisher '{}'", pbl)
+            }
             StorableRepositoryCommand::Publish(pbl, published, updated, withdrawn) => write!(
                 f,
                 "Published for '{}': {} published, {} updated, {} withdrawn",
                 pbl, published, updated, withdrawn
             ),
+            StorableRepositoryCommand::UpdateRrdpRetention(retention) => {
+                write!(f, "Updated RRDP retention policy to: {}", retention)
+            }
+            StorableRepositoryCommand::ResetRrdpSession => write!(f, "Reset RRDP session"),
         }
     }
 }
@@ -0,0 +1,401 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use rpki::x509::Time;
+
+use crate::constants::KEY_ROLL_STALLED_HOURS;
+
+//------------ KeyRollPolicy -----------------------------------------------
+
+/// Determines whether, and when, Krill rolls the keys of a CA by itself.
+///
+/// When enabled, a key roll is started for each resource class where the
+/// current key is older than the maximum key age. The new key is activated
+/// after it has been published for at least the staging period, which
+/// should be no less than 24 hours as described in RFC 6489. Both steps
+/// are only taken inside one of the maintenance windows, or at any time if
+/// no windows are set.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct KeyRollPolicy {
+    enabled: bool,
+    max_key_age_days: u32,
+    staging_hours: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    windows: Vec<MaintenanceWindow>,
+}
+
+impl Default for KeyRollPolicy {
+    fn default() -> Self {
+        KeyRollPolicy {
+            enabled: false,
+            max_key_age_days: 365,
+            staging_hours: 24,
+            windows: vec![],
+        }
+    }
+}
+
+impl KeyRollPolicy {
+    pub fn new(
+        enabled: bool,
+        max_key_age_days: u32,
+        staging_hours: u32,
+        windows: Vec<MaintenanceWindow>,
+    ) -> Self {
+        KeyRollPolicy {
+            enabled,
+            max_key_age_days,
+            staging_hours,
+            windows,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn max_key_age_days(&self) -> u32 {
+        self.max_key_age_days
+    }
+
+    pub fn max_key_age(&self) -> Duration {
+        Duration::days(i64::from(self.max_key_age_days))
+    }
+
+    pub fn staging_hours(&self) -> u32 {
+        self.staging_hours
+    }
+
+    pub fn staging(&self) -> Duration {
+        Duration::hours(i64::from(self.staging_hours))
+    }
+
+    pub fn windows(&self) -> &Vec<MaintenanceWindow> {
+        &self.windows
+    }
+
+    /// Keys need to live for some time, and a new key needs to be staged
+    /// well before the current key would be rolled again.
+    pub fn is_valid(&self) -> bool {
+        self.max_key_age_days > 0 && self.staging_hours > 0 && self.staging() < self.max_key_age()
+    }
+
+    /// Returns whether key roll steps may be taken at the given time.
+    pub fn in_window(&self, time: DateTime<Utc>) -> bool {
+        self.windows.is_empty() || self.windows.iter().any(|w| w.contains(time))
+    }
+}
+
+impl fmt::Display for KeyRollPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.enabled {
+            write!(f, "manual key rolls only")
+        } else {
+            write!(
+                f,
+                "roll keys older than {} days, activate after {} hours",
+                self.max_key_age_days, self.staging_hours
+            )?;
+            if self.windows.is_empty() {
+                write!(f, ", at any time")
+            } else {
+                let windows: Vec<String> = self.windows.iter().map(|w| w.to_string()).collect();
+                write!(f, ", in windows: {}", windows.join(", "))
+            }
+        }
+    }
+}
+
+//------------ MaintenanceWindow -------------------------------------------
+
+/// A period of hours (UTC) on a given day of the week, or on every day,
+/// during which automatic key roll steps may be taken. Windows where the
+/// end hour is before the start hour continue past midnight, an end hour
+/// of 24 ends the window at midnight, e.g. "00-24" covers the whole day.
+///
+/// Windows are written as "[day ]start-end", e.g. "sat 02-06" or "22-04".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MaintenanceWindow {
+    day: Option<Weekday>,
+    start_hour: u32,
+    end_hour: u32,
+}
+
+impl MaintenanceWindow {
+    pub fn new(day: Option<Weekday>, start_hour: u32, end_hour: u32) -> Self {
+        MaintenanceWindow {
+            day,
+            start_hour,
+            end_hour,
+        }
+    }
+
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let hour = time.hour();
+        let day = time.weekday();
+
+        if self.start_hour < self.end_hour {
+            self.on_day(day) && hour >= self.start_hour && hour < self.end_hour
+        } else {
+            (self.on_day(day) && hour >= self.start_hour)
+                || (self.on_day(day.pred()) && hour < self.end_hour)
+        }
+    }
+
+    fn on_day(&self, day: Weekday) -> bool {
+        self.day.map(|d| d == day).unwrap_or(true)
+    }
+
+    fn parse_hour(hour: Option<&str>) -> Result<u32, MaintenanceWindowError> {
+        let hour = hour.ok_or(MaintenanceWindowError)?;
+        let hour = u32::from_str(hour).map_err(|_| MaintenanceWindowError)?;
+        if hour > 24 {
+            Err(MaintenanceWindowError)
+        } else {
+            Ok(hour)
+        }
+    }
+}
+
+impl FromStr for MaintenanceWindow {
+    type Err = MaintenanceWindowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let first = parts.next().ok_or(MaintenanceWindowError)?;
+
+        let (day, hours) = match parts.next() {
+            None => (None, first),
+            Some(hours) => {
+                let day = Weekday::from_str(first).map_err(|_| MaintenanceWindowError)?;
+                (Some(day), hours)
+            }
+        };
+
+        if parts.next().is_some() {
+            return Err(MaintenanceWindowError);
+        }
+
+        let mut hours = hours.split('-');
+        let start_hour = Self::parse_hour(hours.next())?;
+        let end_hour = Self::parse_hour(hours.next())?;
+
+        if hours.next().is_some() || start_hour == end_hour || start_hour == 24 {
+            return Err(MaintenanceWindowError);
+        }
+
+        Ok(MaintenanceWindow {
+            day,
+            start_hour,
+            end_hour,
+        })
+    }
+}
+
+impl fmt::Display for MaintenanceWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "{} ", day.to_string().to_lowercase())?;
+        }
+        write!(f, "{:02}-{:02}", self.start_hour, self.end_hour)
+    }
+}
+
+impl Serialize for MaintenanceWindow {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MaintenanceWindow {
+    fn deserialize<D>(deserializer: D) -> Result<MaintenanceWindow, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(deserializer)?;
+        MaintenanceWindow::from_str(&string).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Clone, Debug, Display, Eq, PartialEq)]
+#[display(fmt = "Expected maintenance window like 'sat 02-06' or '22-04'")]
+pub struct MaintenanceWindowError;
+
+//------------ KeyRollPhase ------------------------------------------------
+
+/// The phase of the key roll in a resource class, see RFC 6489.
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyRollPhase {
+    #[display(fmt = "idle")]
+    Idle,
+    #[display(fmt = "awaiting certificate for new key")]
+    AwaitingCertificate,
+    #[display(fmt = "staging new key")]
+    Staging,
+    #[display(fmt = "awaiting revocation of old key")]
+    RevokingOld,
+}
+
+//------------ KeyRollStatus -----------------------------------------------
+
+/// Shows the progress of key rolls in a resource class. The next step is
+/// only known for the phases where the key roll policy determines when it
+/// is taken, i.e. starting a roll and activating a new key. Other steps
+/// depend on the parent.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct KeyRollStatus {
+    phase: KeyRollPhase,
+    since: Time,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_step: Option<Time>,
+}
+
+impl KeyRollStatus {
+    pub fn new(phase: KeyRollPhase, since: Time, next_step: Option<Time>) -> Self {
+        KeyRollStatus {
+            phase,
+            since,
+            next_step,
+        }
+    }
+
+    pub fn phase(&self) -> KeyRollPhase {
+        self.phase
+    }
+
+    pub fn since(&self) -> Time {
+        self.since
+    }
+
+    pub fn next_step(&self) -> Option<Time> {
+        self.next_step
+    }
+
+    /// Returns whether the next step is due at the given time.
+    pub fn is_due(&self, now: Time) -> bool {
+        self.next_step.map(|next| next <= now).unwrap_or(false)
+    }
+
+    /// A key roll is considered stalled if a step that is due has not been
+    /// taken for a while, or if the parent is slow to respond.
+    pub fn is_stalled(&self, now: Time) -> bool {
+        let grace = Duration::hours(KEY_ROLL_STALLED_HOURS);
+        match self.phase {
+            KeyRollPhase::Idle | KeyRollPhase::Staging => self
+                .next_step
+                .map(|next| next + grace < now)
+                .unwrap_or(false),
+            KeyRollPhase::AwaitingCertificate | KeyRollPhase::RevokingOld => {
+                self.since + grace < now
+            }
+        }
+    }
+}
+
+impl fmt::Display for KeyRollStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} since {}", self.phase, self.since.to_rfc3339())?;
+        if let Some(next) = self.next_step {
+            write!(f, ", next step due at {}", next.to_rfc3339())?;
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(s: &str) -> MaintenanceWindow {
+        MaintenanceWindow::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn parse_maintenance_windows() {
+        assert_eq!(
+            window("sat 02-06"),
+            MaintenanceWindow::new(Some(Weekday::Sat), 2, 6)
+        );
+        assert_eq!(window("22-24"), MaintenanceWindow::new(None, 22, 24));
+        assert_eq!("sat 02-06", window("Saturday 2-6").to_string());
+
+        assert!(MaintenanceWindow::from_str("").is_err());
+        assert!(MaintenanceWindow::from_str("02-02").is_err());
+        assert!(MaintenanceWindow::from_str("02-25").is_err());
+        assert!(MaintenanceWindow::from_str("someday 02-06").is_err());
+        assert!(MaintenanceWindow::from_str("sat 02-06 extra").is_err());
+    }
+
+    #[test]
+    fn maintenance_window_contains() {
+        let sat_morning = window("sat 02-06");
+        // 2 May 2020 was a Saturday
+        assert!(sat_morning.contains(*Time::utc(2020, 5, 2, 2, 0, 0)));
+        assert!(sat_morning.contains(*Time::utc(2020, 5, 2, 5, 59, 59)));
+        assert!(!sat_morning.contains(*Time::utc(2020, 5, 2, 6, 0, 0)));
+        assert!(!sat_morning.contains(*Time::utc(2020, 5, 3, 3, 0, 0)));
+
+        let sat_night = window("sat 22-04");
+        assert!(sat_night.contains(*Time::utc(2020, 5, 2, 23, 0, 0)));
+        assert!(sat_night.contains(*Time::utc(2020, 5, 3, 3, 0, 0)));
+        assert!(!sat_night.contains(*Time::utc(2020, 5, 2, 3, 0, 0)));
+
+        let saturday = window("sat 00-24");
+        assert!(saturday.contains(*Time::utc(2020, 5, 2, 0, 0, 0)));
+        assert!(saturday.contains(*Time::utc(2020, 5, 2, 23, 59, 59)));
+        assert!(!saturday.contains(*Time::utc(2020, 5, 3, 0, 0, 0)));
+
+        let policy = KeyRollPolicy::new(true, 365, 24, vec![sat_morning, sat_night]);
+        assert!(policy.in_window(*Time::utc(2020, 5, 3, 1, 0, 0)));
+        assert!(!policy.in_window(*Time::utc(2020, 5, 4, 1, 0, 0)));
+        assert!(KeyRollPolicy::default().in_window(*Time::utc(2020, 5, 4, 1, 0, 0)));
+    }
+
+    #[test]
+    fn maintenance_window_serde_round_trip() {
+        for s in &["00-24", "sat 00-24", "22-02", "sun 22-02", "sat 02-06"] {
+            let parsed = window(s);
+            let json = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(json, format!("\"{}\"", s));
+            let decoded: MaintenanceWindow = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, parsed);
+        }
+    }
+
+    #[test]
+    fn key_roll_policy_validity() {
+        assert!(KeyRollPolicy::default().is_valid());
+        assert!(!KeyRollPolicy::new(true, 0, 24, vec![]).is_valid());
+        assert!(!KeyRollPolicy::new(true, 1, 0, vec![]).is_valid());
+        assert!(!KeyRollPolicy::new(true, 1, 24, vec![]).is_valid());
+        assert!(KeyRollPolicy::new(true, 2, 24, vec![]).is_valid());
+    }
+
+    #[test]
+    fn key_roll_status_stalled() {
+        let since = Time::utc(2020, 5, 1, 0, 0, 0);
+        let next = Time::utc(2020, 5, 2, 0, 0, 0);
+
+        let staging = KeyRollStatus::new(KeyRollPhase::Staging, since, Some(next));
+        assert!(!staging.is_due(Time::utc(2020, 5, 1, 23, 0, 0)));
+        assert!(staging.is_due(next));
+        assert!(!staging.is_stalled(Time::utc(2020, 5, 2, 23, 0, 0)));
+        assert!(staging.is_stalled(Time::utc(2020, 5, 3, 1, 0, 0)));
+
+        let pending = KeyRollStatus::new(KeyRollPhase::AwaitingCertificate, since, None);
+        assert!(!pending.is_due(next));
+        assert!(pending.is_stalled(Time::utc(2020, 5, 2, 1, 0, 0)));
+
+        let manual = KeyRollStatus::new(KeyRollPhase::Idle, since, None);
+        assert!(!manual.is_stalled(Time::utc(2021, 5, 1, 0, 0, 0)));
+    }
+}
@@ -3,12 +3,24 @@
 mod admin;
 pub use self::admin::*;
 
+mod aspa;
+pub use self::aspa::*;
+
 mod ca;
 pub use self::ca::*;
 
+mod export;
+pub use self::export::*;
+
+mod ghostbusters;
+pub use self::ghostbusters::*;
+
 mod history;
 pub use self::history::*;
 
+mod keyroll;
+pub use self::keyroll::*;
+
 mod provisioning;
 pub use self::provisioning::*;
 
@@ -18,6 +30,9 @@ pub use self::publication::*;
 mod roas;
 pub use self::roas::*;
 
+mod timing;
+pub use self::timing::*;
+
 pub mod rrdp;
 
 use std::collections::HashMap;
@@ -33,7 +48,7 @@ use rpki::manifest::Manifest;
 use rpki::roa::Roa;
 
 use crate::commons::util::sha256;
-use crate::daemon::ca::RouteAuthorization;
+use crate::daemon::ca::{Aspa, Ghostbuster, RouteAuthorization};
 
 // Some syntactic sugar to help this old coder's brain deal with the mess of Strings
 pub type Message = String;
@@ -98,6 +113,18 @@ impl From<&Roa> for Base64 {
     }
 }
 
+impl From<&Aspa> for Base64 {
+    fn from(aspa: &Aspa) -> Self {
+        Base64::from_content(&aspa.to_captured().into_bytes())
+    }
+}
+
+impl From<&Ghostbuster> for Base64 {
+    fn from(gbr: &Ghostbuster) -> Self {
+        Base64::from_content(&gbr.to_captured().into_bytes())
+    }
+}
+
 impl From<&Manifest> for Base64 {
     fn from(mft: &Manifest) -> Self {
         Base64::from_content(&mft.to_captured().into_bytes())
@@ -297,6 +324,10 @@ impl ErrorResponse {
         res
     }
 
+    pub fn with_customer_asn(self, customer: AsNumber) -> Self {
+        self.with_arg("customer", customer)
+    }
+
     pub fn with_key_identifier(self, ki: &KeyIdentifier) -> Self {
         self.with_arg("key_id", ki)
     }
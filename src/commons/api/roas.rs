@@ -7,6 +7,7 @@ use std::str::FromStr;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use rpki::resources::{AsBlocks, AsId, IpBlocks, IpBlocksBuilder, Prefix};
+use rpki::roa::Roa;
 
 use crate::commons::api::ResourceSet;
 use crate::daemon::ca::RouteAuthorizationUpdates;
@@ -51,6 +52,30 @@ impl RoaDefinition {
         }
     }
 
+    /// Returns a definition for each prefix in an existing ROA.
+    pub fn from_roa(roa: &Roa) -> Vec<RoaDefinition> {
+        let content = roa.content();
+        let asn = AsNumber::from(content.as_id());
+
+        content
+            .iter()
+            .map(|addr| {
+                let prefix = addr.prefix();
+                let typed_prefix = if addr.is_v4() {
+                    TypedPrefix::V4(Ipv4Prefix(prefix))
+                } else {
+                    TypedPrefix::V6(Ipv6Prefix(prefix))
+                };
+                let max_length = if addr.max_length() > prefix.addr_len() {
+                    Some(addr.max_length())
+                } else {
+                    None
+                };
+                RoaDefinition::new(asn, typed_prefix, max_length)
+            })
+            .collect()
+    }
+
     pub fn max_length_valid(&self) -> bool {
         if let Some(max_length) = self.max_length {
             match self.prefix {
@@ -154,6 +179,39 @@ impl RoaDefinitionUpdates {
     pub fn remove(&mut self, rem: RoaDefinition) {
         self.removed.insert(rem);
     }
+
+    /// Returns the updates needed to go from the current definitions to
+    /// the target definitions.
+    pub fn replace(current: &[RoaDefinition], target: &[RoaDefinition]) -> Self {
+        let current: HashSet<RoaDefinition> = current.iter().cloned().collect();
+        let target: HashSet<RoaDefinition> = target.iter().cloned().collect();
+
+        let added = target.difference(&current).cloned().collect();
+        let removed = current.difference(&target).cloned().collect();
+
+        RoaDefinitionUpdates { added, removed }
+    }
+
+    /// Returns the updates needed to add the definitions which are not
+    /// present in the current definitions.
+    pub fn add_missing(current: &[RoaDefinition], additional: &[RoaDefinition]) -> Self {
+        let current: HashSet<RoaDefinition> = current.iter().cloned().collect();
+
+        let added = additional
+            .iter()
+            .filter(|d| !current.contains(d))
+            .cloned()
+            .collect();
+
+        RoaDefinitionUpdates {
+            added,
+            removed: HashSet::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
 }
 
 impl Default for RoaDefinitionUpdates {
@@ -221,6 +279,335 @@ impl From<RouteAuthorizationUpdates> for RoaDefinitionUpdates {
     }
 }
 
+//------------ RoaPackingPolicy --------------------------------------------
+
+/// Determines how the route authorizations of a CA are packed into ROA
+/// objects. By default every authorization gets its own ROA. When aggregation
+/// is enabled all authorizations for the same ASN under a resource class are
+/// combined into a single ROA, or into as many ROAs as needed to stay within
+/// the maximum number of prefixes per ROA - if set.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaPackingPolicy {
+    aggregate: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_prefixes: Option<usize>,
+}
+
+impl RoaPackingPolicy {
+    pub fn new(aggregate: bool, max_prefixes: Option<usize>) -> Self {
+        RoaPackingPolicy {
+            aggregate,
+            max_prefixes,
+        }
+    }
+
+    pub fn aggregate(&self) -> bool {
+        self.aggregate
+    }
+
+    pub fn max_prefixes(&self) -> Option<usize> {
+        self.max_prefixes
+    }
+
+    /// A maximum of zero prefixes per ROA cannot be honoured.
+    pub fn is_valid(&self) -> bool {
+        self.max_prefixes != Some(0)
+    }
+}
+
+impl fmt::Display for RoaPackingPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.aggregate {
+            write!(f, "one ROA per prefix")
+        } else {
+            match self.max_prefixes {
+                None => write!(f, "aggregate ROAs per ASN"),
+                Some(max) => write!(f, "aggregate ROAs per ASN, max {} prefixes per ROA", max),
+            }
+        }
+    }
+}
+
+//------------ RoaDefinitionFormat -----------------------------------------
+
+/// The formats supported for bulk import and export of ROA definitions:
+///  - csv:   lines of 'prefix,max-length,asn,comment'
+///  - json:  a plain JSON array of definitions, as shown by 'roas list'
+///  - slurm: the 'prefixAssertions' in an RFC 8416 SLURM file
+///
+/// Comments are accepted when importing, but they are not kept.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoaDefinitionFormat {
+    Csv,
+    Json,
+    Slurm,
+}
+
+impl RoaDefinitionFormat {
+    /// Parses the definitions in the content. All problems found are
+    /// reported, rather than just the first.
+    pub fn parse(self, content: &str) -> Result<Vec<RoaDefinition>, RoaImportError> {
+        match self {
+            RoaDefinitionFormat::Csv => Self::parse_csv(content),
+            RoaDefinitionFormat::Json => {
+                serde_json::from_str(content).map_err(|e| RoaImportError::single(e.to_string()))
+            }
+            RoaDefinitionFormat::Slurm => Self::parse_slurm(content),
+        }
+    }
+
+    /// Formats the definitions, sorted to make the output stable.
+    pub fn format(self, definitions: &[RoaDefinition]) -> String {
+        let mut definitions = definitions.to_vec();
+        definitions.sort_by_key(|d| d.to_string());
+
+        match self {
+            RoaDefinitionFormat::Csv => {
+                let mut res = String::from("prefix,max-length,asn,comment\n");
+                for def in definitions {
+                    let max_length = def.max_length.map(|l| l.to_string()).unwrap_or_default();
+                    res.push_str(&format!("{},{},AS{},\n", def.prefix, max_length, def.asn));
+                }
+                res
+            }
+            RoaDefinitionFormat::Json => serde_json::to_string_pretty(&definitions).unwrap(),
+            RoaDefinitionFormat::Slurm => {
+                let prefix_assertions = definitions
+                    .into_iter()
+                    .map(|def| SlurmPrefixAssertion {
+                        asn: def.asn.0,
+                        prefix: def.prefix.to_string(),
+                        max_prefix_length: def.max_length,
+                        comment: None,
+                    })
+                    .collect();
+
+                let slurm = SlurmFile {
+                    slurm_version: 1,
+                    validation_output_filters: SlurmFilters::default(),
+                    locally_added_assertions: SlurmAssertions {
+                        prefix_assertions,
+                        bgpsec_assertions: vec![],
+                    },
+                };
+                serde_json::to_string_pretty(&slurm).unwrap()
+            }
+        }
+    }
+
+    fn parse_csv(content: &str) -> Result<Vec<RoaDefinition>, RoaImportError> {
+        let mut definitions = vec![];
+        let mut issues = vec![];
+
+        for (idx, line) in content.lines().enumerate() {
+            let nr = idx + 1;
+            let line = line.trim();
+
+            if line.is_empty()
+                || line.starts_with('#')
+                || (nr == 1 && line.to_lowercase().starts_with("prefix"))
+            {
+                continue;
+            }
+
+            match Self::parse_csv_line(line) {
+                Ok(def) => definitions.push(def),
+                Err(e) => issues.push(format!("line {}: {}", nr, e)),
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(definitions)
+        } else {
+            Err(RoaImportError { issues })
+        }
+    }
+
+    // prefix,max-length,asn,comment - where max-length and comment may be empty
+    fn parse_csv_line(line: &str) -> Result<RoaDefinition, AuthorizationFmtError> {
+        let mut fields = line.splitn(4, ',').map(str::trim);
+
+        let prefix = fields.next().unwrap_or("");
+        let max_length = fields
+            .next()
+            .ok_or_else(|| AuthorizationFmtError::auth(line))?;
+        let asn = fields
+            .next()
+            .ok_or_else(|| AuthorizationFmtError::auth(line))?;
+
+        let prefix = TypedPrefix::from_str(prefix)?;
+        let asn = AsNumber::from_str(asn)?;
+        let max_length = if max_length.is_empty() {
+            None
+        } else {
+            Some(u8::from_str(max_length).map_err(|_| AuthorizationFmtError::auth(line))?)
+        };
+
+        Ok(RoaDefinition::new(asn, prefix, max_length))
+    }
+
+    fn parse_slurm(content: &str) -> Result<Vec<RoaDefinition>, RoaImportError> {
+        let slurm: SlurmFile =
+            serde_json::from_str(content).map_err(|e| RoaImportError::single(e.to_string()))?;
+
+        let mut definitions = vec![];
+        let mut issues = vec![];
+
+        for (idx, assertion) in slurm
+            .locally_added_assertions
+            .prefix_assertions
+            .into_iter()
+            .enumerate()
+        {
+            match TypedPrefix::from_str(&assertion.prefix) {
+                Ok(prefix) => definitions.push(RoaDefinition::new(
+                    AsNumber::new(assertion.asn),
+                    prefix,
+                    assertion.max_prefix_length,
+                )),
+                Err(e) => issues.push(format!("prefixAssertions[{}]: {}", idx, e)),
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(definitions)
+        } else {
+            Err(RoaImportError { issues })
+        }
+    }
+}
+
+impl FromStr for RoaDefinitionFormat {
+    type Err = AuthorizationFmtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(RoaDefinitionFormat::Csv),
+            "json" => Ok(RoaDefinitionFormat::Json),
+            "slurm" => Ok(RoaDefinitionFormat::Slurm),
+            _ => Err(AuthorizationFmtError::Format(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for RoaDefinitionFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoaDefinitionFormat::Csv => write!(f, "csv"),
+            RoaDefinitionFormat::Json => write!(f, "json"),
+            RoaDefinitionFormat::Slurm => write!(f, "slurm"),
+        }
+    }
+}
+
+//------------ SLURM (RFC 8416) --------------------------------------------
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmFile {
+    slurm_version: u8,
+    #[serde(default)]
+    validation_output_filters: SlurmFilters,
+    locally_added_assertions: SlurmAssertions,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmFilters {
+    #[serde(default)]
+    prefix_filters: Vec<serde_json::Value>,
+    #[serde(default)]
+    bgpsec_filters: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmAssertions {
+    #[serde(default)]
+    prefix_assertions: Vec<SlurmPrefixAssertion>,
+    #[serde(default)]
+    bgpsec_assertions: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlurmPrefixAssertion {
+    asn: u32,
+    prefix: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_prefix_length: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+//------------ RoaImport ---------------------------------------------------
+
+/// A bulk import of ROA definitions. The definitions are either added to
+/// the current definitions of a CA, or they replace them entirely.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoaImport {
+    format: RoaDefinitionFormat,
+    #[serde(default)]
+    replace: bool,
+    content: String,
+}
+
+impl RoaImport {
+    pub fn new(format: RoaDefinitionFormat, replace: bool, content: String) -> Self {
+        RoaImport {
+            format,
+            replace,
+            content,
+        }
+    }
+
+    pub fn format(&self) -> RoaDefinitionFormat {
+        self.format
+    }
+
+    pub fn replace(&self) -> bool {
+        self.replace
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+}
+
+impl fmt::Display for RoaImport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mode = if self.replace { "replace" } else { "add" };
+        write!(f, "{} import ({})", self.format, mode)
+    }
+}
+
+//------------ RoaImportError ----------------------------------------------
+
+/// The problems found in a bulk import, e.g. 'line 3: Invalid prefix...'
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoaImportError {
+    issues: Vec<String>,
+}
+
+impl RoaImportError {
+    fn single(issue: String) -> Self {
+        RoaImportError {
+            issues: vec![issue],
+        }
+    }
+
+    pub fn issues(&self) -> &Vec<String> {
+        &self.issues
+    }
+}
+
+impl fmt::Display for RoaImportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.issues.join(", "))
+    }
+}
+
 //------------ TypedPrefix -------------------------------------------------
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum TypedPrefix {
@@ -239,6 +626,30 @@ impl TypedPrefix {
             TypedPrefix::V6(v6) => IpAddr::V6(v6.0.to_v6()),
         }
     }
+
+    /// Returns true if this prefix covers the other prefix, i.e. if the
+    /// other prefix is the same prefix or a more specific of the same
+    /// address family.
+    pub fn covers(&self, other: &TypedPrefix) -> bool {
+        let (bits, other_bits, family_len) = match (self.ip_addr(), other.ip_addr()) {
+            (IpAddr::V4(addr), IpAddr::V4(other_addr)) => (
+                u128::from(u32::from(addr)),
+                u128::from(u32::from(other_addr)),
+                32,
+            ),
+            (IpAddr::V6(addr), IpAddr::V6(other_addr)) => {
+                (u128::from(addr), u128::from(other_addr), 128)
+            }
+            _ => return false,
+        };
+
+        if self.addr_len() > other.addr_len() {
+            return false;
+        }
+
+        let host_bits = u32::from(family_len - self.addr_len());
+        bits.checked_shr(host_bits).unwrap_or(0) == other_bits.checked_shr(host_bits).unwrap_or(0)
+    }
 }
 
 impl FromStr for TypedPrefix {
@@ -347,7 +758,7 @@ impl fmt::Display for Ipv6Prefix {
 
 //------------ AsNumber ----------------------------------------------------
 
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct AsNumber(u32);
 
 impl AsNumber {
@@ -356,6 +767,12 @@ impl AsNumber {
     }
 }
 
+impl From<AsId> for AsNumber {
+    fn from(id: AsId) -> Self {
+        AsNumber(u32::from(id))
+    }
+}
+
 impl From<AsNumber> for AsId {
     fn from(asn: AsNumber) -> Self {
         AsId::from(asn.0)
@@ -365,9 +782,15 @@ impl From<AsNumber> for AsId {
 impl FromStr for AsNumber {
     type Err = AuthorizationFmtError;
 
+    // "64496" or "AS64496"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
-        let number = u32::from_str(s).map_err(|_| AuthorizationFmtError::asn(s))?;
+        let number_str = if s.len() > 2 && s[..2].eq_ignore_ascii_case("as") {
+            &s[2..]
+        } else {
+            s
+        };
+        let number = u32::from_str(number_str).map_err(|_| AuthorizationFmtError::asn(s))?;
         Ok(AsNumber(number))
     }
 }
@@ -378,6 +801,13 @@ impl fmt::Display for AsNumber {
     }
 }
 
+impl From<AsNumber> for ResourceSet {
+    fn from(asn: AsNumber) -> ResourceSet {
+        let blocks = AsBlocks::from_str(&format!("AS{}", asn.0)).unwrap();
+        ResourceSet::new(blocks, IpBlocks::empty(), IpBlocks::empty())
+    }
+}
+
 //------------ AuthorizationFmtError -------------------------------------
 
 #[derive(Clone, Debug, Display, Eq, PartialEq)]
@@ -393,6 +823,12 @@ pub enum AuthorizationFmtError {
 
     #[display(fmt = "Invalid authorisation delta string: {}", _0)]
     Delta(String),
+
+    #[display(fmt = "Invalid ASPA string: {}", _0)]
+    Aspa(String),
+
+    #[display(fmt = "Unsupported ROA format: {}, expected csv, json or slurm", _0)]
+    Format(String),
 }
 
 impl AuthorizationFmtError {
@@ -411,6 +847,10 @@ impl AuthorizationFmtError {
     pub fn delta(s: &str) -> Self {
         AuthorizationFmtError::Delta(s.to_string())
     }
+
+    pub fn aspa(s: &str) -> Self {
+        AuthorizationFmtError::Aspa(s.to_string())
+    }
 }
 
 //------------ Tests -------------------------------------------------------
@@ -447,6 +887,114 @@ mod tests {
         assert_eq!(parsed, reparsed);
     }
 
+    #[test]
+    fn import_csv() {
+        let csv = concat!(
+            "prefix,max-length,asn,comment\n",
+            "192.168.0.0/16,24,AS64496,some, comment\n",
+            "# a comment\n",
+            "2001:db8::/32,,64497,\n",
+            "10.0.0.0/8,24\n",
+            "10.0.0.0/8,x,64496,\n",
+        );
+
+        let err = RoaDefinitionFormat::Csv.parse(csv).unwrap_err();
+        assert_eq!(2, err.issues().len());
+        assert!(err.issues()[0].starts_with("line 5:"));
+        assert!(err.issues()[1].starts_with("line 6:"));
+
+        let csv = csv.lines().take(4).collect::<Vec<_>>().join("\n");
+        let definitions = RoaDefinitionFormat::Csv.parse(&csv).unwrap();
+        assert_eq!(
+            vec![
+                RoaDefinition::from_str("192.168.0.0/16-24 => 64496").unwrap(),
+                RoaDefinition::from_str("2001:db8::/32 => 64497").unwrap(),
+            ],
+            definitions
+        );
+    }
+
+    #[test]
+    fn import_export_roundtrip() {
+        let definitions = vec![
+            RoaDefinition::from_str("192.168.0.0/16-24 => 64496").unwrap(),
+            RoaDefinition::from_str("2001:db8::/32 => 64497").unwrap(),
+        ];
+
+        for format in &[
+            RoaDefinitionFormat::Csv,
+            RoaDefinitionFormat::Json,
+            RoaDefinitionFormat::Slurm,
+        ] {
+            let exported = format.format(&definitions);
+            let imported = format.parse(&exported).unwrap();
+            assert_eq!(definitions, imported);
+        }
+    }
+
+    #[test]
+    fn import_slurm() {
+        let slurm = r#"{
+            "slurmVersion": 1,
+            "validationOutputFilters": { "prefixFilters": [], "bgpsecFilters": [] },
+            "locallyAddedAssertions": {
+                "prefixAssertions": [
+                    { "asn": 64496, "prefix": "198.51.100.0/24", "comment": "My other server" },
+                    { "asn": 64496, "prefix": "2001:db8::/32", "maxPrefixLength": 48 },
+                    { "asn": 64496, "prefix": "invalid" }
+                ],
+                "bgpsecAssertions": []
+            }
+        }"#;
+
+        let err = RoaDefinitionFormat::Slurm.parse(slurm).unwrap_err();
+        assert_eq!(
+            &vec!["prefixAssertions[2]: Invalid prefix string: invalid".to_string()],
+            err.issues()
+        );
+    }
+
+    #[test]
+    fn replace_definitions() {
+        let current = vec![
+            RoaDefinition::from_str("192.168.0.0/16 => 64496").unwrap(),
+            RoaDefinition::from_str("192.168.0.0/16 => 64497").unwrap(),
+        ];
+        let target = vec![
+            RoaDefinition::from_str("192.168.0.0/16 => 64497").unwrap(),
+            RoaDefinition::from_str("10.0.0.0/8 => 64497").unwrap(),
+        ];
+
+        let mut expected = RoaDefinitionUpdates::empty();
+        expected.add(RoaDefinition::from_str("10.0.0.0/8 => 64497").unwrap());
+        expected.remove(RoaDefinition::from_str("192.168.0.0/16 => 64496").unwrap());
+        assert_eq!(expected, RoaDefinitionUpdates::replace(&current, &target));
+
+        let mut expected = RoaDefinitionUpdates::empty();
+        expected.add(RoaDefinition::from_str("10.0.0.0/8 => 64497").unwrap());
+        assert_eq!(
+            expected,
+            RoaDefinitionUpdates::add_missing(&current, &target)
+        );
+    }
+
+    #[test]
+    fn prefix_covers() {
+        let pfx = |s: &str| TypedPrefix::from_str(s).unwrap();
+
+        assert!(pfx("10.0.0.0/8").covers(&pfx("10.0.0.0/8")));
+        assert!(pfx("10.0.0.0/8").covers(&pfx("10.1.0.0/16")));
+        assert!(!pfx("10.1.0.0/16").covers(&pfx("10.0.0.0/8")));
+        assert!(!pfx("10.0.0.0/16").covers(&pfx("10.1.0.0/24")));
+        assert!(pfx("0.0.0.0/0").covers(&pfx("192.168.0.0/24")));
+
+        assert!(pfx("2001:db8::/32").covers(&pfx("2001:db8:1::/48")));
+        assert!(!pfx("2001:db8::/32").covers(&pfx("2001:db9::/48")));
+        assert!(pfx("::/0").covers(&pfx("2001:db8::/32")));
+
+        assert!(!pfx("::/0").covers(&pfx("10.0.0.0/8")));
+    }
+
     #[test]
     fn parse_type_prefix() {
         assert!(TypedPrefix::from_str("192.168.0.0/16").is_ok());
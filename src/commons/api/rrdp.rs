@@ -18,6 +18,10 @@ use crate::commons::api::Base64;
 use crate::commons::api::HexEncodedHash;
 use crate::commons::util::file;
 use crate::commons::util::xml::XmlWriter;
+use crate::constants::{
+    REPOSITORY_RRDP_DELTA_FILES_MAX_SIZE_PCT, REPOSITORY_RRDP_DELTA_FILES_MIN_NR,
+    REPOSITORY_RRDP_FILES_RETAIN_MINS,
+};
 
 const VERSION: &str = "1";
 const NS: &str = "http://www.ripe.net/rpki/rrdp";
@@ -233,6 +237,14 @@ impl Notification {
         self.last_delta
     }
 
+    pub fn snapshot(&self) -> &SnapshotRef {
+        &self.snapshot
+    }
+
+    pub fn deltas(&self) -> &Vec<DeltaRef> {
+        &self.deltas
+    }
+
     fn find_last_delta(deltas: &[DeltaRef]) -> Option<u64> {
         if deltas.is_empty() {
             None
@@ -325,9 +337,16 @@ impl Notification {
 
     pub fn write_xml(&self, path: &PathBuf) -> Result<(), io::Error> {
         trace!("Writing notification file: {}", path.to_string_lossy());
-        let mut file = file::create_file_with_path(&path)?;
+        let vec = self.xml();
+        let bytes = Bytes::from(vec);
+
+        file::save(&bytes, path)?;
 
-        XmlWriter::encode_to_file(&mut file, |w| {
+        Ok(())
+    }
+
+    pub fn xml(&self) -> Vec<u8> {
+        XmlWriter::encode_vec(|w| {
             let a = [
                 ("xmlns", NS),
                 ("version", VERSION),
@@ -359,9 +378,7 @@ impl Notification {
 
                 Ok(())
             })
-        })?;
-
-        Ok(())
+        })
     }
 }
 
@@ -560,6 +577,128 @@ impl CurrentObjects {
     }
 }
 
+//------------ RrdpRetention -------------------------------------------------
+
+/// Defines which deltas are included in the notification file, and how long
+/// snapshot and delta files which are no longer referenced are kept on disk.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RrdpRetention {
+    /// Always keep at least this many deltas, if available.
+    delta_files_min_nr: usize,
+
+    /// Beyond the minimum, only keep deltas as long as their combined size
+    /// stays below this percentage of the snapshot size.
+    delta_files_max_size_pct: u64,
+
+    /// The minimum age in minutes of snapshot and delta files which are no
+    /// longer referenced, before they are removed.
+    files_retain_mins: u64,
+}
+
+impl RrdpRetention {
+    pub fn new(
+        delta_files_min_nr: usize,
+        delta_files_max_size_pct: u64,
+        files_retain_mins: u64,
+    ) -> Self {
+        RrdpRetention {
+            delta_files_min_nr,
+            delta_files_max_size_pct,
+            files_retain_mins,
+        }
+    }
+
+    pub fn delta_files_min_nr(&self) -> usize {
+        self.delta_files_min_nr
+    }
+
+    pub fn delta_files_max_size_pct(&self) -> u64 {
+        self.delta_files_max_size_pct
+    }
+
+    pub fn files_retain_mins(&self) -> u64 {
+        self.files_retain_mins
+    }
+
+    /// Returns the maximum combined size of deltas beyond the minimum number
+    /// of deltas, for a snapshot of the given size.
+    pub fn max_deltas_size(&self, snapshot_size: usize) -> usize {
+        (snapshot_size as u64 * self.delta_files_max_size_pct / 100) as usize
+    }
+}
+
+impl Default for RrdpRetention {
+    fn default() -> Self {
+        RrdpRetention {
+            delta_files_min_nr: REPOSITORY_RRDP_DELTA_FILES_MIN_NR,
+            delta_files_max_size_pct: REPOSITORY_RRDP_DELTA_FILES_MAX_SIZE_PCT,
+            files_retain_mins: REPOSITORY_RRDP_FILES_RETAIN_MINS,
+        }
+    }
+}
+
+impl fmt::Display for RrdpRetention {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min deltas: {}, max deltas size: {}% of snapshot, retain files: {} minutes",
+            self.delta_files_min_nr, self.delta_files_max_size_pct, self.files_retain_mins
+        )
+    }
+}
+
+//------------ RrdpRetentionStats --------------------------------------------
+
+/// Shows the current retention policy, and the deltas which are currently
+/// included in the notification file as a result.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RrdpRetentionStats {
+    policy: RrdpRetention,
+    snapshot_size: usize,
+    deltas: usize,
+    deltas_size: usize,
+    oldest_delta: Option<u64>,
+}
+
+impl RrdpRetentionStats {
+    pub fn new(
+        policy: RrdpRetention,
+        snapshot_size: usize,
+        deltas: usize,
+        deltas_size: usize,
+        oldest_delta: Option<u64>,
+    ) -> Self {
+        RrdpRetentionStats {
+            policy,
+            snapshot_size,
+            deltas,
+            deltas_size,
+            oldest_delta,
+        }
+    }
+
+    pub fn policy(&self) -> &RrdpRetention {
+        &self.policy
+    }
+
+    pub fn snapshot_size(&self) -> usize {
+        self.snapshot_size
+    }
+
+    pub fn deltas(&self) -> usize {
+        self.deltas
+    }
+
+    pub fn deltas_size(&self) -> usize {
+        self.deltas_size
+    }
+
+    /// The serial of the oldest delta in the notification file, if any.
+    pub fn oldest_delta(&self) -> Option<u64> {
+        self.oldest_delta
+    }
+}
+
 //------------ Snapshot ------------------------------------------------------
 
 /// A structure to contain the RRDP snapshot data.
@@ -621,8 +760,13 @@ impl Snapshot {
                 ("serial", &format!("{}", self.serial)),
             ];
 
+            // Sort by uri so that the snapshot for a given set of objects
+            // is always the same, and so is its hash.
+            let mut elements = self.current_objects.elements();
+            elements.sort_by_key(|el| el.uri.to_string());
+
             w.put_element("snapshot", Some(&a), |w| {
-                for el in self.current_objects.elements() {
+                for el in elements {
                     let uri = el.uri.to_string();
                     let atr = [("uri", uri.as_ref())];
                     w.put_element("publish", Some(&atr), |w| w.put_text(el.base64.as_ref()))
@@ -0,0 +1,108 @@
+use std::fmt;
+
+use chrono::Duration;
+
+use crate::constants::{PUBLISH_NEXT_HOURS, PUBLISH_THRESHOLD_HOURS, PUBLISH_VALID_DAYS};
+
+//------------ PublicationTiming -------------------------------------------
+
+/// Determines the timing of the manifest and CRL that a CA publishes for
+/// each of its keys.
+///
+/// The next update time of both the manifest and the CRL is set to the
+/// next update interval from the moment they are created. The EE
+/// certificate for the manifest is valid for the object validity period,
+/// which should cover the next update time. Objects are republished once
+/// their next update time is within the republish margin.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublicationTiming {
+    next_update_hours: u32,
+    valid_days: u32,
+    republish_hours: u32,
+}
+
+impl Default for PublicationTiming {
+    fn default() -> Self {
+        PublicationTiming {
+            next_update_hours: PUBLISH_NEXT_HOURS,
+            valid_days: PUBLISH_VALID_DAYS,
+            republish_hours: PUBLISH_THRESHOLD_HOURS,
+        }
+    }
+}
+
+impl PublicationTiming {
+    pub fn new(next_update_hours: u32, valid_days: u32, republish_hours: u32) -> Self {
+        PublicationTiming {
+            next_update_hours,
+            valid_days,
+            republish_hours,
+        }
+    }
+
+    pub fn next_update_hours(&self) -> u32 {
+        self.next_update_hours
+    }
+
+    pub fn next_update(&self) -> Duration {
+        Duration::hours(i64::from(self.next_update_hours))
+    }
+
+    pub fn valid_days(&self) -> u32 {
+        self.valid_days
+    }
+
+    pub fn validity(&self) -> Duration {
+        Duration::days(i64::from(self.valid_days))
+    }
+
+    pub fn republish_hours(&self) -> u32 {
+        self.republish_hours
+    }
+
+    pub fn republish(&self) -> Duration {
+        Duration::hours(i64::from(self.republish_hours))
+    }
+
+    /// The manifest EE certificate must not expire before the next update
+    /// time, and objects must be republished some time before, but not
+    /// immediately after, they are created.
+    pub fn is_valid(&self) -> bool {
+        self.republish_hours > 0
+            && self.republish_hours < self.next_update_hours
+            && u64::from(self.next_update_hours) <= u64::from(self.valid_days) * 24
+    }
+}
+
+impl fmt::Display for PublicationTiming {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "next update: {} hours, validity: {} days, republish: {} hours before next update",
+            self.next_update_hours, self.valid_days, self.republish_hours
+        )
+    }
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_timing_is_valid() {
+        assert!(PublicationTiming::default().is_valid());
+    }
+
+    #[test]
+    fn invalid_timing() {
+        // republish margin must be less than next update interval
+        assert!(!PublicationTiming::new(24, 7, 24).is_valid());
+        assert!(!PublicationTiming::new(24, 7, 0).is_valid());
+
+        // manifest EE must outlive the next update time
+        assert!(!PublicationTiming::new(48, 1, 8).is_valid());
+        assert!(PublicationTiming::new(24, 1, 8).is_valid());
+    }
+}
@@ -0,0 +1,550 @@
+//! Analyse the effect of ROAs on the announcements seen in BGP.
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use rpki::x509::Time;
+
+use crate::commons::api::{AsNumber, ResourceSet, RoaDefinition, RoaDefinitionUpdates};
+use crate::commons::bgp::{Announcement, Announcements, BgpLoadError};
+
+//------------ BgpAnalyser -------------------------------------------------
+
+/// Holds the BGP announcements loaded from a local file, and analyses how
+/// ROAs affect the RPKI validity of the announcements (RFC 6811).
+pub struct BgpAnalyser {
+    source: Option<PathBuf>,
+    announcements: RwLock<Announcements>,
+    loaded: RwLock<Option<Time>>,
+}
+
+impl BgpAnalyser {
+    pub fn new(source: Option<PathBuf>) -> Self {
+        BgpAnalyser {
+            source,
+            announcements: RwLock::new(Announcements::default()),
+            loaded: RwLock::new(None),
+        }
+    }
+
+    /// Returns the time the announcements were last loaded, if ever.
+    pub fn loaded(&self) -> Option<Time> {
+        *self.loaded.read().unwrap()
+    }
+
+    /// (Re-)loads the announcements from the source file, if configured.
+    /// The current announcements are kept if loading fails.
+    pub fn update(&self) -> Result<(), BgpLoadError> {
+        if let Some(source) = &self.source {
+            let announcements = Announcements::load(source)?;
+            info!(
+                "Loaded {} BGP announcements from {}",
+                announcements.len(),
+                source.to_string_lossy()
+            );
+            *self.announcements.write().unwrap() = announcements;
+            *self.loaded.write().unwrap() = Some(Time::now());
+        }
+        Ok(())
+    }
+
+    /// Analyses the announcements for prefixes held in the scope against
+    /// the given ROA definitions.
+    pub fn analyse(&self, definitions: &[RoaDefinition], scope: &ResourceSet) -> BgpAnalysisReport {
+        let announcements = self.announcements.read().unwrap();
+        let in_scope = Self::in_scope(&announcements, scope);
+        Self::report(&in_scope, definitions, self.loaded())
+    }
+
+    /// Analyses the effect that the updates would have on the announcements
+    /// for prefixes held in the scope, compared to the current definitions.
+    pub fn dry_run(
+        &self,
+        current: &[RoaDefinition],
+        updates: RoaDefinitionUpdates,
+        scope: &ResourceSet,
+    ) -> BgpAnalysisDryRun {
+        let (added, removed) = updates.unpack();
+
+        let mut after: HashSet<RoaDefinition> = current.iter().cloned().collect();
+        for definition in removed.iter() {
+            after.remove(definition);
+        }
+        after.extend(added);
+        let after: Vec<RoaDefinition> = after.into_iter().collect();
+
+        let announcements = self.announcements.read().unwrap();
+        let in_scope = Self::in_scope(&announcements, scope);
+
+        let changes = in_scope
+            .iter()
+            .filter_map(|announcement| {
+                let before = Self::validate(announcement, current).0;
+                let after = Self::validate(announcement, &after).0;
+                if before != after {
+                    Some(BgpAnalysisChange {
+                        announcement: **announcement,
+                        before,
+                        after,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let report = Self::report(&in_scope, &after, self.loaded());
+
+        BgpAnalysisDryRun { changes, report }
+    }
+
+    /// Suggests updates to the definitions, based on the announcements for
+    /// prefixes held in the scope:
+    ///  - remove authorizations that do not make any announcement valid
+    ///  - tighten the max length of authorizations to the longest prefix
+    ///    that is actually announced
+    ///  - add authorizations for announcements that would not be valid
+    ///    after the above, i.e. not found or invalid
+    ///
+    /// AS0 authorizations (RFC 7607) are left alone. They are meant to make
+    /// announcements invalid, so they will never make any announcement valid,
+    /// and announcements they cover do not get authorizations added.
+    pub fn suggest(
+        &self,
+        definitions: &[RoaDefinition],
+        scope: &ResourceSet,
+    ) -> RoaDefinitionUpdates {
+        let announcements = self.announcements.read().unwrap();
+        let in_scope = Self::in_scope(&announcements, scope);
+
+        let mut updates = RoaDefinitionUpdates::empty();
+        let mut after: Vec<RoaDefinition> = vec![];
+
+        for definition in definitions {
+            if definition.asn() == AsNumber::new(0) {
+                after.push(*definition);
+                continue;
+            }
+
+            let longest = in_scope
+                .iter()
+                .filter(|a| {
+                    a.asn() == definition.asn()
+                        && definition.prefix().covers(&a.prefix())
+                        && a.prefix().addr_len() <= definition.effective_max_length()
+                })
+                .map(|a| a.prefix().addr_len())
+                .max();
+
+            match longest {
+                None => updates.remove(*definition),
+                Some(longest) if longest < definition.effective_max_length() => {
+                    let max_length = if longest == definition.prefix().addr_len() {
+                        None
+                    } else {
+                        Some(longest)
+                    };
+                    let tightened =
+                        RoaDefinition::new(definition.asn(), definition.prefix(), max_length);
+                    updates.remove(*definition);
+                    updates.add(tightened);
+                    after.push(tightened);
+                }
+                Some(_) => after.push(*definition),
+            }
+        }
+
+        for announcement in in_scope.iter() {
+            let (state, roas) = Self::validate(announcement, &after);
+            let disallowed = roas.iter().any(|d| d.asn() == AsNumber::new(0));
+            if state != BgpAnalysisState::Valid && !disallowed {
+                updates.add(RoaDefinition::new(
+                    announcement.asn(),
+                    announcement.prefix(),
+                    None,
+                ));
+            }
+        }
+
+        updates
+    }
+
+    fn in_scope<'a>(
+        announcements: &'a Announcements,
+        scope: &ResourceSet,
+    ) -> Vec<&'a Announcement> {
+        announcements
+            .iter()
+            .filter(|a| scope.contains(&a.prefix().into()))
+            .collect()
+    }
+
+    fn report(
+        announcements: &[&Announcement],
+        definitions: &[RoaDefinition],
+        loaded: Option<Time>,
+    ) -> BgpAnalysisReport {
+        let mut seen: HashSet<RoaDefinition> = HashSet::new();
+
+        let entries: Vec<BgpAnalysisEntry> = announcements
+            .iter()
+            .map(|announcement| {
+                let (state, roas) = Self::validate(announcement, definitions);
+                if state == BgpAnalysisState::Valid {
+                    seen.extend(roas.iter().cloned());
+                }
+                BgpAnalysisEntry {
+                    announcement: **announcement,
+                    state,
+                    roas,
+                }
+            })
+            .collect();
+
+        let mut roas_unseen: Vec<RoaDefinition> = definitions
+            .iter()
+            .filter(|d| !seen.contains(d))
+            .cloned()
+            .collect();
+        roas_unseen.sort_by_key(|d| d.to_string());
+
+        BgpAnalysisReport {
+            loaded,
+            entries,
+            roas_unseen,
+        }
+    }
+
+    /// Validates an announcement against the definitions as described in
+    /// RFC 6811. Returns the state, and the ROAs that made it so: the
+    /// matching ROAs for valid announcements, or all covering ROAs for
+    /// invalid announcements.
+    fn validate(
+        announcement: &Announcement,
+        definitions: &[RoaDefinition],
+    ) -> (BgpAnalysisState, Vec<RoaDefinition>) {
+        let prefix = announcement.prefix();
+
+        let covering: Vec<RoaDefinition> = definitions
+            .iter()
+            .filter(|d| d.prefix().covers(&prefix))
+            .cloned()
+            .collect();
+
+        if covering.is_empty() {
+            return (BgpAnalysisState::NotFound, covering);
+        }
+
+        let matching: Vec<RoaDefinition> = covering
+            .iter()
+            .filter(|d| {
+                d.asn() == announcement.asn() && prefix.addr_len() <= d.effective_max_length()
+            })
+            .cloned()
+            .collect();
+
+        if !matching.is_empty() {
+            (BgpAnalysisState::Valid, matching)
+        } else if covering.iter().any(|d| d.asn() == announcement.asn()) {
+            (BgpAnalysisState::InvalidLength, covering)
+        } else {
+            (BgpAnalysisState::InvalidAsn, covering)
+        }
+    }
+}
+
+//------------ BgpAnalysisState --------------------------------------------
+
+/// The RPKI validity of an announcement.
+#[derive(Clone, Copy, Debug, Deserialize, Display, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BgpAnalysisState {
+    #[display(fmt = "valid")]
+    Valid,
+
+    #[display(fmt = "invalid asn")]
+    InvalidAsn,
+
+    #[display(fmt = "invalid length")]
+    InvalidLength,
+
+    #[display(fmt = "not found")]
+    NotFound,
+}
+
+//------------ BgpAnalysisEntry --------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BgpAnalysisEntry {
+    announcement: Announcement,
+    state: BgpAnalysisState,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    roas: Vec<RoaDefinition>,
+}
+
+impl BgpAnalysisEntry {
+    pub fn announcement(&self) -> &Announcement {
+        &self.announcement
+    }
+
+    pub fn state(&self) -> BgpAnalysisState {
+        self.state
+    }
+
+    pub fn roas(&self) -> &Vec<RoaDefinition> {
+        &self.roas
+    }
+}
+
+//------------ BgpAnalysisReport -------------------------------------------
+
+/// The validity of all announcements for the prefixes held by a CA, and
+/// the ROAs of the CA that do not make any announcement valid.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BgpAnalysisReport {
+    loaded: Option<Time>,
+    entries: Vec<BgpAnalysisEntry>,
+    roas_unseen: Vec<RoaDefinition>,
+}
+
+impl BgpAnalysisReport {
+    pub fn loaded(&self) -> Option<Time> {
+        self.loaded
+    }
+
+    pub fn entries(&self) -> &Vec<BgpAnalysisEntry> {
+        &self.entries
+    }
+
+    pub fn roas_unseen(&self) -> &Vec<RoaDefinition> {
+        &self.roas_unseen
+    }
+}
+
+//------------ BgpAnalysisChange -------------------------------------------
+
+/// The change in validity of an announcement, caused by ROA updates.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BgpAnalysisChange {
+    announcement: Announcement,
+    before: BgpAnalysisState,
+    after: BgpAnalysisState,
+}
+
+impl BgpAnalysisChange {
+    pub fn announcement(&self) -> &Announcement {
+        &self.announcement
+    }
+
+    pub fn before(&self) -> BgpAnalysisState {
+        self.before
+    }
+
+    pub fn after(&self) -> BgpAnalysisState {
+        self.after
+    }
+}
+
+//------------ BgpAnalysisDryRun -------------------------------------------
+
+/// The announcements that would change validity if ROA updates were
+/// applied, and the full report for the resulting ROAs.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BgpAnalysisDryRun {
+    changes: Vec<BgpAnalysisChange>,
+    report: BgpAnalysisReport,
+}
+
+impl BgpAnalysisDryRun {
+    pub fn changes(&self) -> &Vec<BgpAnalysisChange> {
+        &self.changes
+    }
+
+    pub fn report(&self) -> &BgpAnalysisReport {
+        &self.report
+    }
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn definition(s: &str) -> RoaDefinition {
+        RoaDefinition::from_str(s).unwrap()
+    }
+
+    fn analyser() -> BgpAnalyser {
+        let csv = concat!(
+            "10.0.0.0/16,64496\n",
+            "10.0.1.0/24,64496\n",
+            "10.0.2.0/24,64497\n",
+            "10.1.0.0/24,64496\n",
+            "192.168.0.0/24,64496\n", // out of scope
+        );
+        let analyser = BgpAnalyser::new(None);
+        *analyser.announcements.write().unwrap() = Announcements::from_csv(csv).unwrap();
+        analyser
+    }
+
+    fn scope() -> ResourceSet {
+        ResourceSet::from_strs("", "10.0.0.0/8", "").unwrap()
+    }
+
+    fn state(report: &BgpAnalysisReport, announcement: &str) -> BgpAnalysisState {
+        let announcement = Announcement::from_str(announcement).unwrap();
+        report
+            .entries()
+            .iter()
+            .find(|e| e.announcement() == &announcement)
+            .unwrap()
+            .state()
+    }
+
+    #[test]
+    fn analyse_announcements() {
+        let definitions = vec![
+            definition("10.0.0.0/16 => 64496"),
+            definition("10.0.0.0/16-24 => 64497"),
+            definition("10.2.0.0/16 => 64496"),
+        ];
+
+        let report = analyser().analyse(&definitions, &scope());
+        assert_eq!(4, report.entries().len());
+
+        assert_eq!(BgpAnalysisState::Valid, state(&report, "10.0.0.0/16,64496"));
+        assert_eq!(
+            BgpAnalysisState::InvalidLength,
+            state(&report, "10.0.1.0/24,64496")
+        );
+        assert_eq!(BgpAnalysisState::Valid, state(&report, "10.0.2.0/24,64497"));
+        assert_eq!(
+            BgpAnalysisState::NotFound,
+            state(&report, "10.1.0.0/24,64496")
+        );
+
+        assert_eq!(
+            &vec![definition("10.2.0.0/16 => 64496")],
+            report.roas_unseen()
+        );
+    }
+
+    #[test]
+    fn suggest_updates() {
+        let definitions = vec![
+            definition("10.0.0.0/16-24 => 64496"),
+            definition("10.0.0.0/16-20 => 64497"),
+            definition("10.2.0.0/16 => 64496"),
+        ];
+
+        let suggestion = analyser().suggest(&definitions, &scope());
+
+        // 10.0.2.0/24 from 64497 is invalid once its unused definition is
+        // removed, so it gets an authorization.
+        let mut expected = RoaDefinitionUpdates::empty();
+        expected.add(definition("10.0.2.0/24 => 64497"));
+        expected.add(definition("10.1.0.0/24 => 64496"));
+        expected.remove(definition("10.0.0.0/16-20 => 64497"));
+        expected.remove(definition("10.2.0.0/16 => 64496"));
+
+        assert_eq!(expected, suggestion);
+    }
+
+    #[test]
+    fn suggest_additions_after_removals() {
+        // Not used by any announcement, but covers all in 10.0.0.0/16
+        let definitions = vec![definition("10.0.0.0/16 => 64497")];
+
+        let suggestion = analyser().suggest(&definitions, &scope());
+
+        let mut expected = RoaDefinitionUpdates::empty();
+        expected.remove(definition("10.0.0.0/16 => 64497"));
+        expected.add(definition("10.0.0.0/16 => 64496"));
+        expected.add(definition("10.0.1.0/24 => 64496"));
+        expected.add(definition("10.0.2.0/24 => 64497"));
+        expected.add(definition("10.1.0.0/24 => 64496"));
+
+        assert_eq!(expected, suggestion);
+
+        // Applying the suggestion makes all announcements in scope valid
+        let dry_run = analyser().dry_run(&definitions, suggestion, &scope());
+        assert!(dry_run
+            .report()
+            .entries()
+            .iter()
+            .all(|e| e.state() == BgpAnalysisState::Valid));
+    }
+
+    #[test]
+    fn suggest_tighten_max_length() {
+        let definitions = vec![
+            definition("10.0.0.0/8-20 => 64496"),
+            definition("10.0.0.0/16-20 => 64496"),
+        ];
+
+        let suggestion = analyser().suggest(&definitions, &scope());
+
+        let mut expected = RoaDefinitionUpdates::empty();
+        expected.remove(definition("10.0.0.0/8-20 => 64496"));
+        expected.add(definition("10.0.0.0/8-16 => 64496"));
+        expected.remove(definition("10.0.0.0/16-20 => 64496"));
+        expected.add(definition("10.0.0.0/16 => 64496"));
+
+        // Announcements that are invalid after tightening are added
+        expected.add(definition("10.0.1.0/24 => 64496"));
+        expected.add(definition("10.0.2.0/24 => 64497"));
+        expected.add(definition("10.1.0.0/24 => 64496"));
+
+        assert_eq!(expected, suggestion);
+    }
+
+    #[test]
+    fn suggest_keeps_as0_definitions() {
+        let definitions = vec![
+            definition("10.0.0.0/16-24 => 64496"),
+            definition("10.0.0.0/16-24 => 64497"),
+            definition("10.1.0.0/16-24 => 0"),
+            definition("10.2.0.0/16 => 0"),
+            definition("10.2.0.0/16 => 64496"),
+        ];
+
+        let suggestion = analyser().suggest(&definitions, &scope());
+
+        // 10.1.0.0/24 from 64496 is invalid because of the AS0 definition,
+        // so no authorization is added for it.
+        let mut expected = RoaDefinitionUpdates::empty();
+        expected.remove(definition("10.2.0.0/16 => 64496"));
+
+        assert_eq!(expected, suggestion);
+    }
+
+    #[test]
+    fn dry_run_updates() {
+        let current = vec![definition("10.0.0.0/16 => 64496")];
+
+        let updates = {
+            let mut added = HashSet::new();
+            added.insert(definition("10.1.0.0/16 => 64497"));
+            RoaDefinitionUpdates::new(added, HashSet::new())
+        };
+
+        let dry_run = analyser().dry_run(&current, updates, &scope());
+
+        let change = dry_run.changes().first().unwrap();
+        assert_eq!(1, dry_run.changes().len());
+        assert_eq!(
+            &Announcement::from_str("10.1.0.0/24,64496").unwrap(),
+            change.announcement()
+        );
+        assert_eq!(BgpAnalysisState::NotFound, change.before());
+        assert_eq!(BgpAnalysisState::InvalidAsn, change.after());
+
+        assert_eq!(
+            BgpAnalysisState::InvalidAsn,
+            state(dry_run.report(), "10.1.0.0/24,64496")
+        );
+    }
+}
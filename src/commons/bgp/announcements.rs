@@ -0,0 +1,401 @@
+//! BGP announcements, as loaded from a local MRT RIB dump or CSV file.
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+use std::str::{from_utf8, FromStr};
+
+use crate::commons::api::{AsNumber, AuthorizationFmtError, TypedPrefix};
+use crate::commons::util::file;
+
+//------------ Announcement ------------------------------------------------
+
+/// A prefix as announced in BGP, and the ASN that originates it.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct Announcement {
+    asn: AsNumber,
+    prefix: TypedPrefix,
+}
+
+impl Announcement {
+    pub fn new(asn: AsNumber, prefix: TypedPrefix) -> Self {
+        Announcement { asn, prefix }
+    }
+
+    pub fn asn(&self) -> AsNumber {
+        self.asn
+    }
+
+    pub fn prefix(&self) -> TypedPrefix {
+        self.prefix
+    }
+}
+
+impl FromStr for Announcement {
+    type Err = AuthorizationFmtError;
+
+    // "192.168.0.0/16,64496" or "192.168.0.0/16,AS64496"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+
+        let prefix_str = parts.next().ok_or_else(|| AuthorizationFmtError::auth(s))?;
+        let prefix = TypedPrefix::from_str(prefix_str.trim())?;
+
+        let asn_str = parts.next().ok_or_else(|| AuthorizationFmtError::auth(s))?;
+        let asn = AsNumber::from_str(asn_str)?;
+
+        if parts.next().is_some() {
+            return Err(AuthorizationFmtError::auth(s));
+        }
+
+        Ok(Announcement { asn, prefix })
+    }
+}
+
+impl fmt::Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} => {}", self.prefix, self.asn)
+    }
+}
+
+//------------ Announcements -----------------------------------------------
+
+/// The set of announcements seen in BGP.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Announcements {
+    inner: Vec<Announcement>,
+}
+
+impl Announcements {
+    pub fn new(announcements: HashSet<Announcement>) -> Self {
+        let mut inner: Vec<Announcement> = announcements.into_iter().collect();
+        inner.sort_by_cached_key(|a| (a.prefix.to_string(), a.asn));
+        Announcements { inner }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Announcement> {
+        self.inner.iter()
+    }
+
+    /// Loads announcements from a file. MRT TABLE_DUMP_V2 RIB dumps (as
+    /// published by RIPE RIS and RouteViews, but uncompressed) are
+    /// recognised by their header, anything else is parsed as a simple
+    /// CSV file with a 'prefix,asn' pair on each line.
+    pub fn load(path: &PathBuf) -> Result<Self, BgpLoadError> {
+        let bytes = file::read(path)?;
+        if MrtReader::is_mrt(&bytes) {
+            MrtReader::new(&bytes).read_announcements()
+        } else {
+            let text = from_utf8(&bytes).map_err(|_| BgpLoadError::Csv("not utf8".to_string()))?;
+            Self::from_csv(text)
+        }
+    }
+
+    /// Parses announcements from lines with a 'prefix,asn' pair. Empty lines
+    /// and lines starting with '#' are ignored.
+    pub fn from_csv(text: &str) -> Result<Self, BgpLoadError> {
+        let mut announcements = HashSet::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let announcement =
+                Announcement::from_str(line).map_err(|e| BgpLoadError::Csv(e.to_string()))?;
+            announcements.insert(announcement);
+        }
+        Ok(Announcements::new(announcements))
+    }
+}
+
+//------------ MrtReader ---------------------------------------------------
+
+/// Reads announcements from the RIB entries in an MRT TABLE_DUMP_V2 file,
+/// see RFC 6396 and RFC 8050 (add-path). The origin ASN for each entry is
+/// the last ASN of the AS_PATH, entries which end in an AS_SET are skipped.
+struct MrtReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> MrtReader<'a> {
+    const HEADER_LEN: usize = 12;
+    const TYPE_TABLE_DUMP_V2: u16 = 13;
+
+    const SUBTYPE_RIB_IPV4_UNICAST: u16 = 2;
+    const SUBTYPE_RIB_IPV6_UNICAST: u16 = 4;
+    const SUBTYPE_RIB_IPV4_UNICAST_ADDPATH: u16 = 8;
+    const SUBTYPE_RIB_IPV6_UNICAST_ADDPATH: u16 = 10;
+
+    const ATTR_FLAG_EXTENDED_LENGTH: u8 = 0x10;
+    const ATTR_TYPE_AS_PATH: u8 = 2;
+    const AS_PATH_SEGMENT_SEQUENCE: u8 = 2;
+
+    fn new(bytes: &'a [u8]) -> Self {
+        MrtReader { bytes, pos: 0 }
+    }
+
+    fn is_mrt(bytes: &[u8]) -> bool {
+        bytes.len() >= Self::HEADER_LEN
+            && u16::from_be_bytes([bytes[4], bytes[5]]) == Self::TYPE_TABLE_DUMP_V2
+    }
+
+    fn read_announcements(&mut self) -> Result<Announcements, BgpLoadError> {
+        let mut announcements = HashSet::new();
+
+        while self.pos < self.bytes.len() {
+            let _timestamp = self.u32()?;
+            let mrt_type = self.u16()?;
+            let sub_type = self.u16()?;
+            let len = self.u32()? as usize;
+            let message = self.take(len)?;
+
+            if mrt_type != Self::TYPE_TABLE_DUMP_V2 {
+                continue;
+            }
+
+            let (v4, add_path) = match sub_type {
+                Self::SUBTYPE_RIB_IPV4_UNICAST => (true, false),
+                Self::SUBTYPE_RIB_IPV6_UNICAST => (false, false),
+                Self::SUBTYPE_RIB_IPV4_UNICAST_ADDPATH => (true, true),
+                Self::SUBTYPE_RIB_IPV6_UNICAST_ADDPATH => (false, true),
+                _ => continue, // peer index table, multicast, generic
+            };
+
+            MrtReader::new(message).read_rib(v4, add_path, &mut announcements)?;
+        }
+
+        Ok(Announcements::new(announcements))
+    }
+
+    fn read_rib(
+        &mut self,
+        v4: bool,
+        add_path: bool,
+        announcements: &mut HashSet<Announcement>,
+    ) -> Result<(), BgpLoadError> {
+        let _sequence = self.u32()?;
+        let prefix = self.prefix(v4)?;
+        let entry_count = self.u16()?;
+
+        for _ in 0..entry_count {
+            let _peer_index = self.u16()?;
+            let _originated = self.u32()?;
+            if add_path {
+                let _path_id = self.u32()?;
+            }
+            let attributes_len = self.u16()? as usize;
+            let attributes = self.take(attributes_len)?;
+
+            if let Some(asn) = MrtReader::new(attributes).origin()? {
+                announcements.insert(Announcement::new(asn, prefix));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn prefix(&mut self, v4: bool) -> Result<TypedPrefix, BgpLoadError> {
+        let len = self.u8()?;
+        let max_len = if v4 { 32 } else { 128 };
+        if len > max_len {
+            return Err(BgpLoadError::Mrt(format!("invalid prefix length: {}", len)));
+        }
+
+        let addr_bytes = self.take((usize::from(len) + 7) / 8)?;
+        let addr = if v4 {
+            let mut octets = [0; 4];
+            octets[..addr_bytes.len()].copy_from_slice(addr_bytes);
+            IpAddr::V4(Ipv4Addr::from(octets))
+        } else {
+            let mut octets = [0; 16];
+            octets[..addr_bytes.len()].copy_from_slice(addr_bytes);
+            IpAddr::V6(Ipv6Addr::from(octets))
+        };
+
+        TypedPrefix::from_str(&format!("{}/{}", addr, len))
+            .map_err(|e| BgpLoadError::Mrt(e.to_string()))
+    }
+
+    /// Returns the origin ASN from the AS_PATH in the path attributes, if
+    /// there is an AS_PATH that ends with an AS_SEQUENCE.
+    fn origin(&mut self) -> Result<Option<AsNumber>, BgpLoadError> {
+        while self.pos < self.bytes.len() {
+            let flags = self.u8()?;
+            let attr_type = self.u8()?;
+            let len = if flags & Self::ATTR_FLAG_EXTENDED_LENGTH != 0 {
+                self.u16()? as usize
+            } else {
+                self.u8()? as usize
+            };
+            let value = self.take(len)?;
+
+            if attr_type == Self::ATTR_TYPE_AS_PATH {
+                return MrtReader::new(value).as_path_origin();
+            }
+        }
+        Ok(None)
+    }
+
+    // TABLE_DUMP_V2 always uses 4 octet ASNs in the AS_PATH.
+    fn as_path_origin(&mut self) -> Result<Option<AsNumber>, BgpLoadError> {
+        let mut origin = None;
+        while self.pos < self.bytes.len() {
+            let segment_type = self.u8()?;
+            let segment_len = self.u8()?;
+            let mut last = None;
+            for _ in 0..segment_len {
+                last = Some(self.u32()?);
+            }
+            origin = if segment_type == Self::AS_PATH_SEGMENT_SEQUENCE {
+                last.map(AsNumber::new)
+            } else {
+                None
+            };
+        }
+        Ok(origin)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BgpLoadError> {
+        if self.pos + len > self.bytes.len() {
+            Err(BgpLoadError::Mrt("unexpected end of data".to_string()))
+        } else {
+            let res = &self.bytes[self.pos..self.pos + len];
+            self.pos += len;
+            Ok(res)
+        }
+    }
+
+    fn u8(&mut self) -> Result<u8, BgpLoadError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BgpLoadError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, BgpLoadError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+//------------ BgpLoadError ------------------------------------------------
+
+#[derive(Debug, Display)]
+pub enum BgpLoadError {
+    #[display(fmt = "Cannot read BGP announcements file: {}", _0)]
+    Io(io::Error),
+
+    #[display(fmt = "Invalid MRT data: {}", _0)]
+    Mrt(String),
+
+    #[display(fmt = "Invalid announcement in CSV: {}", _0)]
+    Csv(String),
+}
+
+impl From<io::Error> for BgpLoadError {
+    fn from(e: io::Error) -> Self {
+        BgpLoadError::Io(e)
+    }
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn announcement(s: &str) -> Announcement {
+        Announcement::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn parse_csv() {
+        let csv = concat!(
+            "# prefix,asn\n",
+            "\n",
+            "10.0.0.0/24,64496\n",
+            "10.0.0.0/24, AS64496\n", // duplicate
+            "2001:db8::/32,64497\n",
+        );
+
+        let announcements = Announcements::from_csv(csv).unwrap();
+        assert_eq!(2, announcements.len());
+        assert!(announcements
+            .iter()
+            .any(|a| a == &announcement("2001:db8::/32,64497")));
+
+        assert!(Announcements::from_csv("10.0.0.0/24").is_err());
+    }
+
+    #[test]
+    fn parse_mrt_rib() {
+        fn record(sub_type: u16, message: &[u8]) -> Vec<u8> {
+            let mut res = vec![0, 0, 0, 0];
+            res.extend_from_slice(&13u16.to_be_bytes());
+            res.extend_from_slice(&sub_type.to_be_bytes());
+            res.extend_from_slice(&(message.len() as u32).to_be_bytes());
+            res.extend_from_slice(message);
+            res
+        }
+
+        fn rib_entry(segments: &[(u8, &[u32])]) -> Vec<u8> {
+            let mut as_path = vec![];
+            for (segment_type, asns) in segments {
+                as_path.push(*segment_type);
+                as_path.push(asns.len() as u8);
+                for asn in asns.iter() {
+                    as_path.extend_from_slice(&asn.to_be_bytes());
+                }
+            }
+
+            // ORIGIN attribute, followed by the AS_PATH
+            let mut attributes = vec![0x40, 1, 1, 0];
+            attributes.extend_from_slice(&[0x40, 2, as_path.len() as u8]);
+            attributes.extend_from_slice(&as_path);
+
+            let mut entry = vec![0, 0, 0, 0, 0, 0]; // peer index, originated time
+            entry.extend_from_slice(&(attributes.len() as u16).to_be_bytes());
+            entry.extend_from_slice(&attributes);
+            entry
+        }
+
+        let mut v4_rib = vec![0, 0, 0, 1, 22, 10, 1, 0]; // seq, 10.1.0.0/22
+        v4_rib.extend_from_slice(&2u16.to_be_bytes());
+        v4_rib.extend_from_slice(&rib_entry(&[(2, &[64500, 64496][..])]));
+        v4_rib.extend_from_slice(&rib_entry(&[(2, &[64501, 64496][..])]));
+
+        let mut v6_rib = vec![0, 0, 0, 2, 32, 0x20, 0x01, 0x0d, 0xb8]; // seq, 2001:db8::/32
+        v6_rib.extend_from_slice(&2u16.to_be_bytes());
+        v6_rib.extend_from_slice(&rib_entry(&[(2, &[64500, 64497][..])]));
+        v6_rib.extend_from_slice(&rib_entry(&[(2, &[64500][..]), (1, &[64498, 64499][..])]));
+
+        let mut bytes = record(1, &[0; 10]); // peer index table, skipped
+        bytes.extend_from_slice(&record(2, &v4_rib));
+        bytes.extend_from_slice(&record(4, &v6_rib));
+
+        assert!(MrtReader::is_mrt(&bytes));
+
+        let announcements = MrtReader::new(&bytes).read_announcements().unwrap();
+        let expected = {
+            let mut set = HashSet::new();
+            set.insert(announcement("10.1.0.0/22,64496"));
+            set.insert(announcement("2001:db8::/32,64497"));
+            Announcements::new(set)
+        };
+        assert_eq!(expected, announcements);
+
+        assert!(!MrtReader::is_mrt(b"10.0.0.0/24,64496\n"));
+    }
+}
@@ -0,0 +1,7 @@
+//! Support for analysing ROAs against the announcements seen in BGP.
+
+mod analyser;
+pub use self::analyser::*;
+
+mod announcements;
+pub use self::announcements::*;
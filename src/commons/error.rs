@@ -12,8 +12,9 @@ use rpki::x509::ValidationError;
 
 use crate::commons::api::rrdp::PublicationDeltaError;
 use crate::commons::api::{
-    ChildHandle, ErrorResponse, Handle, ParentHandle, PublisherHandle, ResourceClassName,
-    ResourceSetError,
+    AsNumber, AspaDefinition, ChildHandle, ErrorResponse, Handle, KeyRollPolicy, KeyRollStatus,
+    ParentHandle, PublicationTiming, PublisherHandle, ResourceClassName, ResourceLease,
+    ResourceSetError, RoaImportError, RoaPackingPolicy,
 };
 use crate::commons::eventsourcing::AggregateStoreError;
 use crate::commons::remote::rfc6492;
@@ -92,6 +93,18 @@ pub enum Error {
     #[display(fmt = "No embedded repository configured")]
     PublisherNoEmbeddedRepo,
 
+    #[display(fmt = "Publisher '{}' would exceed its quota: {}", _0, _1)]
+    PublisherQuotaExceeded(PublisherHandle, String),
+
+    #[display(fmt = "Publisher '{}' is suspended", _0)]
+    PublisherSuspended(PublisherHandle),
+
+    #[display(fmt = "Publisher '{}' sent invalid object '{}': {}", _0, _1, _2)]
+    PublisherObjectInvalid(PublisherHandle, String, String),
+
+    #[display(fmt = "Publisher request is for '{}', expected '{}'", _0, _1)]
+    PublisherHandleMismatch(PublisherHandle, PublisherHandle),
+
     //-----------------------------------------------------------------
     // RFC 8181 (publishing)
     //-----------------------------------------------------------------
@@ -115,6 +128,19 @@ pub enum Error {
     #[display(fmt = "CA '{}' is unknown", _0)]
     CaUnknown(Handle),
 
+    #[display(fmt = "CA '{}' still has children, remove these first", _0)]
+    CaHasChildren(Handle),
+
+    #[display(fmt = "Cannot import CA '{}': {}", _0, _1)]
+    CaImportInvalid(Handle, String),
+
+    #[display(
+        fmt = "Cannot import CA '{}': embedded parent '{}' does not have it as a child on this server",
+        _0,
+        _1
+    )]
+    CaImportParentUnknown(Handle, ParentHandle),
+
     // CA Repo Issues
     #[display(fmt = "CA '{}' already uses this repository", _0)]
     CaRepoInUse(Handle),
@@ -182,12 +208,23 @@ pub enum Error {
     CaChildUnauthorised(Handle, ChildHandle),
 
     #[display(
-        fmt = "You can only update one aspect for child '{}' of CA '{}' at a time - i.e. either resources or ID cert",
+        fmt = "You can only update one aspect for child '{}' of CA '{}' at a time - i.e. either resources, leases or ID cert",
         _1,
         _0
     )]
     CaChildUpdateOneThing(Handle, ChildHandle),
 
+    #[display(
+        fmt = "Lease '{}' for child '{}' of CA '{}' has already expired",
+        _2,
+        _1,
+        _0
+    )]
+    CaChildLeaseExpired(Handle, ChildHandle, ResourceLease),
+
+    #[display(fmt = "Lease '{}' for child '{}' of CA '{}' expires soon", _2, _1, _0)]
+    CaChildLeaseExpiring(Handle, ChildHandle, ResourceLease),
+
     // RouteAuthorizations - ROAs
     #[display(fmt = "Cannot remove unknown ROA '{}' from CA '{}'", _0, _1)]
     CaAuthorisationUnknown(Handle, RouteAuthorization),
@@ -201,6 +238,52 @@ pub enum Error {
     #[display(fmt = "Prefix in ROA '{}' not held by CA '{}'.", _1, _0)]
     CaAuthorisationNotEntitled(Handle, RouteAuthorization),
 
+    #[display(fmt = "Invalid ROA packing policy '{}' for CA '{}'", _1, _0)]
+    CaRoaPackingPolicyInvalid(Handle, RoaPackingPolicy),
+
+    #[display(fmt = "Invalid ROA import for CA '{}': {}", _0, _1)]
+    CaRoaImportInvalid(Handle, RoaImportError),
+
+    // ASPA
+    #[display(
+        fmt = "Cannot remove or update unknown ASPA for customer '{}' in CA '{}'",
+        _1,
+        _0
+    )]
+    CaAspaUnknown(Handle, AsNumber),
+
+    #[display(fmt = "Duplicate ASPA for customer '{}' in update for CA '{}'", _1, _0)]
+    CaAspaDuplicate(Handle, AsNumber),
+
+    #[display(fmt = "Customer ASN '{}' in ASPA not held by CA '{}'", _1, _0)]
+    CaAspaNotEntitled(Handle, AsNumber),
+
+    #[display(
+        fmt = "ASPA '{}' for CA '{}' must have at least one provider, no duplicate providers, and not list the customer as provider",
+        _1,
+        _0
+    )]
+    CaAspaProvidersInvalid(Handle, AspaDefinition),
+
+    // Ghostbusters
+    #[display(
+        fmt = "Ghostbusters record for CA '{}' needs a full name, and at least one of address, telephone or email",
+        _0
+    )]
+    CaGhostbustersInvalid(Handle),
+
+    // Publication timing
+    #[display(
+        fmt = "Invalid publication timing '{}' for CA '{}', the republish margin must be less than the next update interval, and the validity must cover it",
+        _1,
+        _0
+    )]
+    CaPublicationTimingInvalid(Handle, PublicationTiming),
+
+    // BGP analysis
+    #[display(fmt = "No BGP announcements loaded, check 'bgp_announcements_file'")]
+    BgpAnalysisUnavailable,
+
     //-----------------------------------------------------------------
     // Key Usage Issues
     //-----------------------------------------------------------------
@@ -222,6 +305,17 @@ pub enum Error {
     #[display(fmt = "No key found matching key identifier: '{}'", _0)]
     KeyUseNoMatch(KeyIdentifier),
 
+    #[display(fmt = "Invalid key roll policy '{}' for CA '{}'", _1, _0)]
+    KeyRollPolicyInvalid(Handle, KeyRollPolicy),
+
+    #[display(
+        fmt = "Key roll for CA '{}' in resource class '{}' is not progressing, {}",
+        _0,
+        _1,
+        _2
+    )]
+    KeyRollStalled(Handle, ResourceClassName, KeyRollStatus),
+
     //-----------------------------------------------------------------
     // Resource Issues
     //-----------------------------------------------------------------
@@ -402,6 +496,25 @@ impl Error {
 
             Error::PublisherNoEmbeddedRepo => ErrorResponse::new("pub-no-embedded-repo", &self),
 
+            Error::PublisherQuotaExceeded(p, e) => ErrorResponse::new("pub-quota-exceeded", &self)
+                .with_publisher(p)
+                .with_cause(e),
+
+            Error::PublisherSuspended(p) => {
+                ErrorResponse::new("pub-suspended", &self).with_publisher(p)
+            }
+
+            Error::PublisherHandleMismatch(_, p) => {
+                ErrorResponse::new("pub-handle-mismatch", &self).with_publisher(p)
+            }
+
+            Error::PublisherObjectInvalid(p, uri, e) => {
+                ErrorResponse::new("pub-object-invalid", &self)
+                    .with_publisher(p)
+                    .with_uri(uri)
+                    .with_cause(e)
+            }
+
             //-----------------------------------------------------------------
             // RFC 8181
             //-----------------------------------------------------------------
@@ -421,6 +534,18 @@ impl Error {
 
             Error::CaUnknown(ca) => ErrorResponse::new("ca-unknown", &self).with_ca(ca),
 
+            Error::CaHasChildren(ca) => ErrorResponse::new("ca-has-children", &self).with_ca(ca),
+
+            Error::CaImportInvalid(ca, err) => ErrorResponse::new("ca-import-invalid", &self)
+                .with_ca(ca)
+                .with_cause(err),
+
+            Error::CaImportParentUnknown(ca, parent) => {
+                ErrorResponse::new("ca-import-parent-unknown", &self)
+                    .with_ca(ca)
+                    .with_parent(parent)
+            }
+
             Error::CaRepoInUse(ca) => ErrorResponse::new("ca-repo-same", &self).with_ca(ca),
 
             Error::CaRepoIssue(ca, err) => ErrorResponse::new("ca-repo-issue", &self)
@@ -508,6 +633,18 @@ impl Error {
                     .with_child(child)
             }
 
+            Error::CaChildLeaseExpired(ca, child, _lease) => {
+                ErrorResponse::new("ca-child-lease-expired", &self)
+                    .with_ca(ca)
+                    .with_child(child)
+            }
+
+            Error::CaChildLeaseExpiring(ca, child, _lease) => {
+                ErrorResponse::new("ca-child-lease-expiring", &self)
+                    .with_ca(ca)
+                    .with_child(child)
+            }
+
             // RouteAuthorizations
             Error::CaAuthorisationUnknown(ca, auth) => ErrorResponse::new("ca-roa-unknown", &self)
                 .with_ca(ca)
@@ -531,6 +668,50 @@ impl Error {
                     .with_auth(auth)
             }
 
+            Error::CaRoaPackingPolicyInvalid(ca, _policy) => {
+                ErrorResponse::new("ca-roa-policy-invalid", &self).with_ca(ca)
+            }
+
+            Error::CaRoaImportInvalid(ca, _err) => {
+                ErrorResponse::new("ca-roa-import-invalid", &self).with_ca(ca)
+            }
+
+            // ASPA
+            Error::CaAspaUnknown(ca, customer) => ErrorResponse::new("ca-aspa-unknown", &self)
+                .with_ca(ca)
+                .with_customer_asn(*customer),
+
+            Error::CaAspaDuplicate(ca, customer) => ErrorResponse::new("ca-aspa-duplicate", &self)
+                .with_ca(ca)
+                .with_customer_asn(*customer),
+
+            Error::CaAspaNotEntitled(ca, customer) => {
+                ErrorResponse::new("ca-aspa-not-entitled", &self)
+                    .with_ca(ca)
+                    .with_customer_asn(*customer)
+            }
+
+            Error::CaAspaProvidersInvalid(ca, definition) => {
+                ErrorResponse::new("ca-aspa-providers-invalid", &self)
+                    .with_ca(ca)
+                    .with_customer_asn(definition.customer())
+            }
+
+            // Ghostbusters
+            Error::CaGhostbustersInvalid(ca) => {
+                ErrorResponse::new("ca-ghostbusters-invalid", &self).with_ca(ca)
+            }
+
+            // Publication timing
+            Error::CaPublicationTimingInvalid(ca, _timing) => {
+                ErrorResponse::new("ca-publication-timing-invalid", &self).with_ca(ca)
+            }
+
+            // BGP analysis
+            Error::BgpAnalysisUnavailable => {
+                ErrorResponse::new("ca-bgp-analysis-unavailable", &self)
+            }
+
             //-----------------------------------------------------------------
             // Key Usage Issues (key-*)
             //-----------------------------------------------------------------
@@ -542,6 +723,14 @@ impl Error {
             Error::KeyUseNoMatch(ki) => {
                 ErrorResponse::new("key-no-match", &self).with_key_identifier(ki)
             }
+            Error::KeyRollPolicyInvalid(ca, _policy) => {
+                ErrorResponse::new("key-roll-policy-invalid", &self).with_ca(ca)
+            }
+            Error::KeyRollStalled(ca, rcn, _status) => {
+                ErrorResponse::new("key-roll-stalled", &self)
+                    .with_ca(ca)
+                    .with_resource_class(rcn)
+            }
 
             //-----------------------------------------------------------------
             // Resource Issues (label: rc-*)
@@ -568,9 +757,9 @@ impl Error {
 
     pub fn to_rfc8181_error_code(&self) -> ReportErrorCode {
         match self {
-            Error::Rfc8181Validation(_) | Error::PublisherUnknown(_) => {
-                ReportErrorCode::PermissionFailure
-            }
+            Error::Rfc8181Validation(_)
+            | Error::PublisherUnknown(_)
+            | Error::PublisherSuspended(_) => ReportErrorCode::PermissionFailure,
             Error::Rfc8181MessageError(_) => ReportErrorCode::XmlError,
             Error::Rfc8181Delta(e) => match e {
                 PublicationDeltaError::UriOutsideJail(_, _) => ReportErrorCode::PermissionFailure,
@@ -9,7 +9,7 @@ use crate::commons::api::{CommandHistory, CommandHistoryCriteria, Handle};
 use crate::commons::eventsourcing::cmd::{Command, StoredCommandBuilder};
 use crate::commons::eventsourcing::{
     Aggregate, CommandKey, DiskKeyStore, Event, EventListener, KeyStore, KeyStoreError,
-    KeyStoreVersion, StoredCommand,
+    KeyStoreVersion, StoredCommand, StoredValueInfo,
 };
 use std::io;
 
@@ -41,6 +41,14 @@ where
     /// Lists all known ids.
     fn list(&self) -> Vec<Handle>;
 
+    /// Removes an aggregate from the store, keeping its commands and
+    /// events in an archive.
+    fn archive(&self, id: &Handle) -> StoreResult<()>;
+
+    /// Adds an aggregate from a snapshot, e.g. one that was exported from
+    /// another store. The history leading up to the snapshot is not kept.
+    fn import(&self, id: &Handle, aggregate: A) -> StoreResult<Arc<A>>;
+
     /// Adds a listener that will receive a reference to all events as they
     /// are stored.
     fn add_listener<L: EventListener<A>>(&mut self, listener: Arc<L>);
@@ -52,6 +60,12 @@ where
         crit: CommandHistoryCriteria,
     ) -> StoreResult<CommandHistory>;
 
+    /// Returns the keys of the stored commands for an aggregate that match
+    /// the label and time criteria, in the order they were applied. The
+    /// offset and rows are ignored, so that callers can select commands
+    /// themselves before loading them.
+    fn command_keys(&self, id: &Handle, crit: &CommandHistoryCriteria) -> Vec<CommandKey>;
+
     /// Returns a stored command if it can be found.
     fn stored_command(
         &self,
@@ -75,6 +89,9 @@ pub enum AggregateStoreError {
     #[display(fmt = "unknown entity: {}", _0)]
     UnknownAggregate(Handle),
 
+    #[display(fmt = "entity already exists: {}", _0)]
+    AggregateExists(Handle),
+
     #[display(fmt = "init event exists, but cannot be applied")]
     InitError,
 
@@ -340,6 +357,36 @@ where
         self.store.aggregates()
     }
 
+    fn archive(&self, id: &Handle) -> StoreResult<()> {
+        let _lock = self.outer_lock.write().unwrap();
+        if !self.store.has_aggregate(id) {
+            return Err(AggregateStoreError::UnknownAggregate(id.clone()));
+        }
+        self.cache.write().unwrap().remove(id);
+        self.store.archive(id)?;
+        Ok(())
+    }
+
+    fn import(&self, id: &Handle, aggregate: A) -> StoreResult<Arc<A>> {
+        let _lock = self.outer_lock.write().unwrap();
+        if self.store.has_aggregate(id) {
+            return Err(AggregateStoreError::AggregateExists(id.clone()));
+        }
+
+        let info = StoredValueInfo {
+            snapshot_version: aggregate.version(),
+            ..StoredValueInfo::default()
+        };
+
+        self.store.store_snapshot(id, &aggregate)?;
+        self.store.save_info(id, &info)?;
+
+        let arc = Arc::new(aggregate);
+        self.cache_update(id, arc.clone());
+
+        Ok(arc)
+    }
+
     fn add_listener<L: EventListener<A>>(&mut self, listener: Arc<L>) {
         let _lock = self.outer_lock.write().unwrap();
         self.listeners.push(listener)
@@ -355,6 +402,10 @@ where
             .map_err(AggregateStoreError::KeyStoreError)
     }
 
+    fn command_keys(&self, id: &Handle, crit: &CommandHistoryCriteria) -> Vec<CommandKey> {
+        self.store.command_keys_ascending(id, crit)
+    }
+
     fn stored_command(
         &self,
         id: &Handle,
@@ -15,6 +15,12 @@ use crate::commons::eventsourcing::{Event, Storable};
 /// CommandSummer for use in history.
 pub trait WithStorableDetails: Storable {
     fn summary(&self) -> CommandSummary;
+
+    /// The subject of the command, if any, is included in its key so that
+    /// commands can be found without reading them.
+    fn subject(&self) -> Option<&Handle> {
+        None
+    }
 }
 
 //------------ Command -------------------------------------------------------
@@ -196,7 +202,8 @@ impl<S: WithStorableDetails> StoredCommand<S> {
 impl<S: WithStorableDetails> Into<CommandHistoryRecord> for StoredCommand<S> {
     fn into(self) -> CommandHistoryRecord {
         let summary = self.details.summary();
-        let command_key = CommandKey::new(self.sequence, self.time, summary.label.clone());
+        let command_key = CommandKey::new(self.sequence, self.time, summary.label.clone())
+            .with_subject(self.details.subject());
 
         CommandHistoryRecord {
             key: command_key.to_string(),
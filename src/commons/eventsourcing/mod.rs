@@ -338,6 +338,50 @@ mod tests {
             crit.set_exclude(&["person-around-sun"]);
             let history = manager.command_history(&id_alice, crit).unwrap();
             assert_eq!(history.total(), 1);
+
+            // Archive alice, she should no longer be known
+            let snapshot = manager.get_latest(&id_alice).unwrap().as_ref().clone();
+            manager.archive(&id_alice).unwrap();
+            assert!(!manager.has(&id_alice));
+            assert!(manager.list().is_empty());
+            assert!(manager.get_latest(&id_alice).is_err());
+
+            let manager = DiskAggregateStore::<Person>::new(&d, "person").unwrap();
+            assert!(manager.list().is_empty());
+
+            // Import alice from her snapshot, she should be known again
+            manager.import(&id_alice, snapshot.clone()).unwrap();
+            assert!(manager.import(&id_alice, snapshot).is_err());
+
+            let get_older = PersonCommand::go_around_sun(&id_alice, Some(23));
+            manager.command(get_older).unwrap();
+
+            let manager = DiskAggregateStore::<Person>::new(&d, "person").unwrap();
+            let alice = manager.get_latest(&id_alice).unwrap();
+            assert_eq!("alice smith-doe", alice.name());
+            assert_eq!(22, alice.age());
         })
     }
+
+    #[test]
+    fn command_key_with_subject() {
+        use std::str::FromStr;
+
+        let time = rpki::x509::Time::now();
+        let plain = CommandKey::new(1, time, "person-around-sun".to_string());
+        assert!(!plain.has_subject());
+        assert_eq!(plain, CommandKey::from_str(&plain.to_string()).unwrap());
+
+        // Subjects may contain "--" and "/"
+        let alice = Handle::from_str_unsafe("alice--smith/doe");
+        let key = plain.clone().with_subject(Some(&alice));
+        assert!(key.has_subject());
+        assert!(key.is_for_subject(&alice));
+        assert!(!key.is_for_subject(&Handle::from_str_unsafe("alice")));
+        assert_eq!(key, CommandKey::from_str(&key.to_string()).unwrap());
+
+        // Very long subjects are left out
+        let long = Handle::from_str_unsafe(&"a".repeat(200));
+        assert!(!plain.with_subject(Some(&long)).has_subject());
+    }
 }
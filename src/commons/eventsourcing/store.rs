@@ -49,22 +49,56 @@ pub enum KeyStoreVersion {
     V0_6,
 }
 
+/// Identifies a stored command, and tells enough about it to filter
+/// commands without reading them: the time, the label and, optionally,
+/// the subject of the command, e.g. the publisher for publication commands.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct CommandKey {
     sequence: u64,
     timestamp_secs: i64,
     label: Label,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subject: Option<String>,
 }
 
+/// Subjects are left out of keys if they would make for unreasonably
+/// long file names. Their commands then need to be read to find out.
+const COMMAND_KEY_SUBJECT_MAX_LEN: usize = 128;
+
 impl CommandKey {
     pub fn new(sequence: u64, time: Time, label: Label) -> Self {
         CommandKey {
             sequence,
             timestamp_secs: time.timestamp(),
             label,
+            subject: None,
         }
     }
 
+    pub fn with_subject(mut self, subject: Option<&Handle>) -> Self {
+        self.subject = subject
+            .map(Self::subject_str)
+            .filter(|s| s.len() <= COMMAND_KEY_SUBJECT_MAX_LEN);
+        self
+    }
+
+    /// Uses the same file system safe form as the handle's path.
+    fn subject_str(handle: &Handle) -> String {
+        handle.to_path_buf().to_string_lossy().to_string()
+    }
+
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn has_subject(&self) -> bool {
+        self.subject.is_some()
+    }
+
+    pub fn is_for_subject(&self, subject: &Handle) -> bool {
+        self.subject.as_ref() == Some(&Self::subject_str(subject))
+    }
+
     pub fn matches_crit(&self, crit: &CommandHistoryCriteria) -> bool {
         crit.matches_timestamp_secs(self.timestamp_secs) && crit.matches_label(&self.label)
     }
@@ -76,25 +110,33 @@ impl fmt::Display for CommandKey {
             f,
             "command--{}--{}--{}",
             self.timestamp_secs, self.sequence, self.label
-        )
+        )?;
+        if let Some(subject) = &self.subject {
+            write!(f, "--{}", subject)?;
+        }
+        Ok(())
     }
 }
 
 impl FromStr for CommandKey {
     type Err = CommandKeyError;
 
+    /// Labels never contain "--", so anything after the label is the
+    /// subject, even if that contains "--" itself.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split("--").collect();
-        if parts.len() != 4 || parts[0] != "command" {
+        let parts: Vec<&str> = s.splitn(5, "--").collect();
+        if parts.len() < 4 || parts[0] != "command" {
             Err(CommandKeyError)
         } else {
             let timestamp_secs = i64::from_str(&parts[1]).map_err(|_| CommandKeyError)?;
             let sequence = u64::from_str(&parts[2]).map_err(|_| CommandKeyError)?;
             let label = parts[3].to_string();
+            let subject = parts.get(4).map(|s| s.to_string());
             Ok(CommandKey {
                 sequence,
                 timestamp_secs,
                 label,
+                subject,
             })
         }
     }
@@ -191,6 +233,10 @@ pub trait KeyStore {
     /// Drop the value for this key
     fn drop(&self, id: &Handle, key: &Self::Key) -> Result<(), KeyStoreError>;
 
+    /// Move all values for this aggregate out of the store, so that it is
+    /// no longer known, but keep them in an archive for reference.
+    fn archive(&self, id: &Handle) -> Result<(), KeyStoreError>;
+
     /// Get the value for this key, if any exists.
     fn get_event<V: Event>(&self, id: &Handle, version: u64) -> Result<Option<V>, KeyStoreError>;
 
@@ -344,6 +390,7 @@ impl KeyStore for DiskKeyStore {
             command.time(),
             command.details().summary().label,
         )
+        .with_subject(command.details().subject())
     }
 
     fn keys(&self, id: &Handle, matching: &str) -> Vec<Self::Key> {
@@ -463,6 +510,23 @@ impl KeyStore for DiskKeyStore {
         }
     }
 
+    fn archive(&self, id: &Handle) -> Result<(), KeyStoreError> {
+        let dir = self.dir_for_aggregate(id);
+        if !dir.exists() {
+            return Err(KeyStoreError::KeyUnknown(id.to_string()));
+        }
+
+        let mut archive_dir = self.archive_dir();
+        archive_dir.push(Time::now().timestamp().to_string());
+        archive_dir.push(id.to_path_buf());
+
+        if let Some(parent) = archive_dir.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(dir, archive_dir)?;
+        Ok(())
+    }
+
     /// Get the value for this key, if any exists.
     fn get_event<V: Event>(&self, id: &Handle, version: u64) -> Result<Option<V>, KeyStoreError> {
         let path = self.path_for_event(id, version);
@@ -562,6 +626,20 @@ impl DiskKeyStore {
         Ok(Self::new(work_dir, name_space))
     }
 
+    /// Archived aggregates are kept next to, rather than inside, the
+    /// directory for this name space, so that they are not picked up as
+    /// aggregates.
+    fn archive_dir(&self) -> PathBuf {
+        let mut path = self.dir.clone();
+        let name_space = path.file_name().map(|n| n.to_os_string());
+        path.pop();
+        path.push("archived");
+        if let Some(name_space) = name_space {
+            path.push(name_space);
+        }
+        path
+    }
+
     fn version_path(&self) -> PathBuf {
         let mut path = self.dir.clone();
         path.push("version");
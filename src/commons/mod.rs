@@ -1,5 +1,6 @@
 //! Common types used by the various Krill components.
 pub mod api;
+pub mod bgp;
 pub mod error;
 pub mod eventsourcing;
 pub mod remote;
@@ -653,6 +653,18 @@ impl ReportError {
             failed_pdu,
         }
     }
+
+    /// Creates an entry with a specific error text, rather than the default
+    /// text for the error code.
+    pub fn reply_with_text(
+        error_code: ReportErrorCode,
+        failed_pdu: Option<PublishDeltaElement>,
+        error_text: String,
+    ) -> Self {
+        let mut report_error = Self::reply(error_code, failed_pdu);
+        report_error.error_text = Some(error_text);
+        report_error
+    }
 }
 
 //------------ ReportErrorCodes ----------------------------------------------
@@ -0,0 +1,158 @@
+//! Support for passphrase based encryption of data, e.g. for exporting a
+//! CA including its private keys.
+use bytes::Bytes;
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkcs5::pbkdf2_hmac;
+use openssl::rand::rand_bytes;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+
+use crate::commons::util::ext_serde;
+
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const KDF_ITERATIONS: usize = 100_000;
+
+//------------ EncryptedData -------------------------------------------------
+
+/// Data encrypted with AES-256-GCM, using a key derived from a passphrase
+/// with PBKDF2-HMAC-SHA256. Everything needed to decrypt the data, except
+/// for the passphrase, is included.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EncryptedData {
+    #[serde(
+        deserialize_with = "ext_serde::de_bytes",
+        serialize_with = "ext_serde::ser_bytes"
+    )]
+    salt: Bytes,
+
+    #[serde(
+        deserialize_with = "ext_serde::de_bytes",
+        serialize_with = "ext_serde::ser_bytes"
+    )]
+    iv: Bytes,
+
+    #[serde(
+        deserialize_with = "ext_serde::de_bytes",
+        serialize_with = "ext_serde::ser_bytes"
+    )]
+    tag: Bytes,
+
+    #[serde(
+        deserialize_with = "ext_serde::de_bytes",
+        serialize_with = "ext_serde::ser_bytes"
+    )]
+    data: Bytes,
+}
+
+impl EncryptedData {
+    /// Encrypts the data using a fresh random salt and iv.
+    pub fn encrypt(passphrase: &str, plain: &[u8]) -> Result<Self, EncryptError> {
+        let mut salt = [0; SALT_LEN];
+        rand_bytes(&mut salt)?;
+
+        let mut iv = [0; IV_LEN];
+        rand_bytes(&mut iv)?;
+
+        let key = Self::derive_key(passphrase, &salt)?;
+
+        let mut tag = [0; TAG_LEN];
+        let data = encrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(&iv[..]),
+            &[],
+            plain,
+            &mut tag,
+        )?;
+
+        Ok(EncryptedData {
+            salt: Bytes::copy_from_slice(&salt),
+            iv: Bytes::copy_from_slice(&iv),
+            tag: Bytes::copy_from_slice(&tag),
+            data: Bytes::from(data),
+        })
+    }
+
+    /// Decrypts the data. Fails if the passphrase is wrong, or if the data
+    /// was tampered with.
+    pub fn decrypt(&self, passphrase: &str) -> Result<Vec<u8>, EncryptError> {
+        let key = Self::derive_key(passphrase, &self.salt)?;
+        decrypt_aead(
+            Cipher::aes_256_gcm(),
+            &key,
+            Some(self.iv.as_ref()),
+            &[],
+            &self.data,
+            &self.tag,
+        )
+        .map_err(|_| EncryptError::DecryptionFailed)
+    }
+
+    fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; KEY_LEN], EncryptError> {
+        if passphrase.is_empty() {
+            return Err(EncryptError::EmptyPassphrase);
+        }
+
+        let mut key = [0; KEY_LEN];
+        pbkdf2_hmac(
+            passphrase.as_bytes(),
+            salt,
+            KDF_ITERATIONS,
+            MessageDigest::sha256(),
+            &mut key,
+        )?;
+        Ok(key)
+    }
+}
+
+//------------ EncryptError --------------------------------------------------
+
+#[derive(Debug, Display)]
+pub enum EncryptError {
+    #[display(fmt = "OpenSsl Error: {}", _0)]
+    OpenSslError(ErrorStack),
+
+    #[display(fmt = "Passphrase must not be empty")]
+    EmptyPassphrase,
+
+    #[display(fmt = "Could not decrypt data, is the passphrase correct?")]
+    DecryptionFailed,
+}
+
+impl From<ErrorStack> for EncryptError {
+    fn from(e: ErrorStack) -> Self {
+        EncryptError::OpenSslError(e)
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_decrypt() {
+        let plain = b"some secret ca export";
+        let encrypted = EncryptedData::encrypt("correct horse", plain).unwrap();
+        assert_ne!(encrypted.data.as_ref(), &plain[..]);
+
+        let json = serde_json::to_string(&encrypted).unwrap();
+        let encrypted: EncryptedData = serde_json::from_str(&json).unwrap();
+
+        let decrypted = encrypted.decrypt("correct horse").unwrap();
+        assert_eq!(decrypted.as_slice(), &plain[..]);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_passphrase_fails() {
+        let encrypted = EncryptedData::encrypt("correct horse", b"secret").unwrap();
+        match encrypted.decrypt("battery staple") {
+            Err(EncryptError::DecryptionFailed) => {}
+            _ => panic!("Expected decryption to fail"),
+        }
+    }
+}
@@ -195,6 +195,22 @@ pub async fn post_empty(uri: &str, token: Option<&Token>) -> Result<(), Error> {
     }
 }
 
+/// Performs a POST with no data to the given URI and expects a json
+/// response that can be deserialized into an owned value of the expected
+/// type.
+pub async fn post_empty_with_response<T: DeserializeOwned>(
+    uri: &str,
+    token: Option<&Token>,
+) -> Result<T, Error> {
+    if env::var(KRILL_CLI_API_ENV).is_ok() {
+        report_post_and_exit(uri, None, token, PostBody::String(&"<empty>".to_string()));
+    }
+
+    let headers = headers(Some(JSON_CONTENT), token)?;
+    let res = client(uri).await?.post(uri).headers(headers).send().await?;
+    process_json_response(res).await
+}
+
 /// Posts binary data, and expects a binary response.
 ///
 /// Note: Bytes may be empty if the post was successful, but the response was
@@ -2,6 +2,7 @@
 use bytes::Bytes;
 use rpki::crypto::DigestAlgorithm;
 
+pub mod encrypt;
 pub mod ext_serde;
 pub mod file;
 pub mod httpclient;
@@ -61,6 +61,29 @@ impl OpenSslSigner {
         Ok(signature)
     }
 
+    /// Returns the key pair for the key, so that it can be exported.
+    pub fn export_key(&self, key_id: &KeyIdentifier) -> Result<OpenSslKeyPair, SignerError> {
+        self.load_key(key_id)
+    }
+
+    /// Adds a key pair that was exported by another signer. The key pair
+    /// must match the key identifier.
+    pub fn import_key(
+        &mut self,
+        key_id: &KeyIdentifier,
+        key_pair: &OpenSslKeyPair,
+    ) -> Result<(), SignerError> {
+        if &key_pair.subject_public_key_info()?.key_identifier() != key_id {
+            return Err(SignerError::KeyMismatch);
+        }
+
+        let json = serde_json::to_string(key_pair)?;
+        let mut f = File::create(self.key_path(key_id))?;
+        f.write_all(json.as_ref())?;
+
+        Ok(())
+    }
+
     fn load_key(&self, id: &KeyIdentifier) -> Result<OpenSslKeyPair, SignerError> {
         let path = self.key_path(id);
         if path.exists() {
@@ -218,6 +241,9 @@ pub enum SignerError {
 
     #[display(fmt = "Could not decode key")]
     DecodeError,
+
+    #[display(fmt = "Key does not match key identifier")]
+    KeyMismatch,
 }
 
 impl From<ErrorStack> for SignerError {
@@ -267,4 +293,32 @@ pub mod tests {
         // not implement Eq and PartialEq.
         assert_eq!(json, json_from_des);
     }
+
+    #[test]
+    fn should_export_and_import_key() {
+        test::test_under_tmp(|d| {
+            let mut from_dir = d.clone();
+            from_dir.push("from");
+            fs::create_dir_all(&from_dir).unwrap();
+
+            let mut to_dir = d.clone();
+            to_dir.push("to");
+            fs::create_dir_all(&to_dir).unwrap();
+
+            let mut from = OpenSslSigner::build(&from_dir).unwrap();
+            let mut to = OpenSslSigner::build(&to_dir).unwrap();
+
+            let ki = from.create_key(PublicKeyFormat::default()).unwrap();
+            let other = from.create_key(PublicKeyFormat::default()).unwrap();
+            let key_pair = from.export_key(&ki).unwrap();
+
+            assert!(to.import_key(&other, &key_pair).is_err());
+            to.import_key(&ki, &key_pair).unwrap();
+
+            assert_eq!(
+                from.get_key_info(&ki).unwrap(),
+                to.get_key_info(&ki).unwrap()
+            );
+        })
+    }
 }
@@ -9,14 +9,28 @@ pub const CASERVER_DIR: &str = "cas";
 pub const PUBSERVER_DFLT: &str = "0";
 pub const PUBSERVER_DIR: &str = "pubd";
 
-pub const PUBLISH_VALID_DAYS: i64 = 7; // mft is valid for 7 days
-pub const PUBLISH_NEXT_HOURS: i64 = 24; // next update in 24 hours (otherwise mft and crl will become stale)
-pub const PUBLISH_THRESHOLD_HOURS: i64 = 8; // republish 8 hours before stale
+pub const PUBLISH_VALID_DAYS: u32 = 7; // mft is valid for 7 days
+pub const PUBLISH_NEXT_HOURS: u32 = 24; // next update in 24 hours (otherwise mft and crl will become stale)
+pub const PUBLISH_THRESHOLD_HOURS: u32 = 8; // republish 8 hours before stale
 
 pub const REPOSITORY_DIR: &str = "repo";
 pub const REPOSITORY_RRDP_DIR: &str = "rrdp";
 pub const REPOSITORY_RSYNC_DIR: &str = "rsync";
-pub const REPOSITORY_RRDP_SNAPSHOT_RETAIN_MINS: u64 = 10;
+pub const REPOSITORY_RSYNC_VERSIONS_DIR: &str = "versions";
+pub const REPOSITORY_RSYNC_VERSIONS_RETAIN: usize = 2;
+pub const REPOSITORY_RRDP_DELTA_FILES_MIN_NR: usize = 5;
+pub const REPOSITORY_RRDP_DELTA_FILES_MAX_SIZE_PCT: u64 = 100; // of the snapshot size
+pub const REPOSITORY_RRDP_FILES_RETAIN_MINS: u64 = 10;
+pub const REPOSITORY_STALE_PUBLISHER_UPDATE_MINS: u64 = 24 * 60;
+pub const REPOSITORY_STALE_PUBLISHER_MANIFEST_MINS: u64 = 4 * 60; // CAs republish 8 hours before
+
+pub const BGP_ANNOUNCEMENTS_REFRESH_MINS: u64 = 60;
+
+pub const KEY_ROLL_CHECK_MINS: u32 = 10;
+pub const KEY_ROLL_STALLED_HOURS: i64 = 24; // report key rolls that did not progress for a day
+
+pub const CHILD_LEASE_CHECK_MINS: u32 = 10;
+pub const CHILD_LEASE_EXPIRING_DAYS: i64 = 14; // report child leases that expire within two weeks
 
 pub const KRILL_CLI_SERVER_ARG: &str = "server";
 pub const KRILL_CLI_SERVER_ENV: &str = "KRILL_CLI_SERVER";
@@ -30,6 +44,8 @@ pub const KRILL_CLI_API_ARG: &str = "api";
 pub const KRILL_CLI_API_ENV: &str = "KRILL_CLI_API";
 pub const KRILL_CLI_MY_CA_ARG: &str = "ca";
 pub const KRILL_CLI_MY_CA_ENV: &str = "KRILL_CLI_MY_CA";
+pub const KRILL_CLI_PASSPHRASE_ARG: &str = "passphrase";
+pub const KRILL_CLI_PASSPHRASE_ENV: &str = "KRILL_CLI_PASSPHRASE";
 
 pub const KRILL_HTTPS_ROOT_CERTS_ENV: &str = "KRILL_HTTPS_ROOT_CERTS";
 
@@ -37,6 +53,10 @@ pub const CHILD_CERTIFICATE_VALIDITY_YEARS: i32 = 1;
 pub const CHILD_CERTIFICATE_REISSUE_WEEKS: i64 = 4;
 pub const ROA_CERTIFICATE_VALIDITY_YEARS: i32 = 1;
 pub const ROA_CERTIFICATE_REISSUE_WEEKS: i64 = 4;
+pub const ASPA_CERTIFICATE_VALIDITY_YEARS: i32 = 1;
+pub const ASPA_CERTIFICATE_REISSUE_WEEKS: i64 = 4;
+pub const GHOSTBUSTERS_CERTIFICATE_VALIDITY_YEARS: i32 = 1;
+pub const GHOSTBUSTERS_CERTIFICATE_REISSUE_WEEKS: i64 = 4;
 pub const ID_CERTIFICATE_VALIDITY_YEARS: i32 = 15;
 
 pub const HTTTP_CLIENT_TIMEOUT_SECS: u64 = 120;
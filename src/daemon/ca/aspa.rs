@@ -0,0 +1,288 @@
+use std::collections::HashMap;
+
+use bcder::encode::{PrimitiveContent, Values};
+use bcder::{encode, Captured, Mode, OctetString, Oid};
+use bytes::Bytes;
+
+use rpki::cert::Cert;
+use rpki::sigobj::{SignedObject, SignedObjectBuilder};
+use rpki::uri;
+use rpki::x509::{Serial, Time};
+
+use crate::commons::api::{
+    AsNumber, AspaDefinition, AspaProvidersUpdate, CurrentObject, ObjectName, ReplacedObject,
+    ResourceSet,
+};
+use crate::commons::KrillResult;
+use crate::constants::ASPA_CERTIFICATE_VALIDITY_YEARS;
+use crate::daemon::ca::events::AspaObjectsUpdates;
+use crate::daemon::ca::{self, CertifiedKey, SignSupport, Signer};
+
+//------------ AspaDefinitions ---------------------------------------------
+
+/// The ASPA definitions configured for a CA, keyed by customer ASN.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaDefinitions {
+    attestations: HashMap<AsNumber, AspaDefinition>,
+}
+
+impl AspaDefinitions {
+    pub fn add_or_replace(&mut self, definition: AspaDefinition) {
+        self.attestations.insert(definition.customer(), definition);
+    }
+
+    pub fn remove(&mut self, customer: AsNumber) {
+        self.attestations.remove(&customer);
+    }
+
+    pub fn apply_update(&mut self, customer: AsNumber, update: &AspaProvidersUpdate) {
+        if let Some(definition) = self.attestations.get_mut(&customer) {
+            definition.apply_update(update);
+        }
+    }
+
+    pub fn get(&self, customer: AsNumber) -> Option<&AspaDefinition> {
+        self.attestations.get(&customer)
+    }
+
+    pub fn has(&self, customer: AsNumber) -> bool {
+        self.attestations.contains_key(&customer)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AspaDefinition> {
+        self.attestations.values()
+    }
+
+    /// Returns all definitions, ordered by customer ASN.
+    pub fn all(&self) -> Vec<AspaDefinition> {
+        let mut all: Vec<AspaDefinition> = self.attestations.values().cloned().collect();
+        all.sort_by_key(|d| d.customer());
+        all
+    }
+}
+
+//------------ Aspa --------------------------------------------------------
+
+/// A signed ASPA object. The rpki library does not support ASPA objects, so
+/// the content is encoded here and wrapped in a generic signed object.
+#[derive(Clone, Debug)]
+pub struct Aspa {
+    signed: SignedObject,
+}
+
+impl Aspa {
+    pub fn cert(&self) -> &Cert {
+        self.signed.cert()
+    }
+
+    pub fn to_captured(&self) -> Captured {
+        self.signed.encode_ref().to_captured(Mode::Der)
+    }
+
+    // ASProviderAttestation ::= SEQUENCE {
+    //     version [0]   ASPAVersion DEFAULT v0,
+    //     customerASID  ASID,
+    //     providers     ProviderASSet }
+    //
+    // ProviderASSet ::= SEQUENCE (SIZE(1..MAX)) OF ProviderAS
+    //
+    // ProviderAS ::= SEQUENCE {
+    //     providerASID  ASID,
+    //     afiLimit      AddressFamilyIdentifier OPTIONAL }
+    //
+    // The version is v0, so it is left out in DER.
+    fn encode_content(definition: &AspaDefinition) -> Bytes {
+        let customer = u32::from(rpki::resources::AsId::from(definition.customer()));
+
+        encode::sequence((
+            customer.encode(),
+            encode::sequence(encode::iter(definition.providers().iter().map(|p| {
+                let provider = u32::from(rpki::resources::AsId::from(p.provider()));
+                let afi_limit = p
+                    .afi_limit()
+                    .map(|afi| OctetString::new(Bytes::copy_from_slice(&afi.to_bytes())).encode());
+                encode::sequence((provider.encode(), afi_limit))
+            }))),
+        ))
+        .to_captured(Mode::Der)
+        .into_bytes()
+    }
+}
+
+//------------ AspaInfo ----------------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaInfo {
+    definition: AspaDefinition,       // the definition this ASPA was made for
+    object: CurrentObject,            // actual ASPA
+    name: ObjectName,                 // Name for object in repo
+    since: Time,                      // first ASPA in RC created
+    replaces: Option<ReplacedObject>, // for revoking when re-newing
+}
+
+impl AspaInfo {
+    pub fn new_aspa(definition: AspaDefinition, aspa: &Aspa, name: ObjectName) -> Self {
+        AspaInfo {
+            definition,
+            object: CurrentObject::from(aspa),
+            name,
+            since: Time::now(),
+            replaces: None,
+        }
+    }
+
+    pub fn updated_aspa(
+        old: &AspaInfo,
+        definition: AspaDefinition,
+        aspa: &Aspa,
+        name: ObjectName,
+    ) -> Self {
+        AspaInfo {
+            definition,
+            object: CurrentObject::from(aspa),
+            name,
+            since: old.since,
+            replaces: Some(ReplacedObject::from(old.object())),
+        }
+    }
+
+    pub fn definition(&self) -> &AspaDefinition {
+        &self.definition
+    }
+
+    pub fn object(&self) -> &CurrentObject {
+        &self.object
+    }
+
+    pub fn name(&self) -> &ObjectName {
+        &self.name
+    }
+
+    pub fn since(&self) -> Time {
+        self.since
+    }
+
+    pub fn replaces(&self) -> Option<&ReplacedObject> {
+        self.replaces.as_ref()
+    }
+}
+
+//------------ AspaObjects -------------------------------------------------
+
+/// ASPA objects held by a resource class in a CA.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaObjects {
+    inner: HashMap<AsNumber, AspaInfo>,
+}
+
+impl AspaObjects {
+    pub fn get(&self, customer: AsNumber) -> Option<&AspaInfo> {
+        self.inner.get(&customer)
+    }
+
+    pub fn updated(&mut self, updates: AspaObjectsUpdates) {
+        let (updated, removed) = updates.unpack();
+
+        for (customer, info) in updated.into_iter() {
+            self.inner.insert(customer, info);
+        }
+
+        for customer in removed.keys() {
+            self.inner.remove(customer);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&AsNumber, &AspaInfo)> {
+        self.inner.iter()
+    }
+
+    pub fn current(&self) -> impl Iterator<Item = &AspaInfo> {
+        self.inner.values()
+    }
+
+    /// Returns the definitions for which ASPA objects exist in this class.
+    pub fn definitions(&self) -> AspaDefinitions {
+        let mut definitions = AspaDefinitions::default();
+        for info in self.inner.values() {
+            definitions.add_or_replace(info.definition().clone());
+        }
+        definitions
+    }
+
+    pub fn make_aspa<S: Signer>(
+        definition: &AspaDefinition,
+        certified_key: &CertifiedKey,
+        new_repo: Option<&uri::Rsync>,
+        signer: &S,
+    ) -> KrillResult<Aspa> {
+        let name = ObjectName::from(definition);
+
+        let incoming_cert = certified_key.incoming_cert();
+        let crl_uri = match &new_repo {
+            None => incoming_cert.crl_uri(),
+            Some(base_uri) => base_uri.join(incoming_cert.crl_name().as_bytes()),
+        };
+
+        let aspa_uri = match &new_repo {
+            None => incoming_cert.uri_for_object(name),
+            Some(base_uri) => base_uri.join(name.as_bytes()),
+        };
+
+        let aia = incoming_cert.uri();
+
+        let signing_key = certified_key.key_id();
+
+        let mut object_builder = SignedObjectBuilder::new(
+            Serial::random(signer).map_err(ca::Error::signer)?,
+            SignSupport::sign_validity_years(ASPA_CERTIFICATE_VALIDITY_YEARS),
+            crl_uri,
+            aia.clone(),
+            aspa_uri,
+        );
+        object_builder.set_issuer(Some(incoming_cert.cert().subject().clone()));
+        object_builder.set_signing_time(Some(Time::now()));
+        object_builder.set_as_resources(ResourceSet::from(definition.customer()).to_as_resources());
+
+        let signed = object_builder
+            .finalize(
+                Oid(Bytes::from_static(oid::CT_ASPA)),
+                Aspa::encode_content(definition),
+                signer,
+                signing_key,
+            )
+            .map_err(ca::Error::signer)?;
+
+        Ok(Aspa { signed })
+    }
+}
+
+//------------ OIDs --------------------------------------------------------
+
+mod oid {
+    // id-ct-ASPA: 1.2.840.113549.1.9.16.1.49
+    pub const CT_ASPA: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 49];
+}
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    use std::str::FromStr;
+
+    #[test]
+    fn serde_aspa_definitions() {
+        let mut definitions = AspaDefinitions::default();
+        definitions.add_or_replace(AspaDefinition::from_str("65000 => 65001").unwrap());
+        definitions.add_or_replace(AspaDefinition::from_str("65002 => 65001(v6)").unwrap());
+
+        let json = serde_json::to_string(&definitions).unwrap();
+        let des: AspaDefinitions = serde_json::from_str(&json).unwrap();
+        assert_eq!(definitions, des);
+
+        let customers: Vec<AsNumber> = des.all().iter().map(|d| d.customer()).collect();
+        assert_eq!(vec![AsNumber::new(65000), AsNumber::new(65002)], customers);
+    }
+}
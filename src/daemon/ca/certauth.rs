@@ -15,11 +15,13 @@ use rpki::x509::{Serial, Time, Validity};
 
 use crate::commons::api::rrdp::PublishElement;
 use crate::commons::api::{
-    self, CertAuthInfo, ChildHandle, EntitlementClass, Entitlements, Handle, IdCertPem,
-    IssuanceRequest, IssuedCert, ObjectsDelta, ParentCaContact, ParentHandle, RcvdCert,
-    RepositoryContact, RequestResourceLimit, ResourceClassName, ResourceSet, RevocationRequest,
-    RevocationResponse, RoaDefinition, SigningCert, StorableCaCommand, TaCertDetails,
-    TrustAnchorLocator,
+    self, AsNumber, AspaDefinition, AspaDefinitionUpdates, AspaProvidersUpdate, CertAuthInfo,
+    ChildHandle, EntitlementClass, Entitlements, GhostbusterVcard, Handle, IdCertPem,
+    IssuanceRequest, IssuedCert, KeyRollPhase, KeyRollPolicy, KeyRollStatus, ObjectsDelta,
+    ParentCaContact, ParentHandle, PublicationTiming, RcvdCert, RepositoryContact,
+    RequestResourceLimit, ResourceClassName, ResourceLease, ResourceSet, RevocationRequest,
+    RevocationResponse, RoaDefinition, RoaPackingPolicy, SigningCert, StorableCaCommand,
+    TaCertDetails, TrustAnchorLocator,
 };
 use crate::commons::error::Error;
 use crate::commons::eventsourcing::{Aggregate, StoredEvent};
@@ -31,11 +33,11 @@ use crate::commons::remote::sigmsg::SignedMessage;
 use crate::commons::KrillResult;
 use crate::constants::{CHILD_CERTIFICATE_REISSUE_WEEKS, CHILD_CERTIFICATE_VALIDITY_YEARS};
 use crate::daemon::ca::events::ChildCertificateUpdates;
-use crate::daemon::ca::rc::PublishMode;
+use crate::daemon::ca::rc::{ObjectDefinitions, PublishMode};
 use crate::daemon::ca::signing::CsrInfo;
 use crate::daemon::ca::{
-    ta_handle, ChildDetails, Cmd, CmdDet, CurrentObjectSetDelta, Evt, EvtDet, Ini, ResourceClass,
-    RouteAuthorization, RouteAuthorizationUpdates, Routes, Signer,
+    ta_handle, AspaDefinitions, ChildDetails, Cmd, CmdDet, CurrentObjectSetDelta, Evt, EvtDet, Ini,
+    ResourceClass, RouteAuthorization, RouteAuthorizationUpdates, Routes, Signer,
 };
 
 //------------ Rfc8183Id ---------------------------------------------------
@@ -86,6 +88,21 @@ pub struct CertAuth<S: Signer> {
 
     routes: Routes,
 
+    #[serde(default)]
+    roa_policy: RoaPackingPolicy,
+
+    #[serde(default)]
+    aspas: AspaDefinitions,
+
+    #[serde(default)]
+    ghostbuster: Option<GhostbusterVcard>,
+
+    #[serde(default)]
+    keyroll_policy: KeyRollPolicy,
+
+    #[serde(default)]
+    publication_timing: Option<PublicationTiming>,
+
     phantom_signer: PhantomData<S>,
 }
 
@@ -136,6 +153,12 @@ impl<S: Signer> Aggregate for CertAuth<S> {
 
             routes,
 
+            roa_policy: RoaPackingPolicy::default(),
+            aspas: AspaDefinitions::default(),
+            ghostbuster: None,
+            keyroll_policy: KeyRollPolicy::default(),
+            publication_timing: None,
+
             phantom_signer: PhantomData,
         })
     }
@@ -213,6 +236,10 @@ impl<S: Signer> Aggregate for CertAuth<S> {
                 .unwrap()
                 .set_resources(resources),
 
+            EvtDet::ChildUpdatedLeases(child, leases) => {
+                self.children.get_mut(&child).unwrap().set_leases(leases)
+            }
+
             EvtDet::ChildRemoved(child) => {
                 self.children.remove(&child);
             }
@@ -290,6 +317,18 @@ impl<S: Signer> Aggregate for CertAuth<S> {
             EvtDet::UnexpectedKeyFound(_, _) => {
                 // no action needed, this is marked to flag that a key may be removed
             }
+            EvtDet::KeyChangeRecorded(class_name, time) => {
+                self.resources
+                    .get_mut(&class_name)
+                    .unwrap()
+                    .key_change_recorded(time);
+            }
+            EvtDet::KeyRollPolicyUpdated(policy) => self.keyroll_policy = policy,
+
+            //-----------------------------------------------------------------------
+            // Publication timing
+            //-----------------------------------------------------------------------
+            EvtDet::PublicationTimingUpdated(timing) => self.publication_timing = timing,
 
             //-----------------------------------------------------------------------
             // Route Authorizations
@@ -299,6 +338,30 @@ impl<S: Signer> Aggregate for CertAuth<S> {
             EvtDet::RoasUpdated(rcn, updates) => {
                 self.resources.get_mut(&rcn).unwrap().roas_updated(updates)
             }
+            EvtDet::RoaPackingPolicyUpdated(policy) => self.roa_policy = policy,
+
+            //-----------------------------------------------------------------------
+            // ASPA
+            //-----------------------------------------------------------------------
+            EvtDet::AspaConfigAdded(definition) => self.aspas.add_or_replace(definition),
+            EvtDet::AspaConfigUpdated(customer, update) => {
+                self.aspas.apply_update(customer, &update)
+            }
+            EvtDet::AspaConfigRemoved(customer) => self.aspas.remove(customer),
+            EvtDet::AspaObjectsUpdated(rcn, updates) => {
+                self.resources.get_mut(&rcn).unwrap().aspas_updated(updates)
+            }
+
+            //-----------------------------------------------------------------------
+            // Ghostbusters
+            //-----------------------------------------------------------------------
+            EvtDet::GhostbusterUpdated(vcard) => self.ghostbuster = Some(vcard),
+            EvtDet::GhostbusterRemoved => self.ghostbuster = None,
+            EvtDet::GhostbusterObjectUpdated(rcn, updates) => self
+                .resources
+                .get_mut(&rcn)
+                .unwrap()
+                .ghostbuster_updated(updates),
 
             //-----------------------------------------------------------------------
             // Publication
@@ -337,13 +400,22 @@ impl<S: Signer> Aggregate for CertAuth<S> {
             }
             CmdDet::ChildUpdateResources(child, res) => self.child_update_resources(&child, res),
             CmdDet::ChildUpdateId(child, id) => self.child_update_id(&child, id),
-            CmdDet::ChildCertify(child, request, signer) => {
-                self.child_certify(child, request, signer)
+            CmdDet::ChildUpdateLeases(child, leases, timing, signer) => {
+                let timing = self.publication_timing(&timing);
+                self.child_update_leases(&child, leases, timing, signer)
+            }
+            CmdDet::ChildExpireLeases(child, now, timing, signer) => {
+                self.child_expire_leases(&child, now, self.publication_timing(&timing), signer)
             }
-            CmdDet::ChildRevokeKey(child, request, signer) => {
-                self.child_revoke_key(child, request, signer)
+            CmdDet::ChildCertify(child, request, timing, signer) => {
+                self.child_certify(child, request, self.publication_timing(&timing), signer)
+            }
+            CmdDet::ChildRevokeKey(child, request, timing, signer) => {
+                self.child_revoke_key(child, request, self.publication_timing(&timing), signer)
+            }
+            CmdDet::ChildRemove(child, timing, signer) => {
+                self.child_remove(&child, self.publication_timing(&timing), signer)
             }
-            CmdDet::ChildRemove(child, signer) => self.child_remove(&child, signer),
 
             // being a child
             CmdDet::GenerateNewIdKey(signer) => self.generate_new_id_key(signer),
@@ -354,24 +426,50 @@ impl<S: Signer> Aggregate for CertAuth<S> {
             CmdDet::UpdateResourceClasses(parent, entitlements, signer) => {
                 self.update_resource_classes(parent, entitlements, signer)
             }
-            CmdDet::UpdateRcvdCert(class_name, rcvd_cert, signer) => {
-                self.update_received_cert(class_name, rcvd_cert, signer)
+            CmdDet::UpdateRcvdCert(class_name, rcvd_cert, timing, signer) => {
+                let timing = self.publication_timing(&timing);
+                self.update_received_cert(class_name, rcvd_cert, timing, signer)
             }
 
             // Key rolls
             CmdDet::KeyRollInitiate(duration, signer) => self.keyroll_initiate(duration, signer),
-            CmdDet::KeyRollActivate(duration, signer) => self.keyroll_activate(duration, signer),
+            CmdDet::KeyRollActivate(duration, timing, signer) => {
+                self.keyroll_activate(duration, self.publication_timing(&timing), signer)
+            }
             CmdDet::KeyRollFinish(rcn, response) => self.keyroll_finish(rcn, response),
+            CmdDet::KeyRollPolicyUpdate(policy) => self.keyroll_policy_update(policy),
 
             // Route Authorizations
-            CmdDet::RouteAuthorizationsUpdate(updates, signer) => {
-                self.route_authorizations_update(updates, signer)
+            CmdDet::RouteAuthorizationsUpdate(updates, timing, signer) => {
+                self.route_authorizations_update(updates, self.publication_timing(&timing), signer)
+            }
+            CmdDet::RoaPolicyUpdate(policy, timing, signer) => {
+                self.roa_policy_update(policy, self.publication_timing(&timing), signer)
+            }
+
+            // ASPA
+            CmdDet::AspasUpdate(updates, timing, signer) => {
+                self.aspas_update(updates, self.publication_timing(&timing), signer)
+            }
+            CmdDet::AspasUpdateProviders(customer, update, timing, signer) => {
+                let timing = self.publication_timing(&timing);
+                self.aspas_update_providers(customer, update, timing, signer)
+            }
+
+            // Ghostbusters
+            CmdDet::GhostbusterUpdate(vcard, timing, signer) => {
+                self.ghostbuster_update(vcard, self.publication_timing(&timing), signer)
             }
 
             // Republish
-            CmdDet::Republish(signer) => self.republish(signer),
-            CmdDet::RepoUpdate(new_contact, signer) => self.update_repo(new_contact, signer),
+            CmdDet::Republish(timing, signer) => {
+                self.republish(self.publication_timing(&timing), signer)
+            }
+            CmdDet::RepoUpdate(new_contact, timing, signer) => {
+                self.update_repo(new_contact, self.publication_timing(&timing), signer)
+            }
             CmdDet::RepoRemoveOld(signer) => self.clean_repo(signer),
+            CmdDet::PublicationTimingUpdate(timing) => self.publication_timing_update(timing),
         }
     }
 }
@@ -379,7 +477,9 @@ impl<S: Signer> Aggregate for CertAuth<S> {
 /// # Data presentation
 ///
 impl<S: Signer> CertAuth<S> {
-    pub fn as_ca_info(&self) -> CertAuthInfo {
+    /// Returns the details of this CA. The default timing for manifests and
+    /// CRLs is shown, unless a timing was set for this CA itself.
+    pub fn as_ca_info(&self, default_timing: &PublicationTiming) -> CertAuthInfo {
         let handle = self.handle.clone();
         let repo_info = self
             .repository
@@ -391,13 +491,22 @@ impl<S: Signer> CertAuth<S> {
         let mut resources = HashMap::new();
 
         for (name, rc) in &self.resources {
-            resources.insert(name.clone(), rc.as_info());
+            resources.insert(name.clone(), rc.as_info(&self.keyroll_policy));
         }
         let children: Vec<ChildHandle> = self.children.keys().cloned().collect();
 
         let id_cert_pem = IdCertPem::from(&self.id.cert);
 
-        CertAuthInfo::new(handle, id_cert_pem, repo_info, parents, resources, children)
+        CertAuthInfo::new(
+            handle,
+            id_cert_pem,
+            repo_info,
+            parents,
+            resources,
+            children,
+            self.keyroll_policy.clone(),
+            self.publication_timing(default_timing).clone(),
+        )
     }
 
     pub fn roa_definitions(&self) -> Vec<RoaDefinition> {
@@ -408,6 +517,22 @@ impl<S: Signer> CertAuth<S> {
             .collect()
     }
 
+    pub fn roa_policy(&self) -> RoaPackingPolicy {
+        self.roa_policy
+    }
+
+    pub fn aspa_definitions(&self) -> Vec<AspaDefinition> {
+        self.aspas.all()
+    }
+
+    pub fn ghostbuster(&self) -> Option<&GhostbusterVcard> {
+        self.ghostbuster.as_ref()
+    }
+
+    pub fn keyroll_policy(&self) -> &KeyRollPolicy {
+        &self.keyroll_policy
+    }
+
     pub fn child_request(&self) -> rfc8183::ChildRequest {
         rfc8183::ChildRequest::new(self.handle.clone(), self.id.cert.clone())
     }
@@ -608,7 +733,7 @@ impl<S: Signer> CertAuth<S> {
             Err(_) => return None,
         };
 
-        let child_resources = my_rcvd_cert.resources().intersection(child.resources());
+        let child_resources = my_rcvd_cert.resources().intersection(&child.resources());
         if child_resources.is_empty() {
             return None;
         }
@@ -627,6 +752,12 @@ impl<S: Signer> CertAuth<S> {
             }
         }
 
+        if let Some(lease_end) = child.not_after_cap(&child_resources) {
+            if lease_end < not_after {
+                not_after = lease_end;
+            }
+        }
+
         Some(EntitlementClass::new(
             rcn.clone(),
             issuer,
@@ -654,6 +785,30 @@ impl<S: Signer> CertAuth<S> {
         }
     }
 
+    /// Returns true if a lease for the child has expired, or if a certificate
+    /// issued to the child would outlive one of its leases.
+    pub fn child_leases_expired(&self, child_handle: &ChildHandle, now: Time) -> KrillResult<bool> {
+        let child = self.get_child(child_handle)?;
+
+        if child.leases().iter().any(|lease| lease.is_expired(now)) {
+            return Ok(true);
+        }
+
+        for (rcn, rc) in self.resources.iter() {
+            for key in child.issued(rcn) {
+                if let Some(issued) = rc.issued(&key) {
+                    if let Some(not_after) = child.not_after_cap(issued.resource_set()) {
+                        if issued.validity().not_after() > not_after {
+                            return Ok(true);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
     /// Returns an iterator for the handles of all children under this CA.
     pub fn children(&self) -> impl Iterator<Item = &ChildHandle> {
         self.children.keys()
@@ -695,6 +850,7 @@ impl<S: Signer> CertAuth<S> {
         &self,
         child: Handle,
         request: IssuanceRequest,
+        timing: &PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> KrillResult<Vec<Evt>> {
         let signer = signer.read().unwrap();
@@ -711,7 +867,7 @@ impl<S: Signer> CertAuth<S> {
 
         let issued = self.issue_child_certificate(&child, rcn.clone(), csr_info, limit, signer)?;
 
-        let set_deltas = self.republish_certs(&rcn, &[&issued], &[], signer)?;
+        let set_deltas = self.republish_certs(&rcn, &[&issued], &[], timing, signer)?;
 
         let issued_event = EvtDet::child_certificate_issued(
             &self.handle,
@@ -751,9 +907,10 @@ impl<S: Signer> CertAuth<S> {
             .ok_or_else(|| Error::ResourceClassUnknown(rcn))?;
 
         let child = self.get_child(&child)?;
-        child.resources().apply_limit(&limit)?;
+        let child_resources = child.resources();
+        let not_after = child.not_after_cap(&child_resources.apply_limit(&limit)?);
 
-        my_rc.issue_cert(csr_info, child.resources(), limit, signer)
+        my_rc.issue_cert(csr_info, &child_resources, limit, not_after, signer)
     }
 
     /// Create a publish event details including the revocations, update, withdrawals needed
@@ -763,6 +920,7 @@ impl<S: Signer> CertAuth<S> {
         rcn: &ResourceClassName,
         issued_certs: &[&IssuedCert],
         removed_certs: &[&Cert],
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<HashMap<KeyIdentifier, CurrentObjectSetDelta>> {
         let repo = self.get_repository_contact()?;
@@ -770,7 +928,13 @@ impl<S: Signer> CertAuth<S> {
         self.resources
             .get(&rcn)
             .ok_or_else(|| Error::ResourceClassUnknown(rcn.clone()))?
-            .republish_certs(issued_certs, removed_certs, repo.repo_info(), signer)
+            .republish_certs(
+                issued_certs,
+                removed_certs,
+                repo.repo_info(),
+                timing,
+                signer,
+            )
     }
 
     /// Updates child Resource entitlements.
@@ -786,7 +950,7 @@ impl<S: Signer> CertAuth<S> {
 
         let child = self.get_child(child_handle)?;
 
-        if &resources != child.resources() {
+        if &resources != child.permanent_resources() {
             res.push(EvtDet::child_updated_resources(
                 &self.handle,
                 self.version,
@@ -798,6 +962,162 @@ impl<S: Signer> CertAuth<S> {
         Ok(res)
     }
 
+    /// Updates the time-limited resource leases for a child.
+    ///
+    /// Unlike updates to the permanent resources, this also re-issues any
+    /// existing certificates for the child that are no longer covered by
+    /// its entitlements, or that would outlive a lease. Certificates for
+    /// which no resources remain are revoked. Note that this is a no-op
+    /// if the leases are unchanged and all certificates are up to date.
+    fn child_update_leases(
+        &self,
+        child_handle: &ChildHandle,
+        leases: Vec<ResourceLease>,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        let child = self.get_child(child_handle)?;
+
+        let now = Time::now();
+        let all_resources = self.all_resources();
+        for lease in leases.iter() {
+            if lease.resources().is_empty() {
+                return Err(Error::CaChildMustHaveResources(
+                    self.handle.clone(),
+                    child_handle.clone(),
+                ));
+            } else if !all_resources.contains(lease.resources()) {
+                return Err(Error::CaChildExtraResources(
+                    self.handle.clone(),
+                    child_handle.clone(),
+                ));
+            } else if lease.is_expired(now) {
+                return Err(Error::CaChildLeaseExpired(
+                    self.handle.clone(),
+                    child_handle.clone(),
+                    lease.clone(),
+                ));
+            }
+        }
+
+        let signer = signer.read().unwrap();
+        self.child_apply_leases(child_handle, child, leases, timing, signer.deref())
+    }
+
+    /// Removes the leases for a child which expired at the given time, and
+    /// re-issues or revokes any certificates for the child accordingly.
+    ///
+    /// Unlike an update of the leases, the remaining leases are not checked
+    /// against the resources held by this CA. Shrinking the certificates of
+    /// the child should never fail because this CA lost resources itself.
+    fn child_expire_leases(
+        &self,
+        child_handle: &ChildHandle,
+        now: Time,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        let child = self.get_child(child_handle)?;
+
+        let leases: Vec<ResourceLease> = child
+            .leases()
+            .iter()
+            .filter(|lease| !lease.is_expired(now))
+            .cloned()
+            .collect();
+
+        let signer = signer.read().unwrap();
+        self.child_apply_leases(child_handle, child, leases, timing, signer.deref())
+    }
+
+    /// Sets the leases for a child, and re-issues any existing certificates
+    /// for the child which are no longer covered by its entitlements, or
+    /// which would outlive a lease. Certificates for which no resources
+    /// remain are revoked.
+    fn child_apply_leases(
+        &self,
+        child_handle: &ChildHandle,
+        child: &ChildDetails,
+        leases: Vec<ResourceLease>,
+        timing: &PublicationTiming,
+        signer: &S,
+    ) -> KrillResult<Vec<Evt>> {
+        let mut updated_child = child.clone();
+        updated_child.set_leases(leases.clone());
+        let entitled = updated_child.resources();
+
+        let mut version = self.version;
+        let handle = &self.handle;
+
+        let mut res = vec![];
+
+        if &leases != child.leases() {
+            res.push(EvtDet::child_updated_leases(
+                handle,
+                version,
+                child_handle.clone(),
+                leases,
+            ));
+            version += 1;
+        }
+
+        for (rcn, rc) in self.resources.iter() {
+            let mut re_issued_certs = vec![];
+            let mut removed_certs = vec![];
+
+            for key in child.issued(rcn) {
+                if let Some(issued) = rc.issued(&key) {
+                    let remaining = issued.resource_set().intersection(&entitled);
+                    if remaining.is_empty() {
+                        removed_certs.push(issued);
+                    } else {
+                        let not_after = updated_child.not_after_cap(&remaining);
+                        let outlives_lease = not_after
+                            .map(|time| issued.validity().not_after() > time)
+                            .unwrap_or(false);
+
+                        if &remaining != issued.resource_set() || outlives_lease {
+                            re_issued_certs
+                                .push(rc.re_issue_cert(issued, remaining, not_after, signer)?);
+                        }
+                    }
+                }
+            }
+
+            if re_issued_certs.is_empty() && removed_certs.is_empty() {
+                continue;
+            }
+
+            let re_issued: Vec<&IssuedCert> = re_issued_certs.iter().collect();
+            let removed: Vec<&Cert> = removed_certs.iter().map(|c| c.cert()).collect();
+            let set_deltas = self.republish_certs(&rcn, &re_issued, &removed, timing, signer)?;
+            res.push(EvtDet::current_set_updated(
+                handle,
+                version,
+                rcn.clone(),
+                set_deltas,
+            ));
+            version += 1;
+
+            let mut cert_updates = ChildCertificateUpdates::default();
+            for issued in removed_certs {
+                cert_updates.remove(issued.subject_key_identifier())
+            }
+            for issued in re_issued_certs {
+                cert_updates.issue(issued)
+            }
+            res.push(EvtDet::child_certificates_updated(
+                handle,
+                version,
+                rcn.clone(),
+                cert_updates,
+            ));
+            version += 1;
+        }
+
+        Ok(res)
+    }
+
     /// Updates child IdCert
     fn child_update_id(&self, child_handle: &Handle, id_cert: IdCert) -> KrillResult<Vec<Evt>> {
         let mut res = vec![];
@@ -822,6 +1142,7 @@ impl<S: Signer> CertAuth<S> {
         &self,
         child_handle: ChildHandle,
         request: RevocationRequest,
+        timing: &PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> KrillResult<Vec<Evt>> {
         let signer = signer.read().unwrap();
@@ -847,7 +1168,7 @@ impl<S: Signer> CertAuth<S> {
         let handle = &self.handle;
         let version = self.version;
 
-        let set_deltas = self.republish_certs(&rcn, &[], &[removed], signer)?;
+        let set_deltas = self.republish_certs(&rcn, &[], &[removed], timing, signer)?;
 
         let mut child_certificate_updates = ChildCertificateUpdates::default();
         child_certificate_updates.remove(key);
@@ -863,6 +1184,7 @@ impl<S: Signer> CertAuth<S> {
     fn child_remove(
         &self,
         child_handle: &ChildHandle,
+        timing: &PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> KrillResult<Vec<Evt>> {
         let signer = signer.read().unwrap();
@@ -890,7 +1212,7 @@ impl<S: Signer> CertAuth<S> {
             }
 
             let removed: Vec<&Cert> = issued_certs.iter().map(|c| c.cert()).collect();
-            let set_deltas = self.republish_certs(&rcn, &[], &removed, signer)?;
+            let set_deltas = self.republish_certs(&rcn, &[], &removed, timing, signer)?;
             res.push(EvtDet::current_set_updated(
                 handle,
                 version,
@@ -1083,6 +1405,32 @@ impl<S: Signer> CertAuth<S> {
         Ok(res)
     }
 
+    /// Returns revocation requests for all keys certified by the given
+    /// parent. Used when this CA is about to be deleted.
+    pub fn revoke_all_requests(
+        &self,
+        parent: &ParentHandle,
+        signer: &S,
+    ) -> KrillResult<HashMap<ResourceClassName, Vec<RevocationRequest>>> {
+        let mut res = HashMap::new();
+        for (name, rc) in self.resources.iter() {
+            if rc.parent_handle() == parent {
+                res.insert(name.clone(), rc.revoke(signer)?);
+            }
+        }
+        Ok(res)
+    }
+
+    /// Returns the identifiers of all keys used by this CA, i.e. its
+    /// RFC 8183 identity key and the keys of all its resource classes.
+    pub fn all_key_ids(&self) -> Vec<KeyIdentifier> {
+        let mut res = vec![self.id.key];
+        for rc in self.resources.values() {
+            res.append(&mut rc.key_ids());
+        }
+        res
+    }
+
     /// Returns the open revocation requests for the given parent.
     pub fn revoke_requests(
         &self,
@@ -1243,6 +1591,7 @@ impl<S: Signer> CertAuth<S> {
         &self,
         rcn: ResourceClassName,
         rcvd_cert: RcvdCert,
+        timing: &PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> KrillResult<Vec<Evt>> {
         debug!(
@@ -1259,7 +1608,13 @@ impl<S: Signer> CertAuth<S> {
 
         let repo = self.get_repository_contact()?;
 
-        let evt_details = rc.update_received_cert(rcvd_cert, repo.repo_info(), signer.deref())?;
+        let evt_details = rc.update_received_cert(
+            rcvd_cert,
+            repo.repo_info(),
+            &self.roa_policy,
+            timing,
+            signer.deref(),
+        )?;
 
         let mut res = vec![];
         let mut version = self.version;
@@ -1290,6 +1645,14 @@ impl<S: Signer> CertAuth<S> {
         let mut res = vec![];
 
         for (rcn, rc) in self.resources.iter() {
+            if rc.has_pending_requests() {
+                info!(
+                    "Skipping key roll for ca: {}, rc: {}, there are pending requests",
+                    &self.handle, rcn
+                );
+                continue;
+            }
+
             let mut started = false;
             let repo = self.get_repository_contact()?;
             for details in rc
@@ -1309,7 +1672,12 @@ impl<S: Signer> CertAuth<S> {
         Ok(res)
     }
 
-    fn keyroll_activate(&self, staging: Duration, signer: Arc<RwLock<S>>) -> KrillResult<Vec<Evt>> {
+    fn keyroll_activate(
+        &self,
+        staging: Duration,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
         if self.is_ta() {
             return Ok(vec![]);
         }
@@ -1324,7 +1692,13 @@ impl<S: Signer> CertAuth<S> {
             let repo = self.get_repository_contact()?;
 
             for details in rc
-                .keyroll_activate(repo.repo_info(), staging, signer.deref())?
+                .keyroll_activate(
+                    repo.repo_info(),
+                    &self.roa_policy,
+                    staging,
+                    timing,
+                    signer.deref(),
+                )?
                 .into_iter()
             {
                 activated = true;
@@ -1365,20 +1739,71 @@ impl<S: Signer> CertAuth<S> {
             finish_details,
         )])
     }
+
+    fn keyroll_policy_update(&self, policy: KeyRollPolicy) -> KrillResult<Vec<Evt>> {
+        if !policy.is_valid() {
+            return Err(Error::KeyRollPolicyInvalid(self.handle.clone(), policy));
+        }
+
+        if policy == self.keyroll_policy {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![StoredEvent::new(
+            self.handle(),
+            self.version,
+            EvtDet::KeyRollPolicyUpdated(policy),
+        )])
+    }
+
+    /// Returns the key roll status for all resource classes.
+    pub fn keyroll_status(&self) -> HashMap<ResourceClassName, KeyRollStatus> {
+        self.resources
+            .iter()
+            .map(|(rcn, rc)| (rcn.clone(), rc.key_roll_status(&self.keyroll_policy)))
+            .collect()
+    }
+
+    /// Returns whether the policy calls for starting a key roll in any
+    /// resource class, at the given time. Resource classes with pending
+    /// requests to the parent are skipped.
+    pub fn keyroll_initiate_due(&self, now: Time) -> bool {
+        !self.is_ta()
+            && self.resources.values().any(|rc| {
+                let status = rc.key_roll_status(&self.keyroll_policy);
+                status.phase() == KeyRollPhase::Idle
+                    && status.is_due(now)
+                    && !rc.has_pending_requests()
+            })
+    }
+
+    /// Returns whether the policy calls for activating a new key in any
+    /// resource class, at the given time.
+    pub fn keyroll_activate_due(&self, now: Time) -> bool {
+        !self.is_ta()
+            && self.resources.values().any(|rc| {
+                let status = rc.key_roll_status(&self.keyroll_policy);
+                status.phase() == KeyRollPhase::Staging && status.is_due(now)
+            })
+    }
 }
 
 /// # Publishing
 ///
 impl<S: Signer> CertAuth<S> {
     /// Republish objects for this CA
-    pub fn republish(&self, signer: Arc<RwLock<S>>) -> KrillResult<Vec<Evt>> {
+    pub fn republish(
+        &self,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
         let signer = signer.read().unwrap();
         let signer = signer.deref();
 
         let mut version = self.version;
         let mut res = vec![];
 
-        for evt_det in self.republish_resource_classes(&PublishMode::Normal, signer)? {
+        for evt_det in self.republish_resource_classes(&PublishMode::Normal, timing, signer)? {
             res.push(StoredEvent::new(&self.handle, version, evt_det));
             version += 1;
         }
@@ -1389,6 +1814,7 @@ impl<S: Signer> CertAuth<S> {
     fn republish_resource_classes(
         &self,
         mode: &PublishMode,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<Vec<EvtDet>> {
         let mut res = vec![];
@@ -1404,13 +1830,61 @@ impl<S: Signer> CertAuth<S> {
                     self.get_repository_contact()?.repo_info()
                 };
 
-                res.append(&mut rc.republish(auths.as_slice(), repo_info, mode, signer)?);
+                let definitions = ObjectDefinitions::new(
+                    auths.as_slice(),
+                    &self.roa_policy,
+                    &self.aspas,
+                    self.ghostbuster.as_ref(),
+                );
+
+                res.append(&mut rc.republish(&definitions, repo_info, mode, timing, signer)?);
             }
         }
 
         Ok(res)
     }
 
+    /// Updates the objects in all resource classes with a current key after
+    /// the definitions were changed, and publishes the changes. The events
+    /// for this are appended to the events for the changed definitions.
+    fn publish_definitions(
+        &self,
+        mut evt_dets: Vec<EvtDet>,
+        definitions: &ObjectDefinitions,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        if evt_dets.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let signer = signer.read().unwrap();
+
+        // The definitions are kept even if there is nothing to sign with yet.
+        for rc in self.resources.values() {
+            if rc.current_key().is_none() {
+                continue;
+            }
+
+            let repo = self.get_repository_contact()?;
+
+            evt_dets.append(&mut rc.publish_definitions(
+                definitions,
+                repo.repo_info(),
+                timing,
+                signer.deref(),
+            )?);
+        }
+
+        let mut version = self.version;
+        let mut res = vec![];
+        for dt in evt_dets.into_iter() {
+            res.push(StoredEvent::new(&self.handle, version, dt));
+            version += 1;
+        }
+        Ok(res)
+    }
+
     /// Update repository:
     /// - check that it is indeed different
     /// - regenerate all objects under the new URI (CRL URIs updated)
@@ -1422,6 +1896,7 @@ impl<S: Signer> CertAuth<S> {
     pub fn update_repo(
         &self,
         new_contact: RepositoryContact,
+        timing: &PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> KrillResult<Vec<Evt>> {
         let signer = signer.read().unwrap();
@@ -1442,9 +1917,11 @@ impl<S: Signer> CertAuth<S> {
         evt_dts.push(EvtDet::RepoUpdated(new_contact));
 
         // issue new things => will trigger publication at the new location
-        evt_dts.append(
-            &mut self.republish_resource_classes(&PublishMode::NewRepo(info.clone()), signer)?,
-        );
+        evt_dts.append(&mut self.republish_resource_classes(
+            &PublishMode::NewRepo(info.clone()),
+            timing,
+            signer,
+        )?);
 
         // request new certs => when received will trigger unpublishing at old location
         for rc in self.resources.values() {
@@ -1474,6 +1951,40 @@ impl<S: Signer> CertAuth<S> {
     pub fn has_old_repo(&self) -> bool {
         self.repository_pending_withdraw.is_some()
     }
+
+    /// Returns the timing used for manifests and CRLs: the timing set for
+    /// this CA, if any, or else the given default.
+    pub fn publication_timing<'a>(
+        &'a self,
+        default: &'a PublicationTiming,
+    ) -> &'a PublicationTiming {
+        self.publication_timing.as_ref().unwrap_or(default)
+    }
+
+    /// Sets the timing for this CA, or reverts to the default if None.
+    fn publication_timing_update(
+        &self,
+        timing: Option<PublicationTiming>,
+    ) -> KrillResult<Vec<Evt>> {
+        if let Some(timing) = &timing {
+            if !timing.is_valid() {
+                return Err(Error::CaPublicationTimingInvalid(
+                    self.handle.clone(),
+                    timing.clone(),
+                ));
+            }
+        }
+
+        if timing == self.publication_timing {
+            return Ok(vec![]);
+        }
+
+        Ok(vec![StoredEvent::new(
+            self.handle(),
+            self.version,
+            EvtDet::PublicationTimingUpdated(timing),
+        )])
+    }
 }
 
 /// # Managing Route Authorizations
@@ -1485,6 +1996,7 @@ impl<S: Signer> CertAuth<S> {
     fn route_authorizations_update(
         &self,
         updates: RouteAuthorizationUpdates,
+        timing: &PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> KrillResult<Vec<Evt>> {
         let (added, removed) = updates.unpack();
@@ -1542,7 +2054,12 @@ impl<S: Signer> CertAuth<S> {
 
         // Update ROAs, and derive deltas and revocations for publishing.
         for (rcn, rc) in self.resources.iter() {
-            let updates = rc.update_roas(current_auths.as_slice(), &mode, signer.deref())?;
+            let updates = rc.update_roas(
+                current_auths.as_slice(),
+                &self.roa_policy,
+                &mode,
+                signer.deref(),
+            )?;
             if updates.contains_changes() {
                 let mut delta = ObjectsDelta::new(repo.repo_info().ca_repository(rc.name_space()));
 
@@ -1573,8 +2090,14 @@ impl<S: Signer> CertAuth<S> {
         for (rcn, (delta, revocations)) in deltas.into_iter() {
             let rc = self.resources.get(&rcn).unwrap();
 
-            let pub_detail =
-                rc.publish_objects(repo.repo_info(), delta, revocations, &mode, signer.deref())?;
+            let pub_detail = rc.publish_objects(
+                repo.repo_info(),
+                delta,
+                revocations,
+                &mode,
+                timing,
+                signer.deref(),
+            )?;
 
             res.push(StoredEvent::new(&self.handle, version, pub_detail));
             version += 1;
@@ -1582,6 +2105,184 @@ impl<S: Signer> CertAuth<S> {
 
         Ok(res)
     }
+
+    /// Updates the ROA packing policy for this CA, and re-issues ROAs for
+    /// all current authorizations in accordance with the new policy. ROAs
+    /// made under the old policy are withdrawn.
+    fn roa_policy_update(
+        &self,
+        policy: RoaPackingPolicy,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        if !policy.is_valid() {
+            return Err(Error::CaRoaPackingPolicyInvalid(
+                self.handle.clone(),
+                policy,
+            ));
+        }
+
+        if policy == self.roa_policy {
+            return Ok(vec![]);
+        }
+
+        let evt_dets = vec![EvtDet::RoaPackingPolicyUpdated(policy)];
+
+        let auths: Vec<RouteAuthorization> = self.routes.authorizations().cloned().collect();
+        let definitions = ObjectDefinitions::new(
+            auths.as_slice(),
+            &policy,
+            &self.aspas,
+            self.ghostbuster.as_ref(),
+        );
+
+        self.publish_definitions(evt_dets, &definitions, timing, signer)
+    }
+}
+
+/// # Managing ASPA definitions
+///
+impl<S: Signer> CertAuth<S> {
+    /// Adds, replaces or removes ASPA definitions for this CA, and updates
+    /// the ASPA objects. Will return an error in case a definition is added
+    /// for a customer ASN that this CA does not hold, or if a definition to
+    /// be removed is unknown.
+    fn aspas_update(
+        &self,
+        updates: AspaDefinitionUpdates,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        let (add_or_replace, remove) = updates.unpack();
+        let all_resources = self.all_resources();
+
+        let mut definitions = self.aspas.clone();
+        let mut evt_dets = vec![];
+        let mut seen = HashSet::new();
+
+        for definition in add_or_replace {
+            let definition = definition.normalized();
+            let customer = definition.customer();
+            if !seen.insert(customer) {
+                return Err(Error::CaAspaDuplicate(self.handle.clone(), customer));
+            } else if !definition.providers_valid() {
+                return Err(Error::CaAspaProvidersInvalid(
+                    self.handle.clone(),
+                    definition,
+                ));
+            } else if !all_resources.contains(&customer.into()) {
+                return Err(Error::CaAspaNotEntitled(self.handle.clone(), customer));
+            } else if definitions.get(customer) != Some(&definition) {
+                definitions.add_or_replace(definition.clone());
+                evt_dets.push(EvtDet::AspaConfigAdded(definition));
+            }
+        }
+
+        for customer in remove {
+            if !seen.insert(customer) {
+                return Err(Error::CaAspaDuplicate(self.handle.clone(), customer));
+            } else if !definitions.has(customer) {
+                return Err(Error::CaAspaUnknown(self.handle.clone(), customer));
+            } else {
+                definitions.remove(customer);
+                evt_dets.push(EvtDet::AspaConfigRemoved(customer));
+            }
+        }
+
+        self.aspas_publish(evt_dets, &definitions, timing, signer)
+    }
+
+    /// Updates the providers for an existing ASPA definition, and updates
+    /// the ASPA object.
+    fn aspas_update_providers(
+        &self,
+        customer: AsNumber,
+        update: AspaProvidersUpdate,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        let mut definitions = self.aspas.clone();
+
+        let current = definitions
+            .get(customer)
+            .ok_or_else(|| Error::CaAspaUnknown(self.handle.clone(), customer))?;
+
+        let mut updated = current.clone();
+        updated.apply_update(&update);
+
+        if !updated.providers_valid() {
+            return Err(Error::CaAspaProvidersInvalid(self.handle.clone(), updated));
+        }
+
+        if &updated == current {
+            return Ok(vec![]);
+        }
+
+        definitions.apply_update(customer, &update);
+        let evt_dets = vec![EvtDet::AspaConfigUpdated(customer, update)];
+
+        self.aspas_publish(evt_dets, &definitions, timing, signer)
+    }
+
+    /// Appends ASPA object updates and publication events for the given
+    /// (updated) definitions to the configuration events.
+    fn aspas_publish(
+        &self,
+        evt_dets: Vec<EvtDet>,
+        aspas: &AspaDefinitions,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        let auths: Vec<RouteAuthorization> = self.routes.authorizations().cloned().collect();
+        let definitions = ObjectDefinitions::new(
+            auths.as_slice(),
+            &self.roa_policy,
+            aspas,
+            self.ghostbuster.as_ref(),
+        );
+
+        self.publish_definitions(evt_dets, &definitions, timing, signer)
+    }
+}
+
+/// # Managing the Ghostbusters record
+///
+impl<S: Signer> CertAuth<S> {
+    /// Sets the Ghostbusters vCard for this CA, or removes it, and updates
+    /// the Ghostbusters records under each resource class accordingly. Will
+    /// return an error if the vCard lacks a name or a way to get in touch.
+    fn ghostbuster_update(
+        &self,
+        vcard: Option<GhostbusterVcard>,
+        timing: &PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> KrillResult<Vec<Evt>> {
+        if self.ghostbuster == vcard {
+            return Ok(vec![]);
+        }
+
+        let mut evt_dets = vec![];
+
+        match &vcard {
+            Some(vcard) => {
+                if !vcard.is_valid() {
+                    return Err(Error::CaGhostbustersInvalid(self.handle.clone()));
+                }
+                evt_dets.push(EvtDet::GhostbusterUpdated(vcard.clone()));
+            }
+            None => evt_dets.push(EvtDet::GhostbusterRemoved),
+        }
+
+        let auths: Vec<RouteAuthorization> = self.routes.authorizations().cloned().collect();
+        let definitions = ObjectDefinitions::new(
+            auths.as_slice(),
+            &self.roa_policy,
+            &self.aspas,
+            vcard.as_ref(),
+        );
+
+        self.publish_definitions(evt_dets, &definitions, timing, signer)
+    }
 }
 
 //------------ Tests ---------------------------------------------------------
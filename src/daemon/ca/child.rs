@@ -5,7 +5,9 @@ use chrono::Duration;
 use rpki::crypto::KeyIdentifier;
 use rpki::x509::Time;
 
-use crate::commons::api::{ChildCaInfo, ChildHandle, IssuedCert, ResourceClassName, ResourceSet};
+use crate::commons::api::{
+    ChildCaInfo, ChildHandle, IssuedCert, ResourceClassName, ResourceLease, ResourceSet,
+};
 use crate::commons::error::Error;
 use crate::commons::remote::id::IdCert;
 use crate::commons::KrillResult;
@@ -31,6 +33,8 @@ impl LastResponse {}
 pub struct ChildDetails {
     id_cert: Option<IdCert>,
     resources: ResourceSet,
+    #[serde(default)]
+    leases: Vec<ResourceLease>,
     used_keys: HashMap<KeyIdentifier, LastResponse>,
 }
 
@@ -39,6 +43,7 @@ impl ChildDetails {
         ChildDetails {
             id_cert,
             resources,
+            leases: vec![],
             used_keys: HashMap::new(),
         }
     }
@@ -51,7 +56,17 @@ impl ChildDetails {
         self.id_cert = Some(id_cert);
     }
 
-    pub fn resources(&self) -> &ResourceSet {
+    /// Returns all resources the child is entitled to, i.e. its permanent
+    /// resources as well as any leased resources.
+    pub fn resources(&self) -> ResourceSet {
+        self.leases
+            .iter()
+            .fold(self.resources.clone(), |all, lease| {
+                all.union(lease.resources())
+            })
+    }
+
+    pub fn permanent_resources(&self) -> &ResourceSet {
         &self.resources
     }
 
@@ -59,6 +74,29 @@ impl ChildDetails {
         self.resources = resources;
     }
 
+    pub fn leases(&self) -> &Vec<ResourceLease> {
+        &self.leases
+    }
+
+    pub fn set_leases(&mut self, leases: Vec<ResourceLease>) {
+        self.leases = leases;
+    }
+
+    /// Returns the time after which a certificate for the given resources
+    /// may no longer be valid, because (part of) the resources are only
+    /// leased to the child. Returns None if all resources are permanent.
+    pub fn not_after_cap(&self, resources: &ResourceSet) -> Option<Time> {
+        let permanent = resources.intersection(&self.resources);
+        self.leases
+            .iter()
+            .filter(|lease| {
+                let leased = resources.intersection(lease.resources());
+                !leased.is_empty() && !permanent.contains(&leased)
+            })
+            .map(ResourceLease::not_after)
+            .min()
+    }
+
     pub fn issued(&self, rcn: &ResourceClassName) -> Vec<KeyIdentifier> {
         let mut res = vec![];
 
@@ -110,7 +148,8 @@ impl ChildDetails {
 
 impl Into<ChildCaInfo> for ChildDetails {
     fn into(self) -> ChildCaInfo {
-        ChildCaInfo::new(self.id_cert.as_ref(), self.resources)
+        let resources = self.resources();
+        ChildCaInfo::new(self.id_cert.as_ref(), resources, self.leases)
     }
 }
 
@@ -177,3 +216,29 @@ impl Default for ChildCertificates {
         }
     }
 }
+
+//------------ Tests -------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_after_capped_by_leases() {
+        let permanent = ResourceSet::from_strs("AS1", "10.0.0.0/16", "").unwrap();
+        let leased = ResourceSet::from_strs("", "192.168.0.0/16", "").unwrap();
+        let lease_end = Time::now() + Duration::days(30);
+
+        let mut child = ChildDetails::new(None, permanent.clone());
+        child.set_leases(vec![ResourceLease::new(leased.clone(), lease_end)]);
+
+        assert_eq!(child.resources(), permanent.union(&leased));
+        assert_eq!(child.not_after_cap(&permanent), None);
+        assert_eq!(child.not_after_cap(&leased), Some(lease_end));
+        assert_eq!(child.not_after_cap(&child.resources()), Some(lease_end));
+
+        // resources which are also held permanently are not capped
+        child.set_resources(permanent.union(&leased));
+        assert_eq!(child.not_after_cap(&leased), None);
+    }
+}
@@ -5,11 +5,13 @@ use std::sync::{Arc, RwLock};
 use chrono::Duration;
 
 use rpki::uri;
+use rpki::x509::Time;
 
 use crate::commons::api::{
-    ChildHandle, Entitlements, Handle, IssuanceRequest, ParentCaContact, ParentHandle, RcvdCert,
-    RepositoryContact, ResourceClassName, ResourceSet, RevocationRequest, RevocationResponse,
-    StorableCaCommand,
+    AsNumber, AspaDefinitionUpdates, AspaProvidersUpdate, ChildHandle, Entitlements,
+    GhostbusterVcard, Handle, IssuanceRequest, KeyRollPolicy, ParentCaContact, ParentHandle,
+    PublicationTiming, RcvdCert, RepositoryContact, ResourceClassName, ResourceLease, ResourceSet,
+    RevocationRequest, RevocationResponse, RoaPackingPolicy, StorableCaCommand,
 };
 use crate::commons::eventsourcing;
 use crate::commons::remote::id::IdCert;
@@ -21,6 +23,9 @@ pub type Cmd<S> = eventsourcing::SentCommand<CmdDet<S>>;
 
 //------------ CommandDetails ----------------------------------------------
 
+/// The commands for a CA. Commands which may (re-)publish objects carry the
+/// configured default timing for manifests and CRLs. A CA uses this default,
+/// unless a timing was set for the CA itself.
 #[derive(Clone, Debug)]
 #[allow(clippy::large_enum_variant)]
 pub enum CmdDet<S: Signer> {
@@ -39,12 +44,33 @@ pub enum CmdDet<S: Signer> {
     ChildUpdateResources(ChildHandle, ResourceSet),
     // Update some details for an existing child, e.g. resources.
     ChildUpdateId(ChildHandle, IdCert),
+    // Update the time-limited resource leases for an existing child, and
+    // re-issue or revoke certificates which are no longer covered.
+    ChildUpdateLeases(
+        ChildHandle,
+        Vec<ResourceLease>,
+        PublicationTiming,
+        Arc<RwLock<S>>,
+    ),
+    // Remove the leases for an existing child which expired at the given
+    // time, and re-issue or revoke certificates which are no longer covered.
+    ChildExpireLeases(ChildHandle, Time, PublicationTiming, Arc<RwLock<S>>),
     // Process an issuance request by an existing child.
-    ChildCertify(ChildHandle, IssuanceRequest, Arc<RwLock<S>>),
+    ChildCertify(
+        ChildHandle,
+        IssuanceRequest,
+        PublicationTiming,
+        Arc<RwLock<S>>,
+    ),
     // Process a revoke request by an existing child.
-    ChildRevokeKey(ChildHandle, RevocationRequest, Arc<RwLock<S>>),
+    ChildRevokeKey(
+        ChildHandle,
+        RevocationRequest,
+        PublicationTiming,
+        Arc<RwLock<S>>,
+    ),
     // Remove child (also revokes, and removes issued certs, and republishes)
-    ChildRemove(ChildHandle, Arc<RwLock<S>>),
+    ChildRemove(ChildHandle, PublicationTiming, Arc<RwLock<S>>),
 
     // ------------------------------------------------------------
     // Being a child (only allowed if this CA is not self-signed)
@@ -70,7 +96,12 @@ pub enum CmdDet<S: Signer> {
     // as needed.
     UpdateResourceClasses(ParentHandle, Entitlements, Arc<RwLock<S>>),
     // Process a new certificate received from a parent.
-    UpdateRcvdCert(ResourceClassName, RcvdCert, Arc<RwLock<S>>),
+    UpdateRcvdCert(
+        ResourceClassName,
+        RcvdCert,
+        PublicationTiming,
+        Arc<RwLock<S>>,
+    ),
 
     // ------------------------------------------------------------
     // Key rolls
@@ -90,29 +121,60 @@ pub enum CmdDet<S: Signer> {
     //
     // RFC6489 dictates that 24 hours MUST be observed. However, shorter time frames can
     // be used for testing, and in case of emergency rolls.
-    KeyRollActivate(Duration, Arc<RwLock<S>>),
+    KeyRollActivate(Duration, PublicationTiming, Arc<RwLock<S>>),
 
     // Finish the keyroll after the parent confirmed that a key for a parent and resource
     // class has been revoked. I.e. remove the old key, and withdraw the crl and mft for it.
     KeyRollFinish(ResourceClassName, RevocationResponse),
 
+    // Set the policy that determines when key rolls are initiated and activated
+    // automatically.
+    KeyRollPolicyUpdate(KeyRollPolicy),
+
     // ------------------------------------------------------------
     // ROA Support
     // ------------------------------------------------------------
-    RouteAuthorizationsUpdate(RouteAuthorizationUpdates, Arc<RwLock<S>>),
+    RouteAuthorizationsUpdate(RouteAuthorizationUpdates, PublicationTiming, Arc<RwLock<S>>),
+    // Change how route authorizations are packed into ROAs, and re-issue ROAs.
+    RoaPolicyUpdate(RoaPackingPolicy, PublicationTiming, Arc<RwLock<S>>),
+
+    // ------------------------------------------------------------
+    // ASPA Support
+    // ------------------------------------------------------------
+
+    // Add, replace or remove ASPA definitions, and update ASPA objects.
+    AspasUpdate(AspaDefinitionUpdates, PublicationTiming, Arc<RwLock<S>>),
+    // Update the providers for the ASPA definition of an existing customer.
+    AspasUpdateProviders(
+        AsNumber,
+        AspaProvidersUpdate,
+        PublicationTiming,
+        Arc<RwLock<S>>,
+    ),
+
+    // ------------------------------------------------------------
+    // Ghostbusters
+    // ------------------------------------------------------------
+
+    // Set or remove (None) the Ghostbusters vCard, and update the records.
+    GhostbusterUpdate(Option<GhostbusterVcard>, PublicationTiming, Arc<RwLock<S>>),
 
     // ------------------------------------------------------------
     // Publishing
     // ------------------------------------------------------------
 
     // Republish, if needed, may be a no-op if everything is still fresh.
-    Republish(Arc<RwLock<S>>),
+    Republish(PublicationTiming, Arc<RwLock<S>>),
 
     // Update the repository where this CA publishes
-    RepoUpdate(RepositoryContact, Arc<RwLock<S>>),
+    RepoUpdate(RepositoryContact, PublicationTiming, Arc<RwLock<S>>),
 
     // Clean up the old pending to withdraw repo.
     RepoRemoveOld(Arc<RwLock<S>>),
+
+    // Set the timing for manifests and CRLs for this CA, or revert to the
+    // default (None). Takes effect when objects are next republished.
+    PublicationTimingUpdate(Option<PublicationTiming>),
 }
 
 impl<S: Signer> eventsourcing::CommandDetails for CmdDet<S> {
@@ -143,13 +205,21 @@ impl<S: Signer> From<CmdDet<S>> for StorableCaCommand {
             CmdDet::ChildUpdateId(child, id) => {
                 StorableCaCommand::ChildUpdateId(child, id.ski_hex())
             }
-            CmdDet::ChildCertify(child, req, _) => {
+            CmdDet::ChildUpdateLeases(child, leases, _, _) => {
+                StorableCaCommand::ChildUpdateLeases(child, leases)
+            }
+            CmdDet::ChildExpireLeases(child, _, _, _) => {
+                StorableCaCommand::ChildExpireLeases(child)
+            }
+            CmdDet::ChildCertify(child, req, _, _) => {
                 let (rcn, limit, csr) = req.unpack();
                 let ki = csr.public_key().key_identifier();
                 StorableCaCommand::ChildCertify(child, rcn, limit, ki)
             }
-            CmdDet::ChildRevokeKey(child, req, _) => StorableCaCommand::ChildRevokeKey(child, req),
-            CmdDet::ChildRemove(child, _) => StorableCaCommand::ChildRemove(child),
+            CmdDet::ChildRevokeKey(child, req, _, _) => {
+                StorableCaCommand::ChildRevokeKey(child, req)
+            }
+            CmdDet::ChildRemove(child, _, _) => StorableCaCommand::ChildRemove(child),
             CmdDet::GenerateNewIdKey(_) => StorableCaCommand::GenerateNewIdKey,
             CmdDet::AddParent(parent, contact) => {
                 StorableCaCommand::AddParent(parent, contact.into())
@@ -169,21 +239,28 @@ impl<S: Signer> From<CmdDet<S>> for StorableCaCommand {
 
                 StorableCaCommand::UpdateResourceClasses(parent, classes)
             }
-            CmdDet::UpdateRcvdCert(rcn, rcvd_cert, _) => {
+            CmdDet::UpdateRcvdCert(rcn, rcvd_cert, _, _) => {
                 StorableCaCommand::UpdateRcvdCert(rcn, rcvd_cert.resources().clone())
             }
             CmdDet::KeyRollInitiate(duration, _) => {
                 StorableCaCommand::KeyRollInitiate(duration.num_seconds())
             }
-            CmdDet::KeyRollActivate(duration, _) => {
+            CmdDet::KeyRollActivate(duration, _, _) => {
                 StorableCaCommand::KeyRollActivate(duration.num_seconds())
             }
             CmdDet::KeyRollFinish(rcn, _) => StorableCaCommand::KeyRollFinish(rcn),
-            CmdDet::RouteAuthorizationsUpdate(updates, _) => {
+            CmdDet::KeyRollPolicyUpdate(policy) => StorableCaCommand::KeyRollPolicyUpdate(policy),
+            CmdDet::RouteAuthorizationsUpdate(updates, _, _) => {
                 StorableCaCommand::RoaDefinitionUpdates(updates.into())
             }
-            CmdDet::Republish(_) => StorableCaCommand::Republish,
-            CmdDet::RepoUpdate(update, _) => {
+            CmdDet::RoaPolicyUpdate(policy, _, _) => StorableCaCommand::RoaPolicyUpdate(policy),
+            CmdDet::AspasUpdate(updates, _, _) => StorableCaCommand::AspasUpdate(updates),
+            CmdDet::AspasUpdateProviders(customer, update, _, _) => {
+                StorableCaCommand::AspasUpdateProviders(customer, update)
+            }
+            CmdDet::GhostbusterUpdate(vcard, _, _) => StorableCaCommand::GhostbusterUpdate(vcard),
+            CmdDet::Republish(_, _) => StorableCaCommand::Republish,
+            CmdDet::RepoUpdate(update, _, _) => {
                 let service_uri_opt = match update {
                     RepositoryContact::Embedded(_) => None,
                     RepositoryContact::Rfc8181(res) => Some(res.service_uri().clone()),
@@ -191,6 +268,9 @@ impl<S: Signer> From<CmdDet<S>> for StorableCaCommand {
                 StorableCaCommand::RepoUpdate(service_uri_opt)
             }
             CmdDet::RepoRemoveOld(_) => StorableCaCommand::RepoRemoveOld,
+            CmdDet::PublicationTimingUpdate(timing) => {
+                StorableCaCommand::PublicationTimingUpdate(timing)
+            }
         }
     }
 }
@@ -236,18 +316,47 @@ impl<S: Signer> CmdDet<S> {
         eventsourcing::SentCommand::new(handle, None, CmdDet::ChildUpdateId(child_handle, id))
     }
 
+    pub fn child_update_leases(
+        handle: &Handle,
+        child_handle: ChildHandle,
+        leases: Vec<ResourceLease>,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::ChildUpdateLeases(child_handle, leases, timing, signer),
+        )
+    }
+
+    pub fn child_expire_leases(
+        handle: &Handle,
+        child_handle: ChildHandle,
+        now: Time,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::ChildExpireLeases(child_handle, now, timing, signer),
+        )
+    }
+
     /// Certify a child. Will return an error in case the child is
     /// unknown, or in case resources are not held by the child.
     pub fn child_certify(
         handle: &Handle,
         child_handle: ChildHandle,
         request: IssuanceRequest,
+        timing: PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> Cmd<S> {
         eventsourcing::SentCommand::new(
             handle,
             None,
-            CmdDet::ChildCertify(child_handle, request, signer),
+            CmdDet::ChildCertify(child_handle, request, timing, signer),
         )
     }
 
@@ -256,21 +365,27 @@ impl<S: Signer> CmdDet<S> {
         handle: &Handle,
         child_handle: ChildHandle,
         request: RevocationRequest,
+        timing: PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> Cmd<S> {
         eventsourcing::SentCommand::new(
             handle,
             None,
-            CmdDet::ChildRevokeKey(child_handle, request, signer),
+            CmdDet::ChildRevokeKey(child_handle, request, timing, signer),
         )
     }
 
     pub fn child_remove(
         handle: &Handle,
         child_handle: ChildHandle,
+        timing: PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> Cmd<S> {
-        eventsourcing::SentCommand::new(handle, None, CmdDet::ChildRemove(child_handle, signer))
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::ChildRemove(child_handle, timing, signer),
+        )
     }
 
     pub fn update_id(handle: &Handle, signer: Arc<RwLock<S>>) -> Cmd<S> {
@@ -306,12 +421,13 @@ impl<S: Signer> CmdDet<S> {
         handle: &Handle,
         class_name: ResourceClassName,
         cert: RcvdCert,
+        timing: PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> Cmd<S> {
         eventsourcing::SentCommand::new(
             handle,
             None,
-            CmdDet::UpdateRcvdCert(class_name, cert, signer),
+            CmdDet::UpdateRcvdCert(class_name, cert, timing, signer),
         )
     }
 
@@ -323,8 +439,17 @@ impl<S: Signer> CmdDet<S> {
         eventsourcing::SentCommand::new(handle, None, CmdDet::KeyRollInitiate(duration, signer))
     }
 
-    pub fn key_roll_activate(handle: &Handle, staging: Duration, signer: Arc<RwLock<S>>) -> Cmd<S> {
-        eventsourcing::SentCommand::new(handle, None, CmdDet::KeyRollActivate(staging, signer))
+    pub fn key_roll_activate(
+        handle: &Handle,
+        staging: Duration,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::KeyRollActivate(staging, timing, signer),
+        )
     }
 
     pub fn key_roll_finish(
@@ -335,34 +460,99 @@ impl<S: Signer> CmdDet<S> {
         eventsourcing::SentCommand::new(handle, None, CmdDet::KeyRollFinish(rcn, res))
     }
 
-    pub fn publish(handle: &Handle, signer: Arc<RwLock<S>>) -> Cmd<S> {
-        eventsourcing::SentCommand::new(handle, None, CmdDet::Republish(signer))
+    pub fn key_roll_policy_update(handle: &Handle, policy: KeyRollPolicy) -> Cmd<S> {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::KeyRollPolicyUpdate(policy))
+    }
+
+    pub fn publish(handle: &Handle, timing: PublicationTiming, signer: Arc<RwLock<S>>) -> Cmd<S> {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::Republish(timing, signer))
     }
 
     pub fn update_repo(
         handle: &Handle,
         contact: RepositoryContact,
+        timing: PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> Cmd<S> {
-        eventsourcing::SentCommand::new(handle, None, CmdDet::RepoUpdate(contact, signer))
+        eventsourcing::SentCommand::new(handle, None, CmdDet::RepoUpdate(contact, timing, signer))
     }
 
     pub fn remove_old_repo(handle: &Handle, signer: Arc<RwLock<S>>) -> Cmd<S> {
         eventsourcing::SentCommand::new(handle, None, CmdDet::RepoRemoveOld(signer))
     }
 
+    pub fn publication_timing_update(handle: &Handle, timing: Option<PublicationTiming>) -> Cmd<S> {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::PublicationTimingUpdate(timing))
+    }
+
     //-------------------------------------------------------------------------------
     // Route Authorizations
     //-------------------------------------------------------------------------------
     pub fn route_authorizations_update(
         handle: &Handle,
         updates: RouteAuthorizationUpdates,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::RouteAuthorizationsUpdate(updates, timing, signer),
+        )
+    }
+
+    pub fn roa_policy_update(
+        handle: &Handle,
+        policy: RoaPackingPolicy,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::RoaPolicyUpdate(policy, timing, signer),
+        )
+    }
+
+    //-------------------------------------------------------------------------------
+    // ASPA
+    //-------------------------------------------------------------------------------
+    pub fn aspas_update(
+        handle: &Handle,
+        updates: AspaDefinitionUpdates,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(handle, None, CmdDet::AspasUpdate(updates, timing, signer))
+    }
+
+    pub fn aspas_update_providers(
+        handle: &Handle,
+        customer: AsNumber,
+        update: AspaProvidersUpdate,
+        timing: PublicationTiming,
+        signer: Arc<RwLock<S>>,
+    ) -> Cmd<S> {
+        eventsourcing::SentCommand::new(
+            handle,
+            None,
+            CmdDet::AspasUpdateProviders(customer, update, timing, signer),
+        )
+    }
+
+    //-------------------------------------------------------------------------------
+    // Ghostbusters
+    //-------------------------------------------------------------------------------
+    pub fn ghostbuster_update(
+        handle: &Handle,
+        vcard: Option<GhostbusterVcard>,
+        timing: PublicationTiming,
         signer: Arc<RwLock<S>>,
     ) -> Cmd<S> {
         eventsourcing::SentCommand::new(
             handle,
             None,
-            CmdDet::RouteAuthorizationsUpdate(updates, signer),
+            CmdDet::GhostbusterUpdate(vcard, timing, signer),
         )
     }
 }
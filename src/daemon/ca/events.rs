@@ -4,20 +4,22 @@ use std::ops::DerefMut;
 use std::sync::{Arc, RwLock};
 
 use rpki::crypto::KeyIdentifier;
+use rpki::x509::Time;
 
 use crate::commons::api::{
-    AddedObject, ChildHandle, Handle, IssuanceRequest, IssuedCert, ObjectName, ObjectsDelta,
-    ParentCaContact, ParentHandle, RcvdCert, RepoInfo, RepositoryContact, ResourceClassName,
-    ResourceSet, Revocation, RevocationRequest, RevokedObject, TaCertDetails, UpdatedObject,
-    WithdrawnObject,
+    AddedObject, AsNumber, AspaDefinition, AspaProvidersUpdate, ChildHandle, GhostbusterVcard,
+    Handle, IssuanceRequest, IssuedCert, KeyRollPolicy, ObjectName, ObjectsDelta, ParentCaContact,
+    ParentHandle, PublicationTiming, RcvdCert, RepoInfo, RepositoryContact, ResourceClassName,
+    ResourceLease, ResourceSet, Revocation, RevocationRequest, RevokedObject, RoaPackingPolicy,
+    TaCertDetails, UpdatedObject, WithdrawnObject,
 };
 use crate::commons::eventsourcing::StoredEvent;
 use crate::commons::remote::id::IdCert;
 use crate::commons::KrillResult;
 use crate::daemon::ca::signing::Signer;
 use crate::daemon::ca::{
-    CertifiedKey, ChildDetails, CurrentObjectSetDelta, ResourceClass, Rfc8183Id, RoaInfo,
-    RouteAuthorization,
+    AggregateRoaInfo, AspaInfo, CertifiedKey, ChildDetails, CurrentObjectSetDelta, GhostbusterInfo,
+    ResourceClass, Rfc8183Id, RoaAggregateKey, RoaInfo, RouteAuthorization,
 };
 
 //------------ Ini -----------------------------------------------------------
@@ -73,11 +75,17 @@ impl fmt::Display for IniDet {
 
 //------------ RoaUpdates --------------------------------------------------
 
-/// Describes an update to the set of ROAs under a ResourceClass.
+/// Describes an update to the set of ROAs under a ResourceClass. ROAs for
+/// a single authorization and aggregated ROAs for many authorizations are
+/// tracked separately.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RoaUpdates {
     updated: HashMap<RouteAuthorization, RoaInfo>,
     removed: HashMap<RouteAuthorization, RevokedObject>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    aggregate_updated: HashMap<RoaAggregateKey, AggregateRoaInfo>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    aggregate_removed: HashMap<RoaAggregateKey, RevokedObject>,
 }
 
 impl Default for RoaUpdates {
@@ -85,6 +93,8 @@ impl Default for RoaUpdates {
         RoaUpdates {
             updated: HashMap::new(),
             removed: HashMap::new(),
+            aggregate_updated: HashMap::new(),
+            aggregate_removed: HashMap::new(),
         }
     }
 }
@@ -94,11 +104,19 @@ impl RoaUpdates {
         updated: HashMap<RouteAuthorization, RoaInfo>,
         removed: HashMap<RouteAuthorization, RevokedObject>,
     ) -> Self {
-        RoaUpdates { updated, removed }
+        RoaUpdates {
+            updated,
+            removed,
+            aggregate_updated: HashMap::new(),
+            aggregate_removed: HashMap::new(),
+        }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.updated.is_empty() && self.removed.is_empty()
+        self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.aggregate_updated.is_empty()
+            && self.aggregate_removed.is_empty()
     }
 
     pub fn contains_changes(&self) -> bool {
@@ -113,9 +131,24 @@ impl RoaUpdates {
         self.removed.insert(auth, revoke);
     }
 
+    pub fn update_aggregate(&mut self, key: RoaAggregateKey, aggregate: AggregateRoaInfo) {
+        self.aggregate_updated.insert(key, aggregate);
+    }
+
+    pub fn remove_aggregate(&mut self, key: RoaAggregateKey, revoke: RevokedObject) {
+        self.aggregate_removed.insert(key, revoke);
+    }
+
+    /// Returns all updated ROAs, both for single and aggregated authorizations.
+    fn all_updated(&self) -> impl Iterator<Item = &RoaInfo> {
+        self.updated
+            .values()
+            .chain(self.aggregate_updated.values().map(|a| a.roa_info()))
+    }
+
     pub fn added(&self) -> Vec<AddedObject> {
         let mut res = vec![];
-        for (_auth, info) in self.updated.iter() {
+        for info in self.all_updated() {
             if info.replaces().is_none() {
                 let object = info.object().clone();
                 let name = info.name().clone();
@@ -127,7 +160,7 @@ impl RoaUpdates {
 
     pub fn updated(&self) -> Vec<UpdatedObject> {
         let mut res = vec![];
-        for (_auth, info) in self.updated.iter() {
+        for info in self.all_updated() {
             if let Some(replaced) = info.replaces() {
                 let object = info.object().clone();
                 let name = info.name().clone();
@@ -144,12 +177,17 @@ impl RoaUpdates {
             let hash = revoked.hash().clone();
             res.push(WithdrawnObject::new(name, hash));
         }
+        for (key, revoked) in self.aggregate_removed.iter() {
+            let name = ObjectName::from(key);
+            let hash = revoked.hash().clone();
+            res.push(WithdrawnObject::new(name, hash));
+        }
         res
     }
 
     pub fn revocations(&self) -> Vec<Revocation> {
         let mut res = vec![];
-        for info in self.updated.values() {
+        for info in self.all_updated() {
             if let Some(old) = info.replaces() {
                 res.push(old.revocation())
             }
@@ -159,19 +197,191 @@ impl RoaUpdates {
             res.push(revoked.revocation())
         }
 
+        for revoked in self.aggregate_removed.values() {
+            res.push(revoked.revocation())
+        }
+
         res
     }
 
+    #[allow(clippy::type_complexity)]
     pub fn unpack(
         self,
     ) -> (
         HashMap<RouteAuthorization, RoaInfo>,
         HashMap<RouteAuthorization, RevokedObject>,
+        HashMap<RoaAggregateKey, AggregateRoaInfo>,
+        HashMap<RoaAggregateKey, RevokedObject>,
+    ) {
+        (
+            self.updated,
+            self.removed,
+            self.aggregate_updated,
+            self.aggregate_removed,
+        )
+    }
+}
+
+//------------ AspaObjectsUpdates ------------------------------------------
+
+/// Describes an update to the set of ASPA objects under a ResourceClass.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AspaObjectsUpdates {
+    updated: HashMap<AsNumber, AspaInfo>,
+    removed: HashMap<AsNumber, RevokedObject>,
+}
+
+impl AspaObjectsUpdates {
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_empty() && self.removed.is_empty()
+    }
+
+    pub fn contains_changes(&self) -> bool {
+        !self.is_empty()
+    }
+
+    pub fn update(&mut self, customer: AsNumber, aspa: AspaInfo) {
+        self.updated.insert(customer, aspa);
+    }
+
+    pub fn remove(&mut self, customer: AsNumber, revoke: RevokedObject) {
+        self.removed.insert(customer, revoke);
+    }
+
+    pub fn added(&self) -> Vec<AddedObject> {
+        let mut res = vec![];
+        for info in self.updated.values() {
+            if info.replaces().is_none() {
+                let object = info.object().clone();
+                let name = info.name().clone();
+                res.push(AddedObject::new(name, object));
+            }
+        }
+        res
+    }
+
+    pub fn updated(&self) -> Vec<UpdatedObject> {
+        let mut res = vec![];
+        for info in self.updated.values() {
+            if let Some(replaced) = info.replaces() {
+                let object = info.object().clone();
+                let name = info.name().clone();
+                res.push(UpdatedObject::new(name, object, replaced.hash().clone()));
+            }
+        }
+        res
+    }
+
+    pub fn withdrawn(&self) -> Vec<WithdrawnObject> {
+        let mut res = vec![];
+        for (customer, revoked) in self.removed.iter() {
+            let name = ObjectName::from(customer);
+            let hash = revoked.hash().clone();
+            res.push(WithdrawnObject::new(name, hash));
+        }
+        res
+    }
+
+    pub fn revocations(&self) -> Vec<Revocation> {
+        let mut res = vec![];
+        for info in self.updated.values() {
+            if let Some(old) = info.replaces() {
+                res.push(old.revocation())
+            }
+        }
+
+        for revoked in self.removed.values() {
+            res.push(revoked.revocation())
+        }
+
+        res
+    }
+
+    pub fn unpack(
+        self,
+    ) -> (
+        HashMap<AsNumber, AspaInfo>,
+        HashMap<AsNumber, RevokedObject>,
     ) {
         (self.updated, self.removed)
     }
 }
 
+//------------ GhostbusterUpdates ------------------------------------------
+
+/// Describes an update to the Ghostbusters record under a ResourceClass.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GhostbusterUpdates {
+    updated: Option<GhostbusterInfo>,
+    removed: Option<RevokedObject>,
+}
+
+impl GhostbusterUpdates {
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_none() && self.removed.is_none()
+    }
+
+    pub fn contains_changes(&self) -> bool {
+        !self.is_empty()
+    }
+
+    pub fn update(&mut self, gbr: GhostbusterInfo) {
+        self.updated = Some(gbr);
+    }
+
+    pub fn remove(&mut self, revoke: RevokedObject) {
+        self.removed = Some(revoke);
+    }
+
+    pub fn added(&self) -> Vec<AddedObject> {
+        let mut res = vec![];
+        if let Some(info) = &self.updated {
+            if info.replaces().is_none() {
+                res.push(AddedObject::new(info.name().clone(), info.object().clone()));
+            }
+        }
+        res
+    }
+
+    pub fn updated(&self) -> Vec<UpdatedObject> {
+        let mut res = vec![];
+        if let Some(info) = &self.updated {
+            if let Some(replaced) = info.replaces() {
+                let object = info.object().clone();
+                let name = info.name().clone();
+                res.push(UpdatedObject::new(name, object, replaced.hash().clone()));
+            }
+        }
+        res
+    }
+
+    pub fn withdrawn(&self) -> Vec<WithdrawnObject> {
+        let mut res = vec![];
+        if let Some(revoked) = &self.removed {
+            let name = ObjectName::ghostbusters();
+            res.push(WithdrawnObject::new(name, revoked.hash().clone()));
+        }
+        res
+    }
+
+    pub fn revocations(&self) -> Vec<Revocation> {
+        let mut res = vec![];
+        if let Some(old) = self.updated.as_ref().and_then(|info| info.replaces()) {
+            res.push(old.revocation())
+        }
+
+        if let Some(revoked) = &self.removed {
+            res.push(revoked.revocation())
+        }
+
+        res
+    }
+
+    pub fn unpack(self) -> (Option<GhostbusterInfo>, Option<RevokedObject>) {
+        (self.updated, self.removed)
+    }
+}
+
 //------------ ChildCertificateUpdates -------------------------------------
 
 /// Describes an update to the set of ROAs under a ResourceClass.
@@ -223,6 +433,7 @@ pub enum EvtDet {
     ChildCertificatesUpdated(ResourceClassName, ChildCertificateUpdates),
     ChildUpdatedIdCert(ChildHandle, IdCert),
     ChildUpdatedResources(ChildHandle, ResourceSet),
+    ChildUpdatedLeases(ChildHandle, Vec<ResourceLease>),
     ChildRemoved(ChildHandle),
 
     // Being a child Events
@@ -248,11 +459,25 @@ pub enum EvtDet {
     KeyRollActivated(ResourceClassName, RevocationRequest),
     KeyRollFinished(ResourceClassName, ObjectsDelta),
     UnexpectedKeyFound(ResourceClassName, RevocationRequest),
+    KeyChangeRecorded(ResourceClassName, Time),
+    KeyRollPolicyUpdated(KeyRollPolicy),
 
     // Route Authorizations
     RouteAuthorizationAdded(RouteAuthorization),
     RouteAuthorizationRemoved(RouteAuthorization),
     RoasUpdated(ResourceClassName, RoaUpdates),
+    RoaPackingPolicyUpdated(RoaPackingPolicy),
+
+    // ASPA
+    AspaConfigAdded(AspaDefinition),
+    AspaConfigUpdated(AsNumber, AspaProvidersUpdate),
+    AspaConfigRemoved(AsNumber),
+    AspaObjectsUpdated(ResourceClassName, AspaObjectsUpdates),
+
+    // Ghostbusters
+    GhostbusterUpdated(GhostbusterVcard),
+    GhostbusterRemoved,
+    GhostbusterObjectUpdated(ResourceClassName, GhostbusterUpdates),
 
     // Publishing
     ObjectSetUpdated(
@@ -261,6 +486,7 @@ pub enum EvtDet {
     ),
     RepoUpdated(RepositoryContact),
     RepoCleaned(RepositoryContact),
+    PublicationTimingUpdated(Option<PublicationTiming>),
 }
 
 impl EvtDet {
@@ -364,6 +590,15 @@ impl EvtDet {
         )
     }
 
+    pub(super) fn child_updated_leases(
+        handle: &Handle,
+        version: u64,
+        child: ChildHandle,
+        leases: Vec<ResourceLease>,
+    ) -> Evt {
+        StoredEvent::new(handle, version, EvtDet::ChildUpdatedLeases(child, leases))
+    }
+
     pub(super) fn child_certificate_issued(
         handle: &Handle,
         version: u64,
@@ -481,6 +716,16 @@ impl fmt::Display for EvtDet {
             EvtDet::ChildUpdatedResources(child, resources) => {
                 write!(f, "updated child '{}' resources to '{}'", child, resources)
             }
+            EvtDet::ChildUpdatedLeases(child, leases) => {
+                write!(f, "updated child '{}' leases to:", child)?;
+                if leases.is_empty() {
+                    write!(f, " <none>")?;
+                }
+                for lease in leases {
+                    write!(f, " '{}'", lease)?;
+                }
+                Ok(())
+            }
             EvtDet::ChildRemoved(child) => {
                 write!(f, "removed child '{}'", child)
             }
@@ -561,6 +806,16 @@ impl fmt::Display for EvtDet {
                 "Found unexpected key in resource class '{}', will try to revoke key id: '{}'",
                 rcn, revoke.key()
             ),
+            EvtDet::KeyChangeRecorded(rcn, time) => write!(
+                f,
+                "recorded key change under resource class '{}' at {}",
+                rcn, time.to_rfc3339()
+            ),
+            EvtDet::KeyRollPolicyUpdated(policy) => write!(
+                f,
+                "updated key roll policy to: {}",
+                policy
+            ),
 
             // Route Authorizations
             EvtDet::RouteAuthorizationAdded(route) => write!(
@@ -573,6 +828,11 @@ impl fmt::Display for EvtDet {
                 "removed ROA: '{}'",
                 route
             ),
+            EvtDet::RoaPackingPolicyUpdated(policy) => write!(
+                f,
+                "updated ROA packing policy to: {}",
+                policy
+            ),
             EvtDet::RoasUpdated(rcn, roa_updates) => {
                 write!(f, "updated ROAs under resource class '{}'", rcn)?;
                 if ! roa_updates.updated.is_empty() {
@@ -587,6 +847,72 @@ impl fmt::Display for EvtDet {
                         write!(f, "{} ", auth)?;
                     }
                 }
+                if ! roa_updates.aggregate_updated.is_empty() {
+                    write!(f, " added aggregates: ")?;
+                    for key in roa_updates.aggregate_updated.keys() {
+                        write!(f, "{} ", key)?;
+                    }
+                }
+                if ! roa_updates.aggregate_removed.is_empty() {
+                    write!(f, " removed aggregates: ")?;
+                    for key in roa_updates.aggregate_removed.keys() {
+                        write!(f, "{} ", key)?;
+                    }
+                }
+                Ok(())
+            },
+
+            // ASPA
+            EvtDet::AspaConfigAdded(definition) => write!(
+                f,
+                "added ASPA: '{}'",
+                definition
+            ),
+            EvtDet::AspaConfigUpdated(customer, update) => write!(
+                f,
+                "updated ASPA providers for customer '{}': {}",
+                customer, update
+            ),
+            EvtDet::AspaConfigRemoved(customer) => write!(
+                f,
+                "removed ASPA for customer '{}'",
+                customer
+            ),
+            EvtDet::AspaObjectsUpdated(rcn, updates) => {
+                write!(f, "updated ASPA objects under resource class '{}'", rcn)?;
+                if ! updates.updated.is_empty() {
+                    write!(f, " updated: ")?;
+                    for customer in updates.updated.keys() {
+                        write!(f, "{} ", customer)?;
+                    }
+                }
+                if ! updates.removed.is_empty() {
+                    write!(f, " removed: ")?;
+                    for customer in updates.removed.keys() {
+                        write!(f, "{} ", customer)?;
+                    }
+                }
+                Ok(())
+            },
+
+            // Ghostbusters
+            EvtDet::GhostbusterUpdated(vcard) => write!(
+                f,
+                "updated Ghostbusters record to: {}",
+                vcard
+            ),
+            EvtDet::GhostbusterRemoved => write!(
+                f,
+                "removed Ghostbusters record"
+            ),
+            EvtDet::GhostbusterObjectUpdated(rcn, updates) => {
+                write!(f, "updated Ghostbusters object under resource class '{}'", rcn)?;
+                if updates.updated.is_some() {
+                    write!(f, " updated")?;
+                }
+                if updates.removed.is_some() {
+                    write!(f, " removed")?;
+                }
                 Ok(())
             },
 
@@ -626,6 +952,10 @@ impl fmt::Display for EvtDet {
                     RepositoryContact::Rfc8181(res) => write!(f, "cleaned repository at remote server: {}", res.service_uri())
                 }
             }
+            EvtDet::PublicationTimingUpdated(timing) => match timing {
+                None => write!(f, "reverted to default publication timing"),
+                Some(timing) => write!(f, "updated publication timing to: {}", timing),
+            },
         }
     }
 }
@@ -0,0 +1,95 @@
+use rpki::crypto::KeyIdentifier;
+
+use crate::commons::api::CaExportArchive;
+use crate::commons::error::Error;
+use crate::commons::util::encrypt::EncryptedData;
+use crate::commons::util::softsigner::{OpenSslKeyPair, OpenSslSigner};
+use crate::commons::KrillResult;
+use crate::constants::KRILL_VERSION;
+use crate::daemon::ca::CertAuth;
+
+//------------ CaExport ------------------------------------------------------
+
+/// The content of a CA export: a snapshot of the CA, which includes its
+/// contacts with parents, children and its repository, and all of its
+/// private keys.
+#[derive(Deserialize, Serialize)]
+pub struct CaExport {
+    ca: CertAuth<OpenSslSigner>,
+    keys: Vec<ExportedKey>,
+}
+
+impl CaExport {
+    pub fn new(ca: CertAuth<OpenSslSigner>, keys: Vec<ExportedKey>) -> Self {
+        CaExport { ca, keys }
+    }
+
+    pub fn unpack(self) -> (CertAuth<OpenSslSigner>, Vec<ExportedKey>) {
+        (self.ca, self.keys)
+    }
+
+    /// Encrypts this export with the passphrase.
+    pub fn encrypt(&self, passphrase: &str) -> KrillResult<CaExportArchive> {
+        let json = serde_json::to_vec(self).map_err(Error::JsonError)?;
+        let content = EncryptedData::encrypt(passphrase, &json).map_err(Error::custom)?;
+        Ok(CaExportArchive::new(
+            self.ca.handle().clone(),
+            KRILL_VERSION.to_string(),
+            content,
+        ))
+    }
+
+    /// Decrypts the export in the archive. Fails if the passphrase is wrong,
+    /// or if the content is not for the CA named in the archive.
+    pub fn decrypt(archive: &CaExportArchive, passphrase: &str) -> KrillResult<Self> {
+        let handle = archive.handle();
+
+        if archive.krill_version() != KRILL_VERSION {
+            warn!(
+                "Importing CA '{}' exported by Krill version {}, this is version {}",
+                handle,
+                archive.krill_version(),
+                KRILL_VERSION
+            );
+        }
+
+        let json = archive
+            .content()
+            .decrypt(passphrase)
+            .map_err(|e| Error::CaImportInvalid(handle.clone(), e.to_string()))?;
+
+        let export: CaExport = serde_json::from_slice(&json)
+            .map_err(|e| Error::CaImportInvalid(handle.clone(), e.to_string()))?;
+
+        if export.ca.handle() != handle {
+            return Err(Error::CaImportInvalid(
+                handle.clone(),
+                format!("archive contains CA '{}'", export.ca.handle()),
+            ));
+        }
+
+        Ok(export)
+    }
+}
+
+//------------ ExportedKey ---------------------------------------------------
+
+#[derive(Deserialize, Serialize)]
+pub struct ExportedKey {
+    key_id: KeyIdentifier,
+    key_pair: OpenSslKeyPair,
+}
+
+impl ExportedKey {
+    pub fn new(key_id: KeyIdentifier, key_pair: OpenSslKeyPair) -> Self {
+        ExportedKey { key_id, key_pair }
+    }
+
+    pub fn key_id(&self) -> &KeyIdentifier {
+        &self.key_id
+    }
+
+    pub fn key_pair(&self) -> &OpenSslKeyPair {
+        &self.key_pair
+    }
+}
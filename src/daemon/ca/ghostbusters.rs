@@ -0,0 +1,143 @@
+use bcder::{Captured, Mode, Oid};
+use bytes::Bytes;
+
+use rpki::cert::Cert;
+use rpki::resources::{AsResources, IpResources};
+use rpki::sigobj::{SignedObject, SignedObjectBuilder};
+use rpki::uri;
+use rpki::x509::{Serial, Time};
+
+use crate::commons::api::{CurrentObject, GhostbusterVcard, ObjectName, ReplacedObject};
+use crate::commons::KrillResult;
+use crate::constants::GHOSTBUSTERS_CERTIFICATE_VALIDITY_YEARS;
+use crate::daemon::ca::{self, CertifiedKey, SignSupport, Signer};
+
+//------------ Ghostbuster -------------------------------------------------
+
+/// A signed Ghostbusters record (RFC 6493). The rpki library does not
+/// support these objects, so the vCard is wrapped in a generic signed
+/// object here.
+#[derive(Clone, Debug)]
+pub struct Ghostbuster {
+    signed: SignedObject,
+}
+
+impl Ghostbuster {
+    pub fn cert(&self) -> &Cert {
+        self.signed.cert()
+    }
+
+    pub fn to_captured(&self) -> Captured {
+        self.signed.encode_ref().to_captured(Mode::Der)
+    }
+
+    /// Signs the vCard under the given key. The EE certificate inherits
+    /// all resources, as required by section 6 of RFC 6493.
+    pub fn make<S: Signer>(
+        vcard: &GhostbusterVcard,
+        certified_key: &CertifiedKey,
+        new_repo: Option<&uri::Rsync>,
+        signer: &S,
+    ) -> KrillResult<Self> {
+        let name = ObjectName::ghostbusters();
+
+        let incoming_cert = certified_key.incoming_cert();
+        let crl_uri = match &new_repo {
+            None => incoming_cert.crl_uri(),
+            Some(base_uri) => base_uri.join(incoming_cert.crl_name().as_bytes()),
+        };
+
+        let gbr_uri = match &new_repo {
+            None => incoming_cert.uri_for_object(name),
+            Some(base_uri) => base_uri.join(name.as_bytes()),
+        };
+
+        let aia = incoming_cert.uri();
+
+        let signing_key = certified_key.key_id();
+
+        let mut object_builder = SignedObjectBuilder::new(
+            Serial::random(signer).map_err(ca::Error::signer)?,
+            SignSupport::sign_validity_years(GHOSTBUSTERS_CERTIFICATE_VALIDITY_YEARS),
+            crl_uri,
+            aia.clone(),
+            gbr_uri,
+        );
+        object_builder.set_issuer(Some(incoming_cert.cert().subject().clone()));
+        object_builder.set_signing_time(Some(Time::now()));
+        object_builder.set_as_resources(AsResources::inherit());
+        object_builder.set_v4_resources(IpResources::inherit());
+        object_builder.set_v6_resources(IpResources::inherit());
+
+        let signed = object_builder
+            .finalize(
+                Oid(Bytes::from_static(oid::CT_GHOSTBUSTERS)),
+                Bytes::from(vcard.to_vcard()),
+                signer,
+                signing_key,
+            )
+            .map_err(ca::Error::signer)?;
+
+        Ok(Ghostbuster { signed })
+    }
+}
+
+//------------ GhostbusterInfo ---------------------------------------------
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GhostbusterInfo {
+    vcard: GhostbusterVcard,          // the contact details in this record
+    object: CurrentObject,            // actual Ghostbusters record
+    name: ObjectName,                 // Name for object in repo
+    since: Time,                      // first record in RC created
+    replaces: Option<ReplacedObject>, // for revoking when re-newing
+}
+
+impl GhostbusterInfo {
+    pub fn new_gbr(vcard: GhostbusterVcard, gbr: &Ghostbuster) -> Self {
+        GhostbusterInfo {
+            vcard,
+            object: CurrentObject::from(gbr),
+            name: ObjectName::ghostbusters(),
+            since: Time::now(),
+            replaces: None,
+        }
+    }
+
+    pub fn updated_gbr(old: &GhostbusterInfo, vcard: GhostbusterVcard, gbr: &Ghostbuster) -> Self {
+        GhostbusterInfo {
+            vcard,
+            object: CurrentObject::from(gbr),
+            name: ObjectName::ghostbusters(),
+            since: old.since,
+            replaces: Some(ReplacedObject::from(old.object())),
+        }
+    }
+
+    pub fn vcard(&self) -> &GhostbusterVcard {
+        &self.vcard
+    }
+
+    pub fn object(&self) -> &CurrentObject {
+        &self.object
+    }
+
+    pub fn name(&self) -> &ObjectName {
+        &self.name
+    }
+
+    pub fn since(&self) -> Time {
+        self.since
+    }
+
+    pub fn replaces(&self) -> Option<&ReplacedObject> {
+        self.replaces.as_ref()
+    }
+}
+
+//------------ OIDs --------------------------------------------------------
+
+mod oid {
+    // id-ct-rpkiGhostbusters: 1.2.840.113549.1.9.16.1.35
+    pub const CT_GHOSTBUSTERS: &[u8] = &[42, 134, 72, 134, 247, 13, 1, 9, 16, 1, 35];
+}
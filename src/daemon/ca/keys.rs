@@ -1,6 +1,5 @@
 use std::ops::{Deref, DerefMut};
 
-use chrono::Duration;
 use serde::{Deserialize, Serialize};
 
 use rpki::crypto::{KeyIdentifier, PublicKeyFormat};
@@ -9,12 +8,11 @@ use rpki::x509::Time;
 
 use crate::commons::api::{
     ActiveInfo, CertifiedKeyInfo, EntitlementClass, IssuanceRequest, PendingInfo, PendingKeyInfo,
-    RcvdCert, RepoInfo, RequestResourceLimit, ResourceClassKeysInfo, ResourceClassName,
-    ResourceSet, RevocationRequest, RollNewInfo, RollOldInfo, RollPendingInfo,
+    PublicationTiming, RcvdCert, RepoInfo, RequestResourceLimit, ResourceClassKeysInfo,
+    ResourceClassName, ResourceSet, RevocationRequest, RollNewInfo, RollOldInfo, RollPendingInfo,
 };
 use crate::commons::error::Error;
 use crate::commons::KrillResult;
-use crate::constants::PUBLISH_THRESHOLD_HOURS;
 use crate::daemon::ca::{CurrentObjectSet, CurrentObjectSetDelta, EvtDet, Signer};
 
 //------------ CertifiedKey --------------------------------------------------
@@ -34,10 +32,12 @@ impl CertifiedKey {
         incoming_cert: RcvdCert,
         repo_info: &RepoInfo,
         name_space: &str,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<Self> {
         let key_id = incoming_cert.cert().subject_key_identifier();
-        let current_set = CurrentObjectSet::create(&incoming_cert, repo_info, name_space, signer)?;
+        let current_set =
+            CurrentObjectSet::create(&incoming_cert, repo_info, name_space, timing, signer)?;
 
         Ok(CertifiedKey {
             key_id,
@@ -123,8 +123,8 @@ impl CertifiedKey {
         }
     }
 
-    pub fn close_to_next_update(&self) -> bool {
-        self.current_set.next_update() < Time::now() + Duration::hours(PUBLISH_THRESHOLD_HOURS)
+    pub fn close_to_next_update(&self, timing: &PublicationTiming) -> bool {
+        self.current_set.next_update() < Time::now() + timing.republish()
     }
 
     pub fn with_new_cert(mut self, cert: RcvdCert) -> Self {
@@ -490,6 +490,17 @@ impl KeyState {
         ))
     }
 
+    /// Returns the identifiers of all keys in this key state.
+    pub fn key_ids(&self) -> Vec<KeyIdentifier> {
+        match self {
+            KeyState::Pending(pending) => vec![*pending.key_id()],
+            KeyState::Active(current) => vec![*current.key_id()],
+            KeyState::RollPending(pending, current) => vec![*pending.key_id(), *current.key_id()],
+            KeyState::RollNew(new, current) => vec![*new.key_id(), *current.key_id()],
+            KeyState::RollOld(current, old) => vec![*current.key_id(), *old.key_id()],
+        }
+    }
+
     /// Returns the revoke request if there is an old key.
     pub fn revoke_request(&self) -> Option<&RevocationRequest> {
         match self {
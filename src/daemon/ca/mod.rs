@@ -22,12 +22,21 @@ pub use self::publishing::*;
 mod routes;
 pub use self::routes::*;
 
+mod aspa;
+pub use self::aspa::*;
+
+mod ghostbusters;
+pub use self::ghostbusters::*;
+
 mod commands;
 pub use self::commands::*;
 
 mod events;
 pub use self::events::*;
 
+mod export;
+pub use self::export::*;
+
 mod server;
 pub use self::server::CaServer;
 
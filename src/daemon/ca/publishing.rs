@@ -3,7 +3,6 @@
 use std::collections::HashMap;
 
 use bytes::Bytes;
-use chrono::Duration;
 
 use rpki::crl::{Crl, TbsCertList};
 use rpki::crypto::{DigestAlgorithm, KeyIdentifier};
@@ -12,12 +11,12 @@ use rpki::sigobj::SignedObjectBuilder;
 use rpki::x509::{Serial, Time, Validity};
 
 use crate::commons::api::{
-    AddedObject, CurrentObject, HexEncodedHash, IssuedCert, ObjectName, ObjectsDelta, RcvdCert,
-    RepoInfo, Revocation, Revocations, RevocationsDelta, UpdatedObject, WithdrawnObject,
+    AddedObject, CurrentObject, HexEncodedHash, IssuedCert, ObjectName, ObjectsDelta,
+    PublicationTiming, RcvdCert, RepoInfo, Revocation, Revocations, RevocationsDelta,
+    UpdatedObject, WithdrawnObject,
 };
 use crate::commons::KrillResult;
-use crate::constants::{PUBLISH_NEXT_HOURS, PUBLISH_VALID_DAYS};
-use crate::daemon::ca::{self, RoaInfo, RouteAuthorization, Signer};
+use crate::daemon::ca::{self, AspaInfo, GhostbusterInfo, RoaInfo, Signer};
 
 //------------ AddedOrUpdated ----------------------------------------------
 
@@ -167,6 +166,7 @@ impl CurrentObjectSet {
         signing_cert: &RcvdCert,
         repo_info: &RepoInfo,
         name_space: &str,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<Self> {
         let number = 1;
@@ -177,6 +177,7 @@ impl CurrentObjectSet {
             number,
             None,
             signing_cert,
+            timing,
             signer,
         )?;
 
@@ -186,6 +187,7 @@ impl CurrentObjectSet {
             name_space,
             number,
             None,
+            timing,
             signer,
         )?;
 
@@ -237,6 +239,7 @@ impl CrlBuilder {
         number: u64,
         old: Option<HexEncodedHash>,
         signing_cert: &RcvdCert,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<(CrlInfo, RevocationsDelta)> {
         let signing_key = signing_cert.cert().subject_public_key_info();
@@ -254,7 +257,7 @@ impl CrlBuilder {
         }
 
         let this_update = Time::five_minutes_ago();
-        let next_update = Time::now() + Duration::hours(PUBLISH_NEXT_HOURS);
+        let next_update = Time::now() + timing.next_update();
         let serial_number = Serial::from(number);
 
         let mut crl = TbsCertList::new(
@@ -295,7 +298,9 @@ impl ManifestBuilder {
     pub fn new<'a>(
         crl_info: &CrlInfo,
         issued: impl Iterator<Item = &'a IssuedCert>,
-        roas: impl Iterator<Item = (&'a RouteAuthorization, &'a RoaInfo)>,
+        roas: impl Iterator<Item = &'a RoaInfo>,
+        aspas: impl Iterator<Item = &'a AspaInfo>,
+        ghostbuster: impl Iterator<Item = &'a GhostbusterInfo>,
         delta: &ObjectsDelta,
     ) -> Self {
         let mut entries: HashMap<Bytes, Bytes> = HashMap::new();
@@ -316,13 +321,29 @@ impl ManifestBuilder {
         }
 
         // Add all *current* ROAs
-        for (_auth, roa_info) in roas {
+        for roa_info in roas {
             let name = roa_info.name().clone();
             let hash = Self::mft_hash(&roa_info.object().content().to_bytes());
 
             entries.insert(name.into(), hash);
         }
 
+        // Add all *current* ASPA objects
+        for aspa_info in aspas {
+            let name = aspa_info.name().clone();
+            let hash = Self::mft_hash(&aspa_info.object().content().to_bytes());
+
+            entries.insert(name.into(), hash);
+        }
+
+        // Add the *current* Ghostbusters record
+        for gbr_info in ghostbuster {
+            let name = gbr_info.name().clone();
+            let hash = Self::mft_hash(&gbr_info.object().content().to_bytes());
+
+            entries.insert(name.into(), hash);
+        }
+
         // Add all *new* objects
         for added in delta.added() {
             let name = added.name().clone();
@@ -350,6 +371,7 @@ impl ManifestBuilder {
         ManifestBuilder { entries }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn build<S: Signer>(
         self,
         signing_cert: &RcvdCert,
@@ -357,6 +379,7 @@ impl ManifestBuilder {
         name_space: &str,
         number: u64,
         old: Option<HexEncodedHash>,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<ManifestInfo> {
         let signing_key = signing_cert.cert().subject_public_key_info();
@@ -372,8 +395,8 @@ impl ManifestBuilder {
 
         let this_update = Time::five_minutes_ago();
         let now = Time::now();
-        let next_update = Time::now() + Duration::hours(PUBLISH_NEXT_HOURS);
-        let valid_until = Time::now() + Duration::days(PUBLISH_VALID_DAYS);
+        let next_update = Time::now() + timing.next_update();
+        let valid_until = Time::now() + timing.validity();
 
         let entries = self.entries.iter().map(|(k, v)| FileAndHash::new(k, v));
 
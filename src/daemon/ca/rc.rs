@@ -10,19 +10,26 @@ use rpki::x509::Time;
 use crate::commons::api::rrdp::PublishElement;
 use crate::commons::api::Base64;
 use crate::commons::api::{
-    AddedObject, CurrentObject, CurrentObjects, EntitlementClass, HexEncodedHash, IssuanceRequest,
-    IssuedCert, ObjectName, ObjectsDelta, ParentHandle, RcvdCert, ReplacedObject, RepoInfo,
+    AddedObject, CurrentObject, CurrentObjects, EntitlementClass, GhostbusterVcard, HexEncodedHash,
+    IssuanceRequest, IssuedCert, KeyRollPhase, KeyRollPolicy, KeyRollStatus, ObjectName,
+    ObjectsDelta, ParentHandle, PublicationTiming, RcvdCert, ReplacedObject, RepoInfo,
     RequestResourceLimit, ResourceClassInfo, ResourceClassName, ResourceSet, Revocation,
-    RevocationRequest, RevokedObject, UpdatedObject, WithdrawnObject,
+    RevocationRequest, RevokedObject, RoaPackingPolicy, UpdatedObject, WithdrawnObject,
 };
 use crate::commons::error::Error;
 use crate::commons::KrillResult;
-use crate::constants::ROA_CERTIFICATE_REISSUE_WEEKS;
-use crate::daemon::ca::events::{ChildCertificateUpdates, RoaUpdates};
+use crate::constants::{
+    ASPA_CERTIFICATE_REISSUE_WEEKS, GHOSTBUSTERS_CERTIFICATE_REISSUE_WEEKS,
+    ROA_CERTIFICATE_REISSUE_WEEKS,
+};
+use crate::daemon::ca::events::{
+    AspaObjectsUpdates, ChildCertificateUpdates, GhostbusterUpdates, RoaUpdates,
+};
 use crate::daemon::ca::signing::CsrInfo;
 use crate::daemon::ca::{
-    self, ta_handle, AddedOrUpdated, CertifiedKey, ChildCertificates, CrlBuilder, CurrentKey,
-    CurrentObjectSetDelta, EvtDet, KeyState, ManifestBuilder, NewKey, OldKey, PendingKey, RoaInfo,
+    self, ta_handle, AddedOrUpdated, AggregateRoaInfo, AspaDefinitions, AspaInfo, AspaObjects,
+    CertifiedKey, ChildCertificates, CrlBuilder, CurrentKey, CurrentObjectSetDelta, EvtDet,
+    Ghostbuster, GhostbusterInfo, KeyState, ManifestBuilder, NewKey, OldKey, PendingKey, RoaInfo,
     Roas, RouteAuthorization, SignSupport, Signer,
 };
 
@@ -48,6 +55,10 @@ pub struct ResourceClass {
     parent_rc_name: ResourceClassName,
 
     roas: Roas,
+    #[serde(default)]
+    aspas: AspaObjects,
+    #[serde(default)]
+    ghostbuster: Option<GhostbusterInfo>,
     certificates: ChildCertificates,
 
     last_key_change: Time,
@@ -71,6 +82,8 @@ impl ResourceClass {
             parent_handle,
             parent_rc_name,
             roas: Roas::default(),
+            aspas: AspaObjects::default(),
+            ghostbuster: None,
             certificates: ChildCertificates::default(),
             last_key_change: Time::now(),
             key_state: KeyState::create(pending_key),
@@ -84,6 +97,8 @@ impl ResourceClass {
             parent_handle: ta_handle(),
             parent_rc_name,
             roas: Roas::default(),
+            aspas: AspaObjects::default(),
+            ghostbuster: None,
             certificates: ChildCertificates::default(),
             last_key_change: Time::now(),
             key_state: KeyState::create(pending_key),
@@ -154,6 +169,14 @@ impl ResourceClass {
             current_objects.insert(roa_info.name().clone(), roa_info.object().clone());
         }
 
+        for aspa_info in self.aspas.current() {
+            current_objects.insert(aspa_info.name().clone(), aspa_info.object().clone());
+        }
+
+        if let Some(gbr_info) = &self.ghostbuster {
+            current_objects.insert(gbr_info.name().clone(), gbr_info.object().clone());
+        }
+
         for issued in self.certificates.current() {
             let cert = issued.cert();
             current_objects.insert(ObjectName::from(cert), CurrentObject::from(cert));
@@ -190,11 +213,12 @@ impl ResourceClass {
 
     /// Returns a ResourceClassInfo for this, which contains all the
     /// same data, but which does not have any behaviour.
-    pub fn as_info(&self) -> ResourceClassInfo {
+    pub fn as_info(&self, keyroll_policy: &KeyRollPolicy) -> ResourceClassInfo {
         ResourceClassInfo::new(
             self.name_space.clone(),
             self.parent_handle.clone(),
             self.key_state.as_info(),
+            self.key_roll_status(keyroll_policy),
             self.current_objects(),
         )
     }
@@ -208,6 +232,8 @@ impl ResourceClass {
         &self,
         rcvd_cert: RcvdCert,
         repo_info: &RepoInfo,
+        roa_policy: &RoaPackingPolicy,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<Vec<EvtDet>> {
         // If this is for a pending key, then we need to promote this key
@@ -218,10 +244,12 @@ impl ResourceClass {
             rcvd_cert: RcvdCert,
             repo_info: &RepoInfo,
             name_space: &str,
+            timing: &PublicationTiming,
             signer: &S,
         ) -> KrillResult<(CertifiedKey, ObjectsDelta)> {
             let mut delta = ObjectsDelta::new(rcvd_cert.ca_repository().clone());
-            let active_key = CertifiedKey::create(rcvd_cert, repo_info, name_space, signer)?;
+            let active_key =
+                CertifiedKey::create(rcvd_cert, repo_info, name_space, timing, signer)?;
 
             match active_key.current_set().manifest_info().added_or_updated() {
                 AddedOrUpdated::Added(added) => delta.add(added),
@@ -244,33 +272,35 @@ impl ResourceClass {
                         rcvd_cert,
                         repo_info,
                         self.name_space(),
+                        timing,
                         signer,
                     )?;
-                    Ok(vec![EvtDet::KeyPendingToActive(
-                        self.name.clone(),
-                        active_key,
-                        delta,
-                    )])
+                    Ok(vec![
+                        EvtDet::KeyPendingToActive(self.name.clone(), active_key, delta),
+                        EvtDet::KeyChangeRecorded(self.name.clone(), Time::now()),
+                    ])
                 }
             }
-            KeyState::Active(current) => {
-                self.update_rcvd_cert_current(current, rcvd_cert, repo_info, signer)
-            }
+            KeyState::Active(current) => self.update_rcvd_cert_current(
+                current, rcvd_cert, repo_info, roa_policy, timing, signer,
+            ),
             KeyState::RollPending(pending, current) => {
                 if rcvd_cert_ki == pending.key_id() {
                     let (active_key, delta) = create_active_key_and_delta(
                         rcvd_cert,
                         repo_info,
                         self.name_space(),
+                        timing,
                         signer,
                     )?;
-                    Ok(vec![EvtDet::KeyPendingToNew(
-                        self.name.clone(),
-                        active_key,
-                        delta,
-                    )])
+                    Ok(vec![
+                        EvtDet::KeyPendingToNew(self.name.clone(), active_key, delta),
+                        EvtDet::KeyChangeRecorded(self.name.clone(), Time::now()),
+                    ])
                 } else {
-                    self.update_rcvd_cert_current(current, rcvd_cert, repo_info, signer)
+                    self.update_rcvd_cert_current(
+                        current, rcvd_cert, repo_info, roa_policy, timing, signer,
+                    )
                 }
             }
             KeyState::RollNew(new, current) => {
@@ -281,12 +311,16 @@ impl ResourceClass {
                         rcvd_cert,
                     )])
                 } else {
-                    self.update_rcvd_cert_current(current, rcvd_cert, repo_info, signer)
+                    self.update_rcvd_cert_current(
+                        current, rcvd_cert, repo_info, roa_policy, timing, signer,
+                    )
                 }
             }
             KeyState::RollOld(current, _old) => {
                 // We will never request a new certificate for an old key
-                self.update_rcvd_cert_current(current, rcvd_cert, repo_info, signer)
+                self.update_rcvd_cert_current(
+                    current, rcvd_cert, repo_info, roa_policy, timing, signer,
+                )
             }
         }
     }
@@ -296,6 +330,8 @@ impl ResourceClass {
         current: &CurrentKey,
         rcvd_cert: RcvdCert,
         repo_info: &RepoInfo,
+        roa_policy: &RoaPackingPolicy,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<Vec<EvtDet>> {
         let rcvd_cert_ki = rcvd_cert.cert().subject_key_identifier();
@@ -316,10 +352,15 @@ impl ResourceClass {
             let publish_mode = PublishMode::UpdatedResources(rcvd_resources);
             let authorizations: Vec<RouteAuthorization> =
                 self.roas.authorizations().cloned().collect();
+            let aspas = self.aspas.definitions();
+            let ghostbuster = self.ghostbuster.as_ref().map(|info| info.vcard());
+            let definitions =
+                ObjectDefinitions::new(authorizations.as_slice(), roa_policy, &aspas, ghostbuster);
             res.append(&mut self.republish(
-                authorizations.as_slice(),
+                &definitions,
                 repo_info,
                 &publish_mode,
+                timing,
                 signer,
             )?)
         }
@@ -371,6 +412,12 @@ impl ResourceClass {
     pub fn revoke_request(&self) -> Option<&RevocationRequest> {
         self.key_state.revoke_request()
     }
+
+    /// Returns whether there are any requests to the parent that have not
+    /// yet been answered.
+    pub fn has_pending_requests(&self) -> bool {
+        !self.cert_requests().is_empty() || self.revoke_request().is_some()
+    }
 }
 
 /// # Publishing
@@ -392,6 +439,7 @@ impl ResourceClass {
         objects_delta: ObjectsDelta,
         new_revocations: Vec<Revocation>,
         mode: &PublishMode,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<EvtDet> {
         let mut key_pub_map = HashMap::new();
@@ -419,6 +467,7 @@ impl ResourceClass {
                 repo_info,
                 objects_delta,
                 publish_key_revocations,
+                timing,
                 signer,
             )
             .map_err(Error::signer)?;
@@ -430,7 +479,14 @@ impl ResourceClass {
             let delta = ObjectsDelta::new(repo_info.ca_repository(ns));
 
             let other_delta = self
-                .make_current_set_delta(other_key, repo_info, delta, other_key_revocations, signer)
+                .make_current_set_delta(
+                    other_key,
+                    repo_info,
+                    delta,
+                    other_key_revocations,
+                    timing,
+                    signer,
+                )
                 .map_err(ca::Error::signer)?;
 
             key_pub_map.insert(other_key.key_id().clone(), other_delta);
@@ -439,44 +495,115 @@ impl ResourceClass {
         Ok(EvtDet::ObjectSetUpdated(self.name.clone(), key_pub_map))
     }
 
-    fn needs_publication(&self, mode: &PublishMode) -> bool {
+    fn needs_publication(&self, mode: &PublishMode, timing: &PublicationTiming) -> bool {
         match mode {
-            PublishMode::Normal => self.get_current_key().unwrap().close_to_next_update(),
+            PublishMode::Normal => self.get_current_key().unwrap().close_to_next_update(timing),
             _ => true,
         }
     }
 
-    /// Republish all keys in this class (that want it). Also update
-    /// ROAs as needed.
-    pub fn republish<S: Signer>(
+    /// Updates the ROAs, ASPA objects and the Ghostbusters record in this
+    /// class for the definitions. Changed objects
+    /// are added to the delta and revocations, and the events describing the
+    /// changes are returned.
+    fn update_objects<S: Signer>(
         &self,
-        authorizations: &[RouteAuthorization],
-        repo_info: &RepoInfo,
+        definitions: &ObjectDefinitions,
         mode: &PublishMode,
         signer: &S,
+        delta: &mut ObjectsDelta,
+        revocations: &mut Vec<Revocation>,
     ) -> KrillResult<Vec<EvtDet>> {
         let mut res = vec![];
 
+        let roa_updates = self.update_roas(
+            definitions.authorizations,
+            definitions.roa_policy,
+            mode,
+            signer,
+        )?;
+        if roa_updates.contains_changes() {
+            delta.extend(
+                roa_updates.added(),
+                roa_updates.updated(),
+                roa_updates.withdrawn(),
+            );
+            revocations.append(&mut roa_updates.revocations());
+            res.push(EvtDet::RoasUpdated(self.name.clone(), roa_updates));
+        }
+
+        let aspa_updates = self.update_aspas(definitions.aspas, mode, signer)?;
+        if aspa_updates.contains_changes() {
+            delta.extend(
+                aspa_updates.added(),
+                aspa_updates.updated(),
+                aspa_updates.withdrawn(),
+            );
+            revocations.append(&mut aspa_updates.revocations());
+            res.push(EvtDet::AspaObjectsUpdated(self.name.clone(), aspa_updates));
+        }
+
+        let gbr_updates = self.update_ghostbuster(definitions.ghostbuster, mode, signer)?;
+        if gbr_updates.contains_changes() {
+            delta.extend(
+                gbr_updates.added(),
+                gbr_updates.updated(),
+                gbr_updates.withdrawn(),
+            );
+            revocations.append(&mut gbr_updates.revocations());
+            res.push(EvtDet::GhostbusterObjectUpdated(
+                self.name.clone(),
+                gbr_updates,
+            ));
+        }
+
+        Ok(res)
+    }
+
+    /// Updates the objects in this class after the definitions changed, and
+    /// publishes them if anything changed. Unlike `republish` this leaves
+    /// child certificates alone.
+    pub fn publish_definitions<S: Signer>(
+        &self,
+        definitions: &ObjectDefinitions,
+        repo_info: &RepoInfo,
+        timing: &PublicationTiming,
+        signer: &S,
+    ) -> KrillResult<Vec<EvtDet>> {
+        let mode = PublishMode::Normal;
+
         let ns = self.name_space();
         let mut delta = ObjectsDelta::new(repo_info.ca_repository(ns));
         let mut revocations = vec![];
 
-        let roa_updates = self.update_roas(authorizations, mode, signer)?;
-        if roa_updates.contains_changes() {
-            for added in roa_updates.added().into_iter() {
-                delta.add(added);
-            }
-            for update in roa_updates.updated().into_iter() {
-                delta.update(update);
-            }
-            for withdraw in roa_updates.withdrawn().into_iter() {
-                delta.withdraw(withdraw);
-            }
-            revocations.append(&mut roa_updates.revocations());
+        let mut res =
+            self.update_objects(definitions, &mode, signer, &mut delta, &mut revocations)?;
 
-            res.push(EvtDet::RoasUpdated(self.name.clone(), roa_updates));
+        if !delta.is_empty() || !revocations.is_empty() {
+            res.push(self.publish_objects(repo_info, delta, revocations, &mode, timing, signer)?);
         }
 
+        Ok(res)
+    }
+
+    /// Republish all keys in this class (that want it). Also update
+    /// ROAs, ASPA objects, the Ghostbusters record and child certificates
+    /// as needed.
+    pub fn republish<S: Signer>(
+        &self,
+        definitions: &ObjectDefinitions,
+        repo_info: &RepoInfo,
+        mode: &PublishMode,
+        timing: &PublicationTiming,
+        signer: &S,
+    ) -> KrillResult<Vec<EvtDet>> {
+        let ns = self.name_space();
+        let mut delta = ObjectsDelta::new(repo_info.ca_repository(ns));
+        let mut revocations = vec![];
+
+        let mut res =
+            self.update_objects(definitions, mode, signer, &mut delta, &mut revocations)?;
+
         let child_cert_updates = self.update_child_certificates(mode, signer)?;
         if !child_cert_updates.is_empty() {
             for issued in child_cert_updates.issued() {
@@ -498,8 +625,8 @@ impl ResourceClass {
             ));
         }
 
-        if !delta.is_empty() || !revocations.is_empty() || self.needs_publication(mode) {
-            res.push(self.publish_objects(&repo_info, delta, revocations, mode, signer)?);
+        if !delta.is_empty() || !revocations.is_empty() || self.needs_publication(mode, timing) {
+            res.push(self.publish_objects(&repo_info, delta, revocations, mode, timing, signer)?);
         }
 
         Ok(res)
@@ -512,6 +639,7 @@ impl ResourceClass {
         issued_certs: &[&IssuedCert],
         removed_certs: &[&Cert],
         repo_info: &RepoInfo,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<HashMap<KeyIdentifier, CurrentObjectSetDelta>> {
         let issuing_key = self.get_current_key()?;
@@ -544,7 +672,14 @@ impl ResourceClass {
         }
 
         let set_delta = self
-            .make_current_set_delta(issuing_key, repo_info, objects_delta, revocations, signer)
+            .make_current_set_delta(
+                issuing_key,
+                repo_info,
+                objects_delta,
+                revocations,
+                timing,
+                signer,
+            )
             .map_err(Error::signer)?;
 
         let mut res = HashMap::new();
@@ -558,6 +693,7 @@ impl ResourceClass {
         repo_info: &RepoInfo,
         mut objects_delta: ObjectsDelta,
         mut new_revocations: Vec<Revocation>,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<CurrentObjectSetDelta> {
         let signing_cert = signing_key.incoming_cert();
@@ -579,6 +715,7 @@ impl ResourceClass {
             number,
             Some(current_crl_hash),
             signing_cert,
+            timing,
             signer,
         )?;
 
@@ -592,19 +729,26 @@ impl ResourceClass {
         // List all current files, i.e.
         //  - the new CRL
         //  - current ROAs
+        //  - current ASPA objects
+        //  - current Ghostbusters record
         //  - current Certs
         //  - applying the delta - which may update the current ROAs and Certs on the MFT
         let issued = self.certificates.current();
-        let roas = self.roas.iter();
-
-        let manifest_info = ManifestBuilder::new(&crl_info, issued, roas, &objects_delta).build(
-            signing_cert,
-            repo_info,
-            self.name_space(),
-            number,
-            Some(current_mft_hash),
-            signer,
-        )?;
+        let roas = self.roas.current();
+        let aspas = self.aspas.current();
+        let ghostbuster = self.ghostbuster.iter();
+
+        let manifest_info =
+            ManifestBuilder::new(&crl_info, issued, roas, aspas, ghostbuster, &objects_delta)
+                .build(
+                    signing_cert,
+                    repo_info,
+                    self.name_space(),
+                    number,
+                    Some(current_mft_hash),
+                    timing,
+                    signer,
+                )?;
 
         match manifest_info.added_or_updated() {
             AddedOrUpdated::Added(added) => objects_delta.add(added),
@@ -631,6 +775,20 @@ impl ResourceClass {
             let uri = base_repo.resolve(ns, object_name.as_str());
             res.push(PublishElement::new(base64, uri));
         }
+        // ASPA objects
+        for info in self.aspas.current() {
+            let base64 = info.object().content().clone();
+            let object_name = info.name().clone();
+            let uri = base_repo.resolve(ns, object_name.as_str());
+            res.push(PublishElement::new(base64, uri));
+        }
+        // Ghostbusters record
+        if let Some(info) = &self.ghostbuster {
+            let base64 = info.object().content().clone();
+            let object_name = info.name().clone();
+            let uri = base_repo.resolve(ns, object_name.as_str());
+            res.push(PublishElement::new(base64, uri));
+        }
         // Certs
         for cert in self.certificates.current() {
             let base64 = Base64::from_content(cert.to_captured().as_slice());
@@ -682,6 +840,11 @@ impl ResourceClass {
     pub fn revoke<S: Signer>(&self, signer: &S) -> KrillResult<Vec<RevocationRequest>> {
         self.key_state.revoke(self.parent_rc_name.clone(), signer)
     }
+
+    /// Returns the identifiers of all keys in this resource class.
+    pub fn key_ids(&self) -> Vec<KeyIdentifier> {
+        self.key_state.key_ids()
+    }
 }
 
 /// # Key Life Cycle and Receiving Certificates
@@ -757,6 +920,38 @@ impl ResourceClass {
         }
     }
 
+    /// Records the time of the last change in keys, i.e. when a key roll
+    /// was started, or a key was certified or activated.
+    pub fn key_change_recorded(&mut self, time: Time) {
+        self.last_key_change = time;
+    }
+
+    /// Returns the progress of key rolls in this resource class. The next
+    /// step is only set if the policy is enabled.
+    pub fn key_roll_status(&self, policy: &KeyRollPolicy) -> KeyRollStatus {
+        let since = self.last_key_change;
+        let next_step = |after: Duration| {
+            if policy.enabled() {
+                Some(since + after)
+            } else {
+                None
+            }
+        };
+
+        match &self.key_state {
+            KeyState::Pending(_) | KeyState::RollPending(_, _) => {
+                KeyRollStatus::new(KeyRollPhase::AwaitingCertificate, since, None)
+            }
+            KeyState::Active(_) => {
+                KeyRollStatus::new(KeyRollPhase::Idle, since, next_step(policy.max_key_age()))
+            }
+            KeyState::RollNew(_, _) => {
+                KeyRollStatus::new(KeyRollPhase::Staging, since, next_step(policy.staging()))
+            }
+            KeyState::RollOld(_, _) => KeyRollStatus::new(KeyRollPhase::RevokingOld, since, None),
+        }
+    }
+
     /// Removes the old key, we return the to the state where there is one active key.
     pub fn old_key_removed(&mut self) {
         match &self.key_state {
@@ -778,20 +973,28 @@ impl ResourceClass {
             return Ok(vec![]);
         }
 
-        self.key_state.keyroll_initiate(
+        let mut res = self.key_state.keyroll_initiate(
             self.name.clone(),
             self.parent_rc_name.clone(),
             base_repo,
             &self.name_space,
             signer,
-        )
+        )?;
+
+        if !res.is_empty() {
+            res.push(EvtDet::KeyChangeRecorded(self.name.clone(), Time::now()));
+        }
+
+        Ok(res)
     }
 
     /// Activate a new key, if it's been longer than the staging period.
     pub fn keyroll_activate<S: Signer>(
         &self,
         repo_info: &RepoInfo,
+        roa_policy: &RoaPackingPolicy,
         staging: Duration,
+        timing: &PublicationTiming,
         signer: &S,
     ) -> KrillResult<Vec<EvtDet>> {
         if !self.key_state.has_new_key() || self.last_key_change + staging > Time::now() {
@@ -801,17 +1004,24 @@ impl ResourceClass {
         let mut res = vec![];
 
         let authorizations: Vec<RouteAuthorization> = self.roas.authorizations().cloned().collect();
+        let aspas = self.aspas.definitions();
+        let ghostbuster = self.ghostbuster.as_ref().map(|info| info.vcard());
 
         res.push(self.key_state.keyroll_activate(
             self.name.clone(),
             self.parent_rc_name.clone(),
             signer,
         )?);
+        res.push(EvtDet::KeyChangeRecorded(self.name.clone(), Time::now()));
+
+        let definitions =
+            ObjectDefinitions::new(authorizations.as_slice(), roa_policy, &aspas, ghostbuster);
 
         res.append(&mut self.republish(
-            authorizations.as_slice(),
+            &definitions,
             repo_info,
             &PublishMode::KeyRollActivation,
+            timing,
             signer,
         )?);
 
@@ -848,6 +1058,8 @@ impl ResourceClass {
     /// returned if a ResourceRequestLimit was used that includes resources
     /// that are not in this intersection.
     ///
+    /// The certificate will not be valid beyond the not after time, if given.
+    ///
     /// Note that this certificate still needs to be added to this RC by
     /// calling the update_certs function.
     pub fn issue_cert<S: Signer>(
@@ -855,6 +1067,7 @@ impl ResourceClass {
         csr: CsrInfo,
         child_resources: &ResourceSet,
         limit: RequestResourceLimit,
+        not_after: Option<Time>,
         signer: &S,
     ) -> KrillResult<IssuedCert> {
         let signing_key = self.get_current_key()?;
@@ -865,16 +1078,49 @@ impl ResourceClass {
             .get(&csr.key_id())
             .map(ReplacedObject::from);
 
-        let issued =
-            SignSupport::make_issued_cert(csr, &resources, limit, replaces, signing_key, signer)?;
+        let issued = SignSupport::make_issued_cert(
+            csr,
+            &resources,
+            limit,
+            not_after,
+            replaces,
+            signing_key,
+            signer,
+        )?;
 
         Ok(issued)
     }
 
+    /// Re-issues a previously issued child certificate under the current key,
+    /// using the updated resources and not after time. This is used when the
+    /// entitlements of a child change in a way that affects existing
+    /// certificates.
+    ///
+    /// Note that this certificate still needs to be added to this RC by
+    /// calling the update_certs function.
+    pub fn re_issue_cert<S: Signer>(
+        &self,
+        previous: &IssuedCert,
+        resources: ResourceSet,
+        not_after: Option<Time>,
+        signer: &S,
+    ) -> KrillResult<IssuedCert> {
+        let signing_key = self.get_current_key()?;
+        self.re_issue(
+            previous,
+            Some(resources),
+            not_after,
+            signing_key,
+            None,
+            signer,
+        )
+    }
+
     fn re_issue<S: Signer>(
         &self,
         previous: &IssuedCert,
         updated_resources: Option<ResourceSet>,
+        not_after: Option<Time>,
         signing_key: &CertifiedKey,
         csr_info_opt: Option<CsrInfo>,
         signer: &S,
@@ -888,6 +1134,7 @@ impl ResourceClass {
             csr,
             &resource_set,
             limit,
+            not_after,
             Some(replaced),
             signing_key,
             signer,
@@ -913,7 +1160,7 @@ impl ResourceClass {
                 // re-issue: things about to expire
                 // revoke: nothing
                 for issued in self.certificates.expiring() {
-                    let re_issued = self.re_issue(issued, None, signing_key, None, signer)?;
+                    let re_issued = self.re_issue(issued, None, None, signing_key, None, signer)?;
                     updates.issue(re_issued);
                 }
             }
@@ -930,6 +1177,7 @@ impl ResourceClass {
                         let re_issued = self.re_issue(
                             issued,
                             Some(remaining_resources),
+                            None,
                             signing_key,
                             None,
                             signer,
@@ -940,7 +1188,7 @@ impl ResourceClass {
             }
             PublishMode::KeyRollActivation => {
                 for issued in self.certificates.iter() {
-                    let re_issued = self.re_issue(issued, None, signing_key, None, signer)?;
+                    let re_issued = self.re_issue(issued, None, None, signing_key, None, signer)?;
                     updates.issue(re_issued);
                 }
             }
@@ -953,8 +1201,14 @@ impl ResourceClass {
                         issued.subject_public_key_info().clone(),
                     );
 
-                    let re_issued =
-                        self.re_issue(issued, None, signing_key, Some(csr_info_update), signer)?;
+                    let re_issued = self.re_issue(
+                        issued,
+                        None,
+                        None,
+                        signing_key,
+                        Some(csr_info_update),
+                        signer,
+                    )?;
                     updates.issue(re_issued);
                 }
             }
@@ -983,10 +1237,14 @@ impl ResourceClass {
 ///
 impl ResourceClass {
     /// Updates the ROAs in accordance with the current authorizations, and
-    /// the target resources and key determined by the PublishMode.
+    /// the target resources and key determined by the PublishMode. Depending
+    /// on the packing policy ROAs are made for each authorization, or for all
+    /// authorizations for the same ASN (up to the maximum prefixes per ROA).
+    /// Any ROAs made under another policy are withdrawn.
     pub fn update_roas<S: Signer>(
         &self,
         auths: &[RouteAuthorization],
+        policy: &RoaPackingPolicy,
         mode: &PublishMode,
         signer: &S,
     ) -> KrillResult<RoaUpdates> {
@@ -1008,37 +1266,180 @@ impl ResourceClass {
             _ => None,
         };
 
-        // Remove any ROAs no longer in auths, or no longer in resources.
-        for (current_auth, roa_info) in self.roas.iter() {
-            if !auths.contains(current_auth) || !resources.contains(&current_auth.prefix().into()) {
+        // Re-issue if the ROA is getting close to its expiration time, if we are
+        // activating the new key, or if we are moving to a new repository.
+        let needs_reissue = |roa: &RoaInfo| {
+            roa.object().expires() < Time::now() + Duration::weeks(ROA_CERTIFICATE_REISSUE_WEEKS)
+                || mode == &PublishMode::KeyRollActivation
+                || new_repo.is_some()
+        };
+
+        // Only auths for prefixes held in this resource class are relevant.
+        let auths: Vec<RouteAuthorization> = auths
+            .iter()
+            .filter(|auth| resources.contains(&auth.prefix().into()))
+            .cloned()
+            .collect();
+
+        if !policy.aggregate() {
+            // Remove all aggregated ROAs, in case the policy was changed.
+            for (current_key, aggregate) in self.roas.iter_aggregate() {
+                let revoke = RevokedObject::from(aggregate.roa_info().object());
+                updates.remove_aggregate(*current_key, revoke);
+            }
+
+            // Remove any ROAs no longer in auths, or no longer in resources.
+            for (current_auth, roa_info) in self.roas.iter() {
+                if !auths.contains(current_auth) {
+                    updates.remove(*current_auth, RevokedObject::from(roa_info.object()));
+                }
+            }
+
+            for auth in auths.iter() {
+                let name = ObjectName::from(auth);
+                match self.roas.get(auth) {
+                    None => {
+                        // NO ROA yet, so create one.
+                        let roa = Roas::make_roa(&[*auth], &name, key, new_repo.as_ref(), signer)?;
+                        updates.update(*auth, RoaInfo::new_roa(&roa, name));
+                    }
+                    Some(roa) => {
+                        if needs_reissue(roa) {
+                            let new_roa =
+                                Roas::make_roa(&[*auth], &name, key, new_repo.as_ref(), signer)?;
+                            updates.update(*auth, RoaInfo::updated_roa(roa, &new_roa, name));
+                        }
+                    }
+                }
+            }
+        } else {
+            // Remove all single authorization ROAs, in case the policy was changed.
+            for (current_auth, roa_info) in self.roas.iter() {
                 updates.remove(*current_auth, RevokedObject::from(roa_info.object()));
             }
+
+            let groups = Roas::aggregate_authorizations(&auths, policy);
+
+            // Remove any aggregated ROAs for groups which no longer exist.
+            for (current_key, aggregate) in self.roas.iter_aggregate() {
+                if !groups.contains_key(current_key) {
+                    let revoke = RevokedObject::from(aggregate.roa_info().object());
+                    updates.remove_aggregate(*current_key, revoke);
+                }
+            }
+
+            for (group_key, group_auths) in groups.into_iter() {
+                let name = ObjectName::from(&group_key);
+                match self.roas.get_aggregate(&group_key) {
+                    None => {
+                        let roa =
+                            Roas::make_roa(&group_auths, &name, key, new_repo.as_ref(), signer)?;
+                        let info = RoaInfo::new_roa(&roa, name);
+                        updates
+                            .update_aggregate(group_key, AggregateRoaInfo::new(group_auths, info));
+                    }
+                    Some(aggregate) => {
+                        let changed = *aggregate.authorizations() != group_auths;
+                        let roa = aggregate.roa_info();
+                        if changed || needs_reissue(roa) {
+                            let new_roa = Roas::make_roa(
+                                &group_auths,
+                                &name,
+                                key,
+                                new_repo.as_ref(),
+                                signer,
+                            )?;
+                            let info = RoaInfo::updated_roa(roa, &new_roa, name);
+                            updates.update_aggregate(
+                                group_key,
+                                AggregateRoaInfo::new(group_auths, info),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Marks the ROAs as updated from a RoaUpdated event.
+    pub fn roas_updated(&mut self, updates: RoaUpdates) {
+        self.roas.updated(updates);
+    }
+}
+
+/// # ASPA objects
+///
+impl ResourceClass {
+    /// Updates the ASPA objects in accordance with the current definitions,
+    /// and the target resources and key determined by the PublishMode. Only
+    /// definitions for customer ASNs held in this resource class are signed
+    /// here.
+    pub fn update_aspas<S: Signer>(
+        &self,
+        definitions: &AspaDefinitions,
+        mode: &PublishMode,
+        signer: &S,
+    ) -> KrillResult<AspaObjectsUpdates> {
+        let mut updates = AspaObjectsUpdates::default();
+
+        let key = match mode {
+            PublishMode::KeyRollActivation => self.get_new_key()?,
+            _ => self.get_current_key()?,
+        };
+
+        let resources = match mode {
+            PublishMode::Normal | PublishMode::NewRepo(_) => key.incoming_cert().resources(),
+            PublishMode::UpdatedResources(resources) => resources,
+            PublishMode::KeyRollActivation => self.get_current_key()?.incoming_cert().resources(),
+        };
+
+        let new_repo = match &mode {
+            PublishMode::NewRepo(info) => Some(info.ca_repository(self.name_space())),
+            _ => None,
+        };
+
+        // Remove any ASPA objects no longer defined, or for customers no longer held.
+        for (customer, aspa_info) in self.aspas.iter() {
+            if !definitions.has(*customer) || !resources.contains(&(*customer).into()) {
+                updates.remove(*customer, RevokedObject::from(aspa_info.object()));
+            }
         }
 
-        for auth in auths {
-            // if the auth is not in this resource class, just skip it.
-            if !resources.contains(&auth.prefix().into()) {
+        for definition in definitions.iter() {
+            let customer = definition.customer();
+
+            // if the customer ASN is not in this resource class, just skip it.
+            if !resources.contains(&customer.into()) {
                 continue;
             }
 
-            match self.roas.get(auth) {
+            match self.aspas.get(customer) {
                 None => {
-                    // NO ROA yet, so create one.
-                    let roa = Roas::make_roa(auth, key, new_repo.as_ref(), signer)?;
-                    let name = ObjectName::from(auth);
-                    updates.update(*auth, RoaInfo::new_roa(&roa, name));
+                    let aspa = AspaObjects::make_aspa(definition, key, new_repo.as_ref(), signer)?;
+                    let name = ObjectName::from(definition);
+                    updates.update(
+                        customer,
+                        AspaInfo::new_aspa(definition.clone(), &aspa, name),
+                    );
                 }
-                Some(roa) => {
-                    // Re-issue if the ROA is getting close to its expiration time, or if we are
-                    //  activating the new key.
-                    let expiring = roa.object().expires()
-                        < Time::now() + Duration::weeks(ROA_CERTIFICATE_REISSUE_WEEKS);
+                Some(existing) => {
+                    // Re-issue if the definition changed, if the object is getting close to
+                    // its expiration time, or if we are activating the new key.
+                    let changed = existing.definition() != definition;
+                    let expiring = existing.object().expires()
+                        < Time::now() + Duration::weeks(ASPA_CERTIFICATE_REISSUE_WEEKS);
                     let activating = mode == &PublishMode::KeyRollActivation;
 
-                    if expiring || activating || new_repo.is_some() {
-                        let new_roa = Roas::make_roa(auth, key, new_repo.as_ref(), signer)?;
-                        let name = ObjectName::from(auth);
-                        updates.update(*auth, RoaInfo::updated_roa(roa, &new_roa, name));
+                    if changed || expiring || activating || new_repo.is_some() {
+                        let aspa =
+                            AspaObjects::make_aspa(definition, key, new_repo.as_ref(), signer)?;
+                        let name = ObjectName::from(definition);
+                        updates.update(
+                            customer,
+                            AspaInfo::updated_aspa(existing, definition.clone(), &aspa, name),
+                        );
                     }
                 }
             }
@@ -1047,9 +1448,99 @@ impl ResourceClass {
         Ok(updates)
     }
 
-    /// Marks the ROAs as updated from a RoaUpdated event.
-    pub fn roas_updated(&mut self, updates: RoaUpdates) {
-        self.roas.updated(updates);
+    /// Marks the ASPA objects as updated from an AspaObjectsUpdated event.
+    pub fn aspas_updated(&mut self, updates: AspaObjectsUpdates) {
+        self.aspas.updated(updates);
+    }
+}
+
+/// # Ghostbusters record
+///
+impl ResourceClass {
+    /// Updates the Ghostbusters record in accordance with the current vCard,
+    /// and the key determined by the PublishMode. The record inherits all
+    /// resources, so it does not depend on the resources held.
+    pub fn update_ghostbuster<S: Signer>(
+        &self,
+        vcard: Option<&GhostbusterVcard>,
+        mode: &PublishMode,
+        signer: &S,
+    ) -> KrillResult<GhostbusterUpdates> {
+        let mut updates = GhostbusterUpdates::default();
+
+        let key = match mode {
+            PublishMode::KeyRollActivation => self.get_new_key()?,
+            _ => self.get_current_key()?,
+        };
+
+        let new_repo = match &mode {
+            PublishMode::NewRepo(info) => Some(info.ca_repository(self.name_space())),
+            _ => None,
+        };
+
+        match (vcard, &self.ghostbuster) {
+            (None, None) => {}
+            (None, Some(existing)) => {
+                updates.remove(RevokedObject::from(existing.object()));
+            }
+            (Some(vcard), None) => {
+                let gbr = Ghostbuster::make(vcard, key, new_repo.as_ref(), signer)?;
+                updates.update(GhostbusterInfo::new_gbr(vcard.clone(), &gbr));
+            }
+            (Some(vcard), Some(existing)) => {
+                // Re-issue if the vCard changed, if the object is getting close to
+                // its expiration time, or if we are activating the new key.
+                let changed = existing.vcard() != vcard;
+                let expiring = existing.object().expires()
+                    < Time::now() + Duration::weeks(GHOSTBUSTERS_CERTIFICATE_REISSUE_WEEKS);
+                let activating = mode == &PublishMode::KeyRollActivation;
+
+                if changed || expiring || activating || new_repo.is_some() {
+                    let gbr = Ghostbuster::make(vcard, key, new_repo.as_ref(), signer)?;
+                    updates.update(GhostbusterInfo::updated_gbr(existing, vcard.clone(), &gbr));
+                }
+            }
+        }
+
+        Ok(updates)
+    }
+
+    /// Marks the Ghostbusters record as updated from a
+    /// GhostbusterObjectUpdated event.
+    pub fn ghostbuster_updated(&mut self, updates: GhostbusterUpdates) {
+        let (updated, removed) = updates.unpack();
+        if let Some(info) = updated {
+            self.ghostbuster = Some(info);
+        } else if removed.is_some() {
+            self.ghostbuster = None;
+        }
+    }
+}
+
+//------------ ObjectDefinitions -------------------------------------------
+
+/// The definitions of the ROAs, ASPA objects and Ghostbusters record of a CA,
+/// which are used to update the objects in each of its resource classes.
+pub struct ObjectDefinitions<'a> {
+    authorizations: &'a [RouteAuthorization],
+    roa_policy: &'a RoaPackingPolicy,
+    aspas: &'a AspaDefinitions,
+    ghostbuster: Option<&'a GhostbusterVcard>,
+}
+
+impl<'a> ObjectDefinitions<'a> {
+    pub fn new(
+        authorizations: &'a [RouteAuthorization],
+        roa_policy: &'a RoaPackingPolicy,
+        aspas: &'a AspaDefinitions,
+        ghostbuster: Option<&'a GhostbusterVcard>,
+    ) -> Self {
+        ObjectDefinitions {
+            authorizations,
+            roa_policy,
+            aspas,
+            ghostbuster,
+        }
     }
 }
 
@@ -11,7 +11,8 @@ use rpki::uri;
 use rpki::x509::{Serial, Time};
 
 use crate::commons::api::{
-    CurrentObject, ObjectName, ReplacedObject, RoaDefinition, RoaDefinitionUpdates,
+    AsNumber, AuthorizationFmtError, CurrentObject, ObjectName, ReplacedObject, RoaDefinition,
+    RoaDefinitionUpdates, RoaPackingPolicy,
 };
 use crate::commons::KrillResult;
 use crate::constants::ROA_CERTIFICATE_VALIDITY_YEARS;
@@ -231,18 +232,132 @@ impl RoaInfo {
     }
 }
 
+//------------ RoaAggregateKey ---------------------------------------------
+
+/// Identifies an aggregated ROA, i.e. a ROA for many authorizations with
+/// the same ASN. If there is a maximum number of prefixes per ROA, then the
+/// authorizations for an ASN may be spread over a number of groups.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RoaAggregateKey {
+    asn: AsNumber,
+    group: Option<u32>,
+}
+
+impl RoaAggregateKey {
+    pub fn new(asn: AsNumber, group: Option<u32>) -> Self {
+        RoaAggregateKey { asn, group }
+    }
+
+    pub fn asn(&self) -> AsNumber {
+        self.asn
+    }
+
+    pub fn group(&self) -> Option<u32> {
+        self.group
+    }
+}
+
+impl FromStr for RoaAggregateKey {
+    type Err = AuthorizationFmtError;
+
+    // "AS65000" or "AS65000-1"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('-');
+
+        let asn_str = parts.next().ok_or_else(|| AuthorizationFmtError::auth(s))?;
+        let asn = AsNumber::from_str(asn_str)?;
+
+        let group = match parts.next() {
+            None => None,
+            Some(group_str) => {
+                Some(u32::from_str(group_str).map_err(|_| AuthorizationFmtError::auth(s))?)
+            }
+        };
+
+        if parts.next().is_some() {
+            return Err(AuthorizationFmtError::auth(s));
+        }
+
+        Ok(RoaAggregateKey { asn, group })
+    }
+}
+
+impl fmt::Display for RoaAggregateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.group {
+            None => write!(f, "AS{}", self.asn),
+            Some(group) => write!(f, "AS{}-{}", self.asn, group),
+        }
+    }
+}
+
+/// We use RoaAggregateKey as (json) map keys and therefore we need it
+/// to be serializable to a single simple string.
+impl Serialize for RoaAggregateKey {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.to_string().serialize(s)
+    }
+}
+
+/// We use RoaAggregateKey as (json) map keys and therefore we need it
+/// to be deserializable from a single simple string.
+impl<'de> Deserialize<'de> for RoaAggregateKey {
+    fn deserialize<D>(d: D) -> Result<RoaAggregateKey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let string = String::deserialize(d)?;
+        RoaAggregateKey::from_str(string.as_str()).map_err(de::Error::custom)
+    }
+}
+
+//------------ AggregateRoaInfo --------------------------------------------
+
+/// An aggregated ROA, and the authorizations that it covers.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AggregateRoaInfo {
+    authorizations: Vec<RouteAuthorization>,
+    #[serde(flatten)]
+    roa: RoaInfo,
+}
+
+impl AggregateRoaInfo {
+    pub fn new(authorizations: Vec<RouteAuthorization>, roa: RoaInfo) -> Self {
+        AggregateRoaInfo {
+            authorizations,
+            roa,
+        }
+    }
+
+    pub fn authorizations(&self) -> &Vec<RouteAuthorization> {
+        &self.authorizations
+    }
+
+    pub fn roa_info(&self) -> &RoaInfo {
+        &self.roa
+    }
+}
+
 //------------ Roas --------------------------------------------------------
 
-/// ROAs held by a resource class in a CA.
+/// ROAs held by a resource class in a CA. Depending on the CA's ROA packing
+/// policy these are ROAs for a single authorization, or aggregated ROAs for
+/// many authorizations for the same ASN.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Roas {
     inner: HashMap<RouteAuthorization, RoaInfo>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    aggregate: HashMap<RoaAggregateKey, AggregateRoaInfo>,
 }
 
 impl Default for Roas {
     fn default() -> Self {
         Roas {
             inner: HashMap::new(),
+            aggregate: HashMap::new(),
         }
     }
 }
@@ -252,8 +367,12 @@ impl Roas {
         self.inner.get(auth)
     }
 
+    pub fn get_aggregate(&self, key: &RoaAggregateKey) -> Option<&AggregateRoaInfo> {
+        self.aggregate.get(key)
+    }
+
     pub fn updated(&mut self, updates: RoaUpdates) {
-        let (updated, removed) = updates.unpack();
+        let (updated, removed, aggregate_updated, aggregate_removed) = updates.unpack();
 
         for (auth, info) in updated.into_iter() {
             self.inner.insert(auth, info);
@@ -262,28 +381,84 @@ impl Roas {
         for auth in removed.keys() {
             self.inner.remove(auth);
         }
+
+        for (key, aggregate) in aggregate_updated.into_iter() {
+            self.aggregate.insert(key, aggregate);
+        }
+
+        for key in aggregate_removed.keys() {
+            self.aggregate.remove(key);
+        }
     }
 
     pub fn iter(&self) -> impl Iterator<Item = (&RouteAuthorization, &RoaInfo)> {
         self.inner.iter()
     }
 
+    pub fn iter_aggregate(&self) -> impl Iterator<Item = (&RoaAggregateKey, &AggregateRoaInfo)> {
+        self.aggregate.iter()
+    }
+
+    /// Returns all current ROAs, for single and aggregated authorizations.
     pub fn current(&self) -> impl Iterator<Item = &RoaInfo> {
-        self.inner.values()
+        self.inner
+            .values()
+            .chain(self.aggregate.values().map(|a| a.roa_info()))
     }
 
+    /// Returns all authorizations for which there are ROAs in this class.
     pub fn authorizations(&self) -> impl Iterator<Item = &RouteAuthorization> {
-        self.inner.keys()
+        self.inner.keys().chain(
+            self.aggregate
+                .values()
+                .flat_map(|a| a.authorizations().iter()),
+        )
+    }
+
+    /// Groups authorizations by ASN for aggregated ROAs, spreading them over
+    /// groups of at most the maximum number of prefixes in the policy. The
+    /// authorizations in each group are sorted, so that the groups are stable
+    /// for the same set of authorizations.
+    pub fn aggregate_authorizations(
+        auths: &[RouteAuthorization],
+        policy: &RoaPackingPolicy,
+    ) -> HashMap<RoaAggregateKey, Vec<RouteAuthorization>> {
+        let mut by_asn: HashMap<AsNumber, Vec<RouteAuthorization>> = HashMap::new();
+        for auth in auths {
+            by_asn
+                .entry(auth.asn())
+                .or_insert_with(Vec::new)
+                .push(*auth);
+        }
+
+        let mut res = HashMap::new();
+        for (asn, mut asn_auths) in by_asn.into_iter() {
+            asn_auths.sort_by_key(|a| a.to_string());
+
+            match policy.max_prefixes() {
+                None => {
+                    res.insert(RoaAggregateKey::new(asn, None), asn_auths);
+                }
+                Some(max) => {
+                    for (group, chunk) in asn_auths.chunks(max).enumerate() {
+                        let key = RoaAggregateKey::new(asn, Some(group as u32));
+                        res.insert(key, chunk.to_vec());
+                    }
+                }
+            }
+        }
+        res
     }
 
+    /// Makes a ROA for one or more authorizations, which must all be for
+    /// the same ASN.
     pub fn make_roa<S: Signer>(
-        auth: &RouteAuthorization,
+        auths: &[RouteAuthorization],
+        name: &ObjectName,
         certified_key: &CertifiedKey,
         new_repo: Option<&uri::Rsync>,
         signer: &S,
     ) -> KrillResult<Roa> {
-        let prefix = auth.prefix();
-
         let incoming_cert = certified_key.incoming_cert();
         let crl_uri = match &new_repo {
             None => incoming_cert.crl_uri(),
@@ -291,16 +466,19 @@ impl Roas {
         };
 
         let roa_uri = match &new_repo {
-            None => incoming_cert.uri_for_object(auth),
-            Some(base_uri) => base_uri.join(ObjectName::from(auth).as_bytes()),
+            None => incoming_cert.uri_for_object(name.clone()),
+            Some(base_uri) => base_uri.join(name.as_bytes()),
         };
 
         let aia = incoming_cert.uri();
 
         let signing_key = certified_key.key_id();
 
-        let mut roa_builder = RoaBuilder::new(auth.asn().into());
-        roa_builder.push_addr(prefix.ip_addr(), prefix.addr_len(), auth.max_length());
+        let mut roa_builder = RoaBuilder::new(auths[0].asn().into());
+        for auth in auths {
+            let prefix = auth.prefix();
+            roa_builder.push_addr(prefix.ip_addr(), prefix.addr_len(), auth.max_length());
+        }
         let mut object_builder = SignedObjectBuilder::new(
             Serial::random(signer).map_err(ca::Error::signer)?,
             SignSupport::sign_validity_years(ROA_CERTIFICATE_VALIDITY_YEARS),
@@ -342,4 +520,45 @@ mod tests {
         parse_encode_authorization("2001:db8::/32 => 64496");
         parse_encode_authorization("2001:db8::/32-48 => 64496");
     }
+
+    #[test]
+    fn aggregate_authorizations() {
+        let auths: Vec<RouteAuthorization> = vec![
+            "10.0.0.0/24 => 64496",
+            "10.0.1.0/24 => 64496",
+            "10.0.2.0/24 => 64496",
+            "10.0.3.0/24 => 64497",
+        ]
+        .into_iter()
+        .map(|s| RouteAuthorization(RoaDefinition::from_str(s).unwrap()))
+        .collect();
+
+        let asn_1 = AsNumber::new(64496);
+        let asn_2 = AsNumber::new(64497);
+
+        let unlimited = RoaPackingPolicy::new(true, None);
+        let groups = Roas::aggregate_authorizations(&auths, &unlimited);
+        assert_eq!(2, groups.len());
+        assert_eq!(3, groups[&RoaAggregateKey::new(asn_1, None)].len());
+        assert_eq!(1, groups[&RoaAggregateKey::new(asn_2, None)].len());
+
+        let max_two = RoaPackingPolicy::new(true, Some(2));
+        let groups = Roas::aggregate_authorizations(&auths, &max_two);
+        assert_eq!(3, groups.len());
+        assert_eq!(
+            &auths[0..2],
+            groups[&RoaAggregateKey::new(asn_1, Some(0))].as_slice()
+        );
+        assert_eq!(
+            &auths[2..3],
+            groups[&RoaAggregateKey::new(asn_1, Some(1))].as_slice()
+        );
+        assert_eq!(1, groups[&RoaAggregateKey::new(asn_2, Some(0))].len());
+
+        let key = RoaAggregateKey::new(asn_1, Some(1));
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!("\"AS64496-1\"", json);
+        let des: RoaAggregateKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(key, des);
+    }
 }
@@ -8,13 +8,16 @@ use chrono::Duration;
 
 use rpki::crypto::KeyIdentifier;
 use rpki::uri;
+use rpki::x509::Time;
 
 use crate::commons::api::{
-    self, AddChildRequest, Base64, CaCommandDetails, CaCommandResult, CertAuthList,
+    self, AddChildRequest, AsNumber, AspaDefinitionUpdates, AspaProvidersUpdate, Base64,
+    CaCommandDetails, CaCommandResult, CaExportArchive, CertAuthInfo, CertAuthList,
     CertAuthSummary, ChildAuthRequest, ChildCaInfo, ChildHandle, CommandHistory,
-    CommandHistoryCriteria, Entitlements, Handle, IssuanceRequest, IssuanceResponse, IssuedCert,
-    ListReply, ParentCaContact, ParentCaReq, ParentHandle, PublishDelta, RcvdCert, RepoInfo,
-    RepositoryContact, ResourceClassName, ResourceSet, RevocationRequest, RevocationResponse,
+    CommandHistoryCriteria, Entitlements, GhostbusterVcard, Handle, IssuanceRequest,
+    IssuanceResponse, IssuedCert, KeyRollPolicy, ListReply, ParentCaContact, ParentCaReq,
+    ParentHandle, PublicationTiming, PublishDelta, RcvdCert, RepoInfo, RepositoryContact,
+    ResourceClassName, ResourceSet, RevocationRequest, RevocationResponse, RoaPackingPolicy,
     StoredEffect, UpdateChildRequest,
 };
 use crate::commons::error::Error;
@@ -25,10 +28,12 @@ use crate::commons::remote::id::IdCert;
 use crate::commons::remote::sigmsg::SignedMessage;
 use crate::commons::remote::{rfc6492, rfc8181, rfc8183};
 use crate::commons::util::httpclient;
+use crate::commons::util::softsigner::OpenSslSigner;
 use crate::commons::KrillResult;
 use crate::constants::CASERVER_DIR;
 use crate::daemon::ca::{
-    self, ta_handle, CertAuth, Cmd, CmdDet, IniDet, RouteAuthorizationUpdates, Signer,
+    self, ta_handle, CaExport, CertAuth, Cmd, CmdDet, ExportedKey, IniDet,
+    RouteAuthorizationUpdates, Signer,
 };
 use crate::daemon::mq::EventQueueListener;
 
@@ -40,6 +45,7 @@ pub struct CaServer<S: Signer> {
     ca_store: Arc<DiskAggregateStore<CertAuth<S>>>,
     rfc8181_log_dir: Option<PathBuf>,
     rfc6492_log_dir: Option<PathBuf>,
+    publication_timing: PublicationTiming,
 }
 
 impl<S: Signer> CaServer<S> {
@@ -51,6 +57,7 @@ impl<S: Signer> CaServer<S> {
         rfc6492_log_dir: Option<&PathBuf>,
         events_queue: Arc<EventQueueListener>,
         signer: Arc<RwLock<S>>,
+        publication_timing: PublicationTiming,
     ) -> KrillResult<Self> {
         let mut ca_store = DiskAggregateStore::<CertAuth<S>>::new(work_dir, CASERVER_DIR)?;
         ca_store.add_listener(events_queue);
@@ -60,6 +67,7 @@ impl<S: Signer> CaServer<S> {
             ca_store: Arc::new(ca_store),
             rfc6492_log_dir: rfc6492_log_dir.cloned(),
             rfc8181_log_dir: rfc8181_log_dir.cloned(),
+            publication_timing,
         })
     }
 
@@ -87,7 +95,12 @@ impl<S: Signer> CaServer<S> {
 
             // add embedded repo
             let embedded = RepositoryContact::embedded(info);
-            let upd_repo_cmd = CmdDet::update_repo(&handle, embedded, self.signer.clone());
+            let upd_repo_cmd = CmdDet::update_repo(
+                &handle,
+                embedded,
+                self.publication_timing.clone(),
+                self.signer.clone(),
+            );
             self.ca_store.command(upd_repo_cmd)?;
 
             // make trust anchor
@@ -102,6 +115,7 @@ impl<S: Signer> CaServer<S> {
                 &handle,
                 ResourceClassName::default(),
                 rcvd_cert,
+                self.publication_timing.clone(),
                 self.signer.clone(),
             );
             self.ca_store.command(rcv_cert)?;
@@ -116,6 +130,29 @@ impl<S: Signer> CaServer<S> {
         Ok(())
     }
 
+    /// Returns the details of a CA.
+    pub fn get_ca_info(&self, handle: &Handle) -> KrillResult<CertAuthInfo> {
+        let ca = self.get_ca(handle)?;
+        Ok(ca.as_ca_info(&self.publication_timing))
+    }
+
+    /// Returns the timing for manifests and CRLs used by a CA: the timing
+    /// set for the CA, or else the configured default.
+    pub fn ca_publication_timing(&self, handle: &Handle) -> KrillResult<PublicationTiming> {
+        let ca = self.get_ca(handle)?;
+        Ok(ca.publication_timing(&self.publication_timing).clone())
+    }
+
+    /// Set the timing for manifests and CRLs for a CA, or revert to the
+    /// configured default if None.
+    pub fn ca_publication_timing_update(
+        &self,
+        handle: &Handle,
+        timing: Option<PublicationTiming>,
+    ) -> KrillResult<()> {
+        self.send_command(CmdDet::publication_timing_update(handle, timing))
+    }
+
     /// Republish the embedded TA and CAs if needed, i.e. if they are close
     /// to their next update time.
     pub fn republish_all(&self) -> KrillResult<()> {
@@ -133,13 +170,18 @@ impl<S: Signer> CaServer<S> {
 
     /// Republish a CA, this is a no-op when there is nothing to publish.
     pub fn republish(&self, handle: &Handle) -> KrillResult<()> {
-        let cmd = CmdDet::publish(handle, self.signer.clone());
+        let cmd = CmdDet::publish(handle, self.publication_timing.clone(), self.signer.clone());
         self.send_command(cmd)
     }
 
     /// Update repository where a CA publishes.
     pub fn update_repo(&self, handle: Handle, new_contact: RepositoryContact) -> KrillResult<()> {
-        let cmd = CmdDet::update_repo(&handle, new_contact, self.signer.clone());
+        let cmd = CmdDet::update_repo(
+            &handle,
+            new_contact,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
         self.send_command(cmd)
     }
 
@@ -249,24 +291,78 @@ impl<S: Signer> CaServer<S> {
         child: ChildHandle,
         req: UpdateChildRequest,
     ) -> KrillResult<()> {
-        let (id_opt, resources_opt) = req.unpack();
-
-        if (id_opt.is_some() && resources_opt.is_some())
-            || (id_opt.is_none() && resources_opt.is_none())
-        {
+        let (id_opt, resources_opt, leases_opt) = req.unpack();
+
+        let nr_updates = [
+            id_opt.is_some(),
+            resources_opt.is_some(),
+            leases_opt.is_some(),
+        ]
+        .iter()
+        .filter(|set| **set)
+        .count();
+
+        if nr_updates != 1 {
             Err(Error::CaChildUpdateOneThing(handle.clone(), child))
         } else if let Some(id) = id_opt {
             self.send_command(CmdDet::child_update_id(handle, child, id))
+        } else if let Some(leases) = leases_opt {
+            let timing = self.publication_timing.clone();
+            let signer = self.signer.clone();
+            self.send_command(CmdDet::child_update_leases(
+                handle, child, leases, timing, signer,
+            ))
         } else {
             let resources = resources_opt.unwrap();
             self.send_command(CmdDet::child_update_resources(handle, child, resources))
         }
     }
 
+    /// Removes expired leases for all children of all CAs, and makes sure
+    /// that certificates issued to these children do not exceed their
+    /// remaining entitlements, or outlive their leases. CAs and children
+    /// which need no changes are left alone.
+    pub fn child_leases_expire_all(&self) {
+        let now = Time::now();
+        for handle in self.ca_store.list() {
+            if let Err(e) = self.child_leases_expire(&handle, now) {
+                error!(
+                    "Failed to update child leases for CA '{}', error: {}",
+                    handle, e
+                );
+            }
+        }
+    }
+
+    fn child_leases_expire(&self, handle: &Handle, now: Time) -> KrillResult<()> {
+        let ca = self.get_ca(handle)?;
+
+        for child_handle in ca.children() {
+            if !ca.child_leases_expired(child_handle, now)? {
+                continue;
+            }
+
+            info!(
+                "Removing expired leases for child '{}' under CA '{}'",
+                child_handle, handle
+            );
+
+            self.send_command(CmdDet::child_expire_leases(
+                handle,
+                child_handle.clone(),
+                now,
+                self.publication_timing.clone(),
+                self.signer.clone(),
+            ))?;
+        }
+        Ok(())
+    }
+
     /// Update a child under this CA.
     pub fn ca_child_remove(&self, handle: &Handle, child: ChildHandle) -> KrillResult<()> {
+        let timing = self.publication_timing.clone();
         let signer = self.signer.clone();
-        self.send_command(CmdDet::child_remove(handle, child, signer))
+        self.send_command(CmdDet::child_remove(handle, child, timing, signer))
     }
 }
 
@@ -423,6 +519,7 @@ impl<S: Signer> CaServer<S> {
             parent,
             child.clone(),
             issue_req.clone(),
+            self.publication_timing.clone(),
             self.signer.clone(),
         );
 
@@ -443,7 +540,13 @@ impl<S: Signer> CaServer<S> {
     ) -> KrillResult<RevocationResponse> {
         let res = (&revoke_request).into(); // response provided that no errors are returned earlier
 
-        let cmd = CmdDet::child_revoke_key(ca_handle, child, revoke_request, self.signer.clone());
+        let cmd = CmdDet::child_revoke_key(
+            ca_handle,
+            child,
+            revoke_request,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
         self.ca_store.command(cmd)?;
 
         Ok(res)
@@ -473,6 +576,78 @@ impl<S: Signer> CaServer<S> {
         }
     }
 
+    /// Returns the CA if it may be deleted. The TA cannot be deleted, and
+    /// neither can a CA which still has children.
+    pub fn get_ca_for_deletion(&self, handle: &Handle) -> KrillResult<Arc<CertAuth<S>>> {
+        let ca = self.get_ca(handle)?;
+        if ca.is_ta() {
+            Err(Error::TaNotAllowed)
+        } else if ca.children().next().is_some() {
+            Err(Error::CaHasChildren(handle.clone()))
+        } else {
+            Ok(ca)
+        }
+    }
+
+    /// Deletes a CA. Revocation of its keys is requested from all parents,
+    /// and the CA is removed as a child from parents under this server. This
+    /// is done on a best effort basis, i.e. the CA will be deleted even if
+    /// a remote parent cannot be reached. Then all keys for the CA are
+    /// destroyed and its history is archived.
+    ///
+    /// Note that the objects of this CA should be withdrawn from its
+    /// repository before calling this function.
+    pub async fn delete_ca(&self, handle: &Handle) -> KrillResult<()> {
+        let ca = self.get_ca_for_deletion(handle)?;
+
+        for parent in ca.parents() {
+            match ca.parent(parent)? {
+                ParentCaContact::Embedded => {
+                    // Removing the child also revokes and withdraws its certificates.
+                    if let Err(e) = self.ca_child_remove(parent, handle.clone()) {
+                        warn!(
+                            "Could not remove CA '{}' from parent '{}', error: {}",
+                            handle, parent, e
+                        );
+                    }
+                }
+                _ => {
+                    let revoke_requests = {
+                        let signer = self.signer.read().unwrap();
+                        ca.revoke_all_requests(parent, signer.deref())
+                    };
+                    let res = match revoke_requests {
+                        Ok(requests) => self.send_revoke_requests(handle, parent, requests).await,
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = res {
+                        warn!(
+                            "Could not revoke keys for CA '{}' at parent '{}', error: {}",
+                            handle, parent, e
+                        );
+                    }
+                }
+            }
+        }
+
+        {
+            let mut signer = self.signer.write().unwrap();
+            for key in ca.all_key_ids() {
+                if let Err(e) = signer.destroy_key(&key) {
+                    warn!(
+                        "Could not destroy key '{}' for CA '{}', error: {}",
+                        key, handle, e
+                    );
+                }
+            }
+        }
+
+        self.ca_store.archive(handle)?;
+        info!("Deleted CA '{}', its history was archived", handle);
+
+        Ok(())
+    }
+
     pub fn ca_update_id(&self, handle: Handle) -> KrillResult<()> {
         let cmd = CmdDet::update_id(&handle, self.signer.clone());
         self.send_command(cmd)
@@ -514,10 +689,62 @@ impl<S: Signer> CaServer<S> {
     /// a staging period of 24 hours, but we may use a shorter period for testing and/or emergency
     /// manual key rolls.
     pub fn ca_keyroll_activate(&self, handle: Handle, staging: Duration) -> KrillResult<()> {
-        let activate_cmd = CmdDet::key_roll_activate(&handle, staging, self.signer.clone());
+        let activate_cmd = CmdDet::key_roll_activate(
+            &handle,
+            staging,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
         self.send_command(activate_cmd)
     }
 
+    /// Set the policy for automatic key rolls for a CA.
+    pub fn ca_keyroll_policy_update(
+        &self,
+        handle: Handle,
+        policy: KeyRollPolicy,
+    ) -> KrillResult<()> {
+        self.send_command(CmdDet::key_roll_policy_update(&handle, policy))
+    }
+
+    /// Initiate and activate key rolls for all CAs, as far as their key roll
+    /// policies call for it at this time. Will try to process all and log
+    /// possible errors, i.e. do not bail out because of issues with one CA.
+    pub fn keyroll_all(&self) {
+        let now = Time::now();
+        for handle in self.ca_store.list() {
+            if let Err(e) = self.keyroll_by_policy(&handle, now) {
+                error!("Failed to roll keys for CA '{}', error: {}", handle, e);
+            }
+        }
+    }
+
+    fn keyroll_by_policy(&self, handle: &Handle, now: Time) -> KrillResult<()> {
+        let ca = self.get_ca(handle)?;
+        let policy = ca.keyroll_policy();
+
+        if !policy.enabled() || !policy.in_window(*now) {
+            return Ok(());
+        }
+
+        if ca.keyroll_activate_due(now) {
+            info!(
+                "Activating new keys for CA '{}' as per its key roll policy",
+                handle
+            );
+            self.ca_keyroll_activate(handle.clone(), policy.staging())?;
+        }
+        if ca.keyroll_initiate_due(now) {
+            info!(
+                "Starting key roll for CA '{}' as per its key roll policy",
+                handle
+            );
+            self.ca_keyroll_init(handle.clone(), policy.max_key_age())?;
+        }
+
+        Ok(())
+    }
+
     /// Try to get updates for all embedded CAs, will skip the TA and/or CAs that
     /// have no parents. Will try to process all and log possible errors, i.e. do
     /// not bail out because of issues with one CA.
@@ -670,8 +897,13 @@ impl<S: Signer> CaServer<S> {
             for req in revoke_requests.into_iter() {
                 revocations.push((&req).into());
 
-                let cmd =
-                    CmdDet::child_revoke_key(parent_h, handle.clone(), req, self.signer.clone());
+                let cmd = CmdDet::child_revoke_key(
+                    parent_h,
+                    handle.clone(),
+                    req,
+                    self.publication_timing.clone(),
+                    self.signer.clone(),
+                );
 
                 self.send_command(cmd)?;
             }
@@ -751,6 +983,7 @@ impl<S: Signer> CaServer<S> {
                     handle,
                     class_name.clone(),
                     received,
+                    self.publication_timing.clone(),
                     self.signer.clone(),
                 );
 
@@ -775,7 +1008,13 @@ impl<S: Signer> CaServer<S> {
                 let pub_key = req.csr().public_key().clone();
                 let parent_class = req.class_name().clone();
 
-                let cmd = CmdDet::child_certify(parent_h, handle.clone(), req, self.signer.clone());
+                let cmd = CmdDet::child_certify(
+                    parent_h,
+                    handle.clone(),
+                    req,
+                    self.publication_timing.clone(),
+                    self.signer.clone(),
+                );
 
                 let parent = self.ca_store.command(cmd)?;
 
@@ -1073,11 +1312,193 @@ impl<S: Signer> CaServer<S> {
         handle: Handle,
         updates: RouteAuthorizationUpdates,
     ) -> KrillResult<()> {
-        let cmd = CmdDet::route_authorizations_update(&handle, updates, self.signer.clone());
+        let cmd = CmdDet::route_authorizations_update(
+            &handle,
+            updates,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
+        self.send_command(cmd)
+    }
+
+    /// Update the ROA packing policy for a CA, and re-issue its ROAs.
+    pub fn ca_routes_policy_update(
+        &self,
+        handle: Handle,
+        policy: RoaPackingPolicy,
+    ) -> KrillResult<()> {
+        let cmd = CmdDet::roa_policy_update(
+            &handle,
+            policy,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
         self.send_command(cmd)
     }
 }
 
+/// # Support ASPA functions
+///
+impl<S: Signer> CaServer<S> {
+    /// Add, replace or remove ASPA definitions for a CA
+    pub fn ca_aspas_update(
+        &self,
+        handle: Handle,
+        updates: AspaDefinitionUpdates,
+    ) -> KrillResult<()> {
+        let cmd = CmdDet::aspas_update(
+            &handle,
+            updates,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
+        self.send_command(cmd)
+    }
+
+    /// Update the providers for an existing ASPA definition of a CA
+    pub fn ca_aspas_update_providers(
+        &self,
+        handle: Handle,
+        customer: AsNumber,
+        update: AspaProvidersUpdate,
+    ) -> KrillResult<()> {
+        let cmd = CmdDet::aspas_update_providers(
+            &handle,
+            customer,
+            update,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
+        self.send_command(cmd)
+    }
+}
+
+/// # Support Ghostbusters functions
+///
+impl<S: Signer> CaServer<S> {
+    /// Set or remove (None) the Ghostbusters vCard for a CA
+    pub fn ca_ghostbuster_update(
+        &self,
+        handle: Handle,
+        vcard: Option<GhostbusterVcard>,
+    ) -> KrillResult<()> {
+        let cmd = CmdDet::ghostbuster_update(
+            &handle,
+            vcard,
+            self.publication_timing.clone(),
+            self.signer.clone(),
+        );
+        self.send_command(cmd)
+    }
+}
+
+/// # Support moving CAs between servers
+///
+impl CaServer<OpenSslSigner> {
+    /// Exports a CA, including all of its private keys, encrypted with the
+    /// passphrase. The CA is not changed, so it should be deleted from this
+    /// server once it is imported elsewhere.
+    pub fn export_ca(&self, handle: &Handle, passphrase: &str) -> KrillResult<CaExportArchive> {
+        let ca = self.get_ca(handle)?;
+        if ca.is_ta() {
+            return Err(Error::TaNotAllowed);
+        }
+
+        let keys = {
+            let signer = self.signer.read().unwrap();
+            let mut keys = vec![];
+            for key_id in ca.all_key_ids() {
+                let key_pair = signer.export_key(&key_id)?;
+                keys.push(ExportedKey::new(key_id, key_pair));
+            }
+            keys
+        };
+
+        CaExport::new(ca.as_ref().clone(), keys).encrypt(passphrase)
+    }
+
+    /// Imports a CA from an archive made by `export_ca`. The CA must not
+    /// exist on this server yet. Its history before the export is not
+    /// imported, only its current state.
+    ///
+    /// A CA that uses an embedded parent can only be imported if that parent
+    /// exists on this server and has the CA as a child. A CA that uses the
+    /// embedded repository can only be imported if this server has one.
+    pub fn import_ca(
+        &self,
+        archive: &CaExportArchive,
+        passphrase: &str,
+        embedded_repo: bool,
+    ) -> KrillResult<()> {
+        let handle = archive.handle();
+        if handle == &ta_handle() || handle.as_str() == "version" {
+            return Err(Error::TaNameReserved);
+        } else if self.ca_store.has(handle) {
+            return Err(Error::CaDuplicate(handle.clone()));
+        }
+
+        let (ca, keys) = CaExport::decrypt(archive, passphrase)?.unpack();
+
+        if let Ok(contact) = ca.get_repository_contact() {
+            if contact.is_embedded() && !embedded_repo {
+                return Err(Error::PublisherNoEmbeddedRepo);
+            }
+        }
+
+        for parent in ca.parents() {
+            if let ParentCaContact::Embedded = ca.parent(parent)? {
+                let known = match self.get_ca(parent) {
+                    Ok(parent_ca) => parent_ca.get_child(handle).is_ok(),
+                    Err(_) => false,
+                };
+                if !known {
+                    return Err(Error::CaImportParentUnknown(handle.clone(), parent.clone()));
+                }
+            }
+        }
+
+        // The keys must be there before the CA can be used. If importing the
+        // keys or the CA fails, then the keys imported so far are removed.
+        let mut imported_keys = vec![];
+        let imported = self.import_keys(&keys, &mut imported_keys).and_then(|_| {
+            self.ca_store.import(handle, ca)?;
+            Ok(())
+        });
+
+        if let Err(e) = imported {
+            let mut signer = self.signer.write().unwrap();
+            for key_id in imported_keys {
+                if let Err(e) = signer.destroy_key(&key_id) {
+                    warn!(
+                        "Could not remove key '{}' for CA '{}' after failed import, error: {}",
+                        key_id, handle, e
+                    );
+                }
+            }
+            return Err(e);
+        }
+
+        info!("Imported CA '{}'", handle);
+        Ok(())
+    }
+
+    /// Imports exported keys into the signer. The identifiers of the keys
+    /// are added to `imported` as they are imported, so that they can be
+    /// removed again if a later step fails.
+    fn import_keys(
+        &self,
+        keys: &[ExportedKey],
+        imported: &mut Vec<KeyIdentifier>,
+    ) -> KrillResult<()> {
+        let mut signer = self.signer.write().unwrap();
+        for key in keys {
+            signer.import_key(key.key_id(), key.key_pair())?;
+            imported.push(*key.key_id());
+        }
+        Ok(())
+    }
+}
+
 //------------ Tests ---------------------------------------------------------
 
 #[cfg(test)]
@@ -1087,10 +1508,66 @@ mod tests {
 
     use std::sync::{Arc, RwLock};
 
-    use crate::commons::api::RepoInfo;
+    use crate::commons::api::{RepoInfo, ResourceLease};
     use crate::commons::util::softsigner::OpenSslSigner;
     use crate::test;
 
+    fn test_server(d: &PathBuf) -> CaServer<OpenSslSigner> {
+        let signer = OpenSslSigner::build(d).unwrap();
+        let signer = Arc::new(RwLock::new(signer));
+        let event_queue = Arc::new(EventQueueListener::in_mem());
+        let timing = PublicationTiming::default();
+
+        CaServer::<OpenSslSigner>::build(d, None, None, event_queue, signer, timing).unwrap()
+    }
+
+    fn init_test_ta(server: &CaServer<OpenSslSigner>) {
+        let ta_repo_info = RepoInfo::new(
+            test::rsync("rsync://localhost/repo/ta/"),
+            test::https("https://localhost/repo/notification.xml"),
+        );
+        let ta_uri = test::https("https://localhost/ta/ta.cer");
+        let ta_aia = test::rsync("rsync://localhost/repo/ta.cer");
+        server.init_ta(ta_repo_info, ta_aia, vec![ta_uri]).unwrap();
+    }
+
+    fn add_test_child_to_ta(
+        server: &CaServer<OpenSslSigner>,
+        child: &Handle,
+        resources: ResourceSet,
+    ) -> ParentCaContact {
+        let service_uri = test::https("https://localhost/");
+        let req = AddChildRequest::new(child.clone(), resources, ChildAuthRequest::Embedded);
+        server
+            .ca_add_child(&ta_handle(), req, &service_uri)
+            .unwrap()
+    }
+
+    fn init_test_child(server: &CaServer<OpenSslSigner>, child: &Handle) {
+        let repo_info = RepoInfo::new(
+            test::rsync(&format!("rsync://localhost/repo/{}/", child)),
+            test::https("https://localhost/repo/notification.xml"),
+        );
+        server.init_ca(child).unwrap();
+        server
+            .update_repo(child.clone(), RepositoryContact::embedded(repo_info))
+            .unwrap();
+    }
+
+    async fn add_test_parent_ta(
+        server: &CaServer<OpenSslSigner>,
+        child: &Handle,
+        contact: ParentCaContact,
+    ) {
+        let parent = ParentCaReq::new(ta_handle(), contact);
+        server.ca_parent_add(child.clone(), parent).unwrap();
+        server
+            .get_updates_from_parent(child, &ta_handle())
+            .await
+            .unwrap();
+        server.send_requests(child, &ta_handle()).await.unwrap();
+    }
+
     #[test]
     fn add_ta() {
         test::test_under_tmp(|d| {
@@ -1099,8 +1576,11 @@ mod tests {
 
             let event_queue = Arc::new(EventQueueListener::in_mem());
 
+            let timing = PublicationTiming::default();
+
             let server =
-                CaServer::<OpenSslSigner>::build(&d, None, None, event_queue, signer).unwrap();
+                CaServer::<OpenSslSigner>::build(&d, None, None, event_queue, signer, timing)
+                    .unwrap();
 
             let repo_info = {
                 let base_uri = test::rsync("rsync://localhost/repo/ta/");
@@ -1118,4 +1598,111 @@ mod tests {
             assert!(server.get_trust_anchor().is_ok());
         })
     }
+
+    #[tokio::test]
+    async fn child_leases_expire() {
+        let d = test::tmp_dir();
+        let server = test_server(&d);
+        init_test_ta(&server);
+
+        let ta_handle = ta_handle();
+        let child = Handle::from_str_unsafe("child");
+        init_test_child(&server, &child);
+
+        // Add the child with permanent resources, and lease it some more
+        // resources for a short time only.
+        let permanent = ResourceSet::from_strs("", "10.0.0.0/16", "").unwrap();
+        let leased = ResourceSet::from_strs("", "192.168.0.0/16", "").unwrap();
+        let lease_end = Time::now() + Duration::hours(1);
+
+        let contact = add_test_child_to_ta(&server, &child, permanent.clone());
+
+        let leases = vec![ResourceLease::new(leased.clone(), lease_end)];
+        let req = UpdateChildRequest::leases(leases);
+        server
+            .ca_child_update(&ta_handle, child.clone(), req)
+            .unwrap();
+
+        add_test_parent_ta(&server, &child, contact).await;
+
+        let issued_resources = || {
+            let entitlements = server.list(&ta_handle, &child).unwrap();
+            entitlements.classes()[0].issued()[0].resource_set().clone()
+        };
+
+        assert_eq!(issued_resources(), permanent.union(&leased));
+
+        // Nothing expired yet, so the TA should not be changed.
+        let version = server.get_trust_anchor().unwrap().version();
+        server.child_leases_expire_all();
+        assert_eq!(server.get_trust_anchor().unwrap().version(), version);
+
+        // Once the lease expired, it is removed and the certificate for the
+        // child is shrunk to its permanent resources.
+        let after_lease_end = lease_end + Duration::seconds(1);
+        server
+            .child_leases_expire(&ta_handle, after_lease_end)
+            .unwrap();
+
+        let ta = server.get_trust_anchor().unwrap();
+        assert!(ta.get_child(&child).unwrap().leases().is_empty());
+        assert_eq!(issued_resources(), permanent);
+
+        let _ = std::fs::remove_dir_all(d);
+    }
+
+    #[tokio::test]
+    async fn export_import_ca() {
+        let d = test::tmp_dir();
+
+        let server = test_server(&test::sub_dir(&d));
+        init_test_ta(&server);
+
+        let child = Handle::from_str_unsafe("child");
+        let resources = ResourceSet::from_strs("", "10.0.0.0/16", "").unwrap();
+        init_test_child(&server, &child);
+        let contact = add_test_child_to_ta(&server, &child, resources.clone());
+        add_test_parent_ta(&server, &child, contact).await;
+
+        let archive = server.export_ca(&child, "secret").unwrap();
+        let key_ids = server.get_ca(&child).unwrap().all_key_ids();
+
+        let other = test_server(&test::sub_dir(&d));
+        init_test_ta(&other);
+
+        let has_keys = |server: &CaServer<OpenSslSigner>| {
+            let signer = server.signer.read().unwrap();
+            key_ids.iter().all(|key| signer.export_key(key).is_ok())
+        };
+
+        match other.import_ca(&archive, "wrong", true) {
+            Err(Error::CaImportInvalid(_, _)) => {}
+            _ => panic!("Expected import to fail for the wrong passphrase"),
+        }
+
+        // The embedded parent of the CA does not have it as a child yet.
+        match other.import_ca(&archive, "secret", true) {
+            Err(Error::CaImportParentUnknown(_, _)) => {}
+            _ => panic!("Expected import to fail for the unknown embedded parent"),
+        }
+
+        assert!(!other.has_ca(&child));
+        assert!(!has_keys(&other));
+
+        add_test_child_to_ta(&other, &child, resources);
+        other.import_ca(&archive, "secret", true).unwrap();
+
+        assert_eq!(
+            other.get_ca_info(&child).unwrap(),
+            server.get_ca_info(&child).unwrap()
+        );
+        assert!(has_keys(&other));
+
+        match other.import_ca(&archive, "secret", true) {
+            Err(Error::CaDuplicate(_)) => {}
+            _ => panic!("Expected import to fail for an existing CA"),
+        }
+
+        let _ = std::fs::remove_dir_all(d);
+    }
 }
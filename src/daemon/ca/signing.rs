@@ -134,10 +134,14 @@ pub struct SignSupport;
 
 impl SignSupport {
     /// Create an IssuedCert
+    ///
+    /// The certificate is valid for a year, unless a not after time is given
+    /// which is earlier than that.
     pub fn make_issued_cert<S: Signer>(
         csr: CsrInfo,
         resources: &ResourceSet,
         limit: RequestResourceLimit,
+        not_after: Option<Time>,
         replaces: Option<ReplacedObject>,
         signing_key: &CertifiedKey,
         signer: &S,
@@ -155,7 +159,11 @@ impl SignSupport {
         let serial = { Serial::random(signer).map_err(ca::Error::signer)? };
         let issuer = signing_cert.cert().subject().clone();
 
-        let validity = Validity::new(Time::five_minutes_ago(), Time::next_year());
+        let not_after = match not_after {
+            Some(not_after) if not_after < Time::next_year() => not_after,
+            _ => Time::next_year(),
+        };
+        let validity = Validity::new(Time::five_minutes_ago(), not_after);
 
         let subject = Some(Name::from_pub_key(&pub_key));
 
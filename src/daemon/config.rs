@@ -16,7 +16,8 @@ use toml;
 
 use rpki::uri;
 
-use crate::commons::api::Token;
+use crate::commons::api::rrdp::RrdpRetention;
+use crate::commons::api::{PublicationTiming, PublisherQuota, StalePublisherThreshold, Token};
 use crate::commons::util::ext_serde;
 use crate::constants::*;
 use crate::daemon::http::tls_keys;
@@ -98,6 +99,46 @@ impl ConfigDefaults {
     fn ipfs_path() -> IpfsPath {
         IpfsPath(PathBuf::from("~/.ipfs"))
     }
+
+    fn rrdp_delta_files_min_nr() -> usize {
+        REPOSITORY_RRDP_DELTA_FILES_MIN_NR
+    }
+
+    fn rrdp_delta_files_max_size_pct() -> u64 {
+        REPOSITORY_RRDP_DELTA_FILES_MAX_SIZE_PCT
+    }
+
+    fn rrdp_files_retain_mins() -> u64 {
+        REPOSITORY_RRDP_FILES_RETAIN_MINS
+    }
+
+    fn rsync_versions_retain() -> usize {
+        REPOSITORY_RSYNC_VERSIONS_RETAIN
+    }
+
+    fn stale_publisher_update_mins() -> u64 {
+        REPOSITORY_STALE_PUBLISHER_UPDATE_MINS
+    }
+
+    fn stale_publisher_manifest_mins() -> u64 {
+        REPOSITORY_STALE_PUBLISHER_MANIFEST_MINS
+    }
+
+    fn bgp_announcements_refresh_mins() -> u64 {
+        BGP_ANNOUNCEMENTS_REFRESH_MINS
+    }
+
+    fn mft_next_update_hours() -> u32 {
+        PUBLISH_NEXT_HOURS
+    }
+
+    fn mft_valid_days() -> u32 {
+        PUBLISH_VALID_DAYS
+    }
+
+    fn mft_republish_hours() -> u32 {
+        PUBLISH_THRESHOLD_HOURS
+    }
 }
 
 //------------ Config --------------------------------------------------------
@@ -172,6 +213,39 @@ pub struct Config {
     pub post_limit_rfc6492: u64,
     pub rfc6492_log_dir: Option<PathBuf>,
 
+    #[serde(default = "ConfigDefaults::rrdp_delta_files_min_nr")]
+    pub rrdp_delta_files_min_nr: usize,
+
+    #[serde(default = "ConfigDefaults::rrdp_delta_files_max_size_pct")]
+    pub rrdp_delta_files_max_size_pct: u64,
+
+    #[serde(default = "ConfigDefaults::rrdp_files_retain_mins")]
+    pub rrdp_files_retain_mins: u64,
+
+    #[serde(default = "ConfigDefaults::rsync_versions_retain")]
+    pub rsync_versions_retain: usize,
+
+    #[serde(default = "ConfigDefaults::stale_publisher_update_mins")]
+    pub stale_publisher_update_mins: u64,
+
+    #[serde(default = "ConfigDefaults::stale_publisher_manifest_mins")]
+    pub stale_publisher_manifest_mins: u64,
+
+    // A local MRT RIB dump, or CSV file, with BGP announcements for ROA analysis
+    pub bgp_announcements_file: Option<PathBuf>,
+
+    #[serde(default = "ConfigDefaults::bgp_announcements_refresh_mins")]
+    pub bgp_announcements_refresh_mins: u64,
+
+    #[serde(default = "ConfigDefaults::mft_next_update_hours")]
+    pub mft_next_update_hours: u32,
+
+    #[serde(default = "ConfigDefaults::mft_valid_days")]
+    pub mft_valid_days: u32,
+
+    #[serde(default = "ConfigDefaults::mft_republish_hours")]
+    pub mft_republish_hours: u32,
+
     // The public key this repository uses to publish ipfs content to ipns
     pub repo_pub_key: Option<RepoPubKey>,
 
@@ -179,7 +253,18 @@ pub struct Config {
     pub tal_pub_key: Option<TalPubKey>,
 
     #[serde(default = "ConfigDefaults::ipfs_path")]
-    pub ipfs_path: IpfsPath
+    pub ipfs_path: IpfsPath,
+
+    // The IPFS pubsub topic used to announce new root CIDs, if any
+    pub ipfs_pubsub_topic: Option<String>,
+
+    // Parse and check RPKI objects before accepting them for publication
+    #[serde(default)]
+    pub validate_published_objects: bool,
+
+    // The quota given to newly added publishers, unlimited by default
+    #[serde(default)]
+    pub publisher_quota: PublisherQuota,
 }
 
 /// # Accessors
@@ -237,6 +322,29 @@ impl Config {
         IpfsPath(self.ipfs_path.0.clone())
     }
 
+    pub fn rrdp_retention(&self) -> RrdpRetention {
+        RrdpRetention::new(
+            self.rrdp_delta_files_min_nr,
+            self.rrdp_delta_files_max_size_pct,
+            self.rrdp_files_retain_mins,
+        )
+    }
+
+    pub fn stale_publisher_threshold(&self) -> StalePublisherThreshold {
+        StalePublisherThreshold::new(
+            self.stale_publisher_update_mins,
+            self.stale_publisher_manifest_mins,
+        )
+    }
+
+    pub fn publication_timing(&self) -> PublicationTiming {
+        PublicationTiming::new(
+            self.mft_next_update_hours,
+            self.mft_valid_days,
+            self.mft_republish_hours,
+        )
+    }
+
     pub fn ta_cert_uri(&self) -> uri::Https {
         uri::Https::from_string(format!("{}ta/ta.cer", &self.service_uri)).unwrap()
     }
@@ -274,6 +382,9 @@ impl Config {
         let repo_pub_key = None;
         let tal_pub_key = None;
         let ipfs_path = IpfsPath(PathBuf::from(String::from("")));
+        let ipfs_pubsub_topic = None;
+        let validate_published_objects = false;
+        let publisher_quota = PublisherQuota::default();
         let log_level = LevelFilter::Trace;
         let log_type = LogType::Stderr;
         let mut log_file = data_dir.clone();
@@ -294,6 +405,17 @@ impl Config {
             dir.push("rfc6492");
             Some(dir)
         };
+        let rrdp_delta_files_min_nr = ConfigDefaults::rrdp_delta_files_min_nr();
+        let rrdp_delta_files_max_size_pct = ConfigDefaults::rrdp_delta_files_max_size_pct();
+        let rrdp_files_retain_mins = ConfigDefaults::rrdp_files_retain_mins();
+        let rsync_versions_retain = ConfigDefaults::rsync_versions_retain();
+        let stale_publisher_update_mins = ConfigDefaults::stale_publisher_update_mins();
+        let stale_publisher_manifest_mins = ConfigDefaults::stale_publisher_manifest_mins();
+        let bgp_announcements_file = None;
+        let bgp_announcements_refresh_mins = ConfigDefaults::bgp_announcements_refresh_mins();
+        let mft_next_update_hours = ConfigDefaults::mft_next_update_hours();
+        let mft_valid_days = ConfigDefaults::mft_valid_days();
+        let mft_republish_hours = ConfigDefaults::mft_republish_hours();
 
         Config {
             ip,
@@ -318,9 +440,23 @@ impl Config {
             rfc8181_log_dir,
             post_limit_rfc6492,
             rfc6492_log_dir,
+            rrdp_delta_files_min_nr,
+            rrdp_delta_files_max_size_pct,
+            rrdp_files_retain_mins,
+            rsync_versions_retain,
+            stale_publisher_update_mins,
+            stale_publisher_manifest_mins,
+            bgp_announcements_file,
+            bgp_announcements_refresh_mins,
+            mft_next_update_hours,
+            mft_valid_days,
+            mft_republish_hours,
             repo_pub_key,
             tal_pub_key,
-            ipfs_path
+            ipfs_path,
+            ipfs_pubsub_topic,
+            validate_published_objects,
+            publisher_quota,
         }
     }
 
@@ -457,6 +593,18 @@ impl Config {
             ));
         }
 
+        if self.bgp_announcements_refresh_mins == 0 {
+            return Err(ConfigError::other(
+                "bgp_announcements_refresh_mins must be at least 1",
+            ));
+        }
+
+        if !self.publication_timing().is_valid() {
+            return Err(ConfigError::other(
+                "mft_republish_hours must be at least 1 and less than mft_next_update_hours, and mft_valid_days must cover mft_next_update_hours",
+            ));
+        }
+
         Ok(())
     }
 
@@ -70,6 +70,7 @@ impl AsRef<str> for ContentType {
 struct Response {
     status: StatusCode,
     content_type: ContentType,
+    headers: Vec<(&'static str, String)>,
     body: Vec<u8>,
 }
 
@@ -78,18 +79,26 @@ impl Response {
         Response {
             status,
             content_type: ContentType::Text,
+            headers: Vec::new(),
             body: Vec::new(),
         }
     }
 
+    fn with_header(mut self, name: &'static str, value: String) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
     fn finalize(self) -> HttpResponse {
-        HttpResponse(
-            hyper::Response::builder()
-                .status(self.status)
-                .header("Content-Type", self.content_type.as_ref())
-                .body(self.body.into())
-                .unwrap(),
-        )
+        let mut builder = hyper::Response::builder()
+            .status(self.status)
+            .header("Content-Type", self.content_type.as_ref());
+
+        for (name, value) in self.headers {
+            builder = builder.header(name, value);
+        }
+
+        HttpResponse(builder.body(self.body.into()).unwrap())
     }
 }
 
@@ -118,6 +127,7 @@ impl HttpResponse {
         Response {
             status: StatusCode::OK,
             content_type,
+            headers: Vec::new(),
             body,
         }
         .finalize()
@@ -142,6 +152,36 @@ impl HttpResponse {
         Self::ok_response(ContentType::Xml, body)
     }
 
+    /// An RRDP notification file, which validators should revalidate
+    /// using the ETag.
+    pub fn rrdp_notification(body: Vec<u8>, etag: String, last_modified: String) -> Self {
+        Response {
+            status: StatusCode::OK,
+            content_type: ContentType::Xml,
+            headers: Vec::new(),
+            body,
+        }
+        .with_header("Cache-Control", "no-cache".to_string())
+        .with_header("ETag", etag)
+        .with_header("Last-Modified", last_modified)
+        .finalize()
+    }
+
+    /// An RRDP snapshot or delta file. These never change once written.
+    pub fn rrdp_immutable(body: Vec<u8>) -> Self {
+        Response {
+            status: StatusCode::OK,
+            content_type: ContentType::Xml,
+            headers: Vec::new(),
+            body,
+        }
+        .with_header(
+            "Cache-Control",
+            "public, max-age=31536000, immutable".to_string(),
+        )
+        .finalize()
+    }
+
     pub fn rfc8181(body: Vec<u8>) -> Self {
         Self::ok_response(ContentType::Rfc8181, body)
     }
@@ -190,6 +230,7 @@ impl HttpResponse {
         Response {
             status,
             content_type: ContentType::Json,
+            headers: Vec::new(),
             body: body.into_bytes(),
         }
         .finalize()
@@ -199,6 +240,12 @@ impl HttpResponse {
         Response::new(StatusCode::OK).finalize()
     }
 
+    pub fn not_modified(etag: String) -> Self {
+        Response::new(StatusCode::NOT_MODIFIED)
+            .with_header("ETag", etag)
+            .finalize()
+    }
+
     pub fn not_found() -> Self {
         Response::new(StatusCode::NOT_FOUND).finalize()
     }
@@ -241,6 +288,14 @@ impl Request {
         self.request.method()
     }
 
+    /// Returns the value of a header, if present and valid text.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+    }
+
     /// Returns whether the request is a GET request.
     pub fn is_get(&self) -> bool {
         self.request.method() == Method::GET
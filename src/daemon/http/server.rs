@@ -5,8 +5,10 @@ use std::fs::File;
 use std::path::{PathBuf, Path};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 use tokio::sync::RwLock;
@@ -19,8 +21,8 @@ use hyper::service::{make_service_fn, service_fn};
 use hyper::Method;
 
 use crate::commons::api::{
-    ChildHandle, CommandHistoryCriteria, Handle, ParentCaContact, ParentCaReq, ParentHandle,
-    PublisherList, RepositoryUpdate,
+    AsNumber, ChildHandle, CommandHistoryCriteria, Handle, HexEncodedHash, ParentCaContact,
+    ParentCaReq, ParentHandle, PublisherList, RepositoryUpdate, RoaDefinitionFormat,
 };
 use crate::commons::error::Error;
 use crate::commons::remote::rfc8183;
@@ -244,6 +246,28 @@ pub async fn metrics(req: Request) -> RoutingResult {
                     ));
                 }
             }
+
+            if let Ok(issues) = server.repository_issues() {
+                let stale = issues.publishers();
+
+                res.push_str("\n");
+                res.push_str("# HELP krill_repo_stale_publishers number of stale publishers\n");
+                res.push_str("# TYPE krill_repo_stale_publishers gauge\n");
+                res.push_str(&format!("krill_repo_stale_publishers {}\n", stale.len()));
+
+                res.push_str("\n");
+                res.push_str(
+                    "# HELP krill_repo_publisher_stale whether a publisher is stale (1) or not (0)\n",
+                );
+                res.push_str("# TYPE krill_repo_publisher_stale gauge\n");
+                for publisher in publishers.keys() {
+                    res.push_str(&format!(
+                        "krill_repo_publisher_stale{{publisher=\"{}\"}} {}\n",
+                        publisher,
+                        if stale.contains_key(publisher) { 1 } else { 0 }
+                    ));
+                }
+            }
         }
 
         let cas_status = server.cas_stats();
@@ -403,6 +427,7 @@ async fn api(req: Request) -> RoutingResult {
             Some("bulk") => api_bulk(req, &mut path).await,
             Some("cas") => api_cas(req, &mut path).await,
             Some("publishers") => api_publishers(req, &mut path).await,
+            Some("pubd") => api_pubd(req, &mut path).await,
             _ => render_unknown_method(),
         }
     }
@@ -428,12 +453,17 @@ async fn api_bulk(req: Request, path: &mut RequestPath) -> RoutingResult {
 async fn api_cas(req: Request, path: &mut RequestPath) -> RoutingResult {
     match path.path_arg() {
         Some(ca) => match path.next() {
-            None => ca_info(req, ca).await,
+            None => match *req.method() {
+                Method::DELETE => ca_delete(req, ca).await,
+                _ => ca_info(req, ca).await,
+            },
             Some("child_request.xml") => ca_child_req_xml(req, ca).await,
             Some("child_request.json") => ca_child_req_json(req, ca).await,
             Some("children") => ca_children(req, path, ca).await,
             Some("history") => ca_history(req, path, ca).await,
             Some("command") => ca_command_details(req, path, ca).await,
+            Some("export") => ca_export(req, ca).await,
+            Some("import") => ca_import(req, ca).await,
             Some("id") => ca_regenerate_id(req, ca).await,
             Some("issues") => ca_issues(req, ca).await,
             Some("keys") => ca_keys(req, path, ca).await,
@@ -441,6 +471,9 @@ async fn api_cas(req: Request, path: &mut RequestPath) -> RoutingResult {
             Some("parents-xml") => ca_add_parent_xml(req, path, ca).await,
             Some("repo") => api_ca_repo(req, path, ca).await,
             Some("routes") => api_ca_routes(req, path, ca).await,
+            Some("aspas") => api_ca_aspas(req, path, ca).await,
+            Some("ghostbusters") => api_ca_ghostbusters(req, path, ca).await,
+            Some("timing") => api_ca_timing(req, path, ca).await,
             _ => render_unknown_method(),
         },
         None => match *req.method() {
@@ -453,9 +486,14 @@ async fn api_cas(req: Request, path: &mut RequestPath) -> RoutingResult {
 
 async fn ca_keys(req: Request, path: &mut RequestPath, ca: Handle) -> RoutingResult {
     match *req.method() {
+        Method::GET => match path.next() {
+            Some("policy") => ca_kr_policy_show(req, ca).await,
+            _ => render_unknown_method(),
+        },
         Method::POST => match path.next() {
             Some("roll_init") => ca_kr_init(req, ca).await,
             Some("roll_activate") => ca_kr_activate(req, ca).await,
+            Some("policy") => ca_kr_policy_update(req, ca).await,
             _ => render_unknown_method(),
         },
         _ => render_unknown_method(),
@@ -499,6 +537,79 @@ async fn api_ca_routes(req: Request, path: &mut RequestPath, ca: Handle) -> Rout
             Method::POST => ca_routes_update(req, ca).await,
             _ => render_unknown_method(),
         },
+        Some("import") => match *req.method() {
+            Method::POST => ca_routes_import(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        Some("export") => match path.path_arg() {
+            Some(format) => match *req.method() {
+                Method::GET => ca_routes_export(req, ca, format).await,
+                _ => render_unknown_method(),
+            },
+            None => render_unknown_resource(),
+        },
+        Some("policy") => match *req.method() {
+            Method::GET => ca_routes_policy_show(req, ca).await,
+            Method::POST => ca_routes_policy_update(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        Some("analysis") => match path.next() {
+            Some("full") => match *req.method() {
+                Method::GET => ca_routes_bgp_analysis(req, ca).await,
+                _ => render_unknown_method(),
+            },
+            Some("dryrun") => match *req.method() {
+                Method::POST => ca_routes_bgp_dry_run(req, ca).await,
+                _ => render_unknown_method(),
+            },
+            Some("suggest") => match *req.method() {
+                Method::GET => ca_routes_bgp_suggest(req, ca).await,
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_aspas(req: Request, path: &mut RequestPath, ca: Handle) -> RoutingResult {
+    match path.next() {
+        None => match *req.method() {
+            Method::GET => ca_aspas_show(req, ca).await,
+            Method::POST => ca_aspas_update(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        Some("as") => match path.next().map(AsNumber::from_str) {
+            Some(Ok(customer)) => match *req.method() {
+                Method::POST => ca_aspas_update_providers(req, ca, customer).await,
+                _ => render_unknown_method(),
+            },
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_ghostbusters(req: Request, path: &mut RequestPath, ca: Handle) -> RoutingResult {
+    match path.next() {
+        None => match *req.method() {
+            Method::GET => ca_ghostbuster_show(req, ca).await,
+            Method::POST => ca_ghostbuster_update(req, ca).await,
+            Method::DELETE => ca_ghostbuster_remove(req, ca).await,
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
+async fn api_ca_timing(req: Request, path: &mut RequestPath, ca: Handle) -> RoutingResult {
+    match path.next() {
+        None => match *req.method() {
+            Method::GET => ca_publication_timing_show(req, ca).await,
+            Method::POST => ca_publication_timing_update(req, ca).await,
+            Method::DELETE => ca_publication_timing_reset(req, ca).await,
+            _ => render_unknown_method(),
+        },
         _ => render_unknown_method(),
     }
 }
@@ -511,13 +622,21 @@ async fn api_publishers(req: Request, path: &mut RequestPath) -> RoutingResult {
                 Some("response.xml") => repository_response_xml(req, publisher).await,
                 Some("response.json") => repository_response_json(req, publisher).await,
                 Some("stale") => stale_publishers(req, path.next()).await,
+                Some("history") => publisher_history(req, path, publisher).await,
+                Some("objects") => show_pbl_objects(req, publisher).await,
                 _ => render_unknown_method(),
             },
             None => list_pbl(req).await,
         },
-        Method::POST => match path.next() {
+        Method::POST => match path.path_arg() {
+            Some(publisher) => match path.next() {
+                Some("quota") => update_pbl_quota(req, publisher).await,
+                Some("id") => update_pbl_id_cert(req, publisher).await,
+                Some("suspend") => suspend_pbl(req, publisher).await,
+                Some("reactivate") => reactivate_pbl(req, publisher).await,
+                _ => render_unknown_method(),
+            },
             None => add_pbl(req).await,
-            _ => render_unknown_method(),
         },
         Method::DELETE => match path.path_arg() {
             Some(publisher) => remove_pbl(req, publisher).await,
@@ -527,6 +646,25 @@ async fn api_publishers(req: Request, path: &mut RequestPath) -> RoutingResult {
     }
 }
 
+/// Operations on the publication server as a whole, kept out of the
+/// publishers path so that they cannot clash with publisher handles.
+async fn api_pubd(req: Request, path: &mut RequestPath) -> RoutingResult {
+    match *req.method() {
+        Method::GET => match path.next() {
+            Some("verify") => verify_repository(req, false).await,
+            Some("rsyncd-conf") => rsyncd_conf(req).await,
+            Some("issues") => repository_issues(req).await,
+            _ => render_unknown_method(),
+        },
+        Method::POST => match path.next() {
+            Some("verify") => verify_repository(req, true).await,
+            Some("session-reset") => reset_rrdp_session(req).await,
+            _ => render_unknown_method(),
+        },
+        _ => render_unknown_method(),
+    }
+}
+
 //------------ Admin: Publishers ---------------------------------------------
 
 /// Returns a list of publisher which have not updated for more
@@ -534,22 +672,32 @@ async fn api_publishers(req: Request, path: &mut RequestPath) -> RoutingResult {
 pub async fn stale_publishers(req: Request, seconds: Option<&str>) -> RoutingResult {
     let seconds = seconds.unwrap_or("");
     match i64::from_str(seconds) {
-        Ok(seconds) => render_json_res(req.state().read().await.repo_stats().map(|stats| {
-            PublisherList::build(&stats.stale_publishers(seconds), "/api/v1/publishers")
-        })),
+        Ok(seconds) => {
+            let server = req.state().read().await;
+            render_json_res(server.repo_stats().and_then(|stats| {
+                let suspended = server.suspended_publishers()?;
+                Ok(PublisherList::build(
+                    &stats.stale_publishers(seconds),
+                    &suspended,
+                    "/api/v1/publishers",
+                ))
+            }))
+        }
         Err(_) => render_error(Error::ApiInvalidSeconds),
     }
 }
 
 /// Returns a json structure with all publishers in it.
 pub async fn list_pbl(req: Request) -> RoutingResult {
-    render_json_res(
-        req.state()
-            .read()
-            .await
-            .publishers()
-            .map(|publishers| PublisherList::build(&publishers, "/api/v1/publishers")),
-    )
+    let server = req.state().read().await;
+    render_json_res(server.publishers().and_then(|publishers| {
+        let suspended = server.suspended_publishers()?;
+        Ok(PublisherList::build(
+            &publishers,
+            &suspended,
+            "/api/v1/publishers",
+        ))
+    }))
 }
 
 /// Adds a publisher
@@ -561,6 +709,80 @@ async fn add_pbl(req: Request) -> RoutingResult {
     }
 }
 
+/// Updates the quota for a publisher
+async fn update_pbl_quota(req: Request, publisher: Handle) -> RoutingResult {
+    let server = req.state().clone();
+    match req.json().await {
+        Ok(quota) => render_empty_res(server.read().await.update_publisher_quota(publisher, quota)),
+        Err(e) => render_error(e),
+    }
+}
+
+/// Replaces the identity certificate for a publisher with the one in the
+/// posted RFC8183 Publisher Request
+async fn update_pbl_id_cert(req: Request, publisher: Handle) -> RoutingResult {
+    let server = req.state().clone();
+    match req.json().await {
+        Ok(pbl_req) => render_empty_res(
+            server
+                .read()
+                .await
+                .update_publisher_id_cert(publisher, pbl_req),
+        ),
+        Err(e) => render_error(e),
+    }
+}
+
+/// Returns the history of publish requests for a publisher
+async fn publisher_history(
+    req: Request,
+    path: &mut RequestPath,
+    publisher: Handle,
+) -> RoutingResult {
+    // /api/v1/publishers/{publisher}/history/<rows>/<offset>/<after>/<before>
+    let crit = parse_history_range(path, CommandHistoryCriteria::default());
+    render_json_res(req.state().read().await.publisher_history(&publisher, crit))
+}
+
+/// Returns the objects published by a publisher, with decoded details
+async fn show_pbl_objects(req: Request, publisher: Handle) -> RoutingResult {
+    render_json_res(req.state().read().await.get_publisher_objects(&publisher))
+}
+
+/// Suspends a publisher
+async fn suspend_pbl(req: Request, publisher: Handle) -> RoutingResult {
+    render_empty_res(req.state().read().await.suspend_publisher(publisher))
+}
+
+/// Reactivates a suspended publisher
+async fn reactivate_pbl(req: Request, publisher: Handle) -> RoutingResult {
+    render_empty_res(req.state().read().await.reactivate_publisher(publisher))
+}
+
+/// Starts a new RRDP session
+async fn reset_rrdp_session(req: Request) -> RoutingResult {
+    render_empty_res(req.state().read().await.reset_rrdp_session())
+}
+
+/// Compares the published content with the objects of all publishers. When
+/// posted to, any differences found are also repaired.
+async fn verify_repository(req: Request, repair: bool) -> RoutingResult {
+    render_json_res(req.state().read().await.verify_repository(repair))
+}
+
+/// Returns the publishers which are stale
+async fn repository_issues(req: Request) -> RoutingResult {
+    render_json_res(req.state().read().await.repository_issues())
+}
+
+/// Returns an rsyncd.conf module stanza for the rsync tree
+async fn rsyncd_conf(req: Request) -> RoutingResult {
+    match req.state().read().await.rsyncd_conf() {
+        Ok(stanza) => Ok(HttpResponse::text(stanza.into_bytes())),
+        Err(e) => render_error(e),
+    }
+}
+
 /// Removes a publisher. Should be idempotent! If if did not exist then
 /// that's just fine.
 #[allow(clippy::needless_pass_by_value)]
@@ -727,6 +949,36 @@ pub async fn ca_init(req: Request) -> RoutingResult {
     }
 }
 
+async fn ca_delete(req: Request, handle: Handle) -> RoutingResult {
+    render_empty_res(req.state().read().await.ca_delete(&handle).await)
+}
+
+async fn ca_export(req: Request, handle: Handle) -> RoutingResult {
+    match *req.method() {
+        Method::POST => {
+            let state = req.state().clone();
+            match req.json().await {
+                Ok(export) => render_json_res(state.read().await.ca_export(&handle, export)),
+                Err(e) => render_error(e),
+            }
+        }
+        _ => render_unknown_method(),
+    }
+}
+
+async fn ca_import(req: Request, handle: Handle) -> RoutingResult {
+    match *req.method() {
+        Method::POST => {
+            let state = req.state().clone();
+            match req.json().await {
+                Ok(import) => render_empty_res(state.read().await.ca_import(&handle, import).await),
+                Err(e) => render_error(e),
+            }
+        }
+        _ => render_unknown_method(),
+    }
+}
+
 async fn ca_regenerate_id(req: Request, handle: Handle) -> RoutingResult {
     match *req.method() {
         Method::POST => render_empty_res(req.state().read().await.ca_update_id(handle)),
@@ -791,29 +1043,37 @@ fn parse_history_path(path: &mut RequestPath) -> Option<CommandHistoryCriteria>
         _ => return None,
     };
 
+    Some(parse_history_range(path, crit))
+}
+
+fn parse_history_range(
+    path: &mut RequestPath,
+    mut crit: CommandHistoryCriteria,
+) -> CommandHistoryCriteria {
+    // .../<rows>/<offset>/<after>/<before>
     if let Some(rows) = path.path_arg() {
         crit.set_rows(rows);
     } else {
-        return Some(crit);
+        return crit;
     }
 
     if let Some(offset) = path.path_arg() {
         crit.set_offset(offset);
     } else {
-        return Some(crit);
+        return crit;
     }
 
     if let Some(after) = path.path_arg() {
         crit.set_after(after);
     } else {
-        return Some(crit);
+        return crit;
     }
 
     if let Some(before) = path.path_arg() {
         crit.set_before(before);
     }
 
-    Some(crit)
+    crit
 }
 
 async fn ca_command_details(req: Request, path: &mut RequestPath, handle: Handle) -> RoutingResult {
@@ -1028,6 +1288,24 @@ async fn ca_kr_activate(req: Request, handle: Handle) -> RoutingResult {
     render_empty_res(req.state().read().await.ca_keyroll_activate(handle))
 }
 
+/// Set the policy for automatic key rolls.
+async fn ca_kr_policy_update(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(policy) => render_empty_res(state.read().await.ca_keyroll_policy_update(handle, policy)),
+    }
+}
+
+/// Show the policy for automatic key rolls.
+async fn ca_kr_policy_show(req: Request, handle: Handle) -> RoutingResult {
+    match req.state().read().await.ca_keyroll_policy_show(&handle) {
+        Ok(policy) => render_json(policy),
+        Err(_) => render_unknown_resource(),
+    }
+}
+
 //------------ Admin: Force republish ----------------------------------------
 
 /// Update the route authorizations for this CA
@@ -1048,6 +1326,160 @@ async fn ca_routes_show(req: Request, handle: Handle) -> RoutingResult {
     }
 }
 
+/// import ROA definitions in bulk for this CA
+async fn ca_routes_import(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(import) => render_json_res(state.read().await.ca_routes_import(handle, import)),
+    }
+}
+
+/// export the ROA definitions for this CA in the requested format
+async fn ca_routes_export(
+    req: Request,
+    handle: Handle,
+    format: RoaDefinitionFormat,
+) -> RoutingResult {
+    match req.state().read().await.ca_routes_export(&handle, format) {
+        Ok(exported) => Ok(HttpResponse::text(exported.into_bytes())),
+        Err(e) => render_error(e),
+    }
+}
+
+/// Update the ROA packing policy for this CA
+async fn ca_routes_policy_update(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(policy) => render_empty_res(state.read().await.ca_routes_policy_update(handle, policy)),
+    }
+}
+
+/// show the ROA packing policy for this CA
+async fn ca_routes_policy_show(req: Request, handle: Handle) -> RoutingResult {
+    match req.state().read().await.ca_routes_policy_show(&handle) {
+        Ok(policy) => render_json(policy),
+        Err(_) => render_unknown_resource(),
+    }
+}
+
+/// show how the current ROAs of this CA affect the BGP announcements
+async fn ca_routes_bgp_analysis(req: Request, handle: Handle) -> RoutingResult {
+    render_json_res(req.state().read().await.ca_routes_bgp_analysis(&handle))
+}
+
+/// show how the posted ROA updates would affect the BGP announcements
+async fn ca_routes_bgp_dry_run(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(updates) => render_json_res(state.read().await.ca_routes_bgp_dry_run(&handle, updates)),
+    }
+}
+
+/// suggest ROA updates for this CA based on the BGP announcements seen
+async fn ca_routes_bgp_suggest(req: Request, handle: Handle) -> RoutingResult {
+    render_json_res(req.state().read().await.ca_routes_bgp_suggest(&handle))
+}
+
+//------------ Admin: ASPA ---------------------------------------------------
+
+/// Add, replace or remove ASPA definitions for this CA
+async fn ca_aspas_update(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(updates) => render_empty_res(state.read().await.ca_aspas_update(handle, updates)),
+    }
+}
+
+/// Update the providers for an existing ASPA definition of this CA
+async fn ca_aspas_update_providers(
+    req: Request,
+    handle: Handle,
+    customer: AsNumber,
+) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(update) => render_empty_res(
+            state
+                .read()
+                .await
+                .ca_aspas_update_providers(handle, customer, update),
+        ),
+    }
+}
+
+/// show the ASPA definitions for this CA
+async fn ca_aspas_show(req: Request, handle: Handle) -> RoutingResult {
+    match req.state().read().await.ca_aspas_show(&handle) {
+        Ok(aspas) => render_json(aspas),
+        Err(_) => render_unknown_resource(),
+    }
+}
+
+//------------ Admin: Ghostbusters -------------------------------------------
+
+/// Set the Ghostbusters contact details for this CA
+async fn ca_ghostbuster_update(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(vcard) => render_empty_res(state.read().await.ca_ghostbuster_update(handle, vcard)),
+    }
+}
+
+/// Remove the Ghostbusters record for this CA
+async fn ca_ghostbuster_remove(req: Request, handle: Handle) -> RoutingResult {
+    render_empty_res(req.state().read().await.ca_ghostbuster_remove(handle))
+}
+
+/// show the Ghostbusters contact details for this CA, if any
+async fn ca_ghostbuster_show(req: Request, handle: Handle) -> RoutingResult {
+    match req.state().read().await.ca_ghostbuster_show(&handle) {
+        Ok(vcard) => render_json(vcard),
+        Err(_) => render_unknown_resource(),
+    }
+}
+
+//------------ Publication timing --------------------------------------------
+
+/// Set the timing for manifests and CRLs for this CA
+async fn ca_publication_timing_update(req: Request, handle: Handle) -> RoutingResult {
+    let state = req.state().clone();
+
+    match req.json().await {
+        Err(e) => render_error(e),
+        Ok(timing) => render_empty_res(
+            state
+                .read()
+                .await
+                .ca_publication_timing_update(&handle, timing),
+        ),
+    }
+}
+
+/// Revert to the configured default timing for this CA
+async fn ca_publication_timing_reset(req: Request, handle: Handle) -> RoutingResult {
+    render_empty_res(req.state().read().await.ca_publication_timing_reset(&handle))
+}
+
+/// Show the effective timing for manifests and CRLs for this CA
+async fn ca_publication_timing_show(req: Request, handle: Handle) -> RoutingResult {
+    match req.state().read().await.ca_publication_timing_show(&handle) {
+        Ok(timing) => render_json(timing),
+        Err(_) => render_unknown_resource(),
+    }
+}
+
 //------------ Admin: Force republish ----------------------------------------
 
 async fn republish_all(req: Request) -> RoutingResult {
@@ -1074,27 +1506,66 @@ async fn refresh_all(req: Request) -> RoutingResult {
 
 //------------ Serve RRDP Files ----------------------------------------------
 
+/// Serves the RRDP files written by the embedded repository. The
+/// notification file can be revalidated using its ETag, all other files
+/// (snapshots and deltas) never change and can be cached indefinitely.
 async fn rrdp(req: Request) -> RoutingResult {
     if !req.path().full().starts_with("/rrdp/") {
         Err(req) // Not for us
+    } else if *req.method() != Method::GET {
+        render_unknown_method()
     } else {
-        let mut full_path: PathBuf = req.state.read().await.rrdp_base_path();
         let (_, path) = req.path.remaining().split_at(1);
+        if path.split('/').any(|part| part == "..") {
+            return Ok(HttpResponse::not_found());
+        }
+
+        let mut full_path: PathBuf = req.state.read().await.rrdp_base_path();
         full_path.push(path);
 
-        match File::open(full_path) {
-            Ok(mut file) => {
-                use std::io::Read;
-                let mut buffer = Vec::new();
-                file.read_to_end(&mut buffer).unwrap();
+        let (buffer, modified) = match read_rrdp_file(&full_path) {
+            Ok(res) => res,
+            Err(_) => return Ok(HttpResponse::not_found()),
+        };
 
-                Ok(HttpResponse::xml(buffer))
+        if path == "notification.xml" {
+            let etag = format!("\"{}\"", HexEncodedHash::from_content(&buffer));
+
+            if let Some(if_none_match) = req.header("If-None-Match") {
+                if etag_matches(if_none_match, &etag) {
+                    return Ok(HttpResponse::not_modified(etag));
+                }
             }
-            _ => Ok(HttpResponse::not_found()),
+
+            let last_modified = DateTime::<Utc>::from(modified)
+                .format("%a, %d %b %Y %H:%M:%S GMT")
+                .to_string();
+
+            Ok(HttpResponse::rrdp_notification(buffer, etag, last_modified))
+        } else {
+            Ok(HttpResponse::rrdp_immutable(buffer))
         }
     }
 }
 
+fn read_rrdp_file(path: &PathBuf) -> Result<(Vec<u8>, SystemTime), std::io::Error> {
+    let mut file = File::open(path)?;
+    let modified = file.metadata()?.modified()?;
+
+    let mut buffer = Vec::new();
+    file.read_to_end(&mut buffer)?;
+
+    Ok((buffer, modified))
+}
+
+/// Checks whether an If-None-Match header value matches the ETag.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.trim() == "*"
+        || if_none_match
+            .split(',')
+            .any(|tag| tag.trim().trim_start_matches("W/") == etag)
+}
+
 //------------ Tests ---------------------------------------------------------
 #[cfg(test)]
 mod tests {
@@ -1105,6 +1576,17 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn should_match_etags() {
+        let etag = "\"abc\"";
+        assert!(etag_matches("\"abc\"", etag));
+        assert!(etag_matches("W/\"abc\"", etag));
+        assert!(etag_matches("\"def\", \"abc\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("\"def\"", etag));
+        assert!(!etag_matches("abc", etag));
+    }
+
     #[tokio::test]
     async fn start_tls_server() {
         let dir = test::sub_dir(&PathBuf::from("work"));
@@ -11,13 +11,18 @@ use rpki::uri;
 use rpki::x509::Time;
 
 use crate::commons::api::{
-    AddChildRequest, AllCertAuthIssues, CaCommandDetails, CaRepoDetails, CertAuthInfo,
-    CertAuthInit, CertAuthIssues, CertAuthList, CertAuthStats, ChildCaInfo, ChildHandle,
-    CommandHistory, CommandHistoryCriteria, CurrentRepoState, Handle, ListReply, ParentCaContact,
-    ParentCaReq, ParentHandle, PublishDelta, PublisherDetails, PublisherHandle, RepoInfo,
-    RepositoryContact, RepositoryUpdate, RoaDefinition, RoaDefinitionUpdates, ServerInfo,
-    TaCertDetails, UpdateChildRequest,
+    AddChildRequest, AllCertAuthIssues, AsNumber, AspaDefinition, AspaDefinitionUpdates,
+    AspaProvidersUpdate, CaCommandDetails, CaExportArchive, CaExportRequest, CaImportRequest,
+    CaRepoDetails, CertAuthInfo, CertAuthInit, CertAuthIssues, CertAuthList, CertAuthStats,
+    ChildCaInfo, ChildHandle, CommandHistory, CommandHistoryCriteria, CurrentRepoState,
+    GhostbusterVcard, Handle, KeyRollPolicy, ListReply, ParentCaContact, ParentCaReq, ParentHandle,
+    PublicationTiming, PublishDelta, PublisherDetails, PublisherHandle, PublisherHistory,
+    PublisherObjects, PublisherQuota, RepoInfo, RepositoryContact, RepositoryIssues,
+    RepositoryUpdate, RepositoryVerification, RoaDefinition, RoaDefinitionFormat,
+    RoaDefinitionUpdates, RoaImport, RoaPackingPolicy, ServerInfo, TaCertDetails,
+    UpdateChildRequest,
 };
+use crate::commons::bgp::{BgpAnalyser, BgpAnalysisDryRun, BgpAnalysisReport};
 use crate::commons::error::Error;
 use crate::commons::eventsourcing::CommandKey;
 use crate::commons::remote::rfc8183;
@@ -52,6 +57,9 @@ pub struct KrillServer {
     // Handles the internal TA and/or CAs
     caserver: Arc<ca::CaServer<OpenSslSigner>>,
 
+    // Analyses ROAs against the BGP announcements, if loaded
+    bgp_analyser: Arc<BgpAnalyser>,
+
     // Responsible for background tasks, e.g. re-publishing
     #[allow(dead_code)] // just need to keep this in scope
     scheduler: Scheduler,
@@ -61,6 +69,9 @@ pub struct KrillServer {
 
     // Global size constraints on things which can be posted
     post_limits: PostLimits,
+
+    // Quota for newly added publishers
+    publisher_quota: PublisherQuota,
 }
 
 pub struct PostLimits {
@@ -114,16 +125,23 @@ impl KrillServer {
 
         let pubserver = {
             if config.repo_enabled {
-                Some(PubServer::build(
-                    &base_uri,
-                    rrdp_base_uri.clone(),
-                    work_dir,
-                    config.rfc8181_log_dir.as_ref(),
-                    signer.clone(),
-                    config.repo_pub_key(),
-                    config.tal_pub_key(),
-                    config.ipfs_path()
-                )?)
+                Some(
+                    PubServer::build(
+                        &base_uri,
+                        rrdp_base_uri.clone(),
+                        work_dir,
+                        config.rfc8181_log_dir.as_ref(),
+                        signer.clone(),
+                        config.repo_pub_key(),
+                        config.tal_pub_key(),
+                        config.ipfs_path(),
+                        config.ipfs_pubsub_topic.clone(),
+                        config.rrdp_retention(),
+                    )?
+                    .with_object_validation(config.validate_published_objects)
+                    .with_rsync_versions_retain(config.rsync_versions_retain)
+                    .with_stale_publisher_threshold(config.stale_publisher_threshold()),
+                )
             } else {
                 PubServer::remove_if_empty(
                     &base_uri,
@@ -134,7 +152,15 @@ impl KrillServer {
                     config.repo_pub_key(),
                     config.tal_pub_key(),
                     config.ipfs_path(),
+                    config.ipfs_pubsub_topic.clone(),
+                    config.rrdp_retention(),
                 )?
+                .map(|server| {
+                    server
+                        .with_object_validation(config.validate_published_objects)
+                        .with_rsync_versions_retain(config.rsync_versions_retain)
+                        .with_stale_publisher_threshold(config.stale_publisher_threshold())
+                })
             }
         };
         let pubserver: Option<Arc<PubServer>> = pubserver.map(Arc::new);
@@ -146,6 +172,7 @@ impl KrillServer {
             config.rfc6492_log_dir.as_ref(),
             event_queue.clone(),
             signer,
+            config.publication_timing(),
         )?);
 
         if config.use_ta() {
@@ -172,18 +199,25 @@ impl KrillServer {
                 let req =
                     rfc8183::PublisherRequest::new(None, ta_handle.clone(), ta.id_cert().clone());
 
-                pubserver.create_publisher(req)?;
+                pubserver.create_publisher(req, PublisherQuota::default())?;
 
                 // Force initial  publication
                 caserver.republish(&ta_handle)?;
             }
         }
 
+        let bgp_analyser = Arc::new(BgpAnalyser::new(config.bgp_announcements_file.clone()));
+        if let Err(e) = bgp_analyser.update() {
+            error!("Could not load BGP announcements: {}", e);
+        }
+
         let scheduler = Scheduler::build(
             event_queue,
             caserver.clone(),
             pubserver.clone(),
+            bgp_analyser.clone(),
             ca_refresh_rate,
+            config.bgp_announcements_refresh_mins,
         );
 
         let post_limits = PostLimits::new(
@@ -198,9 +232,11 @@ impl KrillServer {
             authorizer,
             pubserver,
             caserver,
+            bgp_analyser,
             scheduler,
             started: Time::now(),
             post_limits,
+            publisher_quota: config.publisher_quota.clone(),
         })
     }
 
@@ -245,23 +281,76 @@ impl KrillServer {
         self.get_embedded()?.repo_stats()
     }
 
+    /// Returns the issues found for the publishers in the repository, i.e.
+    /// the publishers which are stale.
+    pub fn repository_issues(&self) -> KrillResult<RepositoryIssues> {
+        self.get_embedded()?.repository_issues()
+    }
+
     /// Returns all currently configured publishers. (excludes deactivated)
     pub fn publishers(&self) -> KrillResult<Vec<Handle>> {
         self.get_embedded()?.publishers()
     }
 
-    /// Adds the publishers, blows up if it already existed.
+    /// Returns the history of publish requests for a publisher.
+    pub fn publisher_history(
+        &self,
+        publisher: &PublisherHandle,
+        crit: CommandHistoryCriteria,
+    ) -> KrillResult<PublisherHistory> {
+        self.get_embedded()?.publisher_history(publisher, crit)
+    }
+
+    /// Returns all currently suspended publishers.
+    pub fn suspended_publishers(&self) -> KrillResult<Vec<Handle>> {
+        self.get_embedded()?.suspended_publishers()
+    }
+
+    /// Adds the publishers, blows up if it already existed. The publisher
+    /// gets the configured default quota.
     pub fn add_publisher(
         &self,
         req: rfc8183::PublisherRequest,
     ) -> KrillResult<rfc8183::RepositoryResponse> {
         let publisher_handle = req.publisher_handle().clone();
 
-        self.get_embedded()?.create_publisher(req)?;
+        self.get_embedded()?
+            .create_publisher(req, self.publisher_quota.clone())?;
 
         self.repository_response(&publisher_handle)
     }
 
+    /// Updates the quota for a publisher, blows up if it didn't exist.
+    pub fn update_publisher_quota(
+        &self,
+        publisher: PublisherHandle,
+        quota: PublisherQuota,
+    ) -> KrillEmptyResult {
+        self.get_embedded()?
+            .update_publisher_quota(publisher, quota)
+    }
+
+    /// Replaces the identity certificate of a publisher with the one in
+    /// the request, blows up if it didn't exist.
+    pub fn update_publisher_id_cert(
+        &self,
+        publisher: PublisherHandle,
+        req: rfc8183::PublisherRequest,
+    ) -> KrillEmptyResult {
+        self.get_embedded()?
+            .update_publisher_id_cert(publisher, req)
+    }
+
+    /// Suspends a publisher, blows up if it didn't exist.
+    pub fn suspend_publisher(&self, publisher: PublisherHandle) -> KrillEmptyResult {
+        self.get_embedded()?.suspend_publisher(publisher)
+    }
+
+    /// Reactivates a suspended publisher, blows up if it didn't exist.
+    pub fn reactivate_publisher(&self, publisher: PublisherHandle) -> KrillEmptyResult {
+        self.get_embedded()?.reactivate_publisher(publisher)
+    }
+
     /// Removes a publisher, blows up if it didn't exist.
     pub fn remove_publisher(&mut self, publisher: PublisherHandle) -> KrillEmptyResult {
         self.get_embedded()?.remove_publisher(publisher)
@@ -272,6 +361,30 @@ impl KrillServer {
         self.get_embedded()?.get_publisher_details(publisher)
     }
 
+    /// Returns the objects published by a publisher, with decoded details.
+    pub fn get_publisher_objects(
+        &self,
+        publisher: &PublisherHandle,
+    ) -> KrillResult<PublisherObjects> {
+        self.get_embedded()?.get_publisher_objects(publisher)
+    }
+
+    /// Starts a new RRDP session for the embedded repository.
+    pub fn reset_rrdp_session(&self) -> KrillEmptyResult {
+        self.get_embedded()?.reset_rrdp_session()
+    }
+
+    /// Compares the published rsync, RRDP and IPFS content with the objects
+    /// of all publishers, and optionally rebuilds it.
+    pub fn verify_repository(&self, repair: bool) -> KrillResult<RepositoryVerification> {
+        self.get_embedded()?.verify_repository(repair)
+    }
+
+    /// Returns an rsyncd.conf module stanza derived from the configuration.
+    pub fn rsyncd_conf(&self) -> KrillResult<String> {
+        self.get_embedded()?.rsyncd_conf()
+    }
+
     pub fn rrdp_base_path(&self) -> PathBuf {
         let mut path = self.work_dir.clone();
         path.push("repo/rrdp");
@@ -492,6 +605,27 @@ impl KrillServer {
             }
         }
 
+        let now = Time::now();
+        for (rcn, status) in ca.keyroll_status() {
+            if status.is_stalled(now) {
+                let e = Error::KeyRollStalled(ca_handle.clone(), rcn.clone(), status);
+                issues.add_key_roll_issue(rcn, e.to_error_response());
+            }
+        }
+
+        let soon = now + Duration::days(CHILD_LEASE_EXPIRING_DAYS);
+        for child_handle in ca.children() {
+            let child = ca.get_child(child_handle)?;
+            if let Some(lease) = child.leases().iter().find(|l| l.is_expired(soon)) {
+                let e = Error::CaChildLeaseExpiring(
+                    ca_handle.clone(),
+                    child_handle.clone(),
+                    lease.clone(),
+                );
+                issues.add_child_issue(child_handle.clone(), e.to_error_response());
+            }
+        }
+
         Ok(issues)
     }
 }
@@ -535,7 +669,7 @@ impl KrillServer {
 
     /// Returns the public CA info for a CA, or NONE if the CA cannot be found.
     pub fn ca_info(&self, handle: &Handle) -> KrillResult<CertAuthInfo> {
-        self.caserver.get_ca(handle).map(|ca| ca.as_ca_info())
+        self.caserver.get_ca_info(handle)
     }
 
     /// Returns the parent contact for a CA and parent, or NONE if either the CA or the parent cannot be found.
@@ -582,6 +716,105 @@ impl KrillServer {
         Ok(())
     }
 
+    /// Deletes a CA. Its objects are withdrawn from its repository first,
+    /// on a best effort basis. If the CA uses the embedded repository, then
+    /// it is also removed as a publisher there. See `CaServer::delete_ca`
+    /// for the rest.
+    pub async fn ca_delete(&self, handle: &Handle) -> KrillEmptyResult {
+        let ca = self.caserver.get_ca_for_deletion(handle)?;
+
+        let publisher = CaPublisher::new(self.caserver.clone(), self.pubserver.clone());
+        if let Err(e) = publisher.withdraw_all(handle).await {
+            warn!(
+                "Could not withdraw objects for CA '{}' from its repository, error: {}",
+                handle, e
+            );
+        }
+
+        self.caserver.delete_ca(handle).await?;
+
+        if let Ok(contact) = ca.get_repository_contact() {
+            if contact.is_embedded() {
+                let removed = self
+                    .get_embedded()
+                    .and_then(|pubserver| pubserver.remove_publisher(handle.clone()));
+                if let Err(e) = removed {
+                    warn!(
+                        "Could not remove CA '{}' as publisher from the embedded repository, error: {}",
+                        handle, e
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Exports a CA, including its keys, encrypted with the passphrase in
+    /// the request.
+    pub fn ca_export(&self, handle: &Handle, req: CaExportRequest) -> KrillResult<CaExportArchive> {
+        self.caserver.export_ca(handle, req.passphrase())
+    }
+
+    /// Imports a CA that was exported from another Krill server. If the CA
+    /// uses the embedded repository, then it is added as a publisher here
+    /// and moved to this server's repository if needed. Once imported the
+    /// CA is republished, and asks its parents for updates. Failures to
+    /// contact the repository or parents are logged, but the CA is kept.
+    pub async fn ca_import(&self, handle: &Handle, req: CaImportRequest) -> KrillEmptyResult {
+        let archive = req.archive();
+        if archive.handle() != handle {
+            return Err(Error::CaImportInvalid(
+                handle.clone(),
+                format!("archive is for CA '{}'", archive.handle()),
+            ));
+        }
+
+        self.caserver
+            .import_ca(archive, req.passphrase(), self.pubserver.is_some())?;
+
+        let ca = self.caserver.get_ca(handle)?;
+        if let Ok(contact) = ca.get_repository_contact() {
+            if contact.is_embedded() {
+                let pubserver = self.get_embedded()?;
+                if pubserver.get_publisher_details(handle).is_err() {
+                    let id_cert = ca.id_cert().clone();
+                    let req = rfc8183::PublisherRequest::new(None, handle.clone(), id_cert);
+                    self.add_publisher(req)?;
+                }
+
+                let repo_info = pubserver.repo_info_for(handle)?;
+                if contact.repo_info() != &repo_info {
+                    self.caserver
+                        .update_repo(handle.clone(), RepositoryContact::embedded(repo_info))?;
+                }
+            }
+        }
+
+        if let Err(e) = self.caserver.republish(handle) {
+            warn!("Could not republish imported CA '{}', error: {}", handle, e);
+        }
+
+        let publisher = CaPublisher::new(self.caserver.clone(), self.pubserver.clone());
+        if let Err(e) = publisher.publish(handle).await {
+            warn!(
+                "Could not publish imported CA '{}' to its repository, error: {}",
+                handle, e
+            );
+        }
+
+        for parent in ca.parents() {
+            if let Err(e) = self.caserver.get_updates_from_parent(handle, parent).await {
+                warn!(
+                    "Could not get updates for imported CA '{}' from parent '{}', error: {}",
+                    handle, parent, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// Return the info about the configured repository server for a given Ca.
     /// and the actual objects published there, as reported by a list reply.
     pub fn ca_repo_details(&self, handle: &Handle) -> KrillResult<CaRepoDetails> {
@@ -668,6 +901,19 @@ impl KrillServer {
             .ca_keyroll_activate(handle, Duration::seconds(0))?)
     }
 
+    pub fn ca_keyroll_policy_update(
+        &self,
+        handle: Handle,
+        policy: KeyRollPolicy,
+    ) -> KrillEmptyResult {
+        Ok(self.caserver.ca_keyroll_policy_update(handle, policy)?)
+    }
+
+    pub fn ca_keyroll_policy_show(&self, handle: &Handle) -> KrillResult<KeyRollPolicy> {
+        let ca = self.caserver.get_ca(handle)?;
+        Ok(ca.keyroll_policy().clone())
+    }
+
     pub fn rfc6492(&self, handle: Handle, msg_bytes: Bytes) -> KrillResult<Bytes> {
         Ok(self.caserver.rfc6492(&handle, msg_bytes)?)
     }
@@ -688,6 +934,166 @@ impl KrillServer {
         let ca = self.caserver.get_ca(handle)?;
         Ok(ca.roa_definitions())
     }
+
+    /// Imports ROA definitions in bulk, either adding them to the current
+    /// definitions or replacing those. Returns the updates that were applied.
+    pub fn ca_routes_import(
+        &self,
+        handle: Handle,
+        import: RoaImport,
+    ) -> KrillResult<RoaDefinitionUpdates> {
+        let imported = import
+            .format()
+            .parse(import.content())
+            .map_err(|e| Error::CaRoaImportInvalid(handle.clone(), e))?;
+
+        let current = self.caserver.get_ca(&handle)?.roa_definitions();
+
+        let updates = if import.replace() {
+            RoaDefinitionUpdates::replace(&current, &imported)
+        } else {
+            RoaDefinitionUpdates::add_missing(&current, &imported)
+        };
+
+        if !updates.is_empty() {
+            self.caserver
+                .ca_routes_update(handle, updates.clone().into())?;
+        }
+
+        Ok(updates)
+    }
+
+    pub fn ca_routes_export(
+        &self,
+        handle: &Handle,
+        format: RoaDefinitionFormat,
+    ) -> KrillResult<String> {
+        let ca = self.caserver.get_ca(handle)?;
+        Ok(format.format(&ca.roa_definitions()))
+    }
+
+    pub fn ca_routes_policy_update(
+        &self,
+        handle: Handle,
+        policy: RoaPackingPolicy,
+    ) -> KrillEmptyResult {
+        Ok(self.caserver.ca_routes_policy_update(handle, policy)?)
+    }
+
+    pub fn ca_routes_policy_show(&self, handle: &Handle) -> KrillResult<RoaPackingPolicy> {
+        let ca = self.caserver.get_ca(handle)?;
+        Ok(ca.roa_policy())
+    }
+
+    /// Analyses the current ROAs of the CA against the announcements for
+    /// the prefixes it holds.
+    pub fn ca_routes_bgp_analysis(&self, handle: &Handle) -> KrillResult<BgpAnalysisReport> {
+        if self.bgp_analyser.loaded().is_none() {
+            return Err(Error::BgpAnalysisUnavailable);
+        }
+        let ca = self.caserver.get_ca(handle)?;
+        let definitions = ca.roa_definitions();
+        Ok(self.bgp_analyser.analyse(&definitions, &ca.all_resources()))
+    }
+
+    /// Analyses how the announcements for the prefixes held by the CA would
+    /// be affected by the ROA updates, without applying them.
+    pub fn ca_routes_bgp_dry_run(
+        &self,
+        handle: &Handle,
+        updates: RoaDefinitionUpdates,
+    ) -> KrillResult<BgpAnalysisDryRun> {
+        if self.bgp_analyser.loaded().is_none() {
+            return Err(Error::BgpAnalysisUnavailable);
+        }
+        let ca = self.caserver.get_ca(handle)?;
+        let definitions = ca.roa_definitions();
+        Ok(self
+            .bgp_analyser
+            .dry_run(&definitions, updates, &ca.all_resources()))
+    }
+
+    /// Suggests ROA updates for the CA, based on the announcements seen for
+    /// the prefixes held by the CA.
+    pub fn ca_routes_bgp_suggest(&self, handle: &Handle) -> KrillResult<RoaDefinitionUpdates> {
+        if self.bgp_analyser.loaded().is_none() {
+            return Err(Error::BgpAnalysisUnavailable);
+        }
+        let ca = self.caserver.get_ca(handle)?;
+        let definitions = ca.roa_definitions();
+        Ok(self.bgp_analyser.suggest(&definitions, &ca.all_resources()))
+    }
+}
+
+/// # Handle ASPA requests
+///
+impl KrillServer {
+    pub fn ca_aspas_update(
+        &self,
+        handle: Handle,
+        updates: AspaDefinitionUpdates,
+    ) -> KrillEmptyResult {
+        Ok(self.caserver.ca_aspas_update(handle, updates)?)
+    }
+
+    pub fn ca_aspas_update_providers(
+        &self,
+        handle: Handle,
+        customer: AsNumber,
+        update: AspaProvidersUpdate,
+    ) -> KrillEmptyResult {
+        Ok(self
+            .caserver
+            .ca_aspas_update_providers(handle, customer, update)?)
+    }
+
+    pub fn ca_aspas_show(&self, handle: &Handle) -> KrillResult<Vec<AspaDefinition>> {
+        let ca = self.caserver.get_ca(handle)?;
+        Ok(ca.aspa_definitions())
+    }
+}
+
+/// # Handle Ghostbusters requests
+///
+impl KrillServer {
+    pub fn ca_ghostbuster_update(
+        &self,
+        handle: Handle,
+        vcard: GhostbusterVcard,
+    ) -> KrillEmptyResult {
+        Ok(self.caserver.ca_ghostbuster_update(handle, Some(vcard))?)
+    }
+
+    pub fn ca_ghostbuster_remove(&self, handle: Handle) -> KrillEmptyResult {
+        Ok(self.caserver.ca_ghostbuster_update(handle, None)?)
+    }
+
+    pub fn ca_ghostbuster_show(&self, handle: &Handle) -> KrillResult<Option<GhostbusterVcard>> {
+        let ca = self.caserver.get_ca(handle)?;
+        Ok(ca.ghostbuster().cloned())
+    }
+}
+
+/// # Handle publication timing requests
+///
+impl KrillServer {
+    pub fn ca_publication_timing_update(
+        &self,
+        handle: &Handle,
+        timing: PublicationTiming,
+    ) -> KrillEmptyResult {
+        Ok(self
+            .caserver
+            .ca_publication_timing_update(handle, Some(timing))?)
+    }
+
+    pub fn ca_publication_timing_reset(&self, handle: &Handle) -> KrillEmptyResult {
+        Ok(self.caserver.ca_publication_timing_update(handle, None)?)
+    }
+
+    pub fn ca_publication_timing_show(&self, handle: &Handle) -> KrillResult<PublicationTiming> {
+        self.caserver.ca_publication_timing(handle)
+    }
 }
 
 /// # Handle publication requests
@@ -10,7 +10,9 @@ use tokio::runtime::Runtime;
 use rpki::x509::Time;
 
 use crate::commons::api::Handle;
+use crate::commons::bgp::BgpAnalyser;
 use crate::commons::util::softsigner::OpenSslSigner;
+use crate::constants::{CHILD_LEASE_CHECK_MINS, KEY_ROLL_CHECK_MINS};
 use crate::daemon::ca::CaServer;
 use crate::daemon::mq::{EventQueueListener, QueueEvent};
 use crate::pubd::PubServer;
@@ -31,6 +33,21 @@ pub struct Scheduler {
     /// they are not renewed within the configured grace period.
     #[allow(dead_code)] // just need to keep this in scope
     ca_refresh_sh: ScheduleHandle,
+
+    /// Responsible for periodically reloading the BGP announcements used for
+    /// ROA analysis.
+    #[allow(dead_code)] // just need to keep this in scope
+    bgp_refresh_sh: ScheduleHandle,
+
+    /// Responsible for initiating and activating key rolls for CAs, as far
+    /// as their key roll policies call for it.
+    #[allow(dead_code)] // just need to keep this in scope
+    keyroll_sh: ScheduleHandle,
+
+    /// Responsible for removing expired child resource leases, and for
+    /// shrinking the certificates issued to these children accordingly.
+    #[allow(dead_code)] // just need to keep this in scope
+    child_lease_sh: ScheduleHandle,
 }
 
 impl Scheduler {
@@ -38,16 +55,24 @@ impl Scheduler {
         event_queue: Arc<EventQueueListener>,
         caserver: Arc<CaServer<OpenSslSigner>>,
         pubserver: Option<Arc<PubServer>>,
+        bgp_analyser: Arc<BgpAnalyser>,
         ca_refresh_rate: u32,
+        bgp_refresh_mins: u64,
     ) -> Self {
         let event_sh = make_event_sh(event_queue, caserver.clone(), pubserver);
         let republish_sh = make_republish_sh(caserver.clone());
-        let ca_refresh_sh = make_ca_refresh_sh(caserver, ca_refresh_rate);
+        let ca_refresh_sh = make_ca_refresh_sh(caserver.clone(), ca_refresh_rate);
+        let bgp_refresh_sh = make_bgp_refresh_sh(bgp_analyser, bgp_refresh_mins);
+        let keyroll_sh = make_keyroll_sh(caserver.clone());
+        let child_lease_sh = make_child_lease_sh(caserver);
 
         Scheduler {
             event_sh,
             republish_sh,
             ca_refresh_sh,
+            bgp_refresh_sh,
+            keyroll_sh,
+            child_lease_sh,
         }
     }
 }
@@ -207,3 +232,29 @@ fn make_ca_refresh_sh(caserver: Arc<CaServer<OpenSslSigner>>, refresh_rate: u32)
     });
     scheduler.watch_thread(Duration::from_millis(100))
 }
+
+fn make_bgp_refresh_sh(bgp_analyser: Arc<BgpAnalyser>, refresh_mins: u64) -> ScheduleHandle {
+    let mut scheduler = clokwerk::Scheduler::new();
+    scheduler.every((refresh_mins as u32).minutes()).run(move || {
+        if let Err(e) = bgp_analyser.update() {
+            error!("Could not reload BGP announcements: {}", e);
+        }
+    });
+    scheduler.watch_thread(Duration::from_millis(100))
+}
+
+fn make_keyroll_sh(caserver: Arc<CaServer<OpenSslSigner>>) -> ScheduleHandle {
+    let mut scheduler = clokwerk::Scheduler::new();
+    scheduler.every(KEY_ROLL_CHECK_MINS.minutes()).run(move || {
+        caserver.keyroll_all();
+    });
+    scheduler.watch_thread(Duration::from_millis(100))
+}
+
+fn make_child_lease_sh(caserver: Arc<CaServer<OpenSslSigner>>) -> ScheduleHandle {
+    let mut scheduler = clokwerk::Scheduler::new();
+    scheduler.every(CHILD_LEASE_CHECK_MINS.minutes()).run(move || {
+        caserver.child_leases_expire_all();
+    });
+    scheduler.watch_thread(Duration::from_millis(100))
+}
@@ -0,0 +1,240 @@
+//! Signed change feed for the repository content published in IPFS.
+//!
+//! Resolving IPNS names through the DHT can be slow, so after each write of
+//! the repository the publication server can also broadcast the new root
+//! CID on an IPFS pubsub topic. Subscribers can verify each message using
+//! the public key in the repository's ID certificate.
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+
+use rpki::crypto::{KeyIdentifier, PublicKey, Signature, SignatureAlgorithm, Signer};
+
+use crate::commons::api::rrdp::RrdpSession;
+use crate::commons::api::Base64;
+use crate::commons::error::Error;
+use crate::commons::KrillResult;
+use crate::ipfs::ipfs::{self, IpfsPath};
+
+//------------ RootChange ----------------------------------------------------
+
+/// Announces a new root CID for the repository content, together with the
+/// RRDP session and serial that it corresponds to. The message is signed
+/// using the repository's identity key.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RootChange {
+    cid: String,
+    session: RrdpSession,
+    serial: u64,
+    key_id: KeyIdentifier,
+    signature: Base64,
+}
+
+impl RootChange {
+    pub fn create<S: Signer<KeyId = KeyIdentifier>>(
+        cid: String,
+        session: RrdpSession,
+        serial: u64,
+        key_id: &KeyIdentifier,
+        signer: &S,
+    ) -> KrillResult<Self> {
+        let content = Self::signed_content(&cid, session, serial);
+        let signature = signer
+            .sign(key_id, SignatureAlgorithm::default(), &content)
+            .map_err(Error::signer)?;
+
+        Ok(RootChange {
+            cid,
+            session,
+            serial,
+            key_id: *key_id,
+            signature: Base64::from_content(signature.value().as_ref()),
+        })
+    }
+
+    /// The content covered by the signature: the session, serial and CID,
+    /// each on their own line.
+    fn signed_content(cid: &str, session: RrdpSession, serial: u64) -> Bytes {
+        Bytes::from(format!("{}\n{}\n{}", session, serial, cid))
+    }
+
+    /// Verifies that this message was signed by the given key. Subscribers
+    /// should use the key from the repository's ID certificate.
+    pub fn verify(&self, key: &PublicKey) -> bool {
+        if key.key_identifier() != self.key_id {
+            return false;
+        }
+
+        let content = Self::signed_content(&self.cid, self.session, self.serial);
+        let signature = Signature::new(SignatureAlgorithm::default(), self.signature.to_bytes());
+
+        key.verify(&content, &signature).is_ok()
+    }
+
+    pub fn cid(&self) -> &str {
+        &self.cid
+    }
+
+    pub fn session(&self) -> RrdpSession {
+        self.session
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
+    pub fn key_id(&self) -> &KeyIdentifier {
+        &self.key_id
+    }
+}
+
+//------------ PubSub --------------------------------------------------------
+
+/// Something that can broadcast a message on a pubsub topic.
+pub trait PubSub: Send + Sync {
+    fn publish(&self, topic: &str, data: &[u8]) -> Result<(), io::Error>;
+}
+
+//------------ IpfsPubSub ----------------------------------------------------
+
+/// Broadcasts messages through the local IPFS node. Note that the node must
+/// be started with '--enable-pubsub-experiment' for this to work.
+pub struct IpfsPubSub {
+    ipfs_path: IpfsPath,
+}
+
+impl IpfsPubSub {
+    pub fn new(ipfs_path: IpfsPath) -> Self {
+        IpfsPubSub { ipfs_path }
+    }
+}
+
+impl PubSub for IpfsPubSub {
+    fn publish(&self, topic: &str, data: &[u8]) -> Result<(), io::Error> {
+        let output = ipfs::pubsub_publish(&self.ipfs_path, topic, data)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ))
+        }
+    }
+}
+
+//------------ InMemoryPubSub ------------------------------------------------
+
+/// Keeps all messages in memory instead of sending them, so that they can
+/// be inspected. Used for testing.
+#[derive(Default)]
+pub struct InMemoryPubSub {
+    messages: Mutex<Vec<(String, Bytes)>>,
+}
+
+impl InMemoryPubSub {
+    /// Returns all (topic, message) pairs published so far.
+    pub fn messages(&self) -> Vec<(String, Bytes)> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+impl PubSub for InMemoryPubSub {
+    fn publish(&self, topic: &str, data: &[u8]) -> Result<(), io::Error> {
+        self.messages
+            .lock()
+            .unwrap()
+            .push((topic.to_string(), Bytes::from(data.to_vec())));
+        Ok(())
+    }
+}
+
+//------------ ChangeFeed ----------------------------------------------------
+
+/// Publishes RootChange messages as JSON on a configured topic.
+#[derive(Clone)]
+pub struct ChangeFeed {
+    topic: String,
+    pubsub: Arc<dyn PubSub>,
+}
+
+impl ChangeFeed {
+    pub fn new(topic: String, pubsub: Arc<dyn PubSub>) -> Self {
+        ChangeFeed { topic, pubsub }
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// Broadcasts the change. Failures to send the message are logged, but
+    /// not returned: the content is published regardless and subscribers can
+    /// always fall back to resolving the IPNS name.
+    pub fn announce(&self, change: &RootChange) -> KrillResult<()> {
+        let json = serde_json::to_string(change).map_err(Error::JsonError)?;
+        match self.pubsub.publish(&self.topic, json.as_bytes()) {
+            Ok(()) => info!(
+                "Announced root CID {} for serial {} on topic '{}'",
+                change.cid(),
+                change.serial(),
+                self.topic
+            ),
+            Err(e) => warn!(
+                "Could not announce root CID {} on topic '{}': {}",
+                change.cid(),
+                self.topic,
+                e
+            ),
+        }
+        Ok(())
+    }
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rpki::crypto::PublicKeyFormat;
+
+    use crate::commons::util::softsigner::OpenSslSigner;
+    use crate::test;
+
+    use super::*;
+
+    #[test]
+    fn should_announce_signed_root_change() {
+        test::test_under_tmp(|d| {
+            let mut signer = OpenSslSigner::build(&d).unwrap();
+            let key_id = signer.create_key(PublicKeyFormat::default()).unwrap();
+            let key = signer.get_key_info(&key_id).unwrap();
+
+            let session = RrdpSession::default();
+            let cid = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG".to_string();
+            let change = RootChange::create(cid, session, 3, &key_id, &signer).unwrap();
+
+            let pubsub = Arc::new(InMemoryPubSub::default());
+            let feed = ChangeFeed::new("krill-test".to_string(), pubsub.clone());
+            feed.announce(&change).unwrap();
+
+            let messages = pubsub.messages();
+            assert_eq!(1, messages.len());
+
+            let (topic, data) = &messages[0];
+            assert_eq!("krill-test", topic);
+
+            let received: RootChange = serde_json::from_slice(data).unwrap();
+            assert_eq!(change, received);
+            assert_eq!(3, received.serial());
+            assert!(received.verify(&key));
+
+            let other_key_id = signer.create_key(PublicKeyFormat::default()).unwrap();
+            let other_key = signer.get_key_info(&other_key_id).unwrap();
+            assert!(!received.verify(&other_key));
+
+            let mut tampered = received;
+            tampered.serial = 4;
+            assert!(!tampered.verify(&key));
+        })
+    }
+}
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 use std::{env, fmt};
 use std::process::{Command, Output};
-use std::io::Error;
+use std::io::{Error, ErrorKind};
 use std::string::FromUtf8Error;
 use std::fmt::Display;
 
@@ -86,6 +86,79 @@ pub fn publish_ta_cer(ipfs_path: &IpfsPath, tal_pub_key: &dyn PubKey) -> Result<
     return Ok(output);
 }
 
+pub fn pubsub_publish(ipfs_path: &IpfsPath, topic: &str, data: &[u8]) -> Result<Output, Error> {
+    Command::new(IPFS)
+        .env("IPFS_PATH", ipfs_path.to_string())
+        .arg("pubsub")
+        .arg("pub")
+        .arg(topic)
+        .arg(String::from_utf8_lossy(data).to_string())
+        .output()
+}
+
+/// Returns the CID that the directory would get if it were added, without
+/// actually adding it.
+pub fn hash_dir(ipfs_path: &IpfsPath, dir: &PathBuf) -> Result<String, Error> {
+    let output = Command::new(IPFS)
+        .env("IPFS_PATH", ipfs_path.to_string())
+        .arg("add")
+        .arg("-r")
+        .arg("-Q")
+        .arg("--only-hash")
+        .arg(dir.display().to_string())
+        .output()?;
+
+    stdout_line(output)
+}
+
+/// Resolves the CID currently published under the IPNS name of the key.
+pub fn resolve(ipfs_path: &IpfsPath, public_key: &dyn PubKey) -> Result<String, Error> {
+    let keys = Command::new(IPFS)
+        .env("IPFS_PATH", ipfs_path.to_string())
+        .arg("key")
+        .arg("list")
+        .arg("-l")
+        .output()?;
+
+    let keys = String::from_utf8_lossy(&keys.stdout).to_string();
+    let name = public_key.key();
+    let id = keys
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some(id), Some(key)) if key == name => Some(id.to_string()),
+                _ => None,
+            }
+        })
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("unknown ipfs key: {}", name)))?;
+
+    let output = Command::new(IPFS)
+        .env("IPFS_PATH", ipfs_path.to_string())
+        .arg("name")
+        .arg("resolve")
+        .arg("--nocache")
+        .arg(format!("/ipns/{}", id))
+        .output()?;
+
+    let path = stdout_line(output)?;
+    Ok(path.trim_start_matches("/ipfs/").to_string())
+}
+
+fn stdout_line(output: Output) -> Result<String, Error> {
+    if !output.status.success() {
+        let msg = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(Error::new(ErrorKind::Other, msg));
+    }
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if line.is_empty() {
+        Err(Error::new(ErrorKind::Other, "no output from ipfs"))
+    } else {
+        Ok(line)
+    }
+}
+
 fn extract_output_cid(output: Output) -> Result<String, Error> {
     let result:Result<String, FromUtf8Error> = String::from_utf8(output.stdout);
     result.map(move |res| {
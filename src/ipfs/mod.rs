@@ -1 +1,2 @@
-pub mod ipfs;
\ No newline at end of file
+pub mod feed;
+pub mod ipfs;
@@ -1,10 +1,12 @@
 use std::fmt;
 
+use crate::commons::api::rrdp::RrdpRetention;
 use crate::commons::api::{
-    PublishDelta, PublisherHandle, RepositoryHandle, StorableRepositoryCommand,
+    PublishDelta, PublisherHandle, PublisherQuota, RepositoryHandle, StorableRepositoryCommand,
 };
 use crate::commons::eventsourcing::CommandDetails;
 use crate::commons::eventsourcing::SentCommand;
+use crate::commons::remote::id::IdCert;
 use crate::commons::remote::rfc8183;
 use crate::pubd::Evt;
 
@@ -16,9 +18,15 @@ pub type Cmd = SentCommand<CmdDet>;
 #[allow(clippy::large_enum_variant)]
 #[serde(rename_all = "snake_case")]
 pub enum CmdDet {
-    AddPublisher(rfc8183::PublisherRequest),
+    AddPublisher(rfc8183::PublisherRequest, PublisherQuota),
     RemovePublisher(PublisherHandle),
+    UpdatePublisherQuota(PublisherHandle, PublisherQuota),
+    UpdatePublisherIdCert(PublisherHandle, IdCert),
+    SuspendPublisher(PublisherHandle),
+    ReactivatePublisher(PublisherHandle),
     Publish(PublisherHandle, PublishDelta),
+    UpdateRrdpRetention(RrdpRetention),
+    ResetRrdpSession,
 }
 
 impl CommandDetails for CmdDet {
@@ -31,14 +39,46 @@ impl CommandDetails for CmdDet {
 }
 
 impl CmdDet {
-    pub fn add_publisher(handle: &RepositoryHandle, request: rfc8183::PublisherRequest) -> Cmd {
-        SentCommand::new(handle, None, CmdDet::AddPublisher(request))
+    pub fn add_publisher(
+        handle: &RepositoryHandle,
+        request: rfc8183::PublisherRequest,
+        quota: PublisherQuota,
+    ) -> Cmd {
+        SentCommand::new(handle, None, CmdDet::AddPublisher(request, quota))
     }
 
     pub fn remove_publisher(handle: &RepositoryHandle, publisher: PublisherHandle) -> Cmd {
         SentCommand::new(handle, None, CmdDet::RemovePublisher(publisher))
     }
 
+    pub fn update_publisher_quota(
+        handle: &RepositoryHandle,
+        publisher: PublisherHandle,
+        quota: PublisherQuota,
+    ) -> Cmd {
+        SentCommand::new(handle, None, CmdDet::UpdatePublisherQuota(publisher, quota))
+    }
+
+    pub fn update_publisher_id_cert(
+        handle: &RepositoryHandle,
+        publisher: PublisherHandle,
+        id_cert: IdCert,
+    ) -> Cmd {
+        SentCommand::new(
+            handle,
+            None,
+            CmdDet::UpdatePublisherIdCert(publisher, id_cert),
+        )
+    }
+
+    pub fn suspend_publisher(handle: &RepositoryHandle, publisher: PublisherHandle) -> Cmd {
+        SentCommand::new(handle, None, CmdDet::SuspendPublisher(publisher))
+    }
+
+    pub fn reactivate_publisher(handle: &RepositoryHandle, publisher: PublisherHandle) -> Cmd {
+        SentCommand::new(handle, None, CmdDet::ReactivatePublisher(publisher))
+    }
+
     pub fn publish(
         handle: &RepositoryHandle,
         publisher: PublisherHandle,
@@ -46,6 +86,14 @@ impl CmdDet {
     ) -> Cmd {
         SentCommand::new(handle, None, CmdDet::Publish(publisher, delta))
     }
+
+    pub fn update_rrdp_retention(handle: &RepositoryHandle, retention: RrdpRetention) -> Cmd {
+        SentCommand::new(handle, None, CmdDet::UpdateRrdpRetention(retention))
+    }
+
+    pub fn reset_rrdp_session(handle: &RepositoryHandle) -> Cmd {
+        SentCommand::new(handle, None, CmdDet::ResetRrdpSession)
+    }
 }
 
 impl fmt::Display for CmdDet {
@@ -57,17 +105,29 @@ impl fmt::Display for CmdDet {
 impl From<CmdDet> for StorableRepositoryCommand {
     fn from(d: CmdDet) -> Self {
         match d {
-            CmdDet::AddPublisher(req) => {
+            CmdDet::AddPublisher(req, quota) => {
                 let (_, pbl, id) = req.unpack();
-                StorableRepositoryCommand::AddPublisher(pbl, id.ski_hex())
+                StorableRepositoryCommand::AddPublisher(pbl, id.ski_hex(), quota)
             }
             CmdDet::RemovePublisher(pbl) => StorableRepositoryCommand::RemovePublisher(pbl),
+            CmdDet::UpdatePublisherQuota(pbl, quota) => {
+                StorableRepositoryCommand::UpdatePublisherQuota(pbl, quota)
+            }
+            CmdDet::UpdatePublisherIdCert(pbl, id_cert) => {
+                StorableRepositoryCommand::UpdatePublisherIdCert(pbl, id_cert.ski_hex())
+            }
+            CmdDet::SuspendPublisher(pbl) => StorableRepositoryCommand::SuspendPublisher(pbl),
+            CmdDet::ReactivatePublisher(pbl) => StorableRepositoryCommand::ReactivatePublisher(pbl),
             CmdDet::Publish(pbl, delta) => StorableRepositoryCommand::Publish(
                 pbl,
                 delta.publishes().len(),
                 delta.updates().len(),
                 delta.withdraws().len(),
             ),
+            CmdDet::UpdateRrdpRetention(retention) => {
+                StorableRepositoryCommand::UpdateRrdpRetention(retention)
+            }
+            CmdDet::ResetRrdpSession => StorableRepositoryCommand::ResetRrdpSession,
         }
     }
 }
@@ -5,8 +5,8 @@ use rpki::crypto::PublicKeyFormat;
 use rpki::uri;
 use rpki::x509::Time;
 
-use crate::commons::api::rrdp::{Delta, DeltaElements, Notification, RrdpSession};
-use crate::commons::api::{Handle, PublisherHandle, RepositoryHandle};
+use crate::commons::api::rrdp::{Delta, DeltaElements, Notification, RrdpRetention, RrdpSession};
+use crate::commons::api::{Handle, PublisherHandle, PublisherQuota, RepositoryHandle};
 use crate::commons::error::Error;
 use crate::commons::eventsourcing::StoredEvent;
 use crate::commons::remote::builder::IdCertBuilder;
@@ -129,6 +129,14 @@ impl RrdpUpdate {
     pub fn elements(&self) -> &DeltaElements {
         self.delta.elements()
     }
+
+    pub fn serial(&self) -> u64 {
+        self.delta.serial()
+    }
+
+    pub fn session(&self) -> RrdpSession {
+        self.notification.session()
+    }
 }
 
 //------------ EvtDet --------------------------------------------------------
@@ -146,9 +154,27 @@ pub enum EvtDet {
     #[display(fmt = "Publisher with handle '{}', and its contents, removed", _0)]
     PublisherRemoved(PublisherHandle, RrdpUpdate),
 
+    #[display(fmt = "Publisher with handle '{}' quota updated to: {}", _0, _1)]
+    PublisherQuotaUpdated(PublisherHandle, PublisherQuota),
+
+    #[display(fmt = "Publisher with handle '{}' ID certificate updated", _0)]
+    PublisherIdCertUpdated(PublisherHandle, IdCert),
+
+    #[display(fmt = "Publisher with handle '{}' suspended", _0)]
+    PublisherSuspended(PublisherHandle),
+
+    #[display(fmt = "Publisher with handle '{}' reactivated", _0)]
+    PublisherReactivated(PublisherHandle),
+
     // RRDP publication events
     #[display(fmt = "Publisher with handle '{}' published", _0)]
     Published(PublisherHandle, RrdpUpdate),
+
+    #[display(fmt = "RRDP retention policy updated to: {}", _0)]
+    RrdpRetentionUpdated(RrdpRetention),
+
+    #[display(fmt = "RRDP session reset")]
+    RrdpSessionReset(RrdpUpdate),
 }
 
 impl EvtDet {
@@ -178,6 +204,56 @@ impl EvtDet {
         )
     }
 
+    pub(super) fn publisher_quota_updated(
+        handle: &Handle,
+        version: u64,
+        publisher_handle: PublisherHandle,
+        quota: PublisherQuota,
+    ) -> Evt {
+        StoredEvent::new(
+            handle,
+            version,
+            EvtDet::PublisherQuotaUpdated(publisher_handle, quota),
+        )
+    }
+
+    pub(super) fn publisher_id_cert_updated(
+        handle: &Handle,
+        version: u64,
+        publisher_handle: PublisherHandle,
+        id_cert: IdCert,
+    ) -> Evt {
+        StoredEvent::new(
+            handle,
+            version,
+            EvtDet::PublisherIdCertUpdated(publisher_handle, id_cert),
+        )
+    }
+
+    pub(super) fn publisher_suspended(
+        handle: &Handle,
+        version: u64,
+        publisher_handle: PublisherHandle,
+    ) -> Evt {
+        StoredEvent::new(
+            handle,
+            version,
+            EvtDet::PublisherSuspended(publisher_handle),
+        )
+    }
+
+    pub(super) fn publisher_reactivated(
+        handle: &Handle,
+        version: u64,
+        publisher_handle: PublisherHandle,
+    ) -> Evt {
+        StoredEvent::new(
+            handle,
+            version,
+            EvtDet::PublisherReactivated(publisher_handle),
+        )
+    }
+
     pub(super) fn published(
         repository: &RepositoryHandle,
         version: u64,
@@ -186,4 +262,20 @@ impl EvtDet {
     ) -> Evt {
         StoredEvent::new(repository, version, EvtDet::Published(publisher, update))
     }
+
+    pub(super) fn rrdp_retention_updated(
+        repository: &RepositoryHandle,
+        version: u64,
+        retention: RrdpRetention,
+    ) -> Evt {
+        StoredEvent::new(repository, version, EvtDet::RrdpRetentionUpdated(retention))
+    }
+
+    pub(super) fn rrdp_session_reset(
+        repository: &RepositoryHandle,
+        version: u64,
+        update: RrdpUpdate,
+    ) -> Evt {
+        StoredEvent::new(repository, version, EvtDet::RrdpSessionReset(update))
+    }
 }
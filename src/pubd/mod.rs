@@ -3,6 +3,7 @@ mod events;
 mod publishers;
 mod pubserver;
 mod repository;
+mod validation;
 
 pub use self::commands::{Cmd, CmdDet};
 pub use self::events::{Evt, EvtDet, Ini, IniDet, RrdpUpdate};
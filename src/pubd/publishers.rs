@@ -1,7 +1,11 @@
 use rpki::uri;
+use rpki::x509::Time;
 
 use crate::commons::api::rrdp::{CurrentObjects, DeltaElements};
-use crate::commons::api::{ListReply, PublisherDetails, PublisherHandle};
+use crate::commons::api::{
+    ListReply, PublishedObject, PublisherDetails, PublisherHandle, PublisherObjects,
+    PublisherQuota, PublisherUsage,
+};
 use crate::commons::error::Error;
 use crate::commons::remote::id::IdCert;
 use crate::commons::KrillResult;
@@ -19,6 +23,20 @@ pub struct Publisher {
 
     /// All objects currently published by this publisher, by hash
     current_objects: CurrentObjects,
+
+    /// Limits on the objects this publisher may publish
+    #[serde(default)]
+    quota: PublisherQuota,
+
+    /// Suspended publishers may not publish, but their current objects
+    /// remain published
+    #[serde(default)]
+    suspended: bool,
+
+    /// When this publisher was added, unknown for publishers added before
+    /// this was recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    added: Option<Time>,
 }
 
 /// # Accessors
@@ -32,6 +50,32 @@ impl Publisher {
     pub fn current_objects(&self) -> &CurrentObjects {
         &self.current_objects
     }
+    pub fn quota(&self) -> &PublisherQuota {
+        &self.quota
+    }
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+    pub fn added(&self) -> Option<Time> {
+        self.added
+    }
+
+    pub fn usage(&self) -> PublisherUsage {
+        let elements = self.current_objects.elements();
+        let largest_object = elements.iter().map(|el| el.size()).max().unwrap_or(0);
+        PublisherUsage::new(elements.len(), self.current_objects.size(), largest_object)
+    }
+
+    pub fn as_api_objects(&self, handle: &PublisherHandle) -> PublisherObjects {
+        let objects = self
+            .current_objects
+            .elements()
+            .into_iter()
+            .map(PublishedObject::from)
+            .collect();
+
+        PublisherObjects::new(handle.clone(), objects)
+    }
 
     pub fn as_api_details(&self, handle: &PublisherHandle) -> PublisherDetails {
         let objects = self
@@ -41,20 +85,52 @@ impl Publisher {
             .cloned()
             .collect();
 
-        PublisherDetails::new(handle, self.id_cert.clone(), &self.base_uri(), objects)
+        PublisherDetails::new(
+            handle,
+            self.id_cert.clone(),
+            &self.base_uri(),
+            objects,
+            self.suspended,
+            self.quota.clone(),
+            self.usage(),
+        )
     }
 }
 
 /// # Life cycle
 ///
 impl Publisher {
-    pub fn new(id_cert: IdCert, base_uri: uri::Rsync, current_objects: CurrentObjects) -> Self {
+    pub fn new(
+        id_cert: IdCert,
+        base_uri: uri::Rsync,
+        current_objects: CurrentObjects,
+        quota: PublisherQuota,
+    ) -> Self {
         Publisher {
             id_cert,
             base_uri,
             current_objects,
+            quota,
+            suspended: false,
+            added: Some(Time::now()),
         }
     }
+
+    pub fn set_quota(&mut self, quota: PublisherQuota) {
+        self.quota = quota;
+    }
+
+    pub fn set_id_cert(&mut self, id_cert: IdCert) {
+        self.id_cert = id_cert;
+    }
+
+    pub fn suspend(&mut self) {
+        self.suspended = true;
+    }
+
+    pub fn reactivate(&mut self) {
+        self.suspended = false;
+    }
 }
 
 /// # Publication protocol
@@ -77,6 +153,58 @@ impl Publisher {
             .map_err(Error::Rfc8181Delta)
     }
 
+    /// Verifies that the publisher would stay within its quota if the delta
+    /// were applied. Assumes that the delta itself was already verified.
+    ///
+    /// Only deltas that add objects or bytes are refused when they end up
+    /// over the limit, so that a publisher which is over its quota, e.g.
+    /// because the quota was lowered, can still withdraw and update objects.
+    pub fn verify_quota(
+        &self,
+        handle: &PublisherHandle,
+        delta_elements: &DeltaElements,
+    ) -> KrillResult<()> {
+        let exceeded = |msg: String| Err(Error::PublisherQuotaExceeded(handle.clone(), msg));
+
+        if let Some(max) = self.quota.max_object_size() {
+            let sizes = delta_elements
+                .publishes()
+                .iter()
+                .map(|p| (p.uri(), p.size()))
+                .chain(delta_elements.updates().iter().map(|u| (u.uri(), u.size())));
+
+            for (uri, size) in sizes {
+                if size > max {
+                    return exceeded(format!(
+                        "object '{}' has size {}, max is {}",
+                        uri, size, max
+                    ));
+                }
+            }
+        }
+
+        if self.quota.max_objects().is_none() && self.quota.max_size().is_none() {
+            return Ok(());
+        }
+
+        let mut objects = self.current_objects.clone();
+        objects.apply_delta(delta_elements.clone());
+
+        if let Some(max) = self.quota.max_objects() {
+            if objects.len() > max && objects.len() > self.current_objects.len() {
+                return exceeded(format!("{} objects, max is {}", objects.len(), max));
+            }
+        }
+
+        if let Some(max) = self.quota.max_size() {
+            if objects.size() > max && objects.size() > self.current_objects.size() {
+                return exceeded(format!("total size {}, max is {}", objects.size(), max));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn apply_delta(&mut self, delta: DeltaElements) {
         self.current_objects.apply_delta(delta);
     }
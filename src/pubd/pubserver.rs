@@ -1,13 +1,18 @@
 use std::fs;
 use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::{Arc, RwLock};
 
 use bytes::Bytes;
 use rpki::uri;
 
+use crate::commons::api::rrdp::RrdpRetention;
 use crate::commons::api::{
-    Handle, ListReply, PublishDelta, PublisherDetails, PublisherHandle, RepoInfo, RepositoryHandle,
+    CommandHistoryCriteria, Handle, ListReply, PublishDelta, PublisherDetails, PublisherHandle,
+    PublisherHistory, PublisherHistoryRecord, PublisherObjects, PublisherQuota, RepoInfo,
+    RepositoryHandle, RepositoryIssues, RepositoryVerification, StalePublisherThreshold,
+    StorableRepositoryCommand, StoredEffect,
 };
 use crate::commons::error::Error;
 use crate::commons::eventsourcing::{AggregateStore, AggregateStoreError, DiskAggregateStore};
@@ -19,7 +24,8 @@ use crate::commons::remote::sigmsg::SignedMessage;
 use crate::commons::util::softsigner::OpenSslSigner;
 use crate::commons::KrillResult;
 use crate::constants::*;
-use crate::pubd::{self, CmdDet, RepoStats, Repository};
+use crate::pubd::{self, validation, CmdDet, EvtDet, RepoStats, Repository};
+use crate::ipfs::feed::{ChangeFeed, IpfsPubSub, RootChange};
 use crate::ipfs::ipfs::{RepoPubKey, IpfsPath, TalPubKey};
 
 //------------ PubServer -----------------------------------------------------
@@ -34,11 +40,16 @@ use crate::ipfs::ipfs::{RepoPubKey, IpfsPath, TalPubKey};
 /// * updating the contents on disk for Rsync
 /// * responding to publishers
 /// * wrapping responses in RFC8183 for remote publishers
+/// * announcing new IPFS root CIDs on the (optional) pubsub change feed
 ///
 pub struct PubServer {
     store: Arc<DiskAggregateStore<Repository>>,
     signer: Arc<RwLock<OpenSslSigner>>,
     rfc8181_log_dir: Option<PathBuf>,
+    change_feed: Option<ChangeFeed>,
+    validate_objects: bool,
+    rsync_versions_retain: usize,
+    stale_publisher_threshold: StalePublisherThreshold,
 }
 
 /// # Constructing
@@ -53,6 +64,8 @@ impl PubServer {
         repo_pubkey: RepoPubKey,
         tal_pubkey: TalPubKey,
         ipfs_path: IpfsPath,
+        ipfs_pubsub_topic: Option<String>,
+        rrdp_retention: RrdpRetention,
     ) -> Result<Option<Self>, Error> {
         let mut pub_server_dir = work_dir.clone();
         pub_server_dir.push(PUBSERVER_DIR);
@@ -66,7 +79,9 @@ impl PubServer {
                     signer,
                     repo_pubkey,
                     tal_pubkey,
-                    ipfs_path)?;
+                    ipfs_path,
+                    ipfs_pubsub_topic,
+                    rrdp_retention)?;
 
             if server.publishers()?.is_empty() {
                 let _result = fs::remove_dir_all(pub_server_dir);
@@ -87,10 +102,14 @@ impl PubServer {
         signer: Arc<RwLock<OpenSslSigner>>,
         repo_pubkey: RepoPubKey,
         tal_pubkey: TalPubKey,
-        ipfs_path: IpfsPath
+        ipfs_path: IpfsPath,
+        ipfs_pubsub_topic: Option<String>, // for the signed root CID change feed
+        rrdp_retention: RrdpRetention,     // for RRDP deltas and old files
     ) -> Result<Self, Error> {
         let default = Self::repository_handle();
-        let _ipfs_path = ipfs_path.clone();
+        let change_feed = ipfs_pubsub_topic.map(|topic| {
+            ChangeFeed::new(topic, Arc::new(IpfsPubSub::new(ipfs_path.clone())))
+        });
         let store = Arc::new(DiskAggregateStore::<Repository>::new(
             work_dir,
             PUBSERVER_DIR,
@@ -114,12 +133,50 @@ impl PubServer {
             store.add(ini)?;
         }
 
+        // Apply the configured retention policy, this is a no-op if
+        // it did not change.
+        let cmd = CmdDet::update_rrdp_retention(&default, rrdp_retention);
+        store.command(cmd)?;
+
         Ok(PubServer {
             store,
             signer,
             rfc8181_log_dir: rfc8181_log_dir.cloned(),
+            change_feed,
+            validate_objects: false,
+            rsync_versions_retain: REPOSITORY_RSYNC_VERSIONS_RETAIN,
+            stale_publisher_threshold: StalePublisherThreshold::new(
+                REPOSITORY_STALE_PUBLISHER_UPDATE_MINS,
+                REPOSITORY_STALE_PUBLISHER_MANIFEST_MINS,
+            ),
         })
     }
+
+    /// Use the given change feed for announcing new IPFS root CIDs, rather
+    /// than the one (if any) derived from the configuration.
+    pub fn with_change_feed(mut self, change_feed: ChangeFeed) -> Self {
+        self.change_feed = Some(change_feed);
+        self
+    }
+
+    /// Parse and check all published objects before accepting them.
+    pub fn with_object_validation(mut self, validate_objects: bool) -> Self {
+        self.validate_objects = validate_objects;
+        self
+    }
+
+    /// Keep the given number of old rsync versions on disk, for clients
+    /// that are still reading them.
+    pub fn with_rsync_versions_retain(mut self, rsync_versions_retain: usize) -> Self {
+        self.rsync_versions_retain = rsync_versions_retain;
+        self
+    }
+
+    /// Use the given threshold for reporting stale publishers.
+    pub fn with_stale_publisher_threshold(mut self, threshold: StalePublisherThreshold) -> Self {
+        self.stale_publisher_threshold = threshold;
+        self
+    }
 }
 
 /// # Publication Protocol support
@@ -162,6 +219,10 @@ impl PubServer {
         let query = content.into_query()?;
 
         let (response, should_log_cms) = match query {
+            _ if publisher.is_suspended() => {
+                let e = Error::PublisherSuspended(publisher_handle);
+                (Self::error_reply(&e), true)
+            }
             rfc8181::QueryMessage::ListQuery => {
                 let list_reply = publisher.list_current();
                 (rfc8181::Message::list_reply(list_reply), false)
@@ -169,13 +230,7 @@ impl PubServer {
             rfc8181::QueryMessage::PublishDelta(delta) => {
                 match self.publish(publisher_handle, delta) {
                     Ok(()) => (rfc8181::Message::success_reply(), true),
-                    Err(e) => {
-                        let error_code = e.to_rfc8181_error_code();
-                        let report_error = rfc8181::ReportError::reply(error_code, None);
-                        let mut builder = rfc8181::ErrorReply::build_with_capacity(1);
-                        builder.add(report_error);
-                        (builder.build_message(), true)
-                    }
+                    Err(e) => (Self::error_reply(&e), true),
                 }
             }
         };
@@ -198,8 +253,31 @@ impl PubServer {
         Ok(response_bytes)
     }
 
+    /// Builds an RFC8181 error reply for the given error. Quota errors,
+    /// suspension and invalid objects get a specific text, so that the
+    /// publisher can see why it was refused, all other errors use the default
+    /// text for their error code.
+    fn error_reply(e: &Error) -> rfc8181::Message {
+        let error_code = e.to_rfc8181_error_code();
+        let report_error = match e {
+            Error::PublisherQuotaExceeded(_, _)
+            | Error::PublisherSuspended(_)
+            | Error::PublisherObjectInvalid(_, _, _) => {
+                rfc8181::ReportError::reply_with_text(error_code, None, e.to_string())
+            }
+            _ => rfc8181::ReportError::reply(error_code, None),
+        };
+        let mut builder = rfc8181::ErrorReply::build_with_capacity(1);
+        builder.add(report_error);
+        builder.build_message()
+    }
+
     /// Let a known publisher publish in a repository.
     pub fn publish(&self, publisher: PublisherHandle, delta: PublishDelta) -> KrillResult<()> {
+        if self.validate_objects {
+            validation::validate_delta(&publisher, &delta)?;
+        }
+
         let repository_handle = Self::repository_handle();
         let cmd = CmdDet::publish(&repository_handle, publisher, delta);
         self.store.command(cmd)?;
@@ -211,11 +289,111 @@ impl PubServer {
         Ok(repo.stats().clone())
     }
 
+    /// Returns the publishers which are stale according to the configured
+    /// threshold.
+    pub fn repository_issues(&self) -> KrillResult<RepositoryIssues> {
+        let stats = self.repo_stats()?;
+        Ok(stats.publisher_issues(&self.stale_publisher_threshold))
+    }
+
     pub fn publishers(&self) -> KrillResult<Vec<PublisherHandle>> {
         let repository = self.repository()?;
         Ok(repository.publishers())
     }
 
+    pub fn suspended_publishers(&self) -> KrillResult<Vec<PublisherHandle>> {
+        let repository = self.repository()?;
+        Ok(repository.suspended_publishers())
+    }
+
+    /// Returns the history of publish requests received from a publisher.
+    /// The offset and rows in the criteria apply to this publisher only.
+    pub fn publisher_history(
+        &self,
+        publisher: &PublisherHandle,
+        crit: CommandHistoryCriteria,
+    ) -> KrillResult<PublisherHistory> {
+        let repository = self.repository()?;
+        repository.get_publisher(publisher)?;
+
+        let handle = Self::repository_handle();
+        let offset = crit.offset();
+        let rows = crit.rows();
+
+        // The command keys tell the publisher, so only the publish commands
+        // for this publisher on the requested page need to be loaded. Keys
+        // without a publisher, e.g. stored by older versions, are checked by
+        // loading the command.
+        let mut crit = crit;
+        crit.set_include(&["pubd-publish"]);
+
+        let mut total = 0;
+        let mut records = vec![];
+        for key in self.store.command_keys(&handle, &crit) {
+            if key.has_subject() && !key.is_for_subject(publisher) {
+                continue;
+            }
+
+            let on_page = total >= offset && records.len() < rows;
+            if key.has_subject() && !on_page {
+                total += 1;
+                continue;
+            }
+
+            let command = self.store.stored_command(&handle, &key)?.ok_or_else(|| {
+                AggregateStoreError::UnknownCommand(handle.clone(), key.sequence())
+            })?;
+
+            if let StorableRepositoryCommand::Publish(pbl, published, updated, withdrawn) =
+                command.details()
+            {
+                if pbl == publisher {
+                    total += 1;
+                    if on_page {
+                        let serial = self.published_serial(&handle, command.effect())?;
+                        let error = match command.effect() {
+                            StoredEffect::Error(msg) => Some(msg.clone()),
+                            StoredEffect::Events(_) => None,
+                        };
+
+                        records.push(PublisherHistoryRecord::new(
+                            key.to_string(),
+                            command.time().timestamp_millis(),
+                            serial,
+                            *published,
+                            *updated,
+                            *withdrawn,
+                            error,
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(PublisherHistory::new(
+            publisher.clone(),
+            offset,
+            total,
+            records,
+        ))
+    }
+
+    /// Returns the RRDP serial that resulted from a publish command, if it
+    /// was accepted.
+    fn published_serial(&self, handle: &Handle, effect: &StoredEffect) -> KrillResult<Option<u64>> {
+        let mut serial = None;
+        if let StoredEffect::Events(versions) = effect {
+            for version in versions {
+                if let Some(event) = self.store.stored_event(handle, *version)? {
+                    if let EvtDet::Published(_, update) = event.details() {
+                        serial = Some(update.serial());
+                    }
+                }
+            }
+        }
+        Ok(serial)
+    }
+
     /// Returns a list reply for a known publisher in a repository
     pub fn list(&self, publisher: &PublisherHandle) -> KrillResult<ListReply> {
         let repository = self.repository()?;
@@ -254,6 +432,18 @@ impl PubServer {
             .map(|p| p.as_api_details(publisher_handle))
     }
 
+    /// Returns all objects currently published by the publisher, with
+    /// their decoded details.
+    pub fn get_publisher_objects(
+        &self,
+        publisher_handle: &PublisherHandle,
+    ) -> KrillResult<PublisherObjects> {
+        let repository = self.repository()?;
+        repository
+            .get_publisher(publisher_handle)
+            .map(|p| p.as_api_objects(publisher_handle))
+    }
+
     /// Returns the RFC8183 Repository Response for the publisher
     pub fn repository_response(
         &self,
@@ -264,11 +454,65 @@ impl PubServer {
         repository.repository_response(rfc8181_uri, publisher)
     }
 
-    /// Adds a publisher. Will complain if a publisher already exists for this
-    /// handle. Will also verify that the base_uri is allowed.
-    pub fn create_publisher(&self, req: rfc8183::PublisherRequest) -> KrillResult<()> {
+    /// Adds a publisher with the given quota. Will complain if a publisher
+    /// already exists for this handle. Will also verify that the base_uri is
+    /// allowed.
+    pub fn create_publisher(
+        &self,
+        req: rfc8183::PublisherRequest,
+        quota: PublisherQuota,
+    ) -> KrillResult<()> {
+        let repository_handle = Self::repository_handle();
+        let cmd = CmdDet::add_publisher(&repository_handle, req, quota);
+        self.store.command(cmd)?;
+        Ok(())
+    }
+
+    /// Updates the quota for an existing publisher.
+    pub fn update_publisher_quota(
+        &self,
+        publisher: PublisherHandle,
+        quota: PublisherQuota,
+    ) -> KrillResult<()> {
+        let repository_handle = Self::repository_handle();
+        let cmd = CmdDet::update_publisher_quota(&repository_handle, publisher, quota);
+        self.store.command(cmd)?;
+        Ok(())
+    }
+
+    /// Replaces the identity certificate of an existing publisher with the
+    /// one in the publisher request, keeping its published objects. Its
+    /// RFC8181 requests must then be signed using the new identity. Will
+    /// complain if the request is for another publisher.
+    pub fn update_publisher_id_cert(
+        &self,
+        publisher: PublisherHandle,
+        req: rfc8183::PublisherRequest,
+    ) -> KrillResult<()> {
+        let (_tag, req_handle, id_cert) = req.unpack();
+        if req_handle != publisher {
+            return Err(Error::PublisherHandleMismatch(req_handle, publisher));
+        }
+
+        let repository_handle = Self::repository_handle();
+        let cmd = CmdDet::update_publisher_id_cert(&repository_handle, publisher, id_cert);
+        self.store.command(cmd)?;
+        Ok(())
+    }
+
+    /// Suspends a publisher. Its RFC8181 requests will be refused, but its
+    /// currently published objects remain available.
+    pub fn suspend_publisher(&self, publisher: PublisherHandle) -> KrillResult<()> {
+        let repository_handle = Self::repository_handle();
+        let cmd = CmdDet::suspend_publisher(&repository_handle, publisher);
+        self.store.command(cmd)?;
+        Ok(())
+    }
+
+    /// Reactivates a suspended publisher.
+    pub fn reactivate_publisher(&self, publisher: PublisherHandle) -> KrillResult<()> {
         let repository_handle = Self::repository_handle();
-        let cmd = CmdDet::add_publisher(&repository_handle, req);
+        let cmd = CmdDet::reactivate_publisher(&repository_handle, publisher);
         self.store.command(cmd)?;
         Ok(())
     }
@@ -288,10 +532,72 @@ impl PubServer {
 /// # Publishing RRDP and rsync
 ///
 impl PubServer {
-    /// Update the RRDP files and rsync content on disk.
+    /// Update the RRDP files and rsync content on disk, and the content in
+    /// IPFS. If a change feed is configured then the new root CID is
+    /// announced there, signed by the repository's ID key.
     pub fn write_repository(&self) -> KrillResult<()> {
         let repository = self.repository()?;
-        repository.write()
+        let cid = repository.write(self.rsync_versions_retain)?;
+        self.announce_root(&repository, cid)
+    }
+
+    /// Starts a new RRDP session with a snapshot at serial 1, so that
+    /// validators will do a full snapshot fetch. The files of the old
+    /// session are removed.
+    pub fn reset_rrdp_session(&self) -> KrillResult<()> {
+        let handle = Self::repository_handle();
+        let cmd = CmdDet::reset_rrdp_session(&handle);
+        self.store.command(cmd)?;
+        self.write_repository()
+    }
+
+    /// Compares the rsync tree, RRDP files and IPFS root with the objects
+    /// of the publishers. If repair is true and differences were found,
+    /// then all of them are rebuilt from the publishers' objects, starting
+    /// a new RRDP session if the RRDP snapshot itself was found wrong.
+    ///
+    /// Returns the differences found before any repair was done.
+    pub fn verify_repository(&self, repair: bool) -> KrillResult<RepositoryVerification> {
+        let mut verification = self.repository()?.verify()?;
+
+        if repair && !verification.is_consistent() {
+            if verification.needs_new_session() {
+                let handle = Self::repository_handle();
+                let cmd = CmdDet::reset_rrdp_session(&handle);
+                self.store.command(cmd)?;
+            }
+
+            let repository = self.repository()?;
+            let cid = repository.repair(self.rsync_versions_retain)?;
+            self.announce_root(&repository, cid)?;
+
+            verification.set_repaired();
+        }
+
+        Ok(verification)
+    }
+
+    /// Returns an rsyncd.conf module stanza for serving the rsync tree of
+    /// this repository.
+    pub fn rsyncd_conf(&self) -> KrillResult<String> {
+        Ok(self.repository()?.rsyncd_conf())
+    }
+
+    fn announce_root(&self, repository: &Repository, cid: String) -> KrillResult<()> {
+        if let Some(change_feed) = &self.change_feed {
+            let stats = repository.stats();
+            let signer = self.signer.read().map_err(Error::signer)?;
+            let change = RootChange::create(
+                cid,
+                stats.session(),
+                stats.serial(),
+                repository.key_id(),
+                signer.deref(),
+            )?;
+            change_feed.announce(&change)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -308,10 +614,15 @@ mod tests {
 
     use crate::commons::api::rrdp::CurrentObjects;
     use crate::commons::api::rrdp::PublicationDeltaError;
-    use crate::commons::api::{ListElement, PublishDeltaBuilder};
+    use crate::commons::api::{
+        ListElement, ObjectDifference, PublishDeltaBuilder, PublisherVerification,
+        RrdpFileDifference,
+    };
+    use crate::commons::eventsourcing::CommandKey;
     use crate::commons::remote::builder::IdCertBuilder;
     use crate::commons::remote::id::IdCert;
-    use crate::commons::util::file::CurrentFile;
+    use crate::commons::util::file::{self, CurrentFile};
+    use crate::ipfs::feed::InMemoryPubSub;
     use crate::pubd::Publisher;
     use crate::test;
 
@@ -333,7 +644,12 @@ mod tests {
 
         let base_uri = uri::Rsync::from_str("rsync://localhost/repo/alice/").unwrap();
 
-        Publisher::new(id_cert, base_uri, CurrentObjects::default())
+        Publisher::new(
+            id_cert,
+            base_uri,
+            CurrentObjects::default(),
+            PublisherQuota::default(),
+        )
     }
 
     fn make_publisher_req(handle: &str, id_cert: &IdCert) -> rfc8183::PublisherRequest {
@@ -351,8 +667,11 @@ mod tests {
             work_dir,
             None,
             signer,
-            String::from(""),
-            PathBuf::from(String::from(""))
+            RepoPubKey(String::from("")),
+            TalPubKey(String::from("")),
+            IpfsPath(PathBuf::from(String::from(""))),
+            None,
+            RrdpRetention::default(),
         )
         .unwrap()
     }
@@ -367,7 +686,9 @@ mod tests {
             let alice_handle = Handle::from_str_unsafe("alice");
             let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
 
-            server.create_publisher(publisher_req).unwrap();
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
 
             let alice_found = server.get_publisher_details(&alice_handle).unwrap();
 
@@ -387,9 +708,11 @@ mod tests {
             let alice_handle = Handle::from_str_unsafe("alice");
             let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
 
-            server.create_publisher(publisher_req.clone()).unwrap();
+            server
+                .create_publisher(publisher_req.clone(), PublisherQuota::default())
+                .unwrap();
 
-            match server.create_publisher(publisher_req) {
+            match server.create_publisher(publisher_req, PublisherQuota::default()) {
                 Err(Error::PublisherDuplicate(name)) => assert_eq!(name, alice_handle),
                 _ => panic!("Expected error"),
             }
@@ -405,13 +728,544 @@ mod tests {
             let alice_handle = Handle::from_str_unsafe("alice");
             let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
 
-            server.create_publisher(publisher_req).unwrap();
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
 
             let list_reply = server.list(&alice_handle).unwrap();
             assert_eq!(0, list_reply.elements().len());
         });
     }
 
+    #[test]
+    fn should_use_error_text_for_quota_suspension_and_invalid_object_errors() {
+        fn reply(report_error: rfc8181::ReportError) -> rfc8181::Message {
+            let mut builder = rfc8181::ErrorReply::build_with_capacity(1);
+            builder.add(report_error);
+            builder.build_message()
+        }
+
+        fn reply_with_text(e: &Error) -> rfc8181::Message {
+            reply(rfc8181::ReportError::reply_with_text(
+                e.to_rfc8181_error_code(),
+                None,
+                e.to_string(),
+            ))
+        }
+
+        let alice_handle = Handle::from_str_unsafe("alice");
+
+        let quota_error =
+            Error::PublisherQuotaExceeded(alice_handle.clone(), "max objects: 1".to_string());
+        let suspended_error = Error::PublisherSuspended(alice_handle.clone());
+        let invalid_error = Error::PublisherObjectInvalid(
+            alice_handle,
+            "rsync://localhost/repo/alice/0.mft".to_string(),
+            "cannot decode manifest".to_string(),
+        );
+
+        for (e, fragment) in &[
+            (quota_error, "max objects: 1"),
+            (suspended_error, "is suspended"),
+            (invalid_error, "cannot decode manifest"),
+        ] {
+            assert_eq!(PubServer::error_reply(e), reply_with_text(e));
+
+            let xml = String::from_utf8(PubServer::error_reply(e).encode_vec()).unwrap();
+            assert!(xml.contains(fragment));
+
+            assert_ne!(
+                PubServer::error_reply(e),
+                reply(rfc8181::ReportError::reply(e.to_rfc8181_error_code(), None))
+            );
+        }
+
+        let unknown_error = Error::PublisherUnknown(Handle::from_str_unsafe("bob"));
+        let code = unknown_error.to_rfc8181_error_code();
+        assert_eq!(
+            PubServer::error_reply(&unknown_error),
+            reply(rfc8181::ReportError::reply(code, None))
+        );
+    }
+
+    #[test]
+    fn should_reject_publish_exceeding_quota() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+            let alice = publisher_alice(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            let quota = PublisherQuota::new(Some(1), None, None);
+            server
+                .create_publisher(publisher_req, quota.clone())
+                .unwrap();
+
+            let details = server.get_publisher_details(&alice_handle).unwrap();
+            assert_eq!(details.quota(), &quota);
+            assert_eq!(details.usage().objects(), 0);
+
+            let file1 = CurrentFile::new(
+                test::rsync("rsync://localhost/repo/alice/file.txt"),
+                &Bytes::from("example content"),
+            );
+            let file2 = CurrentFile::new(
+                test::rsync("rsync://localhost/repo/alice/file2.txt"),
+                &Bytes::from("example content 2"),
+            );
+
+            // Too many objects
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_publish(file1.as_publish());
+            builder.add_publish(file2.as_publish());
+            let delta = builder.finish();
+
+            match server.publish(alice_handle.clone(), delta) {
+                Err(Error::PublisherQuotaExceeded(pbl, _)) => assert_eq!(pbl, alice_handle),
+                _ => panic!("Expected quota exceeded error"),
+            }
+
+            // Object too big
+            let quota = PublisherQuota::new(None, None, Some(4));
+            server
+                .update_publisher_quota(alice_handle.clone(), quota.clone())
+                .unwrap();
+
+            let details = server.get_publisher_details(&alice_handle).unwrap();
+            assert_eq!(details.quota(), &quota);
+
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_publish(file1.as_publish());
+            let delta = builder.finish();
+
+            match server.publish(alice_handle.clone(), delta) {
+                Err(e) => {
+                    assert_eq!(
+                        e.to_rfc8181_error_code(),
+                        rfc8181::ReportErrorCode::OtherError
+                    );
+                    match e {
+                        Error::PublisherQuotaExceeded(_, _) => {}
+                        _ => panic!("Expected quota exceeded error"),
+                    }
+                }
+                _ => panic!("Expected quota exceeded error"),
+            }
+
+            assert_eq!(0, server.list(&alice_handle).unwrap().elements().len());
+        })
+    }
+
+    #[test]
+    fn should_allow_withdraw_and_same_size_update_when_over_quota() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+            let alice = publisher_alice(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            let file = |name: &str, content: &'static str| {
+                CurrentFile::new(
+                    test::rsync(&format!("rsync://localhost/repo/alice/{}", name)),
+                    &Bytes::from(content),
+                )
+            };
+            let file1 = file("file1.txt", "content 1");
+            let file2 = file("file2.txt", "content 2");
+            let file3 = file("file3.txt", "content 3");
+            let file4 = file("file4.txt", "content 4");
+
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_publish(file1.as_publish());
+            builder.add_publish(file2.as_publish());
+            builder.add_publish(file3.as_publish());
+            server
+                .publish(alice_handle.clone(), builder.finish())
+                .unwrap();
+
+            // Lower the quota below what alice has already published
+            let quota = PublisherQuota::new(Some(1), Some(10), None);
+            server
+                .update_publisher_quota(alice_handle.clone(), quota)
+                .unwrap();
+
+            // Adding an object is refused
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_publish(file4.as_publish());
+            match server.publish(alice_handle.clone(), builder.finish()) {
+                Err(Error::PublisherQuotaExceeded(_, _)) => {}
+                _ => panic!("Expected quota exceeded error"),
+            }
+
+            // Growing an object is refused
+            let file1_bigger = file("file1.txt", "bigger content 1");
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_update(file1_bigger.as_update(file1.hash()));
+            match server.publish(alice_handle.clone(), builder.finish()) {
+                Err(Error::PublisherQuotaExceeded(_, _)) => {}
+                _ => panic!("Expected quota exceeded error"),
+            }
+
+            // A withdraw is accepted, even though alice stays over quota
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_withdraw(file3.as_withdraw());
+            server
+                .publish(alice_handle.clone(), builder.finish())
+                .unwrap();
+
+            // An update of the same size is accepted
+            let file1_update = file("file1.txt", "content A");
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_update(file1_update.as_update(file1.hash()));
+            server
+                .publish(alice_handle.clone(), builder.finish())
+                .unwrap();
+
+            let list = server.list(&alice_handle).unwrap();
+            assert_eq!(2, list.elements().len());
+            assert!(list
+                .elements()
+                .iter()
+                .any(|el| el.uri() == file1_update.uri() && el.hash() == file1_update.hash()));
+        })
+    }
+
+    #[test]
+    fn should_reject_invalid_objects() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d).with_object_validation(true);
+            let alice = publisher_alice(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            for name in &["file.txt", "file.roa", "file.cer"] {
+                let file = CurrentFile::new(
+                    test::rsync(&format!("rsync://localhost/repo/alice/{}", name)),
+                    &Bytes::from("example content"),
+                );
+                let mut builder = PublishDeltaBuilder::new();
+                builder.add_publish(file.as_publish());
+                let delta = builder.finish();
+
+                match server.publish(alice_handle.clone(), delta) {
+                    Err(Error::PublisherObjectInvalid(pbl, uri, _)) => {
+                        assert_eq!(pbl, alice_handle);
+                        assert!(uri.ends_with(name));
+                    }
+                    _ => panic!("Expected invalid object error"),
+                }
+            }
+
+            assert_eq!(0, server.list(&alice_handle).unwrap().elements().len());
+        })
+    }
+
+    #[test]
+    fn should_show_publisher_history() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+            let alice = publisher_alice(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            // Refuse all publications, so that the repository is not written
+            let quota = PublisherQuota::new(Some(0), None, None);
+            server.create_publisher(publisher_req, quota).unwrap();
+
+            let file = CurrentFile::new(
+                test::rsync("rsync://localhost/repo/alice/file.txt"),
+                &Bytes::from("example content"),
+            );
+            for _ in 0..3 {
+                let mut builder = PublishDeltaBuilder::new();
+                builder.add_publish(file.as_publish());
+                assert!(server
+                    .publish(alice_handle.clone(), builder.finish())
+                    .is_err());
+            }
+
+            let history = server
+                .publisher_history(&alice_handle, CommandHistoryCriteria::default())
+                .unwrap();
+            assert_eq!(3, history.total());
+            assert_eq!(3, history.records().len());
+
+            let record = &history.records()[0];
+            let key = CommandKey::from_str(record.key()).unwrap();
+            assert!(key.is_for_subject(&alice_handle));
+            assert_eq!(1, record.published());
+            assert_eq!(0, record.updated());
+            assert_eq!(0, record.withdrawn());
+            assert_eq!(None, record.serial());
+            assert!(record.error().is_some());
+
+            let mut crit = CommandHistoryCriteria::default();
+            crit.set_offset(2);
+            crit.set_rows(5);
+            let history = server.publisher_history(&alice_handle, crit).unwrap();
+            assert_eq!(3, history.total());
+            assert_eq!(1, history.records().len());
+
+            let bob_handle = Handle::from_str_unsafe("bob");
+            match server.publisher_history(&bob_handle, CommandHistoryCriteria::default()) {
+                Err(Error::PublisherUnknown(pbl)) => assert_eq!(pbl, bob_handle),
+                _ => panic!("Expected unknown publisher error"),
+            }
+        })
+    }
+
+    #[test]
+    fn should_show_serials_in_publisher_history() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let alice = publisher_alice(&d);
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            let bob_handle = Handle::from_str_unsafe("bob");
+            let bob = publisher_alice(&d);
+            let publisher_req = make_publisher_req(bob_handle.as_str(), bob.id_cert());
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            let publish = |handle: &Handle, name: &str| {
+                let file = CurrentFile::new(
+                    test::rsync(&format!("rsync://localhost/repo/{}/{}", handle, name)),
+                    &Bytes::from("example content"),
+                );
+                let mut builder = PublishDeltaBuilder::new();
+                builder.add_publish(file.as_publish());
+                server.publish(handle.clone(), builder.finish())
+            };
+            let serial = || server.repo_stats().unwrap().serial();
+
+            // Accepted, rejected because the object exists, accepted for
+            // bob, and accepted again for alice.
+            publish(&alice_handle, "file.txt").unwrap();
+            let alice_serial_1 = serial();
+            assert!(publish(&alice_handle, "file.txt").is_err());
+            publish(&bob_handle, "file.txt").unwrap();
+            let bob_serial = serial();
+            publish(&alice_handle, "file2.txt").unwrap();
+            let alice_serial_2 = serial();
+
+            let history = server
+                .publisher_history(&alice_handle, CommandHistoryCriteria::default())
+                .unwrap();
+            assert_eq!(3, history.total());
+
+            let serials: Vec<Option<u64>> = history.records().iter().map(|r| r.serial()).collect();
+            assert_eq!(
+                serials,
+                vec![Some(alice_serial_1), None, Some(alice_serial_2)]
+            );
+            assert!(history.records()[0].error().is_none());
+            assert!(history.records()[1].error().is_some());
+            assert!(history.records()[2].error().is_none());
+
+            let history = server
+                .publisher_history(&bob_handle, CommandHistoryCriteria::default())
+                .unwrap();
+            assert_eq!(1, history.total());
+            assert_eq!(Some(bob_serial), history.records()[0].serial());
+
+            let mut crit = CommandHistoryCriteria::default();
+            crit.set_offset(1);
+            crit.set_rows(1);
+            let history = server.publisher_history(&alice_handle, crit).unwrap();
+            assert_eq!(3, history.total());
+            assert_eq!(1, history.records().len());
+            assert_eq!(None, history.records()[0].serial());
+        })
+    }
+
+    #[test]
+    fn should_refuse_suspended_publisher() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+            let alice = publisher_alice(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+            server.suspend_publisher(alice_handle.clone()).unwrap();
+
+            let details = server.get_publisher_details(&alice_handle).unwrap();
+            assert!(details.suspended());
+            assert_eq!(
+                vec![alice_handle.clone()],
+                server.suspended_publishers().unwrap()
+            );
+
+            let file = CurrentFile::new(
+                test::rsync("rsync://localhost/repo/alice/file.txt"),
+                &Bytes::from("example content"),
+            );
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_publish(file.as_publish());
+            let delta = builder.finish();
+
+            match server.publish(alice_handle.clone(), delta) {
+                Err(Error::PublisherSuspended(pbl)) => assert_eq!(pbl, alice_handle),
+                _ => panic!("Expected publisher suspended error"),
+            }
+
+            server.reactivate_publisher(alice_handle.clone()).unwrap();
+            let details = server.get_publisher_details(&alice_handle).unwrap();
+            assert!(!details.suspended());
+            assert!(server.suspended_publishers().unwrap().is_empty());
+        })
+    }
+
+    #[test]
+    fn should_update_publisher_id_cert() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+
+            let alice = publisher_alice(&d);
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            let new_alice = publisher_alice(&d);
+            assert_ne!(alice.id_cert(), new_alice.id_cert());
+            let new_alice_req = make_publisher_req(alice_handle.as_str(), new_alice.id_cert());
+
+            server
+                .update_publisher_id_cert(alice_handle.clone(), new_alice_req)
+                .unwrap();
+
+            let details = server.get_publisher_details(&alice_handle).unwrap();
+            assert_eq!(details.id_cert(), new_alice.id_cert());
+            assert_eq!(details.base_uri(), alice.base_uri());
+
+            let handle = PubServer::repository_handle();
+            let crit = CommandHistoryCriteria::default();
+            let history = server.store.command_history(&handle, crit).unwrap();
+            assert!(history
+                .commands()
+                .iter()
+                .any(|command| command.summary.label == "pubd-publisher-id-update"));
+        })
+    }
+
+    #[test]
+    fn should_refuse_id_cert_update_for_other_handle() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+
+            let alice = publisher_alice(&d);
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            let new_alice = publisher_alice(&d);
+            let bob_req = make_publisher_req("bob", new_alice.id_cert());
+
+            match server.update_publisher_id_cert(alice_handle.clone(), bob_req) {
+                Err(Error::PublisherHandleMismatch(req_handle, publisher)) => {
+                    assert_eq!(req_handle, Handle::from_str_unsafe("bob"));
+                    assert_eq!(publisher, alice_handle);
+                }
+                _ => panic!("Expected handle mismatch error"),
+            }
+
+            let details = server.get_publisher_details(&alice_handle).unwrap();
+            assert_eq!(details.id_cert(), alice.id_cert());
+        })
+    }
+
+    #[test]
+    fn should_reset_rrdp_session() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+            let old_session = server.repo_stats().unwrap().session();
+
+            // Use the command directly, because writing the repository
+            // needs IPFS.
+            let handle = PubServer::repository_handle();
+            let cmd = CmdDet::reset_rrdp_session(&handle);
+            server.store.command(cmd).unwrap();
+
+            let stats = server.repo_stats().unwrap();
+            assert_ne!(old_session, stats.session());
+            assert_eq!(1, stats.serial());
+
+            let crit = CommandHistoryCriteria::default();
+            let history = server.store.command_history(&handle, crit).unwrap();
+            assert!(history
+                .commands()
+                .iter()
+                .any(|command| command.summary.label == "pubd-rrdp-session-reset"));
+        })
+    }
+
+    #[test]
+    fn should_verify_repository() {
+        test::test_under_tmp(|d| {
+            let server = make_server(&d);
+            let alice = publisher_alice(&d);
+
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            // Nothing was written yet, so the RRDP files are missing
+            let verification = server.verify_repository(false).unwrap();
+            assert!(verification.publishers().is_empty());
+            assert!(verification
+                .rrdp_files()
+                .contains(&RrdpFileDifference::Missing("notification.xml".to_string())));
+            assert!(!verification.repaired());
+
+            // Someone adds a file in the rsync tree for alice
+            let mut path = d.clone();
+            path.push(REPOSITORY_DIR);
+            path.push(REPOSITORY_RSYNC_DIR);
+            path.push("current/alice/file.txt");
+            file::save(b"example content", &path).unwrap();
+
+            let verification = server.verify_repository(false).unwrap();
+            let expected = vec![PublisherVerification::new(
+                alice_handle,
+                vec![ObjectDifference::Unexpected(test::rsync(
+                    "rsync://localhost/repo/alice/file.txt",
+                ))],
+                vec![],
+            )];
+            assert_eq!(verification.publishers(), &expected);
+            assert!(!verification.needs_new_session());
+            assert!(!verification.is_consistent());
+        })
+    }
+
     #[test]
     fn should_publish_files() {
         test::test_under_tmp(|d| {
@@ -422,7 +1276,9 @@ mod tests {
             let alice_handle = Handle::from_str_unsafe("alice");
             let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
 
-            server.create_publisher(publisher_req).unwrap();
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
 
             // get the file out of a list_reply
             fn find_in_reply<'a>(
@@ -562,4 +1418,52 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn should_announce_root_on_write() {
+        test::test_under_tmp(|d| {
+            let pubsub = Arc::new(InMemoryPubSub::default());
+            let feed = ChangeFeed::new("krill-test".to_string(), pubsub.clone());
+            let server = make_server(&d).with_change_feed(feed);
+
+            let alice = publisher_alice(&d);
+            let alice_handle = Handle::from_str_unsafe("alice");
+            let publisher_req = make_publisher_req(alice_handle.as_str(), alice.id_cert());
+
+            server
+                .create_publisher(publisher_req, PublisherQuota::default())
+                .unwrap();
+
+            let file = CurrentFile::new(
+                test::rsync("rsync://localhost/repo/alice/file.txt"),
+                &Bytes::from("example content"),
+            );
+            let mut builder = PublishDeltaBuilder::new();
+            builder.add_publish(file.as_publish());
+            server.publish(alice_handle, builder.finish()).unwrap();
+
+            server.write_repository().unwrap();
+
+            // One announcement for the publish, and one for the explicit write
+            let messages = pubsub.messages();
+            assert_eq!(2, messages.len());
+
+            let repository = server.repository().unwrap();
+            let key = server
+                .signer
+                .read()
+                .unwrap()
+                .get_key_info(repository.key_id())
+                .unwrap();
+
+            for (topic, data) in messages {
+                assert_eq!("krill-test", topic);
+
+                let change: RootChange = serde_json::from_slice(&data).unwrap();
+                assert_eq!(change.session(), repository.stats().session());
+                assert_eq!(change.serial(), repository.stats().serial());
+                assert!(change.verify(&key));
+            }
+        })
+    }
 }
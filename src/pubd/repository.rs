@@ -1,30 +1,33 @@
 use std::collections::HashMap;
 use std::fs;
+use std::io;
 use std::path::PathBuf;
 use std::str::{from_utf8_unchecked, FromStr};
 use std::time::SystemTime;
 
 
 use rpki::crypto::KeyIdentifier;
+use rpki::manifest::Manifest;
 use rpki::uri;
 use rpki::x509::Time;
 
 use crate::commons::api::rrdp::{
-    CurrentObjects, Delta, DeltaElements, DeltaRef, FileRef, Notification, RrdpSession, Snapshot,
-    SnapshotRef,
+    CurrentObjects, Delta, DeltaElements, DeltaRef, FileRef, Notification, PublishElement,
+    RrdpRetention, RrdpRetentionStats, RrdpSession, Snapshot, SnapshotRef,
 };
 use crate::commons::api::{
-    Handle, HexEncodedHash, PublishDelta, PublisherHandle, RepoInfo, StorableRepositoryCommand,
+    Base64, Handle, HexEncodedHash, IpfsRootVerification, ObjectDifference, PublishDelta,
+    PublisherHandle, PublisherIssue, PublisherQuota, PublisherVerification, RepoInfo,
+    RepositoryIssues, RepositoryVerification, RrdpFileDifference, StalePublisherThreshold,
+    StorableRepositoryCommand,
 };
 use crate::commons::error::Error;
 use crate::commons::eventsourcing::Aggregate;
 use crate::commons::remote::id::IdCert;
 use crate::commons::remote::rfc8183;
-use crate::commons::util::file;
+use crate::commons::util::file::{self, CurrentFile};
 use crate::commons::KrillResult;
-use crate::constants::{
-    REPOSITORY_RRDP_DIR, REPOSITORY_RRDP_SNAPSHOT_RETAIN_MINS, REPOSITORY_RSYNC_DIR,
-};
+use crate::constants::{REPOSITORY_RRDP_DIR, REPOSITORY_RSYNC_DIR, REPOSITORY_RSYNC_VERSIONS_DIR};
 use crate::pubd::publishers::Publisher;
 use crate::pubd::{Cmd, CmdDet, Evt, EvtDet, Ini, RrdpUpdate};
 
@@ -60,12 +63,25 @@ impl RsyncdStore {
 /// # Publishing
 ///
 impl RsyncdStore {
-    /// Write all the files to disk for rsync to a tmp-dir, then switch
-    /// things over in an effort to minimise the chance of people getting
-    /// inconsistent syncs..
-    pub fn write(&self, snapshot: &Snapshot) -> KrillResult<()> {
-        let mut new_dir = self.rsync_dir.clone();
-        new_dir.push(&format!("tmp-{}", snapshot.serial()));
+    /// Write all the files to disk for rsync in a new version directory,
+    /// and then atomically switch the 'current' symlink over to it. Rsync
+    /// clients which are still reading an older version can continue to do
+    /// so, because the given number of old versions is kept.
+    pub fn write(&self, snapshot: &Snapshot, versions_retain: usize) -> KrillResult<()> {
+        let versions_dir = self.versions_dir();
+        fs::create_dir_all(&versions_dir)?;
+
+        let current = self.current_path();
+
+        // Trees written before versions were used are a plain directory,
+        // move it so that 'current' can become a symlink.
+        if current.is_dir() && !is_symlink(&current) {
+            let version = self.next_version();
+            fs::rename(&current, versions_dir.join(version.to_string()))?;
+        }
+
+        let version = self.next_version();
+        let new_dir = versions_dir.join(version.to_string());
         fs::create_dir_all(&new_dir)?;
 
         let elements = snapshot.elements();
@@ -84,26 +100,123 @@ impl RsyncdStore {
             file::save(&publish.base64().to_bytes(), &path)?;
         }
 
-        let mut current_dir = self.rsync_dir.clone();
-        current_dir.push("current");
+        // Renaming a new symlink over the old one is atomic, so clients
+        // will either see the old or the new version.
+        let mut new_link = self.rsync_dir.clone();
+        new_link.push("current.tmp");
+        if is_symlink(&new_link) {
+            fs::remove_file(&new_link)?;
+        }
+
+        let mut target = PathBuf::from(REPOSITORY_RSYNC_VERSIONS_DIR);
+        target.push(version.to_string());
+        symlink_dir(&target, &new_link)?;
+        fs::rename(&new_link, &current)?;
 
-        let mut old_dir = self.rsync_dir.clone();
-        old_dir.push("old");
+        self.remove_old_versions(version, versions_retain);
 
-        if current_dir.exists() {
-            fs::rename(&current_dir, &old_dir)?;
+        Ok(())
+    }
+
+    /// Removes all versions before the current one, except for the most
+    /// recent ones to retain.
+    fn remove_old_versions(&self, current: u64, retain: usize) {
+        let mut old_versions: Vec<u64> = self
+            .versions()
+            .into_iter()
+            .filter(|version| *version < current)
+            .collect();
+        old_versions.sort();
+        old_versions.reverse();
+
+        for version in old_versions.into_iter().skip(retain) {
+            let _best_effort_rm = fs::remove_dir_all(self.versions_dir().join(version.to_string()));
         }
+    }
 
-        fs::rename(&new_dir, &current_dir)?;
+    fn next_version(&self) -> u64 {
+        self.versions().into_iter().max().unwrap_or(0) + 1
+    }
 
-        if old_dir.exists() {
-            fs::remove_dir_all(&old_dir)?;
+    fn versions(&self) -> Vec<u64> {
+        match fs::read_dir(self.versions_dir()) {
+            Err(_) => vec![],
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    u64::from_str(entry.file_name().to_string_lossy().as_ref()).ok()
+                })
+                .collect(),
         }
+    }
 
-        Ok(())
+    fn versions_dir(&self) -> PathBuf {
+        let mut path = self.rsync_dir.clone();
+        path.push(REPOSITORY_RSYNC_VERSIONS_DIR);
+        path
+    }
+
+    fn current_path(&self) -> PathBuf {
+        let mut path = self.rsync_dir.clone();
+        path.push("current");
+        path
+    }
+
+    /// Returns an rsyncd.conf module stanza for serving the rsync tree.
+    pub fn rsyncd_conf(&self) -> String {
+        let rsync_dir =
+            fs::canonicalize(&self.rsync_dir).unwrap_or_else(|_| self.rsync_dir.clone());
+        let current = rsync_dir.join("current");
+
+        let mut res = String::new();
+        res.push_str(&format!("[{}]\n", self.base_uri.module().module()));
+        res.push_str(&format!("    path = {}\n", current.to_string_lossy()));
+        res.push_str("    comment = RPKI repository\n");
+        res.push_str("    read only = yes\n");
+        res.push_str("    list = yes\n");
+
+        let path = self.base_uri.path();
+        if !path.is_empty() {
+            res.push_str(&format!(
+                "    # Warning: rsync_base includes the path '{}' below the module. Clients\n",
+                path
+            ));
+            res.push_str(
+                "    # will look for objects there, so it must refer to the path above.\n",
+            );
+        }
+
+        res
+    }
+
+    /// Returns all files currently in the rsync tree on disk.
+    fn current_files(&self) -> KrillResult<Vec<CurrentFile>> {
+        let current_dir = self.current_path();
+
+        if !current_dir.exists() {
+            return Ok(vec![]);
+        }
+
+        file::crawl_incl_rsync_base(&current_dir, &self.base_uri)
+            .map_err(|e| Error::IoError(io::Error::new(io::ErrorKind::Other, e.to_string())))
     }
 }
 
+fn is_symlink(path: &PathBuf) -> bool {
+    fs::symlink_metadata(path)
+        .map(|meta| meta.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn symlink_dir(target: &PathBuf, link: &PathBuf) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn symlink_dir(target: &PathBuf, link: &PathBuf) -> io::Result<()> {
+    std::os::windows::fs::symlink_dir(target, link)
+}
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct IpfsStore {
@@ -120,18 +233,32 @@ impl IpfsStore {
             tal_pubkey
         }
     }
-    pub fn write(&self, rsync_dir: &PathBuf) -> KrillResult<()> {
-        let rsync_dir = rsync_dir.join("current");
+    /// Adds the current rsync content to IPFS and publishes it under the
+    /// repository's IPNS name. Returns the new root CID.
+    pub fn write(&self, rsync_dir: &PathBuf) -> KrillResult<String> {
+        // Add the version that 'current' links to, rather than the link
+        let rsync_dir = fs::canonicalize(rsync_dir.join("current"))?;
         // TODO Probably might be a more efficient, IPFS specific way to do this
         info!("Syncing from updated rsync directory {:?} into IPFS ", rsync_dir);
 
         let ipfs_path = &self.ipfs_path;
         let cid = ipfs::add(ipfs_path, &rsync_dir)?;
-        let result = ipfs::publish(ipfs_path, &self.repo_pubkey, cid)?;
+        let result = ipfs::publish(ipfs_path, &self.repo_pubkey, cid.clone())?;
 
         info!("{:?}", result);
 
-        Ok(())
+        Ok(cid)
+    }
+
+    /// Compares the root CID of the current rsync content with the one that
+    /// is published under the repository's IPNS name.
+    fn verify(&self, rsync_dir: &PathBuf) -> IpfsRootVerification {
+        let expected = fs::canonicalize(rsync_dir.join("current"))
+            .ok()
+            .and_then(|rsync_dir| ipfs::hash_dir(&self.ipfs_path, &rsync_dir).ok());
+        let published = ipfs::resolve(&self.ipfs_path, &self.repo_pubkey).ok();
+
+        IpfsRootVerification::new(expected, published)
     }
 }
 
@@ -151,6 +278,9 @@ struct RrdpServer {
     notification: Notification,
     snapshot: Snapshot,
     deltas: Vec<Delta>,
+
+    #[serde(default)]
+    retention: RrdpRetention,
 }
 
 impl RrdpServer {
@@ -178,6 +308,7 @@ impl RrdpServer {
             notification,
             snapshot,
             deltas,
+            retention: RrdpRetention::default(),
         }
     }
 
@@ -203,20 +334,20 @@ impl RrdpServer {
         let snapshot_hash = HexEncodedHash::from_content(snapshot_xml.as_slice());
         let snapshot_ref = SnapshotRef::new(snapshot_uri, snapshot_path, snapshot_hash);
 
-        // keep at least 5 deltas if available, but beyond that no
-        // more then the combined size of which would exceed the
-        // size of the snapshot.
-        let snapshot_size = next_snapshot.size();
+        // keep at least the configured minimum number of deltas if
+        // available, but beyond that no more then the combined size of
+        // which would exceed the configured portion of the snapshot size.
+        let max_deltas_size = self.retention.max_deltas_size(next_snapshot.size());
         let mut deltas_size = delta.elements().size();
 
         let mut deltas = vec![&delta];
 
         for delta in &self.deltas {
-            if deltas.len() < 5 {
+            if deltas.len() < self.retention.delta_files_min_nr() {
                 deltas.push(delta)
             } else {
                 deltas_size += delta.elements().size();
-                if deltas_size < snapshot_size {
+                if deltas_size < max_deltas_size {
                     deltas.push(delta)
                 } else {
                     break;
@@ -260,14 +391,89 @@ impl RrdpServer {
         self.deltas.retain(|d| d.serial() >= last_delta);
     }
 
+    /// Creates an update that starts a new session at serial 1, with a
+    /// snapshot that contains the given elements, and no deltas.
+    fn reset_session(&self, elements: DeltaElements) -> RrdpUpdate {
+        let session = RrdpSession::new();
+        let serial = 1;
+
+        let delta = Delta::new(session, serial, elements);
+
+        let mut snapshot = Snapshot::new(session);
+        snapshot.apply_delta(delta.clone());
+
+        let snapshot_uri = Self::new_snapshot_uri(&self.rrdp_base_uri, &session, serial);
+        let snapshot_path = Self::new_snapshot_path(&self.rrdp_base_dir, &session, serial);
+        let snapshot_hash = HexEncodedHash::from_content(snapshot.xml().as_slice());
+        let snapshot_ref = SnapshotRef::new(snapshot_uri, snapshot_path, snapshot_hash);
+
+        let notification = Notification::new(session, serial, snapshot_ref, vec![]);
+
+        RrdpUpdate::new(delta, notification)
+    }
+
+    /// Start the new session (as recorded in an event). The delta in the
+    /// update is only used to build the snapshot, it is not published.
+    pub fn apply_session_reset(&mut self, update: RrdpUpdate) {
+        let session = update.session();
+        let (delta, notification) = update.unpack();
+
+        let mut snapshot = Snapshot::new(session);
+        snapshot.apply_delta(delta);
+
+        self.session = session;
+        self.serial = notification.serial();
+        self.notification = notification;
+        self.snapshot = snapshot;
+        self.deltas = vec![];
+    }
+
+    /// Update the retention policy. This will take effect when the next
+    /// delta is published.
+    pub fn set_retention(&mut self, retention: RrdpRetention) {
+        self.retention = retention;
+    }
+
+    pub fn retention_stats(&self) -> RrdpRetentionStats {
+        let deltas_size = self
+            .deltas
+            .iter()
+            .fold(0, |sum, d| sum + d.elements().size());
+
+        RrdpRetentionStats::new(
+            self.retention.clone(),
+            self.snapshot.size(),
+            self.deltas.len(),
+            deltas_size,
+            self.notification.last_delta(),
+        )
+    }
+
+    /// Returns true if the file or dir at the path was last modified longer
+    /// ago than the configured retention time. We use the modification time
+    /// because the creation time is not available on all platforms, and RRDP
+    /// files are never modified after they are written.
+    fn retention_expired(&self, path: &PathBuf) -> bool {
+        if let Ok(meta) = fs::metadata(path) {
+            if let Ok(modified) = meta.modified() {
+                if let Ok(duration) = SystemTime::now().duration_since(modified) {
+                    let minutes_old = duration.as_secs() / 60;
+                    return minutes_old >= self.retention.files_retain_mins();
+                }
+            }
+        }
+        false
+    }
+
     /// Write the (missing) RRDP files to disk, and remove the ones
-    /// no longer referenced in the notification file.
-    fn write(&self) -> Result<(), Error> {
-        let mut something_changed = false;
+    /// no longer referenced in the notification file. If force is true
+    /// then all current files are re-written, even if they exist.
+    fn write(&self, force: bool) -> Result<(), Error> {
+        let mut something_changed = force;
 
         // write snapshot if it's not there
         let snapshot_path = self.snapshot_path(self.serial);
-        if !snapshot_path.exists() {
+        if force || !snapshot_path.exists() {
             self.snapshot.write_xml(&snapshot_path)?;
             something_changed = true;
         }
@@ -275,7 +481,7 @@ impl RrdpServer {
         // write deltas if they are not there
         for delta in &self.deltas {
             let path = self.delta_path(delta.serial());
-            if !path.exists() {
+            if force || !path.exists() {
                 // assume that if the delta exists, it is correct
                 delta.write_xml(&path)?;
                 something_changed = true;
@@ -318,13 +524,25 @@ impl RrdpServer {
             // - a number that is higher than the current serial
             // - a number that is lower than the last delta (if set)
             if let Ok(serial) = u64::from_str(entry.file_name().to_string_lossy().as_ref()) {
-                // Clean up old serial dirs
+                // Clean up old serial dirs no longer referenced in the
+                // notification file *IF* they are older than the configured
+                // retention time. Validators may still be using a cached
+                // notification file that refers to them.
                 if let Some(last) = self.notification.last_delta() {
                     if serial < last {
-                        if path.is_dir() {
-                            let _best_effort_rm = fs::remove_dir_all(path);
+                        let delta_path = self.delta_path(serial);
+                        let age_path = if delta_path.exists() {
+                            &delta_path
                         } else {
-                            let _best_effort_rm = fs::remove_file(path);
+                            &path
+                        };
+
+                        if self.retention_expired(age_path) {
+                            if path.is_dir() {
+                                let _best_effort_rm = fs::remove_dir_all(path);
+                            } else {
+                                let _best_effort_rm = fs::remove_file(path);
+                            }
                         }
 
                         continue;
@@ -332,22 +550,12 @@ impl RrdpServer {
                 }
 
                 // Clean up snapshots in all dirs except the current
-                // *IF* the snapshot is older than REPOSITORY_RRDP_SNAPSHOT_RETAIN_MINS
+                // *IF* the snapshot is older than the configured retention time
                 if serial != self.serial {
                     let snapshot_path =
                         Self::new_snapshot_path(&self.rrdp_base_dir, &self.session, serial);
-                    if snapshot_path.exists() {
-                        if let Ok(meta) = fs::metadata(&snapshot_path) {
-                            if let Ok(created) = meta.created() {
-                                let now = SystemTime::now();
-                                if let Ok(duration) = now.duration_since(created) {
-                                    let minutes_old = duration.as_secs() / 60;
-                                    if minutes_old >= REPOSITORY_RRDP_SNAPSHOT_RETAIN_MINS {
-                                        let _best_effort_rm = fs::remove_file(snapshot_path);
-                                    }
-                                }
-                            }
-                        }
+                    if snapshot_path.exists() && self.retention_expired(&snapshot_path) {
+                        let _best_effort_rm = fs::remove_file(snapshot_path);
                     }
                 }
             } else {
@@ -364,6 +572,69 @@ impl RrdpServer {
     }
 }
 
+/// # Verification
+///
+impl RrdpServer {
+    /// Returns the elements in the current snapshot, by uri.
+    fn snapshot_objects(&self) -> ObjectHashes {
+        object_hashes(self.snapshot.elements())
+    }
+
+    /// Checks that the notification file on disk is current, and that the
+    /// snapshot and delta files it refers to exist and have the expected
+    /// content.
+    fn verify_files(&self) -> Vec<RrdpFileDifference> {
+        let mut res = vec![];
+
+        let notification_xml = self.notification.xml();
+        let notification_hash = HexEncodedHash::from_content(notification_xml.as_slice());
+        if let Some(diff) = Self::verify_file(
+            "notification.xml".to_string(),
+            &self.notification_path(),
+            &notification_hash,
+        ) {
+            res.push(diff);
+        }
+
+        if let Some(diff) = Self::verify_file(
+            Self::snapshot_rel(&self.session, self.serial),
+            &self.snapshot_path(self.serial),
+            self.notification.snapshot().hash(),
+        ) {
+            res.push(diff);
+        }
+
+        for delta in self.notification.deltas() {
+            if let Some(diff) = Self::verify_file(
+                Self::delta_rel(&self.session, delta.serial()),
+                &self.delta_path(delta.serial()),
+                delta.as_ref().hash(),
+            ) {
+                res.push(diff);
+            }
+        }
+
+        res
+    }
+
+    fn verify_file(
+        rel: String,
+        path: &PathBuf,
+        hash: &HexEncodedHash,
+    ) -> Option<RrdpFileDifference> {
+        match file::read(path) {
+            Err(_) => Some(RrdpFileDifference::Missing(rel)),
+            Ok(bytes) => {
+                if &HexEncodedHash::from_content(bytes.as_ref()) == hash {
+                    None
+                } else {
+                    Some(RrdpFileDifference::Modified(rel))
+                }
+            }
+        }
+    }
+}
+
 /// rrdp paths and uris
 ///
 impl RrdpServer {
@@ -449,6 +720,12 @@ pub struct Repository {
 
     #[serde(default = "RepoStats::default")]
     stats: RepoStats,
+
+    /// The manifests of each publisher, to track when they expire without
+    /// decoding all manifests on every publish. Publishers are added when
+    /// they first publish after this was introduced.
+    #[serde(default)]
+    manifests: HashMap<PublisherHandle, PublisherManifests>,
 }
 
 impl Repository {
@@ -489,6 +766,7 @@ impl Aggregate for Repository {
             rsync,
             ipfs,
             stats,
+            manifests: HashMap::new(),
         })
     }
 
@@ -500,31 +778,80 @@ impl Aggregate for Repository {
         self.version += 1;
         match event.into_details() {
             EvtDet::PublisherAdded(publisher_handle, publisher) => {
-                self.stats.new_publisher(&publisher_handle);
+                self.stats
+                    .new_publisher(&publisher_handle, publisher.added());
                 self.publishers.insert(publisher_handle, publisher);
             }
             EvtDet::PublisherRemoved(publisher_handle, update) => {
                 self.publishers.remove(&publisher_handle);
+                self.manifests.remove(&publisher_handle);
                 self.rrdp.apply_update(update);
                 self.stats
                     .remove_publisher(&publisher_handle, &self.rrdp.notification);
+                self.stats.set_retention(self.rrdp.retention_stats());
+            }
+            EvtDet::PublisherQuotaUpdated(publisher_handle, quota) => {
+                self.publishers
+                    .get_mut(&publisher_handle)
+                    .unwrap()
+                    .set_quota(quota);
+            }
+            EvtDet::PublisherIdCertUpdated(publisher_handle, id_cert) => {
+                self.publishers
+                    .get_mut(&publisher_handle)
+                    .unwrap()
+                    .set_id_cert(id_cert);
+            }
+            EvtDet::PublisherSuspended(publisher_handle) => {
+                self.publishers
+                    .get_mut(&publisher_handle)
+                    .unwrap()
+                    .suspend();
+            }
+            EvtDet::PublisherReactivated(publisher_handle) => {
+                self.publishers
+                    .get_mut(&publisher_handle)
+                    .unwrap()
+                    .reactivate();
             }
             EvtDet::Published(publisher_handle, update) => {
                 // update content for publisher
                 self.update_publisher(&publisher_handle, &update);
 
-                let time = update.time();
+                // The current objects already include this update, so
+                // applying it to manifests taken from them is harmless.
+                let publisher = self.publishers.get(&publisher_handle).unwrap();
+                let manifests = self
+                    .manifests
+                    .entry(publisher_handle.clone())
+                    .or_insert_with(|| PublisherManifests::from(publisher.current_objects()));
+                manifests.apply_delta(update.elements());
+
+                let publisher_stats = self
+                    .stats
+                    .publishers
+                    .get(&publisher_handle)
+                    .cloned()
+                    .unwrap_or_default()
+                    .published(publisher.current_objects(), manifests, update.time());
 
                 // update RRDP server
                 self.rrdp.apply_update(update);
 
-                let publisher = self.get_publisher(&publisher_handle).unwrap();
-                let publisher_stats = PublisherStats::new(publisher, time);
-
                 let notification = &self.rrdp.notification;
 
                 self.stats
-                    .publish(&publisher_handle, publisher_stats, notification)
+                    .publish(&publisher_handle, publisher_stats, notification);
+                self.stats.set_retention(self.rrdp.retention_stats());
+            }
+            EvtDet::RrdpRetentionUpdated(retention) => {
+                self.rrdp.set_retention(retention);
+                self.stats.set_retention(self.rrdp.retention_stats());
+            }
+            EvtDet::RrdpSessionReset(update) => {
+                self.rrdp.apply_session_reset(update);
+                self.stats.reset_session(&self.rrdp.notification);
+                self.stats.set_retention(self.rrdp.retention_stats());
             }
         }
     }
@@ -536,9 +863,21 @@ impl Aggregate for Repository {
         );
 
         match command.into_details() {
-            CmdDet::AddPublisher(publisher_request) => self.add_publisher(publisher_request),
+            CmdDet::AddPublisher(publisher_request, quota) => {
+                self.add_publisher(publisher_request, quota)
+            }
             CmdDet::RemovePublisher(publisher) => self.remove_publisher(publisher),
+            CmdDet::UpdatePublisherQuota(publisher, quota) => {
+                self.update_publisher_quota(publisher, quota)
+            }
+            CmdDet::UpdatePublisherIdCert(publisher, id_cert) => {
+                self.update_publisher_id_cert(publisher, id_cert)
+            }
+            CmdDet::SuspendPublisher(publisher) => self.suspend_publisher(publisher),
+            CmdDet::ReactivatePublisher(publisher) => self.reactivate_publisher(publisher),
             CmdDet::Publish(publisher_handle, delta) => self.publish(publisher_handle, delta),
+            CmdDet::UpdateRrdpRetention(retention) => self.update_rrdp_retention(retention),
+            CmdDet::ResetRrdpSession => self.reset_rrdp_session(),
         }
     }
 }
@@ -549,6 +888,7 @@ impl Repository {
     fn add_publisher(
         &self,
         publisher_request: rfc8183::PublisherRequest,
+        quota: PublisherQuota,
     ) -> Result<Vec<Evt>, Error> {
         let (_tag, handle, id_cert) = publisher_request.unpack();
 
@@ -557,7 +897,7 @@ impl Repository {
         } else {
             let base_uri =
                 uri::Rsync::from_string(format!("{}{}/", self.rsync.base_uri, handle)).unwrap();
-            let publisher = Publisher::new(id_cert, base_uri, CurrentObjects::default());
+            let publisher = Publisher::new(id_cert, base_uri, CurrentObjects::default(), quota);
 
             Ok(vec![EvtDet::publisher_added(
                 &self.handle,
@@ -589,6 +929,79 @@ impl Repository {
         )])
     }
 
+    /// Updates the quota for a publisher. Note that this does not affect
+    /// any content already published, but it will apply to future deltas.
+    fn update_publisher_quota(
+        &self,
+        publisher_handle: PublisherHandle,
+        quota: PublisherQuota,
+    ) -> Result<Vec<Evt>, Error> {
+        let publisher = self.get_publisher(&publisher_handle)?;
+
+        if publisher.quota() == &quota {
+            Ok(vec![])
+        } else {
+            Ok(vec![EvtDet::publisher_quota_updated(
+                &self.handle,
+                self.version,
+                publisher_handle,
+                quota,
+            )])
+        }
+    }
+
+    /// Replaces the RFC8183 identity certificate of a publisher, e.g. after
+    /// the remote CA regenerated its identity. Its content is kept.
+    fn update_publisher_id_cert(
+        &self,
+        publisher_handle: PublisherHandle,
+        id_cert: IdCert,
+    ) -> Result<Vec<Evt>, Error> {
+        let publisher = self.get_publisher(&publisher_handle)?;
+
+        if publisher.id_cert() == &id_cert {
+            Ok(vec![])
+        } else {
+            Ok(vec![EvtDet::publisher_id_cert_updated(
+                &self.handle,
+                self.version,
+                publisher_handle,
+                id_cert,
+            )])
+        }
+    }
+
+    /// Suspends a publisher. Its current objects remain published, but it
+    /// may not publish until it is reactivated.
+    fn suspend_publisher(&self, publisher_handle: PublisherHandle) -> Result<Vec<Evt>, Error> {
+        let publisher = self.get_publisher(&publisher_handle)?;
+
+        if publisher.is_suspended() {
+            Ok(vec![])
+        } else {
+            Ok(vec![EvtDet::publisher_suspended(
+                &self.handle,
+                self.version,
+                publisher_handle,
+            )])
+        }
+    }
+
+    /// Reactivates a suspended publisher.
+    fn reactivate_publisher(&self, publisher_handle: PublisherHandle) -> Result<Vec<Evt>, Error> {
+        let publisher = self.get_publisher(&publisher_handle)?;
+
+        if publisher.is_suspended() {
+            Ok(vec![EvtDet::publisher_reactivated(
+                &self.handle,
+                self.version,
+                publisher_handle,
+            )])
+        } else {
+            Ok(vec![])
+        }
+    }
+
     pub fn repo_info_for(&self, publisher: &PublisherHandle) -> RepoInfo {
         let publisher_rsync_base =
             uri::Rsync::from_str(&format!("{}{}/", self.rsync.base_uri, publisher)).unwrap();
@@ -630,6 +1043,14 @@ impl Repository {
         self.publishers.keys().cloned().collect()
     }
 
+    pub fn suspended_publishers(&self) -> Vec<PublisherHandle> {
+        self.publishers
+            .iter()
+            .filter(|(_, publisher)| publisher.is_suspended())
+            .map(|(handle, _)| handle.clone())
+            .collect()
+    }
+
     fn update_publisher(&mut self, publisher: &PublisherHandle, update: &RrdpUpdate) {
         self.publishers
             .get_mut(publisher)
@@ -647,8 +1068,13 @@ impl Repository {
         delta: PublishDelta,
     ) -> Result<Vec<Evt>, Error> {
         let publisher = self.get_publisher(&publisher_handle)?;
+        if publisher.is_suspended() {
+            return Err(Error::PublisherSuspended(publisher_handle));
+        }
+
         let delta_elements = DeltaElements::from(delta);
         publisher.verify_delta(&delta_elements)?;
+        publisher.verify_quota(&publisher_handle, &delta_elements)?;
         let rrdp_update = self.rrdp.publish(delta_elements)?;
 
         Ok(vec![EvtDet::published(
@@ -659,18 +1085,162 @@ impl Repository {
         )])
     }
 
-    /// Update the RRPD and Rsync files on disk.
-    pub fn write(&self) -> Result<(), Error> {
+    /// Update the RRDP retention policy, if it changed.
+    fn update_rrdp_retention(&self, retention: RrdpRetention) -> Result<Vec<Evt>, Error> {
+        if self.rrdp.retention == retention {
+            Ok(vec![])
+        } else {
+            Ok(vec![EvtDet::rrdp_retention_updated(
+                &self.handle,
+                self.version,
+                retention,
+            )])
+        }
+    }
+
+    /// Start a new RRDP session with a snapshot of all current objects of
+    /// all publishers.
+    fn reset_rrdp_session(&self) -> Result<Vec<Evt>, Error> {
+        let publishes = self
+            .publishers
+            .values()
+            .flat_map(|p| p.current_objects().elements())
+            .cloned()
+            .collect();
+        let elements = DeltaElements::new(publishes, vec![], vec![]);
+        let update = self.rrdp.reset_session(elements);
+
+        Ok(vec![EvtDet::rrdp_session_reset(
+            &self.handle,
+            self.version,
+            update,
+        )])
+    }
+
+    /// Update the RRPD and Rsync files on disk, and the content in IPFS.
+    /// Returns the new root CID of the content in IPFS.
+    pub fn write(&self, rsync_versions_retain: usize) -> Result<String, Error> {
+        self.write_files(false, rsync_versions_retain)
+    }
+
+    /// Re-write all current RRDP and Rsync files on disk, and the content
+    /// in IPFS, regardless of what is there now. Returns the new root CID
+    /// of the content in IPFS.
+    pub fn repair(&self, rsync_versions_retain: usize) -> Result<String, Error> {
+        self.write_files(true, rsync_versions_retain)
+    }
+
+    fn write_files(&self, force: bool, rsync_versions_retain: usize) -> Result<String, Error> {
         // update RRDP
-        self.rrdp.write()?;
+        self.rrdp.write(force)?;
 
         // re-sync RRDP snapshot to rsync files
         let snapshot = self.rrdp.snapshot();
-        self.rsync.write(snapshot)?;
-        self.ipfs.write(&self.rsync.rsync_dir)?;
+        self.rsync.write(snapshot, rsync_versions_retain)?;
+        self.ipfs.write(&self.rsync.rsync_dir)
+    }
+}
 
-        Ok(())
+/// # Verify
+///
+impl Repository {
+    /// Compares the objects of all publishers with the RRDP snapshot kept in
+    /// this repository, and with the rsync tree, RRDP files and IPFS root
+    /// published from it.
+    pub fn verify(&self) -> KrillResult<RepositoryVerification> {
+        let mut rsync_objects: ObjectHashes = self
+            .rsync
+            .current_files()?
+            .into_iter()
+            .map(|f| (f.uri().to_string(), (f.uri().clone(), f.hash().clone())))
+            .collect();
+        let mut rrdp_objects = self.rrdp.snapshot_objects();
+
+        let mut publishers = vec![];
+        for (handle, publisher) in self.publishers.iter() {
+            let base_uri = publisher.base_uri();
+            let expected = object_hashes(publisher.current_objects().elements());
+
+            let rsync = object_differences(&expected, &take_under(&mut rsync_objects, base_uri));
+            let rrdp = object_differences(&expected, &take_under(&mut rrdp_objects, base_uri));
+
+            let verification = PublisherVerification::new(handle.clone(), rsync, rrdp);
+            if !verification.is_empty() {
+                publishers.push(verification);
+            }
+        }
+        publishers.sort_by(|a, b| a.publisher().as_str().cmp(b.publisher().as_str()));
+
+        Ok(RepositoryVerification::new(
+            self.rrdp.session,
+            self.rrdp.serial,
+            publishers,
+            sorted_uris(rsync_objects),
+            sorted_uris(rrdp_objects),
+            self.rrdp.verify_files(),
+            self.ipfs.verify(&self.rsync.rsync_dir),
+        ))
+    }
+}
+
+/// Objects by their uri as a string, because uri::Rsync cannot be used as a
+/// key in a HashMap.
+type ObjectHashes = HashMap<String, (uri::Rsync, HexEncodedHash)>;
+
+fn object_hashes(elements: Vec<&PublishElement>) -> ObjectHashes {
+    elements
+        .into_iter()
+        .map(|el| {
+            let hash = el.base64().to_encoded_hash();
+            (el.uri().to_string(), (el.uri().clone(), hash))
+        })
+        .collect()
+}
+
+/// Removes and returns all objects under the base uri.
+fn take_under(objects: &mut ObjectHashes, base_uri: &uri::Rsync) -> ObjectHashes {
+    let base_uri = base_uri.to_string();
+    let keys: Vec<String> = objects
+        .keys()
+        .filter(|uri| uri.starts_with(&base_uri))
+        .cloned()
+        .collect();
+
+    keys.into_iter()
+        .filter_map(|uri| objects.remove_entry(&uri))
+        .collect()
+}
+
+fn object_differences(expected: &ObjectHashes, found: &ObjectHashes) -> Vec<ObjectDifference> {
+    let mut res = vec![];
+
+    for (uri, (rsync_uri, hash)) in expected.iter() {
+        match found.get(uri) {
+            None => res.push((uri, ObjectDifference::Missing(rsync_uri.clone()))),
+            Some((_, found_hash)) if found_hash != hash => {
+                res.push((uri, ObjectDifference::Modified(rsync_uri.clone())))
+            }
+            _ => {}
+        }
+    }
+
+    for (uri, (rsync_uri, _)) in found.iter() {
+        if !expected.contains_key(uri) {
+            res.push((uri, ObjectDifference::Unexpected(rsync_uri.clone())));
+        }
     }
+
+    res.sort_by(|a, b| a.0.cmp(b.0));
+    res.into_iter().map(|(_, diff)| diff).collect()
+}
+
+fn sorted_uris(objects: ObjectHashes) -> Vec<uri::Rsync> {
+    let mut res: Vec<(String, uri::Rsync)> = objects
+        .into_iter()
+        .map(|(uri, (rsync_uri, _))| (uri, rsync_uri))
+        .collect();
+    res.sort_by(|a, b| a.0.cmp(&b.0));
+    res.into_iter().map(|(_, uri)| uri).collect()
 }
 
 /// # Miscellaneous
@@ -688,16 +1258,25 @@ impl Repository {
         self.ipfs.ipfs_path.to_string()
     }
 
+    pub fn rsyncd_conf(&self) -> String {
+        self.rsync.rsyncd_conf()
+    }
+
     pub fn regenerate_stats(&mut self) {
         let mut stats = RepoStats::default();
+        let mut manifests = HashMap::new();
         for (handle, details) in &self.publishers {
-            let publisher_stats: PublisherStats = details.current_objects().into();
+            let mut publisher_stats: PublisherStats = details.current_objects().into();
+            publisher_stats.added = details.added();
             stats.publishers.insert(handle.clone(), publisher_stats);
+            manifests.insert(handle.clone(), details.current_objects().into());
         }
         stats.serial = self.rrdp.serial;
         stats.session = self.rrdp.session;
+        stats.retention = self.rrdp.retention_stats();
 
         self.stats = stats;
+        self.manifests = manifests;
     }
 }
 
@@ -709,6 +1288,8 @@ pub struct RepoStats {
     session: RrdpSession,
     serial: u64,
     last_update: Option<Time>,
+    #[serde(default)]
+    retention: RrdpRetentionStats,
 }
 
 impl Default for RepoStats {
@@ -718,6 +1299,7 @@ impl Default for RepoStats {
             session: RrdpSession::default(),
             serial: 0,
             last_update: None,
+            retention: RrdpRetentionStats::default(),
         }
     }
 }
@@ -729,6 +1311,7 @@ impl RepoStats {
             session,
             serial: 0,
             last_update: None,
+            retention: RrdpRetentionStats::default(),
         }
     }
 
@@ -743,9 +1326,12 @@ impl RepoStats {
         self.last_update = Some(notification.time());
     }
 
-    pub fn new_publisher(&mut self, publisher: &PublisherHandle) {
-        self.publishers
-            .insert(publisher.clone(), PublisherStats::default());
+    pub fn new_publisher(&mut self, publisher: &PublisherHandle, added: Option<Time>) {
+        let stats = PublisherStats {
+            added,
+            ..PublisherStats::default()
+        };
+        self.publishers.insert(publisher.clone(), stats);
     }
 
     pub fn remove_publisher(&mut self, publisher: &PublisherHandle, notification: &Notification) {
@@ -754,24 +1340,76 @@ impl RepoStats {
         self.last_update = Some(notification.time())
     }
 
+    pub fn reset_session(&mut self, notification: &Notification) {
+        self.session = notification.session();
+        self.serial = notification.serial();
+        self.last_update = Some(notification.time());
+    }
+
+    pub fn set_retention(&mut self, retention: RrdpRetentionStats) {
+        self.retention = retention;
+    }
+
     pub fn get_publishers(&self) -> &HashMap<PublisherHandle, PublisherStats> {
         &self.publishers
     }
 
+    /// Returns the publishers which have not updated for the given number
+    /// of seconds. Publishers that never published are given as much time,
+    /// counted from when they were added.
     pub fn stale_publishers(&self, seconds: i64) -> Vec<PublisherHandle> {
+        let now = Time::now().timestamp();
         let mut res = vec![];
         for (publisher, stats) in self.publishers.iter() {
             if let Some(update_time) = stats.last_update {
-                if Time::now().timestamp() - update_time.timestamp() >= seconds {
+                if now - update_time.timestamp() >= seconds {
                     res.push(publisher.clone())
                 }
-            } else {
+            } else if !stats.added_within(now, seconds) {
                 res.push(publisher.clone())
             }
         }
         res
     }
 
+    /// Returns the publishers which are stale according to the threshold,
+    /// with the reasons why. Publishers that never published are only
+    /// reported once the update threshold has passed since they were added.
+    pub fn publisher_issues(&self, threshold: &StalePublisherThreshold) -> RepositoryIssues {
+        let now = Time::now().timestamp();
+        let update_secs = threshold.update_mins() as i64 * 60;
+        let manifest_secs = threshold.manifest_mins() as i64 * 60;
+
+        let mut res = RepositoryIssues::default();
+        for (publisher, stats) in self.publishers.iter() {
+            let mut issues = vec![];
+
+            match stats.last_update {
+                None => {
+                    if !stats.added_within(now, update_secs) {
+                        issues.push(PublisherIssue::NeverPublished)
+                    }
+                }
+                Some(update_time) => {
+                    if now - update_time.timestamp() >= update_secs {
+                        issues.push(PublisherIssue::NotUpdatedSince(update_time))
+                    }
+                }
+            }
+
+            if let Some(next_update) = stats.manifest_next_update {
+                if next_update.timestamp() - now < manifest_secs {
+                    issues.push(PublisherIssue::ManifestExpires(next_update))
+                }
+            }
+
+            if !issues.is_empty() {
+                res.add(publisher.clone(), issues);
+            }
+        }
+        res
+    }
+
     pub fn last_update(&self) -> Option<Time> {
         self.last_update
     }
@@ -783,6 +1421,10 @@ impl RepoStats {
     pub fn session(&self) -> RrdpSession {
         self.session
     }
+
+    pub fn retention(&self) -> &RrdpRetentionStats {
+        &self.retention
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -790,19 +1432,38 @@ pub struct PublisherStats {
     objects: usize,
     size: usize,
     last_update: Option<Time>,
+    #[serde(default)]
+    manifest_next_update: Option<Time>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    added: Option<Time>,
 }
 
 impl PublisherStats {
-    pub fn new(publisher: &Publisher, last_update: Time) -> Self {
-        let objects = publisher.current_objects().len();
-        let size = publisher.current_objects().size();
+    /// Returns the stats after the publisher published, given the resulting
+    /// objects and manifests.
+    pub fn published(
+        &self,
+        objects: &CurrentObjects,
+        manifests: &PublisherManifests,
+        last_update: Time,
+    ) -> Self {
         PublisherStats {
-            objects,
-            size,
+            objects: objects.len(),
+            size: objects.size(),
             last_update: Some(last_update),
+            manifest_next_update: manifests.earliest_next_update(),
+            added: self.added,
         }
     }
 
+    /// Whether the publisher was added less than the given number of seconds
+    /// ago. Unknown for publishers added before this was recorded.
+    fn added_within(&self, now: i64, seconds: i64) -> bool {
+        self.added
+            .map(|added| now - added.timestamp() < seconds)
+            .unwrap_or(false)
+    }
+
     pub fn objects(&self) -> usize {
         self.objects
     }
@@ -814,6 +1475,14 @@ impl PublisherStats {
     pub fn last_update(&self) -> Option<Time> {
         self.last_update
     }
+
+    pub fn manifest_next_update(&self) -> Option<Time> {
+        self.manifest_next_update
+    }
+
+    pub fn added(&self) -> Option<Time> {
+        self.added
+    }
 }
 
 impl From<&CurrentObjects> for PublisherStats {
@@ -822,6 +1491,8 @@ impl From<&CurrentObjects> for PublisherStats {
             objects: objects.len(),
             size: objects.size(),
             last_update: None,
+            manifest_next_update: PublisherManifests::from(objects).earliest_next_update(),
+            added: None,
         }
     }
 }
@@ -832,16 +1503,376 @@ impl Default for PublisherStats {
             objects: 0,
             size: 0,
             last_update: None,
+            manifest_next_update: None,
+            added: None,
         }
     }
 }
 
+//------------ PublisherManifests --------------------------------------------
+
+/// The next update times of the manifests of a publisher, by uri. Only the
+/// manifests in published deltas need to be decoded to keep this current.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PublisherManifests(HashMap<String, Time>);
+
+impl PublisherManifests {
+    pub fn apply_delta(&mut self, delta: &DeltaElements) {
+        for withdraw in delta.withdraws() {
+            self.0.remove(&withdraw.uri().to_string());
+        }
+
+        let published = delta.publishes().iter().map(|p| (p.uri(), p.base64()));
+        let updated = delta.updates().iter().map(|u| (u.uri(), u.base64()));
+        for (uri, base64) in published.chain(updated) {
+            let uri = uri.to_string();
+            if let Some(next_update) = Self::next_update_for(&uri, base64) {
+                self.0.insert(uri, next_update);
+            } else {
+                self.0.remove(&uri);
+            }
+        }
+    }
+
+    pub fn earliest_next_update(&self) -> Option<Time> {
+        self.0
+            .values()
+            .min_by_key(|next_update| next_update.timestamp())
+            .cloned()
+    }
+
+    /// Returns the next update time of the object if it is a manifest that
+    /// can be parsed.
+    fn next_update_for(uri: &str, base64: &Base64) -> Option<Time> {
+        if uri.ends_with(".mft") {
+            Manifest::decode(base64.to_bytes(), false)
+                .ok()
+                .map(|mft| mft.content().next_update())
+        } else {
+            None
+        }
+    }
+}
+
+impl From<&CurrentObjects> for PublisherManifests {
+    fn from(objects: &CurrentObjects) -> Self {
+        PublisherManifests(
+            objects
+                .elements()
+                .into_iter()
+                .filter_map(|el| {
+                    let uri = el.uri().to_string();
+                    Self::next_update_for(&uri, el.base64()).map(|time| (uri, time))
+                })
+                .collect(),
+        )
+    }
+}
+
 //------------ Tests ---------------------------------------------------------
 #[cfg(test)]
 mod tests {
 
     use super::*;
 
+    use bytes::Bytes;
+
+    use crate::test;
+
+    fn make_rrdp_server(work_dir: &PathBuf, retention: RrdpRetention) -> RrdpServer {
+        let mut rrdp = RrdpServer::new(
+            test::https("https://localhost/rrdp/"),
+            work_dir,
+            RrdpSession::default(),
+        );
+        rrdp.set_retention(retention);
+        rrdp.write(false).unwrap();
+        rrdp
+    }
+
+    /// Publishes a new object, always of the same size, and writes the
+    /// resulting RRDP files.
+    fn publish_object(rrdp: &mut RrdpServer, nr: u64) {
+        let publish = PublishElement::new(
+            Base64::from_content(&[0; 300]),
+            test::rsync(&format!("rsync://localhost/repo/ta/{}.cer", nr)),
+        );
+        let elements = DeltaElements::new(vec![publish], vec![], vec![]);
+        let update = rrdp.publish(elements).unwrap();
+        rrdp.apply_update(update);
+        rrdp.write(false).unwrap();
+    }
+
+    fn delta_serials(rrdp: &RrdpServer) -> Vec<u64> {
+        rrdp.notification
+            .deltas()
+            .iter()
+            .map(|d| d.serial())
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_minimum_nr_of_deltas() {
+        test::test_under_tmp(|d| {
+            // No room for deltas beyond the minimum
+            let mut rrdp = make_rrdp_server(&d, RrdpRetention::new(3, 0, 0));
+
+            publish_object(&mut rrdp, 1);
+            assert_eq!(delta_serials(&rrdp), vec![1]);
+
+            for nr in 2..7 {
+                publish_object(&mut rrdp, nr);
+            }
+
+            assert_eq!(delta_serials(&rrdp), vec![6, 5, 4]);
+            assert_eq!(rrdp.deltas.len(), 3);
+            assert_eq!(rrdp.notification.last_delta(), Some(4));
+        })
+    }
+
+    #[test]
+    fn should_limit_deltas_size_to_pct_of_snapshot() {
+        assert_eq!(RrdpRetention::new(1, 50, 0).max_deltas_size(1000), 500);
+        assert_eq!(RrdpRetention::new(1, 150, 0).max_deltas_size(1000), 1500);
+        assert_eq!(RrdpRetention::new(1, 0, 0).max_deltas_size(1000), 0);
+
+        test::test_under_tmp(|d| {
+            let mut rrdp = make_rrdp_server(&d, RrdpRetention::new(1, 50, 0));
+
+            for nr in 1..7 {
+                publish_object(&mut rrdp, nr);
+            }
+
+            // Every delta has one object of the same size, and the snapshot
+            // has 6 of them. So, deltas may be kept as long as their combined
+            // size stays below the size of 3 objects.
+            let object_size = rrdp.deltas[0].elements().size();
+            assert_eq!(rrdp.snapshot.size(), 6 * object_size);
+            assert_eq!(delta_serials(&rrdp), vec![6, 5]);
+
+            // The minimum is kept, even if it exceeds the size limit
+            rrdp.set_retention(RrdpRetention::new(4, 0, 0));
+            publish_object(&mut rrdp, 7);
+            assert_eq!(delta_serials(&rrdp), vec![7, 6, 5]);
+        })
+    }
+
+    #[test]
+    fn should_only_find_retention_expired_for_old_files() {
+        test::test_under_tmp(|d| {
+            let mut rrdp = make_rrdp_server(&d, RrdpRetention::new(1, 0, 60));
+
+            let mut path = d.clone();
+            path.push("file.txt");
+            assert!(!rrdp.retention_expired(&path));
+
+            file::save(b"content", &path).unwrap();
+            assert!(!rrdp.retention_expired(&path));
+
+            rrdp.set_retention(RrdpRetention::new(1, 0, 0));
+            assert!(rrdp.retention_expired(&path));
+        })
+    }
+
+    #[test]
+    fn should_remove_old_rrdp_files_after_retention_time() {
+        test::test_under_tmp(|d| {
+            let mut rrdp = make_rrdp_server(&d, RrdpRetention::new(1, 0, 60));
+
+            for nr in 1..4 {
+                publish_object(&mut rrdp, nr);
+            }
+
+            // Only the last delta is referenced, but the other files are
+            // too recent to be removed.
+            assert_eq!(delta_serials(&rrdp), vec![3]);
+            assert!(rrdp.snapshot_path(0).exists());
+            for serial in 1..4 {
+                assert!(rrdp.snapshot_path(serial).exists());
+                assert!(rrdp.delta_path(serial).exists());
+            }
+
+            // Once the retention time has passed, files which are no longer
+            // referenced are removed on the next write.
+            rrdp.set_retention(RrdpRetention::new(1, 0, 0));
+            publish_object(&mut rrdp, 4);
+
+            assert_eq!(delta_serials(&rrdp), vec![4]);
+            assert!(!rrdp.snapshot_path(0).exists());
+            for serial in 1..4 {
+                assert!(!rrdp.snapshot_path(serial).exists());
+                assert!(!rrdp.delta_path(serial).exists());
+            }
+            assert!(rrdp.snapshot_path(4).exists());
+            assert!(rrdp.delta_path(4).exists());
+            assert!(rrdp.notification_path().exists());
+        })
+    }
+
+    #[test]
+    fn should_write_rsync_versions() {
+        test::test_under_tmp(|d| {
+            let base_uri = test::rsync("rsync://localhost/repo/");
+            let store = RsyncdStore::new(base_uri, &d);
+
+            // Trees written before versions were used are moved aside
+            let mut old_current = store.current_path();
+            old_current.push("old.cer");
+            file::save(b"old", &old_current).unwrap();
+
+            let mut snapshot = Snapshot::new(RrdpSession::default());
+            let publish = PublishElement::new(
+                Base64::from_content(b"content"),
+                test::rsync("rsync://localhost/repo/ta/ta.cer"),
+            );
+            let elements = DeltaElements::new(vec![publish], vec![], vec![]);
+            snapshot.apply_delta(Delta::new(RrdpSession::default(), 1, elements));
+
+            for _ in 0..3 {
+                store.write(&snapshot, 1).unwrap();
+            }
+
+            let current = store.current_path();
+            assert!(is_symlink(&current));
+            assert_eq!(
+                fs::read_link(&current).unwrap(),
+                PathBuf::from(REPOSITORY_RSYNC_VERSIONS_DIR).join("4")
+            );
+            assert!(current.join("ta/ta.cer").exists());
+            assert!(!old_current.exists());
+
+            let mut versions = store.versions();
+            versions.sort();
+            assert_eq!(versions, vec![3, 4]);
+
+            let conf = store.rsyncd_conf();
+            assert!(conf.starts_with("[repo]\n"));
+            assert!(conf.contains("/current\n"));
+        })
+    }
+
+    #[test]
+    fn should_find_stale_publishers() {
+        let threshold = StalePublisherThreshold::new(60, 60);
+        let now = Time::now();
+
+        let publisher_stats = |last_update: Time, manifest_next_update: Time| PublisherStats {
+            objects: 1,
+            size: 1,
+            last_update: Some(last_update),
+            manifest_next_update: Some(manifest_next_update),
+            added: None,
+        };
+
+        let never = Handle::from_str_unsafe("never");
+        let never_unknown = Handle::from_str_unsafe("never-unknown");
+        let just_added = Handle::from_str_unsafe("just-added");
+        let fresh = Handle::from_str_unsafe("fresh");
+        let old = Handle::from_str_unsafe("old");
+        let expiring = Handle::from_str_unsafe("expiring");
+
+        let mut stats = RepoStats::new(RrdpSession::default());
+        stats.new_publisher(&never, Some(now - chrono::Duration::hours(2)));
+        stats.new_publisher(&never_unknown, None);
+        stats.new_publisher(&just_added, Some(now - chrono::Duration::minutes(5)));
+        stats.publishers.insert(
+            fresh.clone(),
+            publisher_stats(now, now + chrono::Duration::hours(8)),
+        );
+        stats.publishers.insert(
+            old.clone(),
+            publisher_stats(
+                now - chrono::Duration::hours(2),
+                now + chrono::Duration::hours(8),
+            ),
+        );
+        stats.publishers.insert(
+            expiring.clone(),
+            publisher_stats(now, now + chrono::Duration::minutes(30)),
+        );
+
+        let issues = stats.publisher_issues(&threshold);
+        let publishers = issues.publishers();
+
+        assert_eq!(4, publishers.len());
+        assert!(!publishers.contains_key(&fresh));
+        assert!(!publishers.contains_key(&just_added));
+        assert_eq!(
+            publishers.get(&never).unwrap(),
+            &vec![PublisherIssue::NeverPublished]
+        );
+        assert_eq!(
+            publishers.get(&never_unknown).unwrap(),
+            &vec![PublisherIssue::NeverPublished]
+        );
+        assert_eq!(
+            publishers.get(&old).unwrap(),
+            &vec![PublisherIssue::NotUpdatedSince(
+                now - chrono::Duration::hours(2)
+            )]
+        );
+        assert_eq!(
+            publishers.get(&expiring).unwrap(),
+            &vec![PublisherIssue::ManifestExpires(
+                now + chrono::Duration::minutes(30)
+            )]
+        );
+
+        let mut stale = stats.stale_publishers(3600);
+        stale.sort_by_key(|h| h.to_string());
+        assert_eq!(stale, vec![never, never_unknown, old]);
+    }
+
+    #[test]
+    fn should_track_manifest_next_update_from_deltas() {
+        let mft = include_bytes!("../../test-resources/validation/child.mft");
+        let next_update = Manifest::decode(Bytes::from_static(mft), false)
+            .unwrap()
+            .content()
+            .next_update();
+
+        let mft_uri = test::rsync("rsync://localhost/repo/child/0/child.mft");
+        let mft_publish = PublishElement::new(Base64::from_content(mft), mft_uri.clone());
+        let cer_publish = PublishElement::new(
+            Base64::from_content(&[0; 300]),
+            test::rsync("rsync://localhost/repo/child/0/child.cer"),
+        );
+
+        let mut objects = CurrentObjects::default();
+        let mut manifests = PublisherManifests::default();
+        let mut stats = PublisherStats::default();
+
+        let delta = DeltaElements::new(vec![mft_publish.clone()], vec![], vec![]);
+        objects.apply_delta(delta.clone());
+        manifests.apply_delta(&delta);
+        stats = stats.published(&objects, &manifests, Time::now());
+        assert_eq!(stats.manifest_next_update(), Some(next_update));
+
+        // Deltas without manifests keep the earlier next update time
+        let delta = DeltaElements::new(vec![cer_publish], vec![], vec![]);
+        objects.apply_delta(delta.clone());
+        manifests.apply_delta(&delta);
+        stats = stats.published(&objects, &manifests, Time::now());
+        assert_eq!(stats.manifest_next_update(), Some(next_update));
+        assert_eq!(stats.objects(), 2);
+        assert_eq!(manifests, PublisherManifests::from(&objects));
+
+        // The manifests are internal, they are not part of the stats
+        let json = serde_json::to_string(&stats).unwrap();
+        assert!(!json.contains("child.mft"));
+
+        let delta = DeltaElements::new(vec![], vec![], vec![mft_publish.as_withdraw()]);
+        objects.apply_delta(delta.clone());
+        manifests.apply_delta(&delta);
+        stats = stats.published(&objects, &manifests, Time::now());
+        assert_eq!(stats.manifest_next_update(), None);
+        assert_eq!(stats.objects(), 1);
+
+        let mut expected = PublisherStats::from(&objects);
+        expected.last_update = stats.last_update();
+        assert_eq!(stats, expected);
+    }
+
     #[test]
     fn deserialize_0_4_2_snapshot() {
         let json = include_str!("../../test-resources/repository/snapshot-v042.json");
@@ -0,0 +1,199 @@
+//! Optional validation of the RPKI objects sent by publishers.
+//!
+//! Objects are parsed based on their file extension. Only checks that can be
+//! done on the object by itself are performed: the structure, the CMS
+//! signature of signed objects, and the validity window. Checking the
+//! chain up to a trust anchor is left to relying parties.
+//!
+//! Note that this means that the signatures of certificates and CRLs are
+//! not checked. That needs the certificate of the issuing CA, which is
+//! published by its parent, often in another repository.
+use bytes::Bytes;
+
+use rpki::cert::Cert;
+use rpki::crl::Crl;
+use rpki::manifest::Manifest;
+use rpki::roa::Roa;
+use rpki::sigobj::SignedObject;
+use rpki::uri;
+use rpki::x509::Time;
+
+use crate::commons::api::{PublishDelta, PublisherHandle};
+use crate::commons::error::Error;
+use crate::commons::KrillEmptyResult;
+
+/// Validates all objects that are published or updated in the delta.
+/// Withdraws are not checked.
+pub fn validate_delta(publisher: &PublisherHandle, delta: &PublishDelta) -> KrillEmptyResult {
+    let now = Time::now();
+
+    let publishes = delta.publishes().iter().map(|p| (p.uri(), p.content()));
+    let updates = delta.updates().iter().map(|u| (u.uri(), u.content()));
+
+    for (uri, content) in publishes.chain(updates) {
+        validate_object(uri, content.to_bytes(), now).map_err(|msg| {
+            Error::PublisherObjectInvalid(publisher.clone(), uri.to_string(), msg)
+        })?;
+    }
+
+    Ok(())
+}
+
+fn validate_object(uri: &uri::Rsync, bytes: Bytes, now: Time) -> Result<(), String> {
+    let uri = uri.to_string();
+    let extension = match uri.rfind('.') {
+        Some(idx) => &uri[idx + 1..],
+        None => "",
+    };
+
+    match extension {
+        "cer" => validate_cert(bytes, now),
+        "crl" => validate_crl(bytes, now),
+        "mft" => validate_mft(bytes, now),
+        "roa" => validate_roa(bytes, now),
+        "gbr" | "asa" => validate_signed_object(bytes, now),
+        _ => Err(format!("unsupported file extension '{}'", extension)),
+    }
+}
+
+fn validate_cert(bytes: Bytes, now: Time) -> Result<(), String> {
+    let cert = Cert::decode(bytes).map_err(|_| "cannot parse certificate".to_string())?;
+    cert.validity()
+        .validate_at(now)
+        .map_err(|_| "certificate is not valid at this time".to_string())
+}
+
+/// Checks the structure and the next update time. The signature is not
+/// checked, see the module documentation.
+fn validate_crl(bytes: Bytes, now: Time) -> Result<(), String> {
+    let crl = Crl::decode(bytes).map_err(|_| "cannot parse CRL".to_string())?;
+    if crl.next_update() < now {
+        Err("CRL is past its next update time".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_mft(bytes: Bytes, now: Time) -> Result<(), String> {
+    let mft =
+        Manifest::decode(bytes.clone(), true).map_err(|_| "cannot parse manifest".to_string())?;
+
+    let content = mft.content();
+    if content.this_update() > now || content.next_update() < now {
+        return Err("manifest is not current".to_string());
+    }
+
+    validate_signed_object(bytes, now)
+}
+
+fn validate_roa(bytes: Bytes, now: Time) -> Result<(), String> {
+    Roa::decode(bytes.clone(), true).map_err(|_| "cannot parse ROA".to_string())?;
+    validate_signed_object(bytes, now)
+}
+
+/// Verifies the CMS signature using the embedded EE certificate, and the
+/// validity of that certificate.
+fn validate_signed_object(bytes: Bytes, now: Time) -> Result<(), String> {
+    let signed =
+        SignedObject::decode(bytes, true).map_err(|_| "cannot parse signed object".to_string())?;
+
+    signed
+        .verify_signature(true)
+        .map_err(|_| "invalid signature".to_string())?;
+
+    signed
+        .cert()
+        .validity()
+        .validate_at(now)
+        .map_err(|_| "EE certificate is not valid at this time".to_string())
+}
+
+//------------ Tests ---------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test;
+
+    // Objects published by a child CA, with a certificate issued to it by
+    // its parent. All of them are valid at the time below.
+    fn objects() -> Vec<(uri::Rsync, Bytes)> {
+        vec![
+            (
+                test::rsync("rsync://localhost/repo/ta/0/child.cer"),
+                Bytes::from_static(include_bytes!("../../test-resources/validation/child.cer")),
+            ),
+            (
+                test::rsync("rsync://localhost/repo/child/0/child.mft"),
+                Bytes::from_static(include_bytes!("../../test-resources/validation/child.mft")),
+            ),
+            (
+                test::rsync("rsync://localhost/repo/child/0/child.crl"),
+                Bytes::from_static(include_bytes!("../../test-resources/validation/child.crl")),
+            ),
+            (
+                test::rsync("rsync://localhost/repo/child/0/child.roa"),
+                Bytes::from_static(include_bytes!("../../test-resources/validation/child.roa")),
+            ),
+        ]
+    }
+
+    fn valid_time() -> Time {
+        Time::utc(2019, 11, 26, 14, 0, 0)
+    }
+
+    #[test]
+    fn should_accept_valid_objects() {
+        for (uri, bytes) in objects() {
+            assert_eq!(
+                Ok(()),
+                validate_object(&uri, bytes, valid_time()),
+                "{}",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_objects_outside_validity() {
+        // The CRL and manifest are past their next update, but the
+        // certificate and ROA are still valid.
+        let later = Time::utc(2019, 11, 28, 0, 0, 0);
+        for (uri, bytes) in objects() {
+            let res = validate_object(&uri, bytes, later);
+            if uri.to_string().ends_with(".crl") || uri.to_string().ends_with(".mft") {
+                assert!(res.is_err(), "{}", uri);
+            } else {
+                assert_eq!(Ok(()), res, "{}", uri);
+            }
+        }
+
+        let earlier = Time::utc(2019, 11, 26, 13, 0, 0);
+        for (uri, bytes) in objects() {
+            if !uri.to_string().ends_with(".crl") {
+                assert!(validate_object(&uri, bytes, earlier).is_err(), "{}", uri);
+            }
+        }
+    }
+
+    #[test]
+    fn should_reject_tampered_signed_object() {
+        let (uri, bytes) = objects().pop().unwrap();
+
+        // The signature value is at the very end of the object
+        let mut tampered = bytes.to_vec();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0xff;
+
+        let res = validate_object(&uri, Bytes::from(tampered), valid_time());
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn should_reject_unsupported_extension() {
+        let uri = test::rsync("rsync://localhost/repo/child/0/child.txt");
+        let res = validate_object(&uri, Bytes::from_static(b"content"), valid_time());
+        assert_eq!(Err("unsupported file extension 'txt'".to_string()), res);
+    }
+}
@@ -97,6 +97,39 @@ impl CaPublisher {
         Ok(())
     }
 
+    /// Withdraws all objects published by the CA from its current
+    /// repository. Used when the CA is about to be deleted.
+    pub async fn withdraw_all(&self, ca_handle: &Handle) -> Result<(), Error> {
+        let ca = self.caserver.get_ca(ca_handle)?;
+
+        let repo_contact = match ca.get_repository_contact() {
+            Ok(repo) => repo,
+            Err(_) => return Ok(()),
+        };
+
+        let list_reply = match &repo_contact {
+            RepositoryContact::Embedded(_) => self.get_embedded()?.list(ca_handle)?,
+            RepositoryContact::Rfc8181(repo) => {
+                self.caserver.send_rfc8181_list(ca_handle, repo).await?
+            }
+        };
+
+        let delta = list_reply.into_withdraw_delta();
+
+        match &repo_contact {
+            RepositoryContact::Embedded(_) => {
+                self.get_embedded()?.publish(ca_handle.clone(), delta)?
+            }
+            RepositoryContact::Rfc8181(repo) => {
+                self.caserver
+                    .send_rfc8181_delta(ca_handle, repo, delta)
+                    .await?
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn clean_up(&self, ca_handle: &Handle) -> Result<(), Error> {
         let ca = self.caserver.get_ca(ca_handle)?;
 
@@ -20,10 +20,11 @@ use crate::cli::options::{BulkCaCommand, CaCommand, Command, Options, Publishers
 use crate::cli::report::{ApiResponse, ReportFormat};
 use crate::cli::{Error, KrillClient};
 use crate::commons::api::{
-    AddChildRequest, CertAuthInfo, CertAuthInit, CertifiedKeyInfo, ChildAuthRequest, ChildHandle,
-    Handle, ParentCaContact, ParentCaReq, ParentHandle, Publish, PublisherDetails, PublisherHandle,
-    RepositoryUpdate, ResourceClassKeysInfo, ResourceClassName, ResourceSet, RoaDefinitionUpdates,
-    UpdateChildRequest,
+    AddChildRequest, AsNumber, AspaDefinitionUpdates, AspaProvidersUpdate, CertAuthInfo,
+    CertAuthInit, CertifiedKeyInfo, ChildAuthRequest, ChildHandle, GhostbusterVcard, Handle,
+    ParentCaContact, ParentCaReq, ParentHandle, Publish, PublisherDetails, PublisherHandle,
+    PublisherObjects, RepositoryUpdate, ResourceClassKeysInfo, ResourceClassName, ResourceSet,
+    RoaDefinitionUpdates, UpdateChildRequest,
 };
 use crate::commons::remote::rfc8183;
 use crate::commons::remote::rfc8183::ChildRequest;
@@ -64,6 +65,16 @@ pub async fn server_ready(uri: &str) -> bool {
     false
 }
 
+/// Returns the data directory used by a krill server which was started
+/// with `start_krill` in the given base directory.
+pub fn krill_data_dir(base_dir: &PathBuf) -> PathBuf {
+    fs::read_dir(base_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.is_dir())
+        .unwrap()
+}
+
 /// Starts krill server for testing, with embedded TA and repo.
 /// Creates a random base directory in the 'work' folder, and returns
 /// it. Be sure to clean it up when the test is done.
@@ -153,6 +164,14 @@ pub async fn init_child_with_embedded_repo(handle: &Handle) {
     .await;
 }
 
+pub async fn delete_ca(handle: &Handle) {
+    krill_admin(Command::CertAuth(CaCommand::Delete(handle.clone()))).await;
+}
+
+pub async fn delete_ca_expect_error(handle: &Handle) -> Error {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::Delete(handle.clone()))).await
+}
+
 pub async fn generate_new_id(handle: &Handle) {
     krill_admin(Command::CertAuth(CaCommand::UpdateId(handle.clone()))).await;
 }
@@ -301,6 +320,71 @@ pub async fn ca_route_authorizations_update_expect_error(
     .await;
 }
 
+pub async fn ca_aspas_update(handle: &Handle, updates: AspaDefinitionUpdates) {
+    krill_admin(Command::CertAuth(CaCommand::AspasUpdate(
+        handle.clone(),
+        updates,
+    )))
+    .await;
+}
+
+pub async fn ca_aspas_update_expect_error(handle: &Handle, updates: AspaDefinitionUpdates) {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::AspasUpdate(
+        handle.clone(),
+        updates,
+    )))
+    .await;
+}
+
+pub async fn ca_aspas_update_providers(
+    handle: &Handle,
+    customer: AsNumber,
+    update: AspaProvidersUpdate,
+) {
+    krill_admin(Command::CertAuth(CaCommand::AspasUpdateProviders(
+        handle.clone(),
+        customer,
+        update,
+    )))
+    .await;
+}
+
+pub async fn ca_aspas_update_providers_expect_error(
+    handle: &Handle,
+    customer: AsNumber,
+    update: AspaProvidersUpdate,
+) {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::AspasUpdateProviders(
+        handle.clone(),
+        customer,
+        update,
+    )))
+    .await;
+}
+
+pub async fn ca_ghostbuster_update(handle: &Handle, vcard: GhostbusterVcard) {
+    krill_admin(Command::CertAuth(CaCommand::GhostbusterUpdate(
+        handle.clone(),
+        vcard,
+    )))
+    .await;
+}
+
+pub async fn ca_ghostbuster_update_expect_error(handle: &Handle, vcard: GhostbusterVcard) {
+    krill_admin_expect_error(Command::CertAuth(CaCommand::GhostbusterUpdate(
+        handle.clone(),
+        vcard,
+    )))
+    .await;
+}
+
+pub async fn ca_ghostbuster_remove(handle: &Handle) {
+    krill_admin(Command::CertAuth(CaCommand::GhostbusterRemove(
+        handle.clone(),
+    )))
+    .await;
+}
+
 pub async fn ca_details(handle: &Handle) -> CertAuthInfo {
     match krill_admin(Command::CertAuth(CaCommand::Show(handle.clone()))).await {
         ApiResponse::CertAuthInfo(inf) => inf,
@@ -411,6 +495,17 @@ pub async fn publisher_details(publisher: &PublisherHandle) -> PublisherDetails
     }
 }
 
+pub async fn publisher_objects(publisher: &PublisherHandle) -> PublisherObjects {
+    match krill_admin(Command::Publishers(PublishersCommand::ShowObjects(
+        publisher.clone(),
+    )))
+    .await
+    {
+        ApiResponse::PublisherObjects(objects) => objects,
+        _ => panic!("Expected publisher objects"),
+    }
+}
+
 pub async fn will_publish_objects(publisher: &PublisherHandle, objects: &[&str]) -> bool {
     for _ in 0..300 {
         let details = publisher_details(publisher).await;
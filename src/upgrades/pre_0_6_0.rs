@@ -7,9 +7,9 @@ use rpki::crypto::KeyIdentifier;
 use rpki::x509::Time;
 
 use crate::commons::api::{
-    Handle, RequestResourceLimit, ResourceClassName, ResourceSet, RevocationRequest, RoaDefinition,
-    RoaDefinitionUpdates, StorableCaCommand, StorableParentContact, StorableRepositoryCommand,
-    StoredEffect,
+    Handle, PublisherQuota, RequestResourceLimit, ResourceClassName, ResourceSet,
+    RevocationRequest, RoaDefinition, RoaDefinitionUpdates, StorableCaCommand,
+    StorableParentContact, StorableRepositoryCommand, StoredEffect,
 };
 use crate::commons::eventsourcing::{
     Aggregate, KeyStore, KeyStoreError, KeyStoreVersion, StoredCommand, StoredValueInfo,
@@ -657,6 +657,7 @@ impl PreviousCommand {
         Ok(StorableRepositoryCommand::AddPublisher(
             publisher,
             ski.clone(),
+            PublisherQuota::default(),
         ))
     }
 
@@ -0,0 +1,96 @@
+extern crate krill;
+
+use std::fs;
+use std::str::FromStr;
+
+use krill::commons::api::{
+    AsNumber, AspaDefinition, AspaDefinitionUpdates, AspaProvidersUpdate, Handle, ObjectName,
+    ParentCaReq, ProviderAs, ResourceSet,
+};
+use krill::daemon::ca::ta_handle;
+use krill::test::*;
+
+#[tokio::test]
+/// Test that CAs can issue and publish ASPA objects for the customer ASNs
+/// they hold, that invalid definitions are refused, and that ASPA objects
+/// get updated and published properly when definitions or resources change,
+/// as well as during and after key rolls.
+async fn ca_aspas() {
+    let dir = start_krill().await;
+
+    let ta_handle = ta_handle();
+    let child = Handle::from_str_unsafe("child");
+    let child_resources = ResourceSet::from_strs("AS65000", "10.0.0.0/16", "").unwrap();
+
+    init_child_with_embedded_repo(&child).await;
+
+    // Set up under parent  ----------------------------------------------------------------
+    {
+        let parent = {
+            let parent_contact = add_child_to_ta_embedded(&child, child_resources.clone()).await;
+            ParentCaReq::new(ta_handle.clone(), parent_contact)
+        };
+        add_parent_to_ca(&child, parent).await;
+        assert!(ca_gets_resources(&child, &child_resources).await);
+    }
+
+    let customer = AsNumber::new(65000);
+    let aspa = AspaDefinition::from_str("AS65000 => AS65001, AS65002(v4)").unwrap();
+
+    let crl_file = ".crl";
+    let mft_file = ".mft";
+    let aspa_file = ObjectName::from(&aspa).to_string();
+    let aspa_file = aspa_file.as_str();
+
+    // Add an ASPA definition
+    let updates = AspaDefinitionUpdates::new(vec![aspa], vec![]);
+    ca_aspas_update(&child, updates).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file, aspa_file]).await);
+
+    // Refuse a definition for a customer ASN not held by the CA
+    let not_held = AspaDefinition::from_str("AS65100 => AS65001").unwrap();
+    let updates = AspaDefinitionUpdates::new(vec![not_held], vec![]);
+    ca_aspas_update_expect_error(&child, updates).await;
+
+    // Refuse a definition which lists the customer as a provider
+    let invalid = AspaDefinition::from_str("AS65000 => AS65000").unwrap();
+    let updates = AspaDefinitionUpdates::new(vec![invalid], vec![]);
+    ca_aspas_update_expect_error(&child, updates).await;
+
+    // Refuse to remove a definition which does not exist
+    let updates = AspaDefinitionUpdates::new(vec![], vec![AsNumber::new(65100)]);
+    ca_aspas_update_expect_error(&child, updates).await;
+
+    // Update the providers, the ASPA object is replaced
+    let added = vec![ProviderAs::from_str("AS65003(v6)").unwrap()];
+    let update = AspaProvidersUpdate::new(added, vec![AsNumber::new(65001)]);
+    ca_aspas_update_providers(&child, customer, update).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file, aspa_file]).await);
+
+    // Refuse to remove all providers
+    let removed = vec![AsNumber::new(65002), AsNumber::new(65003)];
+    let update = AspaProvidersUpdate::new(vec![], removed);
+    ca_aspas_update_providers_expect_error(&child, customer, update).await;
+
+    // The ASPA object should remain there during a roll.
+    ca_roll_init(&child).await;
+    assert!(rc_state_becomes_new_key(&child).await);
+    let during_roll = [crl_file, mft_file, crl_file, mft_file, aspa_file];
+    assert!(will_publish_objects(&child, &during_roll).await);
+
+    ca_roll_activate(&child).await;
+    assert!(rc_state_becomes_active(&child).await);
+    assert!(will_publish_objects(&child, &[crl_file, mft_file, aspa_file]).await);
+
+    // Shrink resources and see that the ASPA object is removed
+    let child_resources = ResourceSet::from_strs("", "10.0.0.0/16", "").unwrap();
+    update_child(&ta_handle, &child, &child_resources).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file]).await);
+
+    // Remove the definition
+    let updates = AspaDefinitionUpdates::new(vec![], vec![customer]);
+    ca_aspas_update(&child, updates).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file]).await);
+
+    let _ = fs::remove_dir_all(dir);
+}
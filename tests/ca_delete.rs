@@ -0,0 +1,126 @@
+extern crate krill;
+
+use std::fs;
+use std::path::PathBuf;
+
+use krill::cli::options::{CaCommand, Command, PublishersCommand};
+use krill::commons::api::{
+    CommandHistoryCriteria, Handle, ParentCaReq, ResourceClassName, ResourceSet,
+    StorableRepositoryCommand,
+};
+use krill::commons::eventsourcing::{DiskKeyStore, KeyStore, StoredCommand};
+use krill::constants::{PUBSERVER_DFLT, PUBSERVER_DIR, REPOSITORY_DIR, REPOSITORY_RSYNC_DIR};
+use krill::daemon::ca::ta_handle;
+use krill::test::*;
+
+#[tokio::test]
+async fn ca_delete() {
+    let dir = start_krill().await;
+    let data_dir = krill_data_dir(&dir);
+
+    let ta_handle = ta_handle();
+
+    let child = Handle::from_str_unsafe("child");
+    let child_resources = ResourceSet::from_strs("", "10.0.0.0/16", "").unwrap();
+
+    let grandchild = Handle::from_str_unsafe("grandchild");
+    let grandchild_resources = ResourceSet::from_strs("", "10.0.0.0/24", "").unwrap();
+
+    init_child_with_embedded_repo(&child).await;
+
+    let parent = {
+        let parent_contact = add_child_to_ta_embedded(&child, child_resources.clone()).await;
+        ParentCaReq::new(ta_handle.clone(), parent_contact)
+    };
+
+    add_parent_to_ca(&child, parent).await;
+    assert!(ca_gets_resources(&child, &child_resources).await);
+    assert!(ta_will_have_issued_n_certs(1).await);
+
+    // Its objects are published in the rsync tree of the embedded repository.
+    let rsync_current = data_dir
+        .join(REPOSITORY_DIR)
+        .join(REPOSITORY_RSYNC_DIR)
+        .join("current");
+    let child_files: Vec<PathBuf> = publisher_details(&child)
+        .await
+        .current_files()
+        .iter()
+        .map(|file| {
+            let uri = file.uri().to_string();
+            rsync_current.join(uri.trim_start_matches("rsync://localhost/repo/"))
+        })
+        .collect();
+    assert!(!child_files.is_empty());
+    assert!(child_files.iter().all(|path| path.is_file()));
+
+    // A CA which still has children cannot be deleted.
+    init_child_with_embedded_repo(&grandchild).await;
+    let req = child_request(&grandchild).await;
+    add_child_rfc6492(&child, &grandchild, req, grandchild_resources).await;
+
+    delete_ca_expect_error(&child).await;
+    ca_details(&child).await;
+
+    delete_child(&child, &grandchild).await;
+
+    let key = ca_key_for_rcn(&child, &ResourceClassName::default()).await;
+    let key_path = data_dir.join("keys").join(key.key_id().to_string());
+    assert!(key_path.exists());
+
+    delete_ca(&child).await;
+
+    // The certificate for the CA is revoked, and it is no longer a child.
+    assert!(ta_will_have_issued_n_certs(0).await);
+    krill_admin_expect_error(Command::CertAuth(CaCommand::ChildInfo(
+        ta_handle.clone(),
+        child.clone(),
+    )))
+    .await;
+
+    // Its objects are withdrawn, and it is no longer a publisher.
+    assert!(child_files.iter().all(|path| !path.exists()));
+    krill_admin_expect_error(Command::Publishers(PublishersCommand::ShowPublisher(
+        child.clone(),
+    )))
+    .await;
+
+    // The withdrawal was done before it was removed as a publisher, rather
+    // than its objects going away as part of that removal.
+    let pubd_store = DiskKeyStore::new(&data_dir, PUBSERVER_DIR);
+    let repository = Handle::from_str_unsafe(PUBSERVER_DFLT);
+    let child_commands: Vec<StorableRepositoryCommand> = pubd_store
+        .command_keys_ascending(&repository, &CommandHistoryCriteria::default())
+        .into_iter()
+        .filter(|key| key.is_for_subject(&child))
+        .map(|key| {
+            let command: StoredCommand<StorableRepositoryCommand> = pubd_store
+                .get(&repository, &PathBuf::from(key))
+                .unwrap()
+                .unwrap();
+            command.details().clone()
+        })
+        .collect();
+
+    let removed = child_commands.len() - 1;
+    assert_eq!(
+        child_commands[removed],
+        StorableRepositoryCommand::RemovePublisher(child.clone())
+    );
+    assert_eq!(
+        child_commands[removed - 1],
+        StorableRepositoryCommand::Publish(child.clone(), 0, 0, child_files.len())
+    );
+
+    // Its keys are destroyed, and its history is archived.
+    assert!(!key_path.exists());
+    krill_admin_expect_error(Command::CertAuth(CaCommand::Show(child.clone()))).await;
+
+    assert!(!data_dir.join("cas").join("child").exists());
+    let archived = fs::read_dir(data_dir.join("archived").join("cas"))
+        .unwrap()
+        .any(|entry| entry.unwrap().path().join("child").is_dir());
+    assert!(archived);
+
+    let _ = fs::remove_dir_all(dir);
+}
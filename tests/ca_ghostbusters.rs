@@ -0,0 +1,96 @@
+extern crate krill;
+
+use std::fs;
+
+use krill::commons::api::{
+    GhostbusterVcard, Handle, ObjectName, ParentCaReq, PublishedObjectDetails, ResourceSet,
+};
+use krill::daemon::ca::ta_handle;
+use krill::test::*;
+
+fn vcard(full_name: &str, email: Option<&str>) -> GhostbusterVcard {
+    GhostbusterVcard::new(
+        full_name.to_string(),
+        Some("Example Org".to_string()),
+        email.map(|s| s.to_string()),
+        None,
+        None,
+    )
+}
+
+/// Returns the decoded vCard text of the published Ghostbusters record.
+async fn published_vcard(publisher: &Handle) -> Option<String> {
+    publisher_objects(publisher)
+        .await
+        .objects()
+        .iter()
+        .find_map(|object| match object.details() {
+            PublishedObjectDetails::Ghostbusters { vcard, .. } => Some(vcard.clone()),
+            _ => None,
+        })
+}
+
+#[tokio::test]
+/// Test that CAs can publish, update and remove a Ghostbusters record, that
+/// incomplete contact details are refused, and that the record stays
+/// published during and after key rolls.
+async fn ca_ghostbusters() {
+    let dir = start_krill().await;
+
+    let ta_handle = ta_handle();
+    let child = Handle::from_str_unsafe("child");
+    let child_resources = ResourceSet::from_strs("", "10.0.0.0/16", "").unwrap();
+
+    init_child_with_embedded_repo(&child).await;
+
+    // Set up under parent  ----------------------------------------------------------------
+    {
+        let parent = {
+            let parent_contact = add_child_to_ta_embedded(&child, child_resources.clone()).await;
+            ParentCaReq::new(ta_handle.clone(), parent_contact)
+        };
+        add_parent_to_ca(&child, parent).await;
+        assert!(ca_gets_resources(&child, &child_resources).await);
+    }
+
+    let crl_file = ".crl";
+    let mft_file = ".mft";
+    let gbr_file = ObjectName::ghostbusters().to_string();
+    let gbr_file = gbr_file.as_str();
+
+    // Refuse a record without any way to get in touch
+    ca_ghostbuster_update_expect_error(&child, vcard("Jane Doe", None)).await;
+
+    // Publish a record
+    ca_ghostbuster_update(&child, vcard("Jane Doe", Some("jane@example.com"))).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file, gbr_file]).await);
+
+    let published = published_vcard(&child).await.unwrap();
+    assert!(published.contains("FN:Jane Doe"));
+    assert!(published.contains("jane@example.com"));
+
+    // Update the record, the published object is replaced
+    ca_ghostbuster_update(&child, vcard("John Doe", Some("john@example.com"))).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file, gbr_file]).await);
+
+    let published = published_vcard(&child).await.unwrap();
+    assert!(published.contains("FN:John Doe"));
+    assert!(!published.contains("Jane Doe"));
+
+    // The record should remain there during a roll.
+    ca_roll_init(&child).await;
+    assert!(rc_state_becomes_new_key(&child).await);
+    let during_roll = [crl_file, mft_file, crl_file, mft_file, gbr_file];
+    assert!(will_publish_objects(&child, &during_roll).await);
+
+    ca_roll_activate(&child).await;
+    assert!(rc_state_becomes_active(&child).await);
+    assert!(will_publish_objects(&child, &[crl_file, mft_file, gbr_file]).await);
+
+    // Remove the record
+    ca_ghostbuster_remove(&child).await;
+    assert!(will_publish_objects(&child, &[crl_file, mft_file]).await);
+    assert!(published_vcard(&child).await.is_none());
+
+    let _ = fs::remove_dir_all(dir);
+}
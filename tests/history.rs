@@ -3,7 +3,7 @@ extern crate krill;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
-use krill::commons::api::{CaCommandDetails, CommandHistoryCriteria, Handle};
+use krill::commons::api::{CaCommandDetails, CommandHistoryCriteria, Handle, PublicationTiming};
 use krill::commons::util::file;
 use krill::commons::util::softsigner::OpenSslSigner;
 use krill::daemon::ca::CaServer;
@@ -58,8 +58,10 @@ fn make_server(work_dir: &PathBuf, scenario: &str) -> CaServer<OpenSslSigner> {
         let signer = Arc::new(RwLock::new(signer));
 
         let event_queue = Arc::new(EventQueueListener::in_mem());
+        let timing = PublicationTiming::default();
 
-        CaServer::<OpenSslSigner>::build(&server_dir, None, None, event_queue, signer).unwrap()
+        CaServer::<OpenSslSigner>::build(&server_dir, None, None, event_queue, signer, timing)
+            .unwrap()
     };
 
     server